@@ -50,7 +50,7 @@ fn main() {
     builder.add_struct(name.clone(), &data);
 
     // Make the Surfer
-    let mut surfer = Surfer::try_from(builder).unwrap();
+    let surfer = Surfer::try_from(builder).unwrap();
 
     // Prepare your data or get it from somewhere
     let title = "The Old Man and the Sea".to_string();
@@ -68,7 +68,7 @@ fn main() {
     let data = User::default();
     builder.add_struct(name.clone(), &data);
 
-    let mut surfer = Surfer::try_from(builder).unwrap();
+    let surfer = Surfer::try_from(builder).unwrap();
 
     let first = "John".to_string();
     let last = "Doe".to_string();
@@ -66,7 +66,7 @@ fn main() {
     let data = User::default();
     builder.add_struct(name.clone(), &data);
 
-    let mut surfer = Surfer::try_from(builder).unwrap();
+    let surfer = Surfer::try_from(builder).unwrap();
 
     let john_doe_full_name = "John Doe".to_string();
     let john_doe_user_id = "john.doe.1".to_string();
@@ -0,0 +1,407 @@
+//! # Derive macro for json-surf
+//! * `#[derive(Surf)]` generates the index name & fields of a struct at compile time
+//! * No sample instance is needed to register the schema with `SurferBuilder::add_surf`
+//! * Nested structs must derive Surf too and are flattened into dotted field names
+//! * Fields of a `Vec` of structs become multi-valued dotted fields e.g. `items.sku` of `items: Vec<LineItem>`
+//! * `#[surf(stored, indexed, fast, tokenizer = "en_stem")]` declares the options of a field in place of the defaults
+//! * `#[surf(stem = "french")]` stems a text field for the language, the name or its ISO 639-1 code e.g. fr
+//! * `#[surf(skip)]` leaves a field out of the schema
+//! * `#[derive(FromDocument)]` reads the stored fields straight into the struct, integers widened to the field type
+//! * Fields skipped are read as their Default, nested structs must derive FromDocument too
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields, GenericArgument, Lit, Meta, NestedMeta, PathArguments, Type};
+
+/// Implements json_surf::surf::Surf for a struct with named fields
+#[proc_macro_derive(Surf, attributes(surf))]
+pub fn derive_surf(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2, syn::Error> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new_spanned(input, "Surf requires named fields")),
+        },
+        _ => return Err(syn::Error::new_spanned(input, "Surf can only be derived for structs")),
+    };
+
+    let mut pushes = Vec::with_capacity(fields.len());
+    for field in fields {
+        let attributes = attributes(&field.attrs)?;
+        if attributes.skip {
+            continue;
+        };
+        let name = field.ident.as_ref().unwrap().to_string();
+        let push = match kind(&field.ty) {
+            Some(kind) => {
+                let options = attributes.options(field, &kind.to_string())?;
+                quote! {
+                    fields.push(::json_surf::surf::SurfField::new(#name, ::json_surf::surf::SurfKind::#kind)#options);
+                }
+            }
+            None => {
+                if attributes.is_declared() {
+                    return Err(syn::Error::new_spanned(field, "Options are not supported on nested structs"));
+                };
+                match seq_item(&field.ty) {
+                    Some(ty) => quote! {
+                        for field in <#ty as ::json_surf::surf::Surf>::surf_fields() {
+                            fields.push(field.repeated(#name));
+                        };
+                    },
+                    None => {
+                        let ty = inner(&field.ty);
+                        quote! {
+                            for field in <#ty as ::json_surf::surf::Surf>::surf_fields() {
+                                fields.push(field.prefixed(#name));
+                            };
+                        }
+                    }
+                }
+            }
+        };
+        pushes.push(push);
+    }
+
+    let ident = &input.ident;
+    let index_name = snake_case(&ident.to_string());
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::json_surf::surf::Surf for #ident #ty_generics #where_clause {
+            fn index_name() -> &'static str {
+                #index_name
+            }
+            fn surf_fields() -> Vec<::json_surf::surf::SurfField> {
+                let mut fields = Vec::new();
+                #(#pushes)*
+                fields
+            }
+        }
+    })
+}
+
+/// Implements json_surf::document::FromDocument reading every field by name
+#[proc_macro_derive(FromDocument, attributes(surf))]
+pub fn derive_from_document(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_from_document(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand_from_document(input: &DeriveInput) -> Result<TokenStream2, syn::Error> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new_spanned(input, "FromDocument requires named fields")),
+        },
+        _ => return Err(syn::Error::new_spanned(input, "FromDocument can only be derived for structs")),
+    };
+
+    let mut reads = Vec::with_capacity(fields.len());
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let name = ident.to_string();
+        let read = if attributes(&field.attrs)?.skip {
+            quote!(::std::default::Default::default())
+        } else if kind(&field.ty).is_some() {
+            quote!(::json_surf::document::FromStored::from_stored(document, #name)?)
+        } else if let Some(ty) = seq_item(&field.ty) {
+            match last_ident(&field.ty) {
+                Some(ident) if ident == "Option" => quote! {
+                    match document.nested(#name) {
+                        nested if nested.is_empty() => None,
+                        nested => Some(nested.items::<#ty>()?),
+                    }
+                },
+                _ => quote!(document.nested(#name).items::<#ty>()?),
+            }
+        } else {
+            let ty = inner(&field.ty);
+            match last_ident(&field.ty) {
+                Some(ident) if ident == "Option" => quote! {
+                    match document.nested(#name) {
+                        nested if nested.is_empty() => None,
+                        nested => Some(<#ty as ::json_surf::document::FromDocument>::from_document(&nested)?),
+                    }
+                },
+                _ => quote!(<#ty as ::json_surf::document::FromDocument>::from_document(&document.nested(#name))?),
+            }
+        };
+        reads.push(quote!(#ident: #read));
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::json_surf::document::FromDocument for #ident #ty_generics #where_clause {
+            fn from_document(document: &::json_surf::document::StoredDocument) -> Result<Self, ::json_surf::errors::IndexError> {
+                Ok(Self {
+                    #(#reads),*
+                })
+            }
+        }
+    })
+}
+
+/// Parsed #[surf(..)] attributes of a field
+#[derive(Default)]
+struct SurfAttributes {
+    skip: bool,
+    stored: bool,
+    indexed: bool,
+    fast: bool,
+    tokenizer: Option<String>,
+    stem: Option<String>,
+}
+
+impl SurfAttributes {
+    fn is_declared(&self) -> bool {
+        self.stored || self.indexed || self.fast || self.tokenizer.is_some() || self.stem.is_some()
+    }
+
+    /// with_options call for the kind, empty when nothing is declared
+    fn options(&self, field: &syn::Field, kind: &str) -> Result<TokenStream2, syn::Error> {
+        if !self.is_declared() {
+            return Ok(quote!());
+        };
+        let text = kind == "Text" || kind == "TextSeq";
+        if kind == "Bytes" {
+            return Err(syn::Error::new_spanned(field, "Options are not supported on bytes fields"));
+        };
+        if text && self.fast {
+            return Err(syn::Error::new_spanned(field, "fast is only supported on numeric & date fields"));
+        };
+        if !text && (self.tokenizer.is_some() || self.stem.is_some()) {
+            return Err(syn::Error::new_spanned(field, "tokenizer is only supported on text fields"));
+        };
+        if self.tokenizer.is_some() && self.stem.is_some() {
+            return Err(syn::Error::new_spanned(field, "stem names the tokenizer, declare only one of them"));
+        };
+        let (stored, indexed, fast) = (self.stored, self.indexed, self.fast);
+        let stem = self.stem.as_ref().map(|code| format!("{}_stem", code));
+        let tokenizer = match stem.as_ref().or(self.tokenizer.as_ref()) {
+            Some(tokenizer) => quote!(Some(#tokenizer)),
+            None => quote!(None),
+        };
+        Ok(quote! {
+            .with_options(::json_surf::surf::SurfOptions::new(#stored, #indexed, #fast, #tokenizer))
+        })
+    }
+}
+
+fn attributes(attrs: &[Attribute]) -> Result<SurfAttributes, syn::Error> {
+    let mut attributes = SurfAttributes::default();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("surf")) {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => return Err(syn::Error::new_spanned(meta, "Expected #[surf(..)]")),
+        };
+        for nested in list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => attributes.skip = true,
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("stored") => attributes.stored = true,
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("indexed") => attributes.indexed = true,
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("fast") => attributes.fast = true,
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("tokenizer") => match value.lit {
+                    Lit::Str(tokenizer) => attributes.tokenizer = Some(tokenizer.value()),
+                    lit => return Err(syn::Error::new_spanned(lit, "tokenizer expects a string")),
+                },
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("stem") => match &value.lit {
+                    Lit::Str(language) => match stem_code(&language.value()) {
+                        Some(code) => attributes.stem = Some(code.to_string()),
+                        None => return Err(syn::Error::new_spanned(language, "Unknown stemming language")),
+                    },
+                    lit => return Err(syn::Error::new_spanned(lit, "stem expects a string")),
+                },
+                nested => return Err(syn::Error::new_spanned(nested, "Unknown surf attribute")),
+            };
+        };
+    };
+    Ok(attributes)
+}
+
+/// ISO 639-1 code of the stemming languages of tantivy, by name or code
+fn stem_code(language: &str) -> Option<&'static str> {
+    const LANGUAGES: [(&str, &str); 18] = [
+        ("arabic", "ar"), ("danish", "da"), ("dutch", "nl"), ("english", "en"), ("finnish", "fi"), ("french", "fr"),
+        ("german", "de"), ("greek", "el"), ("hungarian", "hu"), ("italian", "it"), ("norwegian", "no"),
+        ("portuguese", "pt"), ("romanian", "ro"), ("russian", "ru"), ("spanish", "es"), ("swedish", "sv"),
+        ("tamil", "ta"), ("turkish", "tr"),
+    ];
+    let language = language.to_lowercase();
+    LANGUAGES.iter()
+        .find(|(name, code)| *name == language || *code == language)
+        .map(|(_, code)| *code)
+}
+
+/// Kind of the field, None for nested structs & sequences of them
+fn kind(ty: &Type) -> Option<TokenStream2> {
+    let ident = last_ident(ty)?;
+    let kind = match ident.as_str() {
+        "String" | "str" => quote!(Text),
+        "u8" | "u16" | "u32" | "u64" | "usize" => quote!(U64),
+        "i8" | "i16" | "i32" | "i64" | "isize" => quote!(I64),
+        "f32" | "f64" => quote!(F64),
+        "bool" => quote!(Bool),
+        "DateTime" => quote!(Date),
+        "NaiveDateTime" => quote!(NaiveDate),
+        "Option" => return kind(&generic(ty)?),
+        "Vec" => {
+            let item = generic(ty)?;
+            match last_ident(&item)?.as_str() {
+                "String" | "str" => quote!(TextSeq),
+                _ if kind(&item).is_some() => quote!(Bytes),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// Struct of a sequence of structs with any Option unwrapped e.g. LineItem of Option<Vec<LineItem>>
+fn seq_item(ty: &Type) -> Option<Type> {
+    let ty = inner(ty);
+    match last_ident(&ty) {
+        Some(ident) if ident == "Vec" => generic(&ty),
+        _ => None,
+    }
+}
+
+/// Nested struct type with any Option unwrapped
+fn inner(ty: &Type) -> Type {
+    match last_ident(ty) {
+        Some(ident) if ident == "Option" => generic(ty).map(|ty| inner(&ty)).unwrap_or_else(|| ty.clone()),
+        _ => ty.clone(),
+    }
+}
+
+fn last_ident(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(path) => path.path.segments.last().map(|segment| segment.ident.to_string()),
+        Type::Reference(reference) => last_ident(&reference.elem),
+        _ => None,
+    }
+}
+
+/// First type argument e.g. T of Vec<T>
+fn generic(ty: &Type) -> Option<Type> {
+    let path = match ty {
+        Type::Path(path) => path,
+        _ => return None,
+    };
+    let segment = path.path.segments.last()?;
+    match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => arguments.args.iter().find_map(|argument| match argument {
+            GenericArgument::Type(ty) => Some(ty.clone()),
+            _ => None,
+        }),
+        _ => None,
+    }
+}
+
+/// UserData becomes user_data
+fn snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            };
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        };
+    }
+    snake
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_snake_case() {
+        assert_eq!(snake_case("UserData"), "user_data");
+        assert_eq!(snake_case("Book"), "book");
+    }
+
+    #[test]
+    fn validate_attributes() {
+        let field: syn::FieldsNamed = syn::parse_str(r#"{ #[surf(stored, tokenizer = "en_stem")] title: String, #[surf(skip)] secret: String }"#).unwrap();
+        let fields: Vec<&syn::Field> = field.named.iter().collect();
+
+        let title = attributes(&fields[0].attrs).unwrap();
+        assert!(!title.skip);
+        assert!(title.stored);
+        assert_eq!(title.tokenizer, Some("en_stem".to_string()));
+        assert!(title.options(fields[0], "Text").is_ok());
+        assert!(title.options(fields[0], "U64").is_err());
+
+        let secret = attributes(&fields[1].attrs).unwrap();
+        assert!(secret.skip);
+        assert!(!secret.is_declared());
+        assert!(secret.options(fields[1], "Text").unwrap().is_empty());
+    }
+
+    #[test]
+    fn validate_stem() {
+        assert_eq!(stem_code("French"), Some("fr"));
+        assert_eq!(stem_code("de"), Some("de"));
+        assert_eq!(stem_code("klingon"), None);
+
+        let field: syn::FieldsNamed = syn::parse_str(r#"{ #[surf(stem = "french")] titre: String, #[surf(stem = "fr", tokenizer = "raw")] nom: String }"#).unwrap();
+        let fields: Vec<&syn::Field> = field.named.iter().collect();
+        let titre = attributes(&fields[0].attrs).unwrap();
+        assert_eq!(titre.stem, Some("fr".to_string()));
+        assert!(titre.options(fields[0], "Text").unwrap().to_string().contains("\"fr_stem\""));
+        assert!(titre.options(fields[0], "U64").is_err());
+        let nom = attributes(&fields[1].attrs).unwrap();
+        assert!(nom.options(fields[1], "Text").is_err());
+
+        let field: syn::FieldsNamed = syn::parse_str(r#"{ #[surf(stem = "klingon")] title: String }"#).unwrap();
+        assert!(attributes(&field.named[0].attrs).is_err());
+    }
+
+    #[test]
+    fn validate_from_document() {
+        let input: DeriveInput = syn::parse_str(r#"struct Book { title: String, pages: Option<u32>, #[surf(skip)] cache: Vec<u8>, author: Author, editor: Option<Author> }"#).unwrap();
+        let computed = expand_from_document(&input).unwrap().to_string();
+        assert!(computed.contains("FromStored :: from_stored (document , \"title\")"));
+        assert!(computed.contains("cache : :: std :: default :: Default :: default ()"));
+        assert!(computed.contains("< Author as :: json_surf :: document :: FromDocument > :: from_document (& document . nested (\"author\"))"));
+        assert!(computed.contains("nested if nested . is_empty () => None"));
+
+        let input: DeriveInput = syn::parse_str("struct Pair(String, u64);").unwrap();
+        assert!(expand_from_document(&input).is_err());
+    }
+
+    #[test]
+    fn validate_kind() {
+        let ty: Type = syn::parse_str("Option<Vec<String>>").unwrap();
+        assert_eq!(kind(&ty).unwrap().to_string(), "TextSeq");
+        let ty: Type = syn::parse_str("chrono::DateTime<Utc>").unwrap();
+        assert_eq!(kind(&ty).unwrap().to_string(), "Date");
+        let ty: Type = syn::parse_str("Address").unwrap();
+        assert!(kind(&ty).is_none());
+        let ty: Type = syn::parse_str("Option<Address>").unwrap();
+        assert_eq!(inner(&ty), syn::parse_str::<Type>("Address").unwrap());
+        let ty: Type = syn::parse_str("Vec<u8>").unwrap();
+        assert_eq!(kind(&ty).unwrap().to_string(), "Bytes");
+        let ty: Type = syn::parse_str("Option<Vec<LineItem>>").unwrap();
+        assert!(kind(&ty).is_none());
+        assert_eq!(seq_item(&ty), Some(syn::parse_str::<Type>("LineItem").unwrap()));
+        assert!(seq_item(&syn::parse_str::<Type>("Address").unwrap()).is_none());
+    }
+}
@@ -0,0 +1,200 @@
+use tantivy::collector::{Collector, SegmentCollector};
+use tantivy::fastfield::FastFieldReader;
+use tantivy::query::Query;
+use tantivy::schema::{Field, FieldType};
+use tantivy::{DocId, Score, Searcher, SegmentLocalId, SegmentReader, TantivyError};
+
+use serde::Serialize;
+
+use crate::prelude::*;
+
+/// Numeric statistics over a fast field of the matching documents
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Stats {
+    count: u64,
+    sum: f64,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            sum: 0.0,
+            min: None,
+            max: None,
+        }
+    }
+}
+
+impl Stats {
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+    pub fn min(&self) -> Option<f64> {
+        self.min
+    }
+    pub fn max(&self) -> Option<f64> {
+        self.max
+    }
+    /// Average of the values, None when nothing matched
+    pub fn avg(&self) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        };
+        Some(self.sum / self.count as f64)
+    }
+    fn add(&mut self, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        self.min = Some(self.min.map_or(value, |min| min.min(value)));
+        self.max = Some(self.max.map_or(value, |max| max.max(value)));
+    }
+    fn merge(&mut self, other: Stats) {
+        self.count += other.count;
+        self.sum += other.sum;
+        self.min = match (self.min, other.min) {
+            (Some(left), Some(right)) => Some(left.min(right)),
+            (left, right) => left.or(right),
+        };
+        self.max = match (self.max, other.max) {
+            (Some(left), Some(right)) => Some(left.max(right)),
+            (left, right) => left.or(right),
+        };
+    }
+}
+
+/// Fast field reader of a numeric field
+enum NumericReader {
+    U64(FastFieldReader<u64>),
+    I64(FastFieldReader<i64>),
+    F64(FastFieldReader<f64>),
+}
+
+impl NumericReader {
+    fn get(&self, doc: DocId) -> f64 {
+        match self {
+            NumericReader::U64(reader) => reader.get(doc) as f64,
+            NumericReader::I64(reader) => reader.get(doc) as f64,
+            NumericReader::F64(reader) => reader.get(doc),
+        }
+    }
+}
+
+/// Collects Stats of a numeric fast field
+struct StatsCollector {
+    field: Field,
+    field_type: FieldType,
+}
+
+struct StatsSegmentCollector {
+    reader: NumericReader,
+    stats: Stats,
+}
+
+impl Collector for StatsCollector {
+    type Fruit = Stats;
+    type Child = StatsSegmentCollector;
+
+    fn for_segment(&self, _: SegmentLocalId, segment: &SegmentReader) -> tantivy::Result<Self::Child> {
+        let fast_fields = segment.fast_fields();
+        let reader = match self.field_type {
+            FieldType::U64(_) => fast_fields.u64(self.field).map(NumericReader::U64),
+            FieldType::I64(_) => fast_fields.i64(self.field).map(NumericReader::I64),
+            FieldType::F64(_) => fast_fields.f64(self.field).map(NumericReader::F64),
+            _ => None,
+        };
+        let reader = reader.ok_or_else(|| {
+            TantivyError::SchemaError("Field is not a numeric fast field".to_string())
+        })?;
+        Ok(StatsSegmentCollector {
+            reader,
+            stats: Stats::default(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<Stats>) -> tantivy::Result<Stats> {
+        let mut stats = Stats::default();
+        for fruit in segment_fruits {
+            stats.merge(fruit);
+        };
+        Ok(stats)
+    }
+}
+
+impl SegmentCollector for StatsSegmentCollector {
+    type Fruit = Stats;
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let value = self.reader.get(doc);
+        self.stats.add(value);
+    }
+
+    fn harvest(self) -> Stats {
+        self.stats
+    }
+}
+
+/// Computes Stats of a numeric fast field for the documents matching the query
+pub(crate) fn aggregate(searcher: &Searcher, query: &dyn Query, field_name: &str) -> Result<Stats, IndexError> {
+    let schema = searcher.schema();
+    let field = schema.get_field(field_name);
+    if field.is_none() {
+        let reason = format!("Field: {} does not exist", field_name);
+        return Err(IndexError::schema_mismatch("Unable to aggregate", &reason));
+    };
+    let field = field.unwrap();
+    let entry = schema.get_field_entry(field);
+    if !entry.is_int_fast() {
+        let reason = format!("Field: {} is not a fast field", field_name);
+        return Err(IndexError::schema_mismatch("Unable to aggregate", &reason));
+    };
+    let field_type = entry.field_type().clone();
+    match field_type {
+        FieldType::U64(_) | FieldType::I64(_) | FieldType::F64(_) => {}
+        _ => {
+            let reason = format!("Field: {} is not numeric", field_name);
+            return Err(IndexError::schema_mismatch("Unable to aggregate", &reason));
+        }
+    };
+    let collector = StatsCollector {
+        field,
+        field_type,
+    };
+    let stats = searcher.search(query, &collector)?;
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_stats() {
+        let mut stats = Stats::default();
+        assert_eq!(stats.count(), 0);
+        assert_eq!(stats.avg(), None);
+        assert_eq!(stats.min(), None);
+
+        stats.add(2.0);
+        stats.add(4.0);
+        let mut other = Stats::default();
+        other.add(-3.0);
+        stats.merge(other);
+        stats.merge(Stats::default());
+
+        assert_eq!(stats.count(), 3);
+        assert_eq!(stats.sum(), 3.0);
+        assert_eq!(stats.avg(), Some(1.0));
+        assert_eq!(stats.min(), Some(-3.0));
+        assert_eq!(stats.max(), Some(4.0));
+    }
+}
@@ -0,0 +1,159 @@
+//! Command line access to json-surf indexes, enable with the cli feature
+//! * `json-surf create <home> <name> <sample.json>` creates an index from a sample document
+//! * `json-surf load <home> <name> <docs.ndjson>` inserts one JSON document per line
+//! * `json-surf query <home> <name> <query> [limit]` prints the matching documents as JSON lines
+//! * `json-surf export <home> <name>` prints every stored document as JSON lines
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::BufReader;
+use std::process::exit;
+
+use serde_json::Value as JsonValue;
+
+use json_surf::prelude::*;
+
+const USAGE: &str = "Usage:
+    json-surf create <home> <name> <sample.json>
+    json-surf load <home> <name> <docs.ndjson>
+    json-surf query <home> <name> <query> [limit]
+    json-surf export <home> <name>";
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Create { home: String, name: String, sample: String },
+    Load { home: String, name: String, docs: String },
+    Query { home: String, name: String, query: String, limit: Option<usize> },
+    Export { home: String, name: String },
+}
+
+fn parse(args: &[String]) -> Option<Command> {
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+    let command = match args.as_slice() {
+        ["create", home, name, sample] => Command::Create {
+            home: home.to_string(),
+            name: name.to_string(),
+            sample: sample.to_string(),
+        },
+        ["load", home, name, docs] => Command::Load {
+            home: home.to_string(),
+            name: name.to_string(),
+            docs: docs.to_string(),
+        },
+        ["query", home, name, query] => Command::Query {
+            home: home.to_string(),
+            name: name.to_string(),
+            query: query.to_string(),
+            limit: None,
+        },
+        ["query", home, name, query, limit] => Command::Query {
+            home: home.to_string(),
+            name: name.to_string(),
+            query: query.to_string(),
+            limit: Some(limit.parse().ok()?),
+        },
+        ["export", home, name] => Command::Export {
+            home: home.to_string(),
+            name: name.to_string(),
+        },
+        _ => return None,
+    };
+    Some(command)
+}
+
+/// Surfer over a single index already on disk
+fn open(home: &str, name: &str) -> Result<Surfer, IndexError> {
+    let mut builder = SurferBuilder::default();
+    builder.set_home(home);
+    builder.add_existing(name)?;
+    Surfer::try_from(builder)
+}
+
+fn run(command: Command) -> Result<(), IndexError> {
+    match command {
+        Command::Create { home, name, sample } => {
+            let sample: JsonValue = serde_json::from_reader(File::open(sample)?)?;
+            if !sample.is_object() {
+                return Err(IndexError::new("Unable to create index", "Sample must be a JSON object"));
+            };
+            let mut builder = SurferBuilder::default();
+            builder.set_home(&home);
+            builder.add_struct(name.clone(), &sample);
+            let surfer = Surfer::try_from(builder)?;
+            println!("Created index: {}", surfer.which_index(&name).unwrap_or(name));
+        }
+        Command::Load { home, name, docs } => {
+            let surfer = open(&home, &name)?;
+            let report = surfer.insert_ndjson(&name, BufReader::new(File::open(docs)?))?.unwrap_or_default();
+            for error in report.errors() {
+                eprintln!("Line: {} {:?}", error.line(), error.error());
+            };
+            println!("Loaded documents: {}", report.inserted());
+        }
+        Command::Query { home, name, query, limit } => {
+            let surfer = open(&home, &name)?;
+            let docs = surfer.read_string(&name, &query, limit, None)?.unwrap_or_default();
+            for doc in docs {
+                println!("{}", doc);
+            };
+        }
+        Command::Export { home, name } => {
+            let surfer = open(&home, &name)?;
+            surfer.export(&name, std::io::stdout().lock())?;
+        }
+    };
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = match parse(&args) {
+        Some(command) => command,
+        None => {
+            eprintln!("{}", USAGE);
+            exit(2);
+        }
+    };
+    if let Err(e) = run(command) {
+        eprintln!("{:?}", e);
+        exit(1);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn validate_parse() {
+        let computed = parse(&args(&["query", "indexes", "books", "title:sea", "5"]));
+        let expected = Command::Query {
+            home: "indexes".to_string(),
+            name: "books".to_string(),
+            query: "title:sea".to_string(),
+            limit: Some(5),
+        };
+        assert_eq!(computed, Some(expected));
+
+        let computed = parse(&args(&["load", "indexes", "books", "books.ndjson"]));
+        let expected = Command::Load {
+            home: "indexes".to_string(),
+            name: "books".to_string(),
+            docs: "books.ndjson".to_string(),
+        };
+        assert_eq!(computed, Some(expected));
+
+        let computed = parse(&args(&["export", "indexes", "books"]));
+        let expected = Command::Export {
+            home: "indexes".to_string(),
+            name: "books".to_string(),
+        };
+        assert_eq!(computed, Some(expected));
+
+        assert_eq!(parse(&args(&["query", "indexes", "books", "title:sea", "many"])), None);
+        assert_eq!(parse(&args(&["drop", "indexes", "books"])), None);
+    }
+}
@@ -0,0 +1,188 @@
+use std::ops::Range;
+
+use chrono::{DateTime, Utc};
+
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use tantivy::Index;
+use tantivy::query::{AllQuery, BooleanQuery, Occur, Query, TermQuery};
+use tantivy::schema::IndexRecordOption;
+
+use crate::prelude::*;
+
+/// Leaf of a boolean query, compiled against the schema of the index
+#[derive(Clone, Debug, PartialEq)]
+pub enum Clause {
+    /// Every document, e.g. next to must_not clauses only
+    All,
+    /// Exact value of the field, the value is not tokenized
+    Term(String, JsonValue),
+    /// Query string parsed against the default search fields
+    Text(String),
+    /// Words of the text field next to each other in order
+    Phrase(String, Vec<String>),
+    /// Term of the text field within the edit distance
+    Fuzzy(String, String, u8),
+    /// Terms of the text field matching the regex
+    Regex(String, String),
+    /// Numeric field within the range, start inclusive & end exclusive
+    Range(String, Range<JsonValue>),
+    /// Date field within the range, start inclusive & end exclusive
+    DateRange(String, Range<DateTime<Utc>>),
+    /// Nested boolean query
+    Bool(BoolQuery),
+}
+
+impl Clause {
+    pub fn term<V: Serialize>(field: &str, value: &V) -> Self {
+        let value = serde_json::to_value(value).unwrap_or(JsonValue::Null);
+        Clause::Term(field.to_string(), value)
+    }
+    pub fn text(query: &str) -> Self {
+        Clause::Text(query.to_string())
+    }
+    pub fn phrase(field: &str, words: &[&str]) -> Self {
+        let words = words.iter().map(|word| word.to_string()).collect();
+        Clause::Phrase(field.to_string(), words)
+    }
+    pub fn fuzzy(field: &str, term: &str, distance: u8) -> Self {
+        Clause::Fuzzy(field.to_string(), term.to_string(), distance)
+    }
+    pub fn regex(field: &str, pattern: &str) -> Self {
+        Clause::Regex(field.to_string(), pattern.to_string())
+    }
+    pub fn range<V: Serialize>(field: &str, range: Range<V>) -> Self {
+        let start = serde_json::to_value(range.start).unwrap_or(JsonValue::Null);
+        let end = serde_json::to_value(range.end).unwrap_or(JsonValue::Null);
+        Clause::Range(field.to_string(), start..end)
+    }
+    pub fn date_range(field: &str, range: Range<DateTime<Utc>>) -> Self {
+        Clause::DateRange(field.to_string(), range)
+    }
+    /// Date range between date math expressions e.g. now-7d & now, resolved at once
+    pub fn date_between(field: &str, from: &str, to: &str) -> Result<Self, IndexError> {
+        let now = Utc::now();
+        let range = resolve_date(from, now)?..resolve_date(to, now)?;
+        Ok(Clause::DateRange(field.to_string(), range))
+    }
+}
+
+/// Documents matching every must clause, any should clause & none of the must_not clauses
+/// Should clauses only add to the score once there is a must clause, must_not alone matches nothing
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BoolQuery {
+    must: Vec<Clause>,
+    should: Vec<Clause>,
+    must_not: Vec<Clause>,
+}
+
+impl BoolQuery {
+    pub fn must(&mut self, clause: Clause) -> &mut Self {
+        self.must.push(clause);
+        self
+    }
+    pub fn should(&mut self, clause: Clause) -> &mut Self {
+        self.should.push(clause);
+        self
+    }
+    pub fn must_not(&mut self, clause: Clause) -> &mut Self {
+        self.must_not.push(clause);
+        self
+    }
+    pub fn is_empty(&self) -> bool {
+        self.must.is_empty() && self.should.is_empty() && self.must_not.is_empty()
+    }
+}
+
+/// Parses text clauses into queries
+pub(crate) type Parse<'a> = dyn Fn(&str) -> Result<Box<dyn Query>, IndexError> + 'a;
+
+/// Compiles to a tantivy BooleanQuery, text clauses go through the parser of the index
+pub(crate) fn bool_query(index: &Index, query: &BoolQuery, parse: &Parse) -> Result<BooleanQuery, IndexError> {
+    let mut clauses = Vec::with_capacity(query.must.len() + query.should.len() + query.must_not.len());
+    let occurs = [
+        (Occur::Must, &query.must),
+        (Occur::Should, &query.should),
+        (Occur::MustNot, &query.must_not),
+    ];
+    for (occur, group) in occurs.iter() {
+        for clause in group.iter() {
+            clauses.push((*occur, compile(index, clause, parse)?));
+        };
+    };
+    Ok(BooleanQuery::from(clauses))
+}
+
+fn compile(index: &Index, clause: &Clause, parse: &Parse) -> Result<Box<dyn Query>, IndexError> {
+    let schema = index.schema();
+    let query: Box<dyn Query> = match clause {
+        Clause::All => Box::new(AllQuery),
+        Clause::Term(field, value) => {
+            let term = as_term(&schema, field, value)?;
+            Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs))
+        }
+        Clause::Text(text) => parse(text)?,
+        Clause::Phrase(field, words) => {
+            let words: Vec<&str> = words.iter().map(|word| word.as_str()).collect();
+            Box::new(phrase_query(index, field, &words)?)
+        }
+        Clause::Fuzzy(field, term, distance) => Box::new(fuzzy_query(index, field, term, *distance)?),
+        Clause::Regex(field, pattern) => Box::new(regex_query(&schema, field, pattern)?),
+        Clause::Range(field, range) => Box::new(numeric_range_query(&schema, field, range.clone())?),
+        Clause::DateRange(field, range) => Box::new(date_range_query(&schema, field, range.clone())?),
+        Clause::Bool(inner) => Box::new(bool_query(index, inner, parse)?),
+    };
+    Ok(query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::collector::Count;
+    use tantivy::query::QueryParser;
+    use tantivy::schema::{SchemaBuilder, TEXT, STORED, INDEXED};
+    use tantivy::doc;
+
+    #[test]
+    fn validate_bool_query() {
+        let mut builder = SchemaBuilder::new();
+        let title = builder.add_text_field("title", TEXT | STORED);
+        let pages = builder.add_u64_field("pages", INDEXED | STORED);
+        let index = Index::create_in_ram(builder.build());
+        let mut writer = open_index_writer(&index).unwrap();
+        writer.add_document(doc!(title => "The Old Man and the Sea", pages => 127u64));
+        writer.add_document(doc!(title => "The Sea Wolf", pages => 300u64));
+        writer.add_document(doc!(title => "Moby Dick", pages => 635u64));
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let parser = QueryParser::for_index(&index, vec![title]);
+        let parse = |text: &str| -> Result<Box<dyn Query>, IndexError> { Ok(parser.parse_query(text)?) };
+        let count = |query: &BoolQuery| {
+            let query = bool_query(&index, query, &parse).unwrap();
+            searcher.search(&query, &Count).unwrap()
+        };
+
+        let mut query = BoolQuery::default();
+        query.must(Clause::text("sea"));
+        assert_eq!(count(&query), 2);
+        query.must_not(Clause::range("pages", 200u64..400u64));
+        assert_eq!(count(&query), 1);
+
+        let mut inner = BoolQuery::default();
+        inner.should(Clause::phrase("title", &["sea", "wolf"]))
+            .should(Clause::fuzzy("title", "dik", 1));
+        let mut query = BoolQuery::default();
+        query.must(Clause::Bool(inner));
+        assert_eq!(count(&query), 2);
+
+        let mut query = BoolQuery::default();
+        query.must(Clause::All).must_not(Clause::term("pages", &635u64));
+        assert_eq!(count(&query), 2);
+
+        let mut query = BoolQuery::default();
+        query.must(Clause::term("author", &"Melville"));
+        assert!(bool_query(&index, &query, &parse).is_err());
+    }
+}
@@ -0,0 +1,72 @@
+use tantivy::schema::{FieldType, Schema, SchemaBuilder, TEXT};
+use tantivy::Document;
+
+use crate::prelude::*;
+
+/// Indexed only text field gathering every text field of the document, searched by default when present
+/// Query strings cannot prefix it since field names start with a letter, leave the field out instead
+pub const ALL: &str = "_all";
+
+/// Schema along with the catch-all field
+pub(crate) fn with_catch_all(schema: &Schema) -> Schema {
+    if schema.get_field(ALL).is_some() {
+        return schema.clone();
+    };
+    let mut builder = SchemaBuilder::new();
+    for (_, entry) in schema.fields() {
+        builder.add_field(entry.clone());
+    };
+    builder.add_text_field(ALL, TEXT);
+    builder.build()
+}
+
+/// Copies the values of the text fields into the catch-all field when the schema has it
+pub(crate) fn stamp_catch_all(schema: &Schema, document: &mut Document) {
+    let all = match schema.get_field(ALL) {
+        Some(all) => all,
+        None => return,
+    };
+    let texts: Vec<String> = document.field_values()
+        .iter()
+        .filter(|field_value| {
+            let entry = schema.get_field_entry(field_value.field());
+            entry.name() != SOURCE && entry.name() != ALL && matches!(entry.field_type(), FieldType::Str(_))
+        })
+        .filter_map(|field_value| field_value.value().text().map(str::to_string))
+        .collect();
+    for text in texts {
+        document.add_text(all, &text);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::schema::{STORED, STRING};
+
+    #[test]
+    fn validate_catch_all() {
+        let mut builder = SchemaBuilder::new();
+        let title = builder.add_text_field("title", TEXT | STORED);
+        let isbn = builder.add_text_field("isbn", STRING);
+        let pages = builder.add_u64_field("pages", STORED);
+        let plain = builder.build();
+        let schema = with_catch_all(&with_source(&plain));
+        let entry = schema.get_field_entry(schema.get_field(ALL).unwrap());
+        assert!(entry.is_indexed() && !entry.is_stored());
+
+        let mut document = Document::default();
+        stamp_source(&schema, &mut document, &serde_json::json!({"title": "Dune"}));
+        document.add_text(title, "Dune");
+        document.add_text(isbn, "978-0441013593");
+        document.add_u64(pages, 412);
+        stamp_catch_all(&schema, &mut document);
+        let computed: Vec<&str> = document.get_all(schema.get_field(ALL).unwrap()).into_iter().filter_map(|value| value.text()).collect();
+        assert_eq!(computed, vec!["Dune", "978-0441013593"]);
+
+        let mut document = Document::default();
+        document.add_text(title, "Dune");
+        stamp_catch_all(&plain, &mut document);
+        assert_eq!(document.len(), 1);
+    }
+}
@@ -0,0 +1,127 @@
+use tantivy::tokenizer::{BoxTokenStream, Token, TokenStream, Tokenizer};
+
+/// Name of the tokenizer, use it as the tokenizer of Control text options or #[surf(tokenizer = "cjk")]
+pub const CJK_TOKENIZER: &str = "cjk";
+
+/// Splits Chinese, Japanese & Korean runs into overlapping bigrams & other text into words
+/// Needs no dictionary, phrase queries match the bigrams of the query in order
+#[derive(Clone, Debug, Default)]
+pub struct CjkTokenizer;
+
+impl Tokenizer for CjkTokenizer {
+    fn token_stream<'a>(&self, text: &'a str) -> BoxTokenStream<'a> {
+        BoxTokenStream::from(CjkTokenStream {
+            tokens: cjk_tokens(text),
+            cursor: 0,
+            token: Token::default(),
+        })
+    }
+}
+
+struct CjkTokenStream {
+    tokens: Vec<Token>,
+    cursor: usize,
+    token: Token,
+}
+
+impl TokenStream for CjkTokenStream {
+    fn advance(&mut self) -> bool {
+        if self.cursor >= self.tokens.len() {
+            return false;
+        };
+        self.token = self.tokens[self.cursor].clone();
+        self.cursor += 1;
+        true
+    }
+    fn token(&self) -> &Token {
+        &self.token
+    }
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+/// Han, kana & hangul
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x11FF | 0x3040..=0x30FF | 0x3130..=0x318F | 0x31F0..=0x31FF | 0x3400..=0x4DBF |
+        0x4E00..=0x9FFF | 0xAC00..=0xD7AF | 0xF900..=0xFAFF | 0xFF66..=0xFF9F | 0x20000..=0x2FA1F)
+}
+
+/// Bigrams of each CJK run, a lone CJK char as is, words for the rest
+fn cjk_tokens(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut push = |offset_from: usize, offset_to: usize| {
+        let position = tokens.len();
+        tokens.push(Token {
+            offset_from,
+            offset_to,
+            position,
+            text: text[offset_from..offset_to].to_string(),
+            position_length: 1,
+        });
+    };
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let end = |i: usize| chars.get(i).map_or(text.len(), |(offset, _)| *offset);
+    let mut i = 0;
+    while i < chars.len() {
+        let (offset, c) = chars[i];
+        if is_cjk(c) {
+            let mut j = i + 1;
+            while j < chars.len() && is_cjk(chars[j].1) {
+                j += 1;
+            };
+            if j - i == 1 {
+                push(offset, end(j));
+            };
+            for pair in chars[i..j].windows(2) {
+                let (last, c) = pair[1];
+                push(pair[0].0, last + c.len_utf8());
+            };
+            i = j;
+        } else if c.is_alphanumeric() {
+            let mut j = i + 1;
+            while j < chars.len() && chars[j].1.is_alphanumeric() && !is_cjk(chars[j].1) {
+                j += 1;
+            };
+            push(offset, end(j));
+            i = j;
+        } else {
+            i += 1;
+        };
+    };
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(text: &str) -> Vec<String> {
+        cjk_tokens(text).into_iter().map(|token| token.text).collect()
+    }
+
+    #[test]
+    fn validate_cjk_tokens() {
+        assert_eq!(texts("東京都に住む"), vec!["東京", "京都", "都に", "に住", "住む"]);
+        assert_eq!(texts("Tokyo 東京 2020"), vec!["Tokyo", "東京", "2020"]);
+        assert_eq!(texts("한국어 text"), vec!["한국", "국어", "text"]);
+        assert_eq!(texts("中 文"), vec!["中", "文"]);
+        assert!(texts(" 、。").is_empty());
+
+        let tokens = cjk_tokens("sea 北京");
+        assert_eq!(tokens[1].offset_from, 4);
+        assert_eq!(tokens[1].offset_to, 10);
+        assert_eq!(tokens[1].position, 1);
+    }
+
+    #[test]
+    fn validate_cjk_tokenizer() {
+        let mut stream = CjkTokenizer.token_stream("北京大学");
+        let mut computed = Vec::new();
+        while let Some(token) = stream.next() {
+            computed.push(token.text.clone());
+        };
+        assert_eq!(computed, vec!["北京", "京大", "大学"]);
+    }
+}
@@ -0,0 +1,204 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock, Weak};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use tantivy::directory::{AntiCallToken, Directory, DirectoryLock, Lock, MmapDirectory, ReadOnlySource, TerminatingWrite, WatchCallback, WatchHandle, WritePtr};
+use tantivy::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
+use tantivy::IndexWriter;
+
+use crate::prelude::*;
+
+/// When the writes of an index with auto commit are committed
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CommitPolicy {
+    /// Every write is searchable once it returns
+    EveryDoc,
+    /// Once that many documents are pending
+    EveryN(usize),
+    /// Pending writes are committed by a background timer
+    Interval(Duration),
+}
+
+/// Default impl commits every document
+impl Default for CommitPolicy {
+    fn default() -> Self {
+        CommitPolicy::EveryDoc
+    }
+}
+
+impl CommitPolicy {
+    /// Is a commit due on the writing thread with that many documents pending
+    pub(crate) fn is_due(&self, pending: usize) -> bool {
+        match self {
+            CommitPolicy::EveryDoc => true,
+            CommitPolicy::EveryN(n) => pending >= *n,
+            CommitPolicy::Interval(_) => false,
+        }
+    }
+}
+
+/// Does a commit wait for tantivy to fsync the segments & the meta file
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Durability {
+    /// Commit returns once the writes survive a crash & are searchable
+    Durable,
+    /// Commit returns once the writes are searchable, without fsync the OS flushes them to disk later
+    /// The writes survive the process crashing but not the machine
+    Deferred,
+}
+
+/// Default impl waits for the commit
+impl Default for Durability {
+    fn default() -> Self {
+        Durability::Durable
+    }
+}
+
+/// Mmap directory handing the writes to the OS without fsync, deferred indexes commit through it
+#[derive(Clone, Debug)]
+pub(crate) struct UnsyncedDirectory {
+    root: PathBuf,
+    inner: MmapDirectory,
+}
+
+impl UnsyncedDirectory {
+    pub(crate) fn open(root: PathBuf) -> Result<Self, IndexError> {
+        let inner = open_mmap_directory(root.clone())?;
+        Ok(Self {
+            root,
+            inner,
+        })
+    }
+}
+
+/// File flushed to the OS once terminated, never synced
+struct UnsyncedFile(File);
+
+impl Write for UnsyncedFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl TerminatingWrite for UnsyncedFile {
+    fn terminate_ref(&mut self, _: AntiCallToken) -> io::Result<()> {
+        self.flush()
+    }
+}
+
+impl Directory for UnsyncedDirectory {
+    fn open_read(&self, path: &Path) -> Result<ReadOnlySource, OpenReadError> {
+        self.inner.open_read(path)
+    }
+
+    fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+        self.inner.delete(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.inner.exists(path)
+    }
+
+    fn open_write(&mut self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(self.root.join(path))
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::AlreadyExists => OpenWriteError::FileAlreadyExists(path.to_owned()),
+                _ => OpenWriteError::IOError(e.into()),
+            })?;
+        Ok(BufWriter::new(Box::new(UnsyncedFile(file))))
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        self.inner.atomic_read(path)
+    }
+
+    /// Written aside then renamed, readers never see a partial meta.json
+    fn atomic_write(&mut self, path: &Path, data: &[u8]) -> io::Result<()> {
+        let target = self.root.join(path);
+        let file_name = target.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        let staged = self.root.join(format!(".{}.unsynced", file_name));
+        fs::write(&staged, data)?;
+        fs::rename(staged, target)
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        self.inner.acquire_lock(lock)
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> tantivy::Result<WatchHandle> {
+        self.inner.watch(watch_callback)
+    }
+}
+
+/// Commits the pending writes every interval, stops once the writer is dropped or a commit fails
+pub(crate) fn spawn_committer(name: &str, interval: Duration, writer: Weak<RwLock<IndexWriter>>, pending: Arc<AtomicUsize>) -> Result<(), IndexError> {
+    let commit = move || -> Result<bool, IndexError> {
+        let writer = match writer.upgrade() {
+            Some(writer) => writer,
+            None => return Ok(false),
+        };
+        if pending.swap(0, Ordering::SeqCst) > 0 {
+            writer.write()?.commit()?;
+        };
+        Ok(true)
+    };
+    thread::Builder::new()
+        .name(format!("committer-{}", name))
+        .spawn(move || loop {
+            thread::sleep(interval);
+            match commit() {
+                Ok(true) => continue,
+                _ => break,
+            };
+        })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_commit_policy() {
+        assert_eq!(CommitPolicy::default(), CommitPolicy::EveryDoc);
+        assert!(CommitPolicy::EveryDoc.is_due(1));
+        assert!(!CommitPolicy::EveryN(3).is_due(2));
+        assert!(CommitPolicy::EveryN(3).is_due(3));
+        assert!(!CommitPolicy::Interval(Duration::from_millis(10)).is_due(usize::MAX));
+        assert_eq!(Durability::default(), Durability::Durable);
+        assert_eq!(serde_json::from_str::<Durability>(r#""deferred""#).unwrap(), Durability::Deferred);
+    }
+
+    #[test]
+    fn validate_unsynced_directory() {
+        let root = PathBuf::from(format!("tmp/{}", random_string(None)));
+        let mut directory = UnsyncedDirectory::open(root.clone()).unwrap();
+
+        directory.atomic_write(Path::new("meta.json"), b"{}").unwrap();
+        directory.atomic_write(Path::new("meta.json"), b"[]").unwrap();
+        assert_eq!(directory.atomic_read(Path::new("meta.json")).unwrap(), b"[]".to_vec());
+        assert!(!root.join(".meta.json.unsynced").exists());
+
+        let mut writer = directory.open_write(Path::new("segment")).unwrap();
+        writer.write_all(b"data").unwrap();
+        writer.terminate().unwrap();
+        assert_eq!(directory.open_read(Path::new("segment")).unwrap().as_slice(), b"data");
+        assert!(matches!(directory.open_write(Path::new("segment")), Err(OpenWriteError::FileAlreadyExists(_))));
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
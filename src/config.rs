@@ -0,0 +1,378 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use tantivy::schema::{Cardinality, IndexRecordOption, IntOptions, Schema, SchemaBuilder, TextFieldIndexing, TextOptions};
+
+use crate::prelude::*;
+
+/// Declarative definition of the indexes of a builder, read from a JSON file - valid YAML as well
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SurferConfig {
+    home: Option<String>,
+    lazy: bool,
+    indexes: Vec<IndexConfig>,
+}
+
+/// Index along with its fields & options
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IndexConfig {
+    name: String,
+    fields: Vec<FieldConfig>,
+    #[serde(default)]
+    primary_key: Option<String>,
+    #[serde(default)]
+    default_search_fields: Option<Vec<String>>,
+    #[serde(default)]
+    auto_commit: Option<bool>,
+    /// Commits once that many documents are pending instead of every document
+    #[serde(default)]
+    commit_every: Option<usize>,
+    /// Commits pending writes from a background timer instead
+    #[serde(default)]
+    commit_interval_ms: Option<u64>,
+    /// Durable or deferred, see Durability
+    #[serde(default)]
+    durability: Option<Durability>,
+    #[serde(default)]
+    conjunction_by_default: Option<bool>,
+    /// Skip, default or reject null & missing fields, see NullPolicy
+    #[serde(default)]
+    null_policy: Option<NullPolicy>,
+    /// Converts strings & numbers to the type of their field e.g. "42" into a u64
+    #[serde(default)]
+    coerce: bool,
+    #[serde(default)]
+    in_memory: bool,
+    #[serde(default)]
+    stopwords: Option<StopwordsConfig>,
+    /// Rules e.g. tv, television or laptop => notebook, see Synonyms::parse
+    #[serde(default)]
+    synonyms: Option<Vec<String>>,
+    #[serde(default)]
+    index_synonyms: bool,
+    #[serde(default)]
+    ttl_secs: Option<u64>,
+    #[serde(default)]
+    store_source: bool,
+    #[serde(default)]
+    catch_all: bool,
+    #[serde(default)]
+    default_limit: Option<usize>,
+    #[serde(default)]
+    max_limit: Option<usize>,
+}
+
+/// Field of an index, indexed & stored unless told otherwise
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FieldConfig {
+    name: String,
+    #[serde(rename = "type")]
+    kind: FieldKind,
+    #[serde(default = "enabled")]
+    indexed: bool,
+    #[serde(default = "enabled")]
+    stored: bool,
+    #[serde(default)]
+    fast: bool,
+    #[serde(default)]
+    multi: bool,
+    #[serde(default)]
+    tokenizer: Option<String>,
+    #[serde(default)]
+    boost: Option<f32>,
+    /// JSON pointer the value is read from e.g. /user/address/city
+    #[serde(default)]
+    pointer: Option<String>,
+    /// Value indexed when null or missing under the default null policy
+    #[serde(default)]
+    default: Option<JsonValue>,
+    /// Coerced or strict whatever the index does
+    #[serde(default)]
+    coerce: Option<bool>,
+}
+
+/// Type of a field, string is indexed untokenized e.g. ids & keywords
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldKind {
+    Text,
+    String,
+    U64,
+    I64,
+    F64,
+    Date,
+    Bool,
+    Facet,
+}
+
+/// A built-in language e.g. english or an own list of words
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum StopwordsConfig {
+    Language(String),
+    Words(Vec<String>),
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl SurferConfig {
+    /// Reads the config file
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, IndexError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        serde_json::from_str(&text).map_err(|e| {
+            let message = format!("Unable to read config: {:?}", path);
+            IndexError::new(message, e.to_string())
+        })
+    }
+    /// Builder with every index declared, validated field by field
+    pub fn builder(&self) -> Result<SurferBuilder, IndexError> {
+        let mut builder = SurferBuilder::default();
+        if let Some(home) = &self.home {
+            builder.set_home(home);
+        };
+        builder.set_lazy(self.lazy);
+        for index in &self.indexes {
+            index.declare(&mut builder)?;
+        };
+        Ok(builder)
+    }
+}
+
+impl IndexConfig {
+    fn declare(&self, builder: &mut SurferBuilder) -> Result<(), IndexError> {
+        let name = self.name.as_str();
+        let invalid = |reason: String| IndexError::new(format!("Unable to configure index: {}", name), reason);
+        if name.is_empty() {
+            return Err(invalid("Index name is empty".to_string()));
+        };
+        let mut schema_builder = SchemaBuilder::new();
+        let mut names = HashSet::new();
+        let mut text_seqs = HashSet::new();
+        let mut bools = HashSet::new();
+        for field in &self.fields {
+            if !names.insert(field.name.as_str()) {
+                return Err(invalid(format!("Field: {} is declared twice", field.name)));
+            };
+            field.add_to(&mut schema_builder).map_err(invalid)?;
+            if field.multi && field.kind == FieldKind::Text {
+                text_seqs.insert(field.name.clone());
+            };
+            if field.kind == FieldKind::Bool {
+                bools.insert(field.name.clone());
+            };
+            if let Some(boost) = field.boost {
+                builder.set_field_boost(name, &field.name, boost);
+            };
+            if let Some(pointer) = &field.pointer {
+                if !pointer.starts_with('/') {
+                    return Err(invalid(format!("Pointer: {} of field: {} does not start with /", pointer, field.name)));
+                };
+                builder.add_json_pointer(name, pointer, &field.name);
+            };
+            if let Some(value) = &field.default {
+                builder.set_null_default(name, &field.name, value.clone());
+            };
+            if let Some(coerce) = field.coerce {
+                builder.set_field_coercion(name, &field.name, coerce);
+            };
+        };
+        let schema: Schema = schema_builder.build();
+        for field in self.primary_key.iter().chain(self.default_search_fields.iter().flatten()) {
+            if schema.get_field(field).is_none() {
+                return Err(invalid(format!("Field: {} is not declared", field)));
+            };
+        };
+        builder.add_declared_schema(name, schema, text_seqs, bools);
+
+        if let Some(primary_key) = &self.primary_key {
+            builder.set_primary_key(name, primary_key);
+        };
+        if let Some(fields) = &self.default_search_fields {
+            let fields: Vec<&str> = fields.iter().map(String::as_str).collect();
+            builder.set_default_search_fields(name, &fields);
+        };
+        if let Some(auto_commit) = self.auto_commit {
+            builder.set_auto_commit(name, auto_commit);
+        };
+        match (self.commit_every, self.commit_interval_ms) {
+            (Some(_), Some(_)) => return Err(invalid("Commit every & commit interval are exclusive".to_string())),
+            (Some(docs), None) => builder.set_commit_policy(name, CommitPolicy::EveryN(docs)),
+            (None, Some(ms)) => builder.set_commit_policy(name, CommitPolicy::Interval(Duration::from_millis(ms))),
+            (None, None) => {}
+        };
+        if let Some(durability) = self.durability {
+            builder.set_durability(name, durability);
+        };
+        if let Some(policy) = self.null_policy {
+            builder.set_null_policy(name, policy);
+        };
+        if self.coerce {
+            builder.set_coercion(name, true);
+        };
+        if let Some(conjunction) = self.conjunction_by_default {
+            builder.set_conjunction_by_default(name, conjunction);
+        };
+        if self.in_memory {
+            builder.set_in_memory(name, true);
+        };
+        if let Some(stopwords) = &self.stopwords {
+            builder.set_stopwords(name, stopwords.stopwords().map_err(invalid)?);
+        };
+        if let Some(rules) = &self.synonyms {
+            builder.set_synonyms(name, Synonyms::parse(&rules.join("\n"))?, self.index_synonyms);
+        };
+        if let Some(ttl_secs) = self.ttl_secs {
+            builder.set_ttl(name, Duration::from_secs(ttl_secs));
+        };
+        if self.store_source {
+            builder.set_store_source(name, true);
+        };
+        if self.catch_all {
+            builder.set_catch_all(name, true);
+        };
+        if let Some(limit) = self.default_limit {
+            builder.set_default_limit(name, limit);
+        };
+        if let Some(limit) = self.max_limit {
+            builder.set_max_limit(name, limit);
+        };
+        Ok(())
+    }
+}
+
+impl FieldConfig {
+    fn add_to(&self, builder: &mut SchemaBuilder) -> Result<(), String> {
+        let name = self.name.as_str();
+        if self.tokenizer.is_some() && self.kind != FieldKind::Text {
+            return Err(format!("Field: {} is not text & takes no tokenizer", name));
+        };
+        if self.fast && matches!(self.kind, FieldKind::Text | FieldKind::String | FieldKind::Facet) {
+            return Err(format!("Field: {} is not numeric & cannot be fast", name));
+        };
+        match self.kind {
+            FieldKind::Text | FieldKind::String => {
+                let (tokenizer, record) = match self.kind {
+                    FieldKind::Text => (self.tokenizer.as_deref().unwrap_or("default"), IndexRecordOption::WithFreqsAndPositions),
+                    _ => ("raw", IndexRecordOption::Basic),
+                };
+                let mut options = TextOptions::default();
+                if self.indexed {
+                    let indexing = TextFieldIndexing::default().set_tokenizer(tokenizer).set_index_option(record);
+                    options = options.set_indexing_options(indexing);
+                };
+                if self.stored {
+                    options = options.set_stored();
+                };
+                builder.add_text_field(name, options);
+            }
+            FieldKind::Facet => {
+                builder.add_facet_field(name);
+            }
+            _ => {
+                let mut options = IntOptions::default();
+                if self.indexed {
+                    options = options.set_indexed();
+                };
+                if self.stored {
+                    options = options.set_stored();
+                };
+                if self.fast {
+                    options = options.set_fast(Cardinality::SingleValue);
+                };
+                match self.kind {
+                    FieldKind::I64 => builder.add_i64_field(name, options),
+                    FieldKind::F64 => builder.add_f64_field(name, options),
+                    FieldKind::Date => builder.add_date_field(name, options),
+                    _ => builder.add_u64_field(name, options),
+                };
+            }
+        };
+        Ok(())
+    }
+}
+
+impl StopwordsConfig {
+    fn stopwords(&self) -> Result<Stopwords, String> {
+        let language = match self {
+            StopwordsConfig::Words(words) => return Ok(Stopwords::Custom(words.clone())),
+            StopwordsConfig::Language(language) => language.to_lowercase(),
+        };
+        match language.as_str() {
+            "english" | "en" => Ok(Stopwords::English),
+            "french" | "fr" => Ok(Stopwords::French),
+            "german" | "de" => Ok(Stopwords::German),
+            "spanish" | "es" => Ok(Stopwords::Spanish),
+            "italian" | "it" => Ok(Stopwords::Italian),
+            "portuguese" | "pt" => Ok(Stopwords::Portuguese),
+            "dutch" | "nl" => Ok(Stopwords::Dutch),
+            _ => Err(format!("Stopwords: {} are not built-in, list the words instead", language)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(text: &str) -> SurferConfig {
+        serde_json::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn validate_field_config() {
+        let config = config(r#"{
+            "indexes": [{
+                "name": "books",
+                "fields": [
+                    {"name": "isbn", "type": "string"},
+                    {"name": "title", "type": "text", "tokenizer": "en_stem", "boost": 2.0},
+                    {"name": "pages", "type": "u64", "fast": true},
+                    {"name": "published", "type": "date", "stored": false},
+                    {"name": "category", "type": "facet"}
+                ],
+                "primary_key": "isbn",
+                "stopwords": "english",
+                "synonyms": ["sf, scifi"],
+                "durability": "deferred"
+            }]
+        }"#);
+        let mut builder = SchemaBuilder::new();
+        for field in &config.indexes[0].fields {
+            field.add_to(&mut builder).unwrap();
+        };
+        let schema = builder.build();
+        let entry = |name: &str| schema.get_field_entry(schema.get_field(name).unwrap()).clone();
+        assert!(entry("isbn").is_indexed() && entry("isbn").is_stored());
+        assert!(entry("pages").is_int_fast());
+        assert!(entry("published").is_indexed() && !entry("published").is_stored());
+        assert_eq!(config.indexes[0].stopwords.as_ref().unwrap().stopwords(), Ok(Stopwords::English));
+        assert!(config.builder().is_ok());
+    }
+
+    #[test]
+    fn validate_invalid_config() {
+        let invalid = |fields: &str| {
+            let text = format!(r#"{{"indexes": [{{"name": "books", "fields": {}}}]}}"#, fields);
+            config(&text).builder().is_err()
+        };
+        assert!(invalid(r#"[{"name": "a", "type": "text"}, {"name": "a", "type": "u64"}]"#));
+        assert!(invalid(r#"[{"name": "a", "type": "u64", "tokenizer": "raw"}]"#));
+        assert!(invalid(r#"[{"name": "a", "type": "text", "fast": true}]"#));
+        assert!(invalid(r#"[{"name": "a", "type": "text", "pointer": "a/b"}]"#));
+        assert!(!invalid(r#"[{"name": "a", "type": "text", "pointer": "/a/b"}]"#));
+        assert!(!invalid(r#"[{"name": "a", "type": "u64", "default": 0, "coerce": false}]"#));
+        assert!(!invalid(r#"[{"name": "a", "type": "text"}]"#));
+        let text = r#"{"indexes": [{"name": "books", "fields": [], "commit_every": 10, "commit_interval_ms": 100}]}"#;
+        assert!(config(text).builder().is_err());
+        assert!(serde_json::from_str::<SurferConfig>(r#"{"indexes": [{"name": "a", "fields": [{"name": "a", "type": "blob"}]}]}"#).is_err());
+        assert!(StopwordsConfig::Language("klingon".to_string()).stopwords().is_err());
+    }
+}
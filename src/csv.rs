@@ -0,0 +1,163 @@
+use std::io::BufRead;
+
+use tantivy::schema::{FieldType, Schema};
+
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+use crate::prelude::*;
+
+/// Records of RFC 4180 CSV, quoted cells may hold commas, quotes as "" & line breaks
+pub(crate) struct Records<R> {
+    reader: R,
+    line: usize,
+}
+
+impl<R: BufRead> Records<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: 0,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+    /// Line the record starts at along with its cells
+    type Item = Result<(usize, Vec<String>), IndexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut record = String::new();
+        let mut start = self.line + 1;
+        loop {
+            let mut line = String::new();
+            match self.reader.read_line(&mut line) {
+                Ok(0) if record.is_empty() => return None,
+                Ok(0) => {
+                    let reason = format!("Line: {} has an unterminated quote", start);
+                    return Some(Err(IndexError::new("Unable to read csv", &reason)));
+                }
+                Ok(_) => self.line += 1,
+                Err(e) => return Some(Err(e.into())),
+            };
+            if record.is_empty() && line.trim().is_empty() {
+                start = self.line + 1;
+                continue;
+            };
+            record.push_str(&line);
+            if record.matches('"').count() & 1 == 0 {
+                break;
+            };
+        };
+        let record = record.trim_end_matches(['\n', '\r']);
+        Some(Ok((start, split(record))))
+    }
+}
+
+fn split(record: &str) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = record.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                cell.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => cells.push(std::mem::take(&mut cell)),
+            c => cell.push(c),
+        };
+    };
+    cells.push(cell);
+    cells
+}
+
+/// Every header must name a field of the schema
+pub(crate) fn validate_headers(schema: &Schema, headers: &[String]) -> Result<(), IndexError> {
+    for header in headers {
+        if schema.get_field(header).is_none() {
+            let reason = format!("Column: {} is not a field", header);
+            return Err(IndexError::schema_mismatch("Unable to read csv", &reason));
+        };
+    };
+    Ok(())
+}
+
+/// Cells coerced to the types of the fields named by the headers, empty cells are left out
+pub(crate) fn as_json(schema: &Schema, headers: &[String], cells: Vec<String>) -> Result<JsonValue, IndexError> {
+    if cells.len() != headers.len() {
+        let reason = format!("Expected: {} cells found: {}", headers.len(), cells.len());
+        return Err(IndexError::new("Unable to read csv", &reason));
+    };
+    let mut data = JsonMap::new();
+    for (header, cell) in headers.iter().zip(cells) {
+        if cell.is_empty() {
+            continue;
+        };
+        let value = coerce(schema, header, cell)?;
+        data.insert(header.to_string(), value);
+    };
+    Ok(JsonValue::Object(data))
+}
+
+fn coerce(schema: &Schema, header: &str, cell: String) -> Result<JsonValue, IndexError> {
+    let field = schema.get_field(header).unwrap();
+    let invalid = |e: String| {
+        let reason = format!("Column: {} {}", header, e);
+        IndexError::schema_mismatch("Unable to coerce cell", &reason)
+    };
+    let value = match schema.get_field_entry(field).field_type() {
+        FieldType::U64(_) => match cell.as_str() {
+            "true" => JsonValue::from(1u64),
+            "false" => JsonValue::from(0u64),
+            _ => JsonValue::from(cell.trim().parse::<u64>().map_err(|e| invalid(e.to_string()))?),
+        },
+        FieldType::I64(_) => JsonValue::from(cell.trim().parse::<i64>().map_err(|e| invalid(e.to_string()))?),
+        FieldType::F64(_) => JsonValue::from(cell.trim().parse::<f64>().map_err(|e| invalid(e.to_string()))?),
+        _ => JsonValue::String(cell),
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::schema::{SchemaBuilder, TEXT, STORED, INDEXED};
+
+    #[test]
+    fn validate_records() {
+        let csv = "title,body\n\"Sea, the \"\"Old\"\" Man\",\"Gulf\nStream\"\n\nMoby Dick,\r\n";
+        let computed: Vec<(usize, Vec<String>)> = Records::new(csv.as_bytes()).map(|record| record.unwrap()).collect();
+        let expected = vec![
+            (1, vec!["title".to_string(), "body".to_string()]),
+            (2, vec!["Sea, the \"Old\" Man".to_string(), "Gulf\nStream".to_string()]),
+            (5, vec!["Moby Dick".to_string(), "".to_string()]),
+        ];
+        assert_eq!(computed, expected);
+
+        let mut records = Records::new("\"unterminated\n".as_bytes());
+        assert!(records.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn validate_as_json() {
+        let mut builder = SchemaBuilder::new();
+        builder.add_text_field("title", TEXT | STORED);
+        builder.add_u64_field("pages", INDEXED | STORED);
+        builder.add_f64_field("price", INDEXED | STORED);
+        let schema = builder.build();
+
+        let headers = vec!["title".to_string(), "pages".to_string(), "price".to_string()];
+        assert!(validate_headers(&schema, &headers).is_ok());
+        assert!(validate_headers(&schema, &["author".to_string()]).is_err());
+
+        let cells = vec!["The Sea Wolf".to_string(), "300".to_string(), "".to_string()];
+        let computed = as_json(&schema, &headers, cells).unwrap();
+        assert_eq!(computed, serde_json::json!({"title": "The Sea Wolf", "pages": 300}));
+
+        let cells = vec!["The Sea Wolf".to_string(), "many".to_string(), "9.5".to_string()];
+        assert!(as_json(&schema, &headers, cells).is_err());
+        assert!(as_json(&schema, &headers, vec!["The Sea Wolf".to_string()]).is_err());
+    }
+}
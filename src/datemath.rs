@@ -0,0 +1,107 @@
+use chrono::{DateTime, Datelike, Duration, Months, TimeZone, Timelike, Utc};
+
+use crate::prelude::*;
+
+/// Resolves a date math expression against now e.g. now-7d, now/d, 2020-01-01T00:00:00Z||+1M
+/// Units are y, M, w, d, h, m & s - a slash rounds down to the start of the unit
+pub fn resolve_date(expression: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, IndexError> {
+    let expression = expression.trim();
+    let invalid = |reason: &str| {
+        let reason = format!("Expression: {} {}", expression, reason);
+        IndexError::new("Unable to resolve date", &reason)
+    };
+    let (anchor, mut math) = if let Some(math) = expression.strip_prefix("now") {
+        (now, math)
+    } else {
+        let (anchor, math) = match expression.find("||") {
+            Some(i) => (&expression[..i], &expression[i + 2..]),
+            None => (expression, ""),
+        };
+        let anchor = DateTime::parse_from_rfc3339(anchor)
+            .map_err(|_| invalid("does not start with now or an RFC3339 date"))?;
+        (anchor.with_timezone(&Utc), math)
+    };
+
+    let mut date = anchor;
+    while !math.is_empty() {
+        let op = math.chars().next().unwrap();
+        math = &math[op.len_utf8()..];
+        let digits = math.chars().take_while(|c| c.is_ascii_digit()).count();
+        let amount: u32 = match (op, digits) {
+            ('/', 0) => 0,
+            ('+', 0) | ('-', 0) => 1,
+            ('+', _) | ('-', _) => math[..digits].parse().map_err(|_| invalid("has too large an amount"))?,
+            _ => return Err(invalid("expects +, - or / before a unit")),
+        };
+        math = &math[digits..];
+        let unit = math.chars().next().ok_or_else(|| invalid("ends without a unit"))?;
+        math = &math[unit.len_utf8()..];
+        date = match op {
+            '/' => round_down(date, unit).ok_or_else(|| invalid("has an unknown unit"))?,
+            '+' => shift(date, amount, unit, false).ok_or_else(|| invalid("is out of range or has an unknown unit"))?,
+            _ => shift(date, amount, unit, true).ok_or_else(|| invalid("is out of range or has an unknown unit"))?,
+        };
+    };
+    Ok(date)
+}
+
+fn shift(date: DateTime<Utc>, amount: u32, unit: char, back: bool) -> Option<DateTime<Utc>> {
+    let months = match unit {
+        'y' => Some(Months::new(amount.checked_mul(12)?)),
+        'M' => Some(Months::new(amount)),
+        _ => None,
+    };
+    if let Some(months) = months {
+        return if back { date.checked_sub_months(months) } else { date.checked_add_months(months) };
+    };
+    let amount = i64::from(amount);
+    let duration = match unit {
+        'w' => Duration::weeks(amount),
+        'd' => Duration::days(amount),
+        'h' => Duration::hours(amount),
+        'm' => Duration::minutes(amount),
+        's' => Duration::seconds(amount),
+        _ => return None,
+    };
+    if back { date.checked_sub_signed(duration) } else { date.checked_add_signed(duration) }
+}
+
+/// Start of the year, month, week from monday, day, hour, minute or second
+fn round_down(date: DateTime<Utc>, unit: char) -> Option<DateTime<Utc>> {
+    let day = |date: DateTime<Utc>| Utc.with_ymd_and_hms(date.year(), date.month(), date.day(), 0, 0, 0).single();
+    match unit {
+        'y' => Utc.with_ymd_and_hms(date.year(), 1, 1, 0, 0, 0).single(),
+        'M' => Utc.with_ymd_and_hms(date.year(), date.month(), 1, 0, 0, 0).single(),
+        'w' => day(date - Duration::days(i64::from(date.weekday().num_days_from_monday()))),
+        'd' => day(date),
+        'h' => Utc.with_ymd_and_hms(date.year(), date.month(), date.day(), date.hour(), 0, 0).single(),
+        'm' => Utc.with_ymd_and_hms(date.year(), date.month(), date.day(), date.hour(), date.minute(), 0).single(),
+        's' => date.with_nanosecond(0),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_resolve_date() {
+        let now = Utc.with_ymd_and_hms(2020, 3, 31, 15, 42, 7).unwrap();
+        assert_eq!(resolve_date("now", now).unwrap(), now);
+        assert_eq!(resolve_date("now-7d", now).unwrap(), Utc.with_ymd_and_hms(2020, 3, 24, 15, 42, 7).unwrap());
+        assert_eq!(resolve_date("now+1h-30m", now).unwrap(), Utc.with_ymd_and_hms(2020, 3, 31, 16, 12, 7).unwrap());
+        assert_eq!(resolve_date("now-1M", now).unwrap(), Utc.with_ymd_and_hms(2020, 2, 29, 15, 42, 7).unwrap());
+        assert_eq!(resolve_date("now-1y/M", now).unwrap(), Utc.with_ymd_and_hms(2019, 3, 1, 0, 0, 0).unwrap());
+        assert_eq!(resolve_date("now/w", now).unwrap(), Utc.with_ymd_and_hms(2020, 3, 30, 0, 0, 0).unwrap());
+        assert_eq!(resolve_date("now/d+1d", now).unwrap(), Utc.with_ymd_and_hms(2020, 4, 1, 0, 0, 0).unwrap());
+        assert_eq!(resolve_date("2020-01-01T00:00:00Z", now).unwrap(), Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap());
+        assert_eq!(resolve_date("2020-01-31T00:00:00Z||+1M", now).unwrap(), Utc.with_ymd_and_hms(2020, 2, 29, 0, 0, 0).unwrap());
+
+        assert!(resolve_date("yesterday", now).is_err());
+        assert!(resolve_date("now-7", now).is_err());
+        assert!(resolve_date("now-7x", now).is_err());
+        assert!(resolve_date("now*2d", now).is_err());
+        assert!(resolve_date("now→2d", now).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use tantivy::collector::{Collector, SegmentCollector};
+use tantivy::query::Query;
+use tantivy::schema::{Field, Schema, Value};
+use tantivy::{DocId, Score, Searcher, SegmentLocalId, SegmentReader};
+
+use crate::prelude::*;
+
+/// Unique value of a field along with the number of matching documents holding it
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DistinctValue {
+    value: JsonValue,
+    count: u64,
+}
+
+impl DistinctValue {
+    pub fn new(value: JsonValue, count: u64) -> Self {
+        Self {
+            value,
+            count,
+        }
+    }
+    pub fn value(&self) -> &JsonValue {
+        &self.value
+    }
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+/// Counts keyed by the serialized value
+type Counts = HashMap<String, DistinctValue>;
+
+type ValueReader = Box<dyn Fn(DocId) -> Vec<JsonValue> + Send>;
+
+/// Counts the values of a single valued fast field or of a stored field
+struct DistinctCollector {
+    field: Field,
+}
+
+struct DistinctSegmentCollector {
+    values: ValueReader,
+    counts: Counts,
+}
+
+impl Collector for DistinctCollector {
+    type Fruit = Counts;
+    type Child = DistinctSegmentCollector;
+
+    fn for_segment(&self, _: SegmentLocalId, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        Ok(DistinctSegmentCollector {
+            values: value_reader(segment_reader, self.field),
+            counts: Counts::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<Counts>) -> tantivy::Result<Counts> {
+        let mut counts = Counts::new();
+        for (key, distinct) in segment_fruits.into_iter().flatten() {
+            counts.entry(key)
+                .and_modify(|current| current.count += distinct.count)
+                .or_insert(distinct);
+        };
+        Ok(counts)
+    }
+}
+
+impl SegmentCollector for DistinctSegmentCollector {
+    type Fruit = Counts;
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let mut seen = Vec::new();
+        for value in (self.values)(doc) {
+            let key = value.to_string();
+            if seen.contains(&key) {
+                continue;
+            };
+            self.counts.entry(key.clone())
+                .and_modify(|current| current.count += 1)
+                .or_insert_with(|| DistinctValue::new(value, 1));
+            seen.push(key);
+        };
+    }
+
+    fn harvest(self) -> Counts {
+        self.counts
+    }
+}
+
+/// Reads the value of the single valued fast field, falls back to the stored values
+fn value_reader(segment_reader: &SegmentReader, field: Field) -> ValueReader {
+    let fast_fields = segment_reader.fast_fields();
+    if let Some(reader) = fast_fields.u64(field) {
+        return Box::new(move |doc| vec![JsonValue::from(reader.get(doc))]);
+    };
+    if let Some(reader) = fast_fields.i64(field) {
+        return Box::new(move |doc| vec![JsonValue::from(reader.get(doc))]);
+    };
+    if let Some(reader) = fast_fields.f64(field) {
+        return Box::new(move |doc| vec![JsonValue::from(reader.get(doc))]);
+    };
+    if let Some(reader) = fast_fields.date(field) {
+        return Box::new(move |doc| vec![JsonValue::String(reader.get(doc).to_rfc3339())]);
+    };
+    let store = segment_reader.get_store_reader();
+    Box::new(move |doc| {
+        let doc = match store.get(doc) {
+            Ok(doc) => doc,
+            Err(_) => return Vec::new(),
+        };
+        doc.get_all(field)
+            .into_iter()
+            .filter_map(|value| match value {
+                Value::Facet(facet) => Some(JsonValue::String(facet_path(facet))),
+                value => serde_json::to_value(value).ok(),
+            })
+            .collect()
+    })
+}
+
+/// Unique values of the field among the documents matching the query, most documents first
+pub(crate) fn distinct_values(searcher: &Searcher, query: &dyn Query, schema: &Schema, field_name: &str, limit: usize) -> Result<Vec<DistinctValue>, IndexError> {
+    let field = schema.get_field(field_name);
+    if field.is_none() {
+        let reason = format!("Field: {} does not exist", field_name);
+        return Err(IndexError::schema_mismatch("Unable to find distinct values", &reason));
+    };
+    let field = field.unwrap();
+    let entry = schema.get_field_entry(field);
+    if !entry.is_int_fast() && !entry.is_stored() {
+        let reason = format!("Field: {} is neither a fast nor a stored field", field_name);
+        return Err(IndexError::schema_mismatch("Unable to find distinct values", &reason));
+    };
+    let counts = searcher.search(query, &DistinctCollector { field })?;
+    let mut values: Vec<(String, DistinctValue)> = counts.into_iter().collect();
+    values.sort_by(|(a_key, a), (b_key, b)| b.count.cmp(&a.count).then_with(|| a_key.cmp(b_key)));
+    Ok(values.into_iter().take(limit).map(|(_, distinct)| distinct).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tantivy::query::{AllQuery, TermQuery};
+    use tantivy::schema::{IndexRecordOption, SchemaBuilder, FAST, STORED, STRING, TEXT};
+    use tantivy::{doc, Index, Term};
+
+    #[test]
+    fn validate_distinct_values() {
+        let mut builder = SchemaBuilder::new();
+        let brand = builder.add_text_field("brand", STRING | STORED);
+        let tags = builder.add_text_field("tags", TEXT | STORED);
+        let price = builder.add_u64_field("price", FAST);
+        let title = builder.add_text_field("title", TEXT);
+        let schema = builder.build();
+        let index = Index::create_in_ram(schema.clone());
+        let mut writer = open_index_writer(&index).unwrap();
+        writer.add_document(doc!(brand => "Acme", tags => "new", tags => "new", price => 10u64, title => "anvil"));
+        writer.add_document(doc!(brand => "Acme", tags => "sale", price => 20u64, title => "rocket"));
+        writer.add_document(doc!(brand => "Globex", tags => "new", price => 10u64, title => "rocket"));
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let computed = distinct_values(&searcher, &AllQuery, &schema, "brand", 10).unwrap();
+        assert_eq!(computed, vec![DistinctValue::new(json!("Acme"), 2), DistinctValue::new(json!("Globex"), 1)]);
+        let computed = distinct_values(&searcher, &AllQuery, &schema, "tags", 10).unwrap();
+        assert_eq!(computed, vec![DistinctValue::new(json!("new"), 2), DistinctValue::new(json!("sale"), 1)]);
+        let computed = distinct_values(&searcher, &AllQuery, &schema, "price", 1).unwrap();
+        assert_eq!(computed, vec![DistinctValue::new(json!(10), 2)]);
+
+        let query = TermQuery::new(Term::from_field_text(title, "rocket"), IndexRecordOption::Basic);
+        let computed = distinct_values(&searcher, &query, &schema, "brand", 10).unwrap();
+        assert_eq!(computed, vec![DistinctValue::new(json!("Acme"), 1), DistinctValue::new(json!("Globex"), 1)]);
+        assert!(distinct_values(&searcher, &AllQuery, &schema, "title", 10).is_err());
+        assert!(distinct_values(&searcher, &AllQuery, &schema, "missing", 10).is_err());
+    }
+}
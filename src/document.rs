@@ -0,0 +1,248 @@
+use std::any::type_name;
+use std::convert::TryFrom;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde_json::Value as JsonValue;
+
+use tantivy::schema::{Schema, Value};
+use tantivy::Document;
+
+use crate::prelude::*;
+
+/// Stored document read back from an index, handed to FromDocument
+pub struct StoredDocument<'a> {
+    surfer: &'a Surfer,
+    name: &'a str,
+    schema: Schema,
+    document: &'a Document,
+    select: Option<&'a [String]>,
+    prefix: String,
+}
+
+impl<'a> StoredDocument<'a> {
+    pub(crate) fn new(surfer: &'a Surfer, name: &'a str, schema: Schema, document: &'a Document, select: Option<&'a [String]>) -> Self {
+        Self {
+            surfer,
+            name,
+            schema,
+            document,
+            select,
+            prefix: String::new(),
+        }
+    }
+    /// Name of the index the document was read from
+    pub fn name(&self) -> &str {
+        self.name
+    }
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+    pub fn document(&self) -> &Document {
+        self.document
+    }
+    /// Dotted name of the field within the nested structs
+    pub fn path(&self, field: &str) -> String {
+        format!("{}{}", self.prefix, field)
+    }
+    /// First stored value of the field, None if the field is unknown or not stored
+    pub fn get(&self, field: &str) -> Option<&Value> {
+        self.document.get_first(self.schema.get_field(&self.path(field))?)
+    }
+    /// Every stored value of the field
+    pub fn get_all(&self, field: &str) -> Vec<&Value> {
+        match self.schema.get_field(&self.path(field)) {
+            Some(field) => self.document.get_all(field),
+            None => Vec::new(),
+        }
+    }
+    /// Fields of the nested struct e.g. city of address.city
+    pub fn nested(&self, field: &str) -> StoredDocument<'a> {
+        StoredDocument {
+            surfer: self.surfer,
+            name: self.name,
+            schema: self.schema.clone(),
+            document: self.document,
+            select: self.select,
+            prefix: format!("{}.", self.path(field)),
+        }
+    }
+    /// Does the document hold no value within the nested struct
+    pub fn is_empty(&self) -> bool {
+        !self.document.field_values().iter().any(|value| self.schema.get_field_name(value.field()).starts_with(&self.prefix))
+    }
+    /// Same JSON the documents are deserialized from by read_structs, only the nested struct once nested
+    pub fn to_json(&self) -> Result<JsonValue, IndexError> {
+        let data = self.surfer.valueify_selected(self.name, self.document, self.select)?;
+        if self.prefix.is_empty() {
+            return Ok(data);
+        };
+        let pointer = format!("/{}", self.prefix.trim_end_matches('.').replace('.', "/"));
+        Ok(data.pointer(&pointer).cloned().unwrap_or(JsonValue::Null))
+    }
+    /// Structs of the nested sequence e.g. items of Vec<LineItem>, read through JSON, empty when missing
+    pub fn items<T: DeserializeOwned>(&self) -> Result<Vec<T>, IndexError> {
+        match self.to_json()? {
+            JsonValue::Null => Ok(Vec::new()),
+            data => serde_json::from_value(data).map_err(|e| {
+                let message = format!("Unable to deserialize document of: {}", self.name);
+                IndexError::Serde {
+                    message,
+                    source: e,
+                }
+            }),
+        }
+    }
+    /// The field holds no value
+    pub fn missing(&self, field: &str) -> IndexError {
+        let message = format!("Unable to read document of: {}", self.name);
+        IndexError::schema_mismatch(message, format!("Field: {} does not have any value", self.path(field)))
+    }
+    /// The field holds a value of another type or out of range
+    pub fn mismatch(&self, field: &str, value: &Value, expected: &str) -> IndexError {
+        let message = format!("Unable to read document of: {}", self.name);
+        IndexError::schema_mismatch(message, format!("Field: {} holds {:?} instead of {}", self.path(field), value, expected))
+    }
+}
+
+/// Field value read straight from a stored document, see #[derive(FromDocument)]
+/// Integers convert into any width holding them & into floats
+pub trait FromStored: Sized {
+    /// None when the value is of another type or out of range
+    fn from_value(value: &Value) -> Option<Self>;
+    /// First value of the field, an error when missing
+    fn from_stored(document: &StoredDocument, field: &str) -> Result<Self, IndexError> {
+        let value = document.get(field).ok_or_else(|| document.missing(field))?;
+        Self::from_value(value).ok_or_else(|| document.mismatch(field, value, type_name::<Self>()))
+    }
+}
+
+macro_rules! from_stored_int {
+    ($($int:ty),*) => {$(
+        impl FromStored for $int {
+            fn from_value(value: &Value) -> Option<Self> {
+                match value {
+                    Value::U64(value) => Self::try_from(*value).ok(),
+                    Value::I64(value) => Self::try_from(*value).ok(),
+                    _ => None,
+                }
+            }
+        }
+    )*};
+}
+
+from_stored_int!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl FromStored for f64 {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::F64(value) => Some(*value),
+            Value::U64(value) => Some(*value as f64),
+            Value::I64(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+}
+
+impl FromStored for f32 {
+    fn from_value(value: &Value) -> Option<Self> {
+        f64::from_value(value).map(|value| value as f32)
+    }
+}
+
+/// Booleans are indexed as 0 or 1
+impl FromStored for bool {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::U64(value) => Some(*value != 0),
+            Value::I64(value) => Some(*value != 0),
+            _ => None,
+        }
+    }
+}
+
+impl FromStored for String {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Str(text) => Some(text.to_string()),
+            Value::Facet(facet) => Some(facet_path(facet)),
+            _ => None,
+        }
+    }
+}
+
+impl FromStored for DateTime<Utc> {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Date(date) => Some(*date),
+            _ => None,
+        }
+    }
+}
+
+impl FromStored for NaiveDateTime {
+    fn from_value(value: &Value) -> Option<Self> {
+        DateTime::<Utc>::from_value(value).map(|date| date.naive_utc())
+    }
+}
+
+impl FromStored for Vec<u8> {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Bytes(bytes) => Some(bytes.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// None when missing
+impl<T: FromStored> FromStored for Option<T> {
+    fn from_value(value: &Value) -> Option<Self> {
+        T::from_value(value).map(Some)
+    }
+    fn from_stored(document: &StoredDocument, field: &str) -> Result<Self, IndexError> {
+        match document.get(field) {
+            Some(value) => T::from_value(value).map(Some).ok_or_else(|| document.mismatch(field, value, type_name::<T>())),
+            None => Ok(None),
+        }
+    }
+}
+
+macro_rules! from_stored_seq {
+    ($($item:ty),*) => {$(
+        /// Every value of the field, empty when missing
+        impl FromStored for Vec<$item> {
+            fn from_value(value: &Value) -> Option<Self> {
+                <$item>::from_value(value).map(|item| vec![item])
+            }
+            fn from_stored(document: &StoredDocument, field: &str) -> Result<Self, IndexError> {
+                document.get_all(field).into_iter()
+                    .map(|value| <$item>::from_value(value).ok_or_else(|| document.mismatch(field, value, type_name::<$item>())))
+                    .collect()
+            }
+        }
+    )*};
+}
+
+from_stored_seq!(String, u64, i64, f64, bool, DateTime<Utc>);
+
+/// Converts a stored document into a user type, read_documents calls it for every hit
+/// The default goes through JSON just like read_structs, implement it or #[derive(FromDocument)] to map the fields directly
+pub trait FromDocument: DeserializeOwned {
+    fn from_document(document: &StoredDocument) -> Result<Self, IndexError> {
+        let data = document.to_json()?;
+        serde_json::from_value(data).map_err(|e| {
+            let message = format!("Unable to deserialize document of: {}", document.name());
+            IndexError::Serde {
+                message,
+                source: e,
+            }
+        })
+    }
+}
+
+/// Converts the hit of a search, see AsDoc
+pub(crate) fn from_document<T: FromDocument>(surfer: &Surfer, name: &str, document: &Document, select: Option<&[String]>) -> Result<T, IndexError> {
+    let schema = surfer.schema(name).ok_or_else(|| IndexError::unknown_index(name))?;
+    T::from_document(&StoredDocument::new(surfer, name, schema, document, select))
+}
@@ -1,85 +1,191 @@
-use std::fmt::Debug;
+use std::error::Error;
+use std::fmt;
 use std::io;
 use std::convert::From;
+use std::sync::PoisonError;
 
-use serde::{Serialize};
+use serde::{Serialize, Serializer};
+use serde::ser::SerializeStruct;
 use serde_json::error::Error as JsonError;
 
-use failure::Fail;
+use failure::{Compat, Fail};
 
-use tantivy::directory::error::OpenDirectoryError;
+use tantivy::directory::error::{LockError, OpenDirectoryError};
 use tantivy::TantivyError;
 use tantivy::schema::DocParsingError;
 use tantivy::query::QueryParserError;
 
 
-#[derive(Debug, Fail, Clone, Serialize)]
-#[fail(display = "Message: {}", message)]
-pub struct IndexError {
-    message: String,
-    reason: String,
-
-}
-
-impl From<TantivyError> for IndexError {
-    fn from(error: TantivyError) -> Self {
-        Self::new("Unable to open Index", &error.to_string())
-    }
+/// Cause of a failure, match on the variant to handle it
+#[derive(Debug)]
+pub enum IndexError {
+    /// Document, field or value does not fit the schema
+    SchemaMismatch { message: String, reason: String },
+    /// Query does not parse against the schema
+    QueryParse { message: String, source: Compat<QueryParserError> },
+    /// Reading or writing files & sockets
+    Io { message: String, source: io::Error },
+    /// Converting to or from JSON
+    Serde { message: String, source: JsonError },
+    /// Raised by tantivy e.g. opening a writer
+    Tantivy { message: String, source: Compat<TantivyError> },
+    /// Index is not registered with the Surfer
+    UnknownIndex { name: String },
+    /// Lock poisoned by a thread which panicked holding it
+    Lock { reason: String },
+    /// Index writer held by another Surfer, in this process or another
+    Locked { holder_hint: String },
+    /// Any other invalid input or state
+    Invalid { message: String, reason: String },
 }
 
 impl IndexError {
     pub fn new<T: ToString>(message: T, reason: T) -> Self {
         let message = message.to_string();
         let reason = reason.to_string();
-        Self {
+        IndexError::Invalid {
             message,
             reason,
         }
     }
-}
-
-impl From<OpenDirectoryError> for IndexError {
-    fn from(error: OpenDirectoryError) -> Self {
-        let message = "Unable to MMap directory for indexing".to_string();
-        let reason = error.to_string();
-        Self {
+    pub fn schema_mismatch<T: ToString>(message: T, reason: T) -> Self {
+        let message = message.to_string();
+        let reason = reason.to_string();
+        IndexError::SchemaMismatch {
             message,
             reason,
         }
     }
+    pub fn unknown_index(name: &str) -> Self {
+        let name = name.to_string();
+        IndexError::UnknownIndex {
+            name,
+        }
+    }
+    pub fn locked<T: ToString>(holder_hint: T) -> Self {
+        let holder_hint = holder_hint.to_string();
+        IndexError::Locked {
+            holder_hint,
+        }
+    }
+    pub fn tantivy<T: ToString>(message: T, error: TantivyError) -> Self {
+        let message = message.to_string();
+        let source = error.compat();
+        IndexError::Tantivy {
+            message,
+            source,
+        }
+    }
+    /// Name of the variant
+    pub fn kind(&self) -> &'static str {
+        match self {
+            IndexError::SchemaMismatch { .. } => "SchemaMismatch",
+            IndexError::QueryParse { .. } => "QueryParse",
+            IndexError::Io { .. } => "Io",
+            IndexError::Serde { .. } => "Serde",
+            IndexError::Tantivy { .. } => "Tantivy",
+            IndexError::UnknownIndex { .. } => "UnknownIndex",
+            IndexError::Lock { .. } => "Lock",
+            IndexError::Locked { .. } => "Locked",
+            IndexError::Invalid { .. } => "Invalid",
+        }
+    }
+    pub fn message(&self) -> &str {
+        match self {
+            IndexError::SchemaMismatch { message, .. } => message,
+            IndexError::QueryParse { message, .. } => message,
+            IndexError::Io { message, .. } => message,
+            IndexError::Serde { message, .. } => message,
+            IndexError::Tantivy { message, .. } => message,
+            IndexError::UnknownIndex { .. } => "Unknown index",
+            IndexError::Lock { .. } => "Unable to acquire lock",
+            IndexError::Locked { .. } => "Index is locked by another writer",
+            IndexError::Invalid { message, .. } => message,
+        }
+    }
+    /// Reason given or the description of the source
+    pub fn reason(&self) -> String {
+        match self {
+            IndexError::SchemaMismatch { reason, .. } => reason.to_string(),
+            IndexError::QueryParse { source, .. } => source.to_string(),
+            IndexError::Io { source, .. } => source.to_string(),
+            IndexError::Serde { source, .. } => source.to_string(),
+            IndexError::Tantivy { source, .. } => source.to_string(),
+            IndexError::UnknownIndex { name } => format!("Index: {} does not exist", name),
+            IndexError::Lock { reason } => reason.to_string(),
+            IndexError::Locked { holder_hint } => holder_hint.to_string(),
+            IndexError::Invalid { reason, .. } => reason.to_string(),
+        }
+    }
 }
 
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.message(), self.reason())
+    }
+}
+
+impl Error for IndexError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            IndexError::QueryParse { source, .. } => Some(source),
+            IndexError::Io { source, .. } => Some(source),
+            IndexError::Serde { source, .. } => Some(source),
+            IndexError::Tantivy { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Serialized as kind, message & reason e.g. for HTTP responses
+impl Serialize for IndexError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("IndexError", 3)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", self.message())?;
+        state.serialize_field("reason", &self.reason())?;
+        state.end()
+    }
+}
+
+/// Busy writer locks become Locked
+impl From<TantivyError> for IndexError {
+    fn from(error: TantivyError) -> Self {
+        match error {
+            TantivyError::LockFailure(LockError::LockBusy, _) => Self::locked("Another writer holds the index, in this process or another"),
+            error => Self::tantivy("Unable to open Index", error),
+        }
+    }
+}
+
+impl From<OpenDirectoryError> for IndexError {
+    fn from(error: OpenDirectoryError) -> Self {
+        Self::tantivy("Unable to MMap directory for indexing", error.into())
+    }
+}
 
 impl From<io::Error> for IndexError {
     fn from(error: io::Error) -> Self {
         let message = "Unable to create index dir".to_string();
-        let reason = error.to_string();
-        Self {
+        IndexError::Io {
             message,
-            reason,
+            source: error,
         }
     }
 }
 
-
 impl From<DocParsingError> for IndexError {
     fn from(error: DocParsingError) -> Self {
-        let message = "Unable to parse document".to_string();
-        let reason = error.to_string();
-        Self {
-            message,
-            reason,
-        }
+        Self::schema_mismatch("Unable to parse document".to_string(), error.to_string())
     }
 }
 
 impl From<QueryParserError> for IndexError {
     fn from(error: QueryParserError) -> Self {
         let message = "Unable to parse query".to_string();
-        let reason = error.to_string();
-        Self {
+        IndexError::QueryParse {
             message,
-            reason,
+            source: error.compat(),
         }
     }
 }
@@ -87,9 +193,17 @@ impl From<QueryParserError> for IndexError {
 impl From<JsonError> for IndexError {
     fn from(error: JsonError) -> Self {
         let message = "Unable to covert to json".to_string();
-        let reason = error.to_string();
-        Self {
+        IndexError::Serde {
             message,
+            source: error,
+        }
+    }
+}
+
+impl<T> From<PoisonError<T>> for IndexError {
+    fn from(error: PoisonError<T>) -> Self {
+        let reason = error.to_string();
+        IndexError::Lock {
             reason,
         }
     }
@@ -113,15 +227,19 @@ mod tests {
 
     #[test]
     fn validate_index_error() {
-        let message = "message".to_string();
-        let reason = "reason".to_string();
-        let error = IndexError {
-            message,
-            reason,
-        };
+        let error = IndexError::new("message", "reason");
         assert_eq!(format!("{}", error), error.to_string());
-        assert_eq!(&error.message, "message");
-        assert_eq!(&error.reason, "reason");
+        assert_eq!(error.kind(), "Invalid");
+        assert_eq!(error.message(), "message");
+        assert_eq!(error.reason(), "reason");
+        assert!(error.source().is_none());
+
+        let computed = serde_json::to_string(&error).unwrap();
+        assert_eq!(computed, r#"{"kind":"Invalid","message":"message","reason":"reason"}"#);
+
+        let error = IndexError::unknown_index("books");
+        assert!(matches!(error, IndexError::UnknownIndex { ref name } if name == "books"));
+        assert_eq!(error.reason(), "Index: books does not exist");
     }
 
     #[test]
@@ -131,6 +249,15 @@ mod tests {
         assert_eq!(format!("{}", error), error.to_string());
     }
 
+    #[test]
+    fn validate_index_error_from_lock_failure() {
+        let error: IndexError = TantivyError::LockFailure(LockError::LockBusy, None).into();
+        assert!(matches!(error, IndexError::Locked { .. }));
+        assert_eq!(error.kind(), "Locked");
+        let error: IndexError = TantivyError::LockFailure(LockError::IOError(io::Error::from(io::ErrorKind::Other)), None).into();
+        assert_eq!(error.kind(), "Tantivy");
+    }
+
     #[test]
     fn validate_index_error_from_open_directory_error() {
         let path = PathBuf::from_str("doesnotexist").unwrap();
@@ -145,6 +272,9 @@ mod tests {
         let error = io::Error::from(ErrorKind::PermissionDenied);
         let error: IndexError = error.into();
         assert_eq!(format!("{}", error), error.to_string());
+        assert!(matches!(error, IndexError::Io { .. }));
+        let source = error.source().unwrap().downcast_ref::<io::Error>().unwrap();
+        assert_eq!(source.kind(), ErrorKind::PermissionDenied);
     }
 
     #[test]
@@ -159,6 +289,10 @@ mod tests {
         let error = QueryParserError::FieldDoesNotExist("bs_field is not yet present".to_string());
         let error: IndexError = error.into();
         assert_eq!(format!("{}", error), error.to_string());
+        match error {
+            IndexError::QueryParse { source, .. } => assert!(matches!(source.get_ref(), QueryParserError::FieldDoesNotExist(_))),
+            _ => panic!("Expected a query parse error"),
+        };
     }
 
     #[test]
@@ -169,5 +303,6 @@ mod tests {
 
         let error: IndexError = json.into();
         assert_eq!(format!("{}", error), error.to_string());
+        assert!(error.source().unwrap().is::<serde_json::Error>());
     }
 }
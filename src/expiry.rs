@@ -0,0 +1,139 @@
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Bound;
+use std::time::Duration;
+
+use chrono::{DateTime, TimeZone, Utc};
+
+use tantivy::query::RangeQuery;
+use tantivy::schema::{Field, IndexRecordOption, Schema, SchemaBuilder, Type, INDEXED};
+use tantivy::{DocId, DocSet, Document, Searcher, SegmentReader, Term};
+
+use crate::prelude::*;
+
+/// Date field managed for indexes with a TTL, indexed only so it never shows up in the documents read
+pub const EXPIRES_AT: &str = "_expires_at";
+
+/// Schema along with the expiry field
+pub(crate) fn with_expiry(schema: &Schema) -> Schema {
+    if schema.get_field(EXPIRES_AT).is_some() {
+        return schema.clone();
+    };
+    let mut builder = SchemaBuilder::new();
+    for (_, entry) in schema.fields() {
+        builder.add_field(entry.clone());
+    };
+    builder.add_date_field(EXPIRES_AT, INDEXED);
+    builder.build()
+}
+
+/// Document expiring once the TTL elapsed from now
+pub(crate) fn stamp_expiry(schema: &Schema, document: &mut Document, ttl: Duration, now: DateTime<Utc>) -> Result<(), IndexError> {
+    let field = expiry_field(schema)?;
+    let ttl = chrono::Duration::from_std(ttl).map_err(|_| IndexError::new("Unable to stamp expiry", "TTL is out of range"))?;
+    document.add_date(field, &(now + ttl));
+    Ok(())
+}
+
+/// Distinct expiry terms before now, deleting them deletes every expired document
+pub(crate) fn expired_terms(searcher: &Searcher, schema: &Schema, now: DateTime<Utc>) -> Result<Vec<Term>, IndexError> {
+    let field = expiry_field(schema)?;
+    let bound = Term::from_field_date(field, &now);
+    let mut timestamps = BTreeSet::new();
+    for segment_reader in searcher.segment_readers() {
+        let inverted_index = segment_reader.inverted_index(field);
+        let mut stream = inverted_index.terms().range().lt(bound.value_bytes()).into_stream();
+        while stream.advance() {
+            timestamps.insert(timestamp_of(stream.key()));
+        };
+    };
+    Ok(timestamps.into_iter().map(|timestamp| Term::from_field_i64(field, timestamp)).collect())
+}
+
+/// Expiry of the segment documents holding one, e.g. to carry it over when reindexing
+pub(crate) fn expiries(segment_reader: &SegmentReader, schema: &Schema) -> HashMap<DocId, DateTime<Utc>> {
+    let mut expiries = HashMap::new();
+    let field = match schema.get_field(EXPIRES_AT) {
+        Some(field) => field,
+        None => return expiries,
+    };
+    let inverted_index = segment_reader.inverted_index(field);
+    let mut stream = inverted_index.terms().stream();
+    while stream.advance() {
+        let expires_at = match Utc.timestamp_opt(timestamp_of(stream.key()), 0).single() {
+            Some(expires_at) => expires_at,
+            None => continue,
+        };
+        let mut postings = inverted_index.read_postings_from_terminfo(stream.value(), IndexRecordOption::Basic);
+        while postings.advance() {
+            expiries.insert(postings.doc(), expires_at);
+        };
+    };
+    expiries
+}
+
+/// Documents expired before now
+pub(crate) fn expired_query(schema: &Schema, now: DateTime<Utc>) -> Result<RangeQuery, IndexError> {
+    let field = expiry_field(schema)?;
+    let end = Term::from_field_date(field, &now);
+    Ok(RangeQuery::new_term_bounds(field, Type::Date, &Bound::Unbounded, &Bound::Excluded(end)))
+}
+
+/// Timestamp of an encoded date term
+fn timestamp_of(key: &[u8]) -> i64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&key[..8]);
+    (u64::from_be_bytes(bytes) ^ (1 << 63)) as i64
+}
+
+fn expiry_field(schema: &Schema) -> Result<Field, IndexError> {
+    schema.get_field(EXPIRES_AT).ok_or_else(|| {
+        let reason = format!("Field: {} does not exist, set the TTL before the index is created", EXPIRES_AT);
+        IndexError::schema_mismatch("Unable to expire documents", &reason)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::collector::Count;
+    use tantivy::schema::{TEXT, STORED};
+    use tantivy::{doc, Index};
+
+    #[test]
+    fn validate_expired_terms() {
+        let mut builder = SchemaBuilder::new();
+        let title = builder.add_text_field("title", TEXT | STORED);
+        let schema = with_expiry(&builder.build());
+        assert!(schema.get_field(EXPIRES_AT).is_some());
+        assert_eq!(serde_json::to_string(&with_expiry(&schema)).unwrap(), serde_json::to_string(&schema).unwrap());
+
+        let index = Index::create_in_ram(schema.clone());
+        let mut writer = open_index_writer(&index).unwrap();
+        let now: DateTime<Utc> = "2020-01-01T00:00:00Z".parse().unwrap();
+        for (text, hours) in &[("old", 1), ("older", 1), ("fresh", 48)] {
+            let mut document = doc!(title => *text);
+            stamp_expiry(&schema, &mut document, Duration::from_secs(hours * 3600), now).unwrap();
+            writer.add_document(document);
+        };
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let later = now + chrono::Duration::hours(2);
+        assert_eq!(expired_terms(&searcher, &schema, later).unwrap().len(), 1);
+        assert_eq!(searcher.search(&expired_query(&schema, later).unwrap(), &Count).unwrap(), 2);
+        assert!(expired_terms(&searcher, &schema, now).unwrap().is_empty());
+
+        let mut computed: Vec<DateTime<Utc>> = searcher.segment_readers().iter()
+            .flat_map(|segment_reader| expiries(segment_reader, &schema).into_values())
+            .collect();
+        computed.sort();
+        let expected = vec![now + chrono::Duration::hours(1), now + chrono::Duration::hours(1), now + chrono::Duration::hours(48)];
+        assert_eq!(computed, expected);
+
+        let mut builder = SchemaBuilder::new();
+        builder.add_text_field("title", TEXT);
+        let schema = builder.build();
+        assert!(expired_query(&schema, now).is_err());
+        assert!(stamp_expiry(&schema, &mut Document::default(), Duration::from_secs(1), now).is_err());
+    }
+}
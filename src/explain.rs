@@ -0,0 +1,95 @@
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use tantivy::query::Query;
+use tantivy::{DocAddress, Score, Searcher, TantivyError};
+
+use crate::prelude::*;
+
+/// Why a document did or did not rank, the tree mirrors the scoring formula of the query
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ScoreExplanation {
+    matched: bool,
+    score: Score,
+    tree: JsonValue,
+}
+
+impl ScoreExplanation {
+    pub fn new(matched: bool, score: Score, tree: JsonValue) -> Self {
+        Self {
+            matched,
+            score,
+            tree,
+        }
+    }
+    /// Does the document match the query at all
+    pub fn matched(&self) -> bool {
+        self.matched
+    }
+    pub fn score(&self) -> Score {
+        self.score
+    }
+    /// Nodes of value, description & details
+    pub fn tree(&self) -> &JsonValue {
+        &self.tree
+    }
+    /// Indented json for debugging relevance
+    pub fn to_pretty_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Scoring explanation of the document, a document the query does not match scores 0
+pub(crate) fn explain_doc(searcher: &Searcher, query: &dyn Query, doc_address: DocAddress) -> Result<ScoreExplanation, IndexError> {
+    match query.explain(searcher, doc_address) {
+        Ok(explanation) => {
+            let tree = serde_json::to_value(&explanation)?;
+            Ok(ScoreExplanation::new(true, explanation.value(), tree))
+        }
+        Err(TantivyError::InvalidArgument(_)) => {
+            let tree = serde_json::json!({
+                "value": 0.0,
+                "description": "Document does not match the query",
+            });
+            Ok(ScoreExplanation::new(false, 0.0, tree))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::collector::TopDocs;
+    use tantivy::query::{QueryParser, TermQuery};
+    use tantivy::schema::{IndexRecordOption, SchemaBuilder, STRING, TEXT};
+    use tantivy::{doc, Index, Term};
+
+    #[test]
+    fn validate_explain_doc() {
+        let mut builder = SchemaBuilder::new();
+        let id = builder.add_text_field("id", STRING);
+        let title = builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(builder.build());
+        let mut writer = open_index_writer(&index).unwrap();
+        writer.add_document(doc!(id => "a", title => "rust search engine"));
+        writer.add_document(doc!(id => "b", title => "python web framework"));
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let address = |key: &str| {
+            let query = TermQuery::new(Term::from_field_text(id, key), IndexRecordOption::Basic);
+            searcher.search(&query, &TopDocs::with_limit(1)).unwrap()[0].1
+        };
+        let query = QueryParser::for_index(&index, vec![title]).parse_query("rust").unwrap();
+
+        let computed = explain_doc(&searcher, &query, address("a")).unwrap();
+        assert!(computed.matched());
+        assert!(computed.score() > 0.0);
+        assert!(computed.tree()["details"].is_array());
+        assert!(computed.to_pretty_json().contains("description"));
+
+        let computed = explain_doc(&searcher, &query, address("b")).unwrap();
+        assert!(!computed.matched());
+        assert_eq!(computed.score(), 0.0);
+    }
+}
@@ -0,0 +1,112 @@
+use serde::Serialize;
+
+use tantivy::collector::FacetCollector;
+use tantivy::query::Query;
+use tantivy::schema::{Facet, Field, FieldType, Schema};
+use tantivy::Searcher;
+
+use crate::prelude::*;
+
+/// Matching documents under a sub-category of a hierarchical facet
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FacetCount {
+    path: String,
+    count: u64,
+}
+
+impl FacetCount {
+    pub fn new(path: &str, count: u64) -> Self {
+        let path = path.to_string();
+        Self {
+            path,
+            count,
+        }
+    }
+    /// Path of the sub-category as inserted e.g. electronics/phones
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+/// Facet of a path-like value, slashes around it are optional e.g. electronics/phones
+pub(crate) fn facet_text(path: &str) -> String {
+    format!("/{}", path.trim_matches('/'))
+}
+
+/// Path of a facet without the leading slash, the way it is inserted
+pub(crate) fn facet_path(facet: &Facet) -> String {
+    facet.to_path_string().trim_start_matches('/').to_string()
+}
+
+/// Counts of the sub-categories one level below the path, most documents first
+/// An empty path counts the top level categories
+pub(crate) fn facet_counts(searcher: &Searcher, query: &dyn Query, schema: &Schema, field_name: &str, path: &str) -> Result<Vec<FacetCount>, IndexError> {
+    let field = facet_field(schema, field_name)?;
+    let parent = facet_text(path);
+    let mut collector = FacetCollector::for_field(field);
+    collector.add_facet(parent.as_str());
+    let counts = searcher.search(query, &collector)?;
+    let mut facets: Vec<FacetCount> = counts.get(parent.as_str())
+        .map(|(facet, count)| FacetCount::new(&facet_path(facet), count))
+        .collect();
+    facets.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.path.cmp(&b.path)));
+    Ok(facets)
+}
+
+fn facet_field(schema: &Schema, field_name: &str) -> Result<Field, IndexError> {
+    let field = schema.get_field(field_name);
+    if field.is_none() {
+        let reason = format!("Field: {} does not exist", field_name);
+        return Err(IndexError::schema_mismatch("Unable to count facets", &reason));
+    };
+    let field = field.unwrap();
+    if !matches!(schema.get_field_entry(field).field_type(), FieldType::HierarchicalFacet) {
+        let reason = format!("Field: {} is not a facet, declare it through Control", field_name);
+        return Err(IndexError::schema_mismatch("Unable to count facets", &reason));
+    };
+    Ok(field)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::query::AllQuery;
+    use tantivy::schema::{SchemaBuilder, TEXT};
+    use tantivy::{doc, Index};
+
+    #[test]
+    fn validate_facet_text() {
+        assert_eq!(facet_text("electronics/phones"), "/electronics/phones");
+        assert_eq!(facet_text("/electronics/phones/"), "/electronics/phones");
+        assert_eq!(facet_text(""), "/");
+        assert_eq!(facet_path(&Facet::from("/electronics/phones")), "electronics/phones");
+    }
+
+    #[test]
+    fn validate_facet_counts() {
+        let mut builder = SchemaBuilder::new();
+        let title = builder.add_text_field("title", TEXT);
+        let category = builder.add_facet_field("category");
+        let schema = builder.build();
+        let index = Index::create_in_ram(schema.clone());
+        let mut writer = open_index_writer(&index).unwrap();
+        writer.add_document(doc!(title => "Pixel", category => Facet::from("/electronics/phones/android")));
+        writer.add_document(doc!(title => "iPhone", category => Facet::from("/electronics/phones/ios")));
+        writer.add_document(doc!(title => "Galaxy", category => Facet::from("/electronics/phones/android")));
+        writer.add_document(doc!(title => "Kindle", category => Facet::from("/electronics/readers")));
+        writer.add_document(doc!(title => "Dune", category => Facet::from("/books/scifi")));
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let computed = facet_counts(&searcher, &AllQuery, &schema, "category", "").unwrap();
+        assert_eq!(computed, vec![FacetCount::new("electronics", 4), FacetCount::new("books", 1)]);
+        let computed = facet_counts(&searcher, &AllQuery, &schema, "category", "electronics/phones").unwrap();
+        assert_eq!(computed, vec![FacetCount::new("electronics/phones/android", 2), FacetCount::new("electronics/phones/ios", 1)]);
+        assert!(facet_counts(&searcher, &AllQuery, &schema, "category", "toys").unwrap().is_empty());
+        assert!(facet_counts(&searcher, &AllQuery, &schema, "title", "").is_err());
+        assert!(facet_counts(&searcher, &AllQuery, &schema, "brand", "").is_err());
+    }
+}
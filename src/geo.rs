@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use tantivy::query::{BooleanQuery, Occur, Query};
+use tantivy::schema::Schema;
+
+use crate::prelude::*;
+
+/// Mean radius of the earth
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// Latitude & longitude in degrees, indexed as the f64 fields {field}.lat & {field}.lon of a struct
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeoPoint {
+    lat: f64,
+    lon: f64,
+}
+
+impl GeoPoint {
+    pub fn new(lat: f64, lon: f64) -> Self {
+        Self {
+            lat,
+            lon,
+        }
+    }
+    pub fn lat(&self) -> f64 {
+        self.lat
+    }
+    pub fn lon(&self) -> f64 {
+        self.lon
+    }
+    /// Great circle distance by the haversine formula
+    pub fn distance_km(&self, other: &GeoPoint) -> f64 {
+        let (lat, other_lat) = (self.lat.to_radians(), other.lat.to_radians());
+        let d_lat = other_lat - lat;
+        let d_lon = (other.lon - self.lon).to_radians();
+        let a = (d_lat / 2.0).sin().powi(2) + lat.cos() * other_lat.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+    }
+}
+
+/// Document within the radius along with its distance to the center
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Nearby<T> {
+    distance_km: f64,
+    doc: T,
+}
+
+impl<T> Nearby<T> {
+    pub fn new(distance_km: f64, doc: T) -> Self {
+        Self {
+            distance_km,
+            doc,
+        }
+    }
+    pub fn distance_km(&self) -> f64 {
+        self.distance_km
+    }
+    pub fn doc(&self) -> &T {
+        &self.doc
+    }
+    pub fn into_doc(self) -> T {
+        self.doc
+    }
+}
+
+/// Range queries over the latitude & longitude fields of the box bounding the circle
+/// Longitudes wrapping around the antimeridian are split in two ranges
+pub(crate) fn bounding_box_query(schema: &Schema, field: &str, center: &GeoPoint, km: f64) -> Result<BooleanQuery, IndexError> {
+    if km < 0.0 || !km.is_finite() {
+        let reason = format!("Radius: {} km is not a positive distance", km);
+        return Err(IndexError::new("Unable to create geo query", &reason));
+    };
+    let lat_field = format!("{}.lat", field);
+    let lon_field = format!("{}.lon", field);
+    let d_lat = (km / EARTH_RADIUS_KM).to_degrees();
+    let min_lat = (center.lat - d_lat).max(-90.0);
+    let max_lat = (center.lat + d_lat).min(90.0);
+    let lat: Box<dyn Query> = Box::new(numeric_range_query(schema, &lat_field, min_lat..next_up(max_lat))?);
+
+    let widest = min_lat.abs().max(max_lat.abs());
+    let d_lon = if widest >= 90.0 {
+        180.0
+    } else {
+        (d_lat / widest.to_radians().cos()).min(180.0)
+    };
+    let (min_lon, max_lon) = (center.lon - d_lon, center.lon + d_lon);
+    let mut ranges = Vec::new();
+    if d_lon >= 180.0 {
+        ranges.push((-180.0, 180.0));
+    } else if min_lon < -180.0 {
+        ranges.push((min_lon + 360.0, 180.0));
+        ranges.push((-180.0, max_lon));
+    } else if max_lon > 180.0 {
+        ranges.push((min_lon, 180.0));
+        ranges.push((-180.0, max_lon - 360.0));
+    } else {
+        ranges.push((min_lon, max_lon));
+    };
+    let mut lons = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        let query: Box<dyn Query> = Box::new(numeric_range_query(schema, &lon_field, start..next_up(end))?);
+        lons.push((Occur::Should, query));
+    };
+    let lon: Box<dyn Query> = Box::new(BooleanQuery::from(lons));
+    Ok(BooleanQuery::from(vec![(Occur::Must, lat), (Occur::Must, lon)]))
+}
+
+/// Point held by the dotted field of the document, None when missing
+pub(crate) fn geo_point(doc: &JsonValue, field: &str) -> Option<GeoPoint> {
+    let mut value = doc;
+    for key in field.split('.') {
+        value = value.get(key)?;
+    };
+    let lat = value.get("lat")?.as_f64()?;
+    let lon = value.get("lon")?.as_f64()?;
+    Some(GeoPoint::new(lat, lon))
+}
+
+/// Ranges are end exclusive, keeps the bound itself within
+fn next_up(value: f64) -> f64 {
+    value + value.abs().max(1.0) * f64::EPSILON
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::collector::Count;
+    use tantivy::schema::{SchemaBuilder, INDEXED, STORED};
+    use tantivy::{doc, Index};
+
+    #[test]
+    fn validate_distance_km() {
+        let paris = GeoPoint::new(48.8566, 2.3522);
+        let london = GeoPoint::new(51.5074, -0.1278);
+        let computed = paris.distance_km(&london);
+        assert!((computed - 343.5).abs() < 1.0);
+        assert_eq!(paris.distance_km(&paris), 0.0);
+    }
+
+    #[test]
+    fn validate_geo_point() {
+        let doc = serde_json::json!({"shop": {"location": {"lat": 1.5, "lon": -2.0}}, "name": "x"});
+        assert_eq!(geo_point(&doc, "shop.location"), Some(GeoPoint::new(1.5, -2.0)));
+        assert_eq!(geo_point(&doc, "name"), None);
+        assert_eq!(geo_point(&doc, "missing"), None);
+    }
+
+    #[test]
+    fn validate_bounding_box_query() {
+        let mut builder = SchemaBuilder::new();
+        let lat = builder.add_f64_field("location.lat", INDEXED | STORED);
+        let lon = builder.add_f64_field("location.lon", INDEXED | STORED);
+        let schema = builder.build();
+        let index = Index::create_in_ram(schema.clone());
+        let mut writer = open_index_writer(&index).unwrap();
+        writer.add_document(doc!(lat => 48.8566, lon => 2.3522));
+        writer.add_document(doc!(lat => 51.5074, lon => -0.1278));
+        writer.add_document(doc!(lat => -17.7134, lon => 178.0650));
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let count = |center: GeoPoint, km: f64| {
+            let query = bounding_box_query(&schema, "location", &center, km).unwrap();
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count(GeoPoint::new(48.8566, 2.3522), 10.0), 1);
+        assert_eq!(count(GeoPoint::new(50.0, 1.0), 400.0), 2);
+        assert_eq!(count(GeoPoint::new(-17.0, -179.5), 300.0), 1);
+        assert_eq!(count(GeoPoint::new(0.0, 0.0), 100.0), 0);
+        assert!(bounding_box_query(&schema, "location", &GeoPoint::new(0.0, 0.0), -1.0).is_err());
+        assert!(bounding_box_query(&schema, "missing", &GeoPoint::new(0.0, 0.0), 1.0).is_err());
+    }
+}
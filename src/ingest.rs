@@ -0,0 +1,266 @@
+use std::sync::{Arc, RwLock};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::Serialize;
+
+use tantivy::schema::Schema;
+use tantivy::{Document, IndexReader, IndexWriter};
+
+use crate::prelude::*;
+
+/// Documents inserted per writer lock while ingesting
+pub(crate) const BATCH_SIZE: usize = 1000;
+
+/// Line of the input which could not be inserted, lines start at 1
+#[derive(Debug, Serialize)]
+pub struct LineError {
+    line: usize,
+    error: IndexError,
+}
+
+impl LineError {
+    pub fn new(line: usize, error: IndexError) -> Self {
+        Self {
+            line,
+            error,
+        }
+    }
+    pub fn line(&self) -> usize {
+        self.line
+    }
+    pub fn error(&self) -> &IndexError {
+        &self.error
+    }
+}
+
+/// Outcome of a bulk ingestion, the valid lines are inserted even when others fail
+#[derive(Debug, Default, Serialize)]
+pub struct IngestReport {
+    inserted: usize,
+    errors: Vec<LineError>,
+}
+
+impl IngestReport {
+    pub fn inserted(&self) -> usize {
+        self.inserted
+    }
+    pub fn errors(&self) -> &Vec<LineError> {
+        &self.errors
+    }
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+    pub(crate) fn add_inserted(&mut self, inserted: usize) {
+        self.inserted += inserted;
+    }
+    pub(crate) fn add_error(&mut self, line: usize, error: IndexError) {
+        self.errors.push(LineError::new(line, error));
+    }
+}
+
+/// Counts of a bulk insertion so far, everything consumed is committed when reported
+/// Resume an interrupted ingest by skipping the consumed items
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct Progress {
+    consumed: usize,
+    indexed: usize,
+    errors: usize,
+}
+
+impl Progress {
+    /// Items read off the iterator, valid or not
+    pub fn consumed(&self) -> usize {
+        self.consumed
+    }
+    pub fn indexed(&self) -> usize {
+        self.indexed
+    }
+    pub fn errors(&self) -> usize {
+        self.errors
+    }
+    pub(crate) fn update(&mut self, consumed: usize, report: &IngestReport) {
+        self.consumed = consumed;
+        self.indexed = report.inserted();
+        self.errors = report.errors().len();
+    }
+}
+
+/// Called with the progress of an ingest
+pub type OnProgress<'a> = Box<dyn FnMut(&Progress) + 'a>;
+
+/// When to commit while inserting from an iterator & whom to tell, commits once at the end by default
+#[derive(Default)]
+pub struct IngestOptions<'a> {
+    docs: Option<usize>,
+    bytes: Option<usize>,
+    progress: Option<OnProgress<'a>>,
+}
+
+impl<'a> IngestOptions<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Commits after that many documents
+    pub fn set_commit_docs(&mut self, docs: usize) {
+        self.docs = Some(docs.max(1));
+    }
+    /// Commits after that many megabytes of JSON
+    pub fn set_commit_megabytes(&mut self, megabytes: usize) {
+        self.bytes = Some(megabytes.max(1) * 1024 * 1024);
+    }
+    /// Called after every commit
+    pub fn on_progress<F: FnMut(&Progress) + 'a>(&mut self, progress: F) {
+        self.progress = Some(Box::new(progress));
+    }
+    pub fn commit_docs(&self) -> Option<usize> {
+        self.docs
+    }
+    pub fn commit_bytes(&self) -> Option<usize> {
+        self.bytes
+    }
+    /// Is a commit due after the documents & bytes since the last one
+    pub(crate) fn is_due(&self, docs: usize, bytes: usize) -> bool {
+        self.docs.is_some_and(|limit| docs >= limit) || self.bytes.is_some_and(|limit| bytes >= limit)
+    }
+    pub(crate) fn report(&mut self, progress: &Progress) {
+        if let Some(callback) = self.progress.as_mut() {
+            callback(progress);
+        };
+    }
+}
+
+/// Indexes the documents sent over a bounded channel on its own thread, committing whenever the channel runs dry
+/// Sending blocks only while the channel is full, closing or dropping commits whatever is left
+pub struct Ingester {
+    name: String,
+    schema: Schema,
+    ttl: Option<Duration>,
+    shape: Shape,
+    sender: Option<SyncSender<Document>>,
+    worker: Option<JoinHandle<Result<usize, IndexError>>>,
+}
+
+impl Ingester {
+    pub(crate) fn spawn(name: &str, schema: Schema, ttl: Option<Duration>, shape: Shape, writer: Arc<RwLock<IndexWriter>>, reader: IndexReader, capacity: usize) -> Result<Self, IndexError> {
+        let (sender, receiver) = sync_channel(capacity.max(1));
+        let worker = thread::Builder::new()
+            .name(format!("ingester-{}", name))
+            .spawn(move || index_received(&writer, &reader, receiver))?;
+        Ok(Self {
+            name: name.to_string(),
+            schema,
+            ttl,
+            shape,
+            sender: Some(sender),
+            worker: Some(worker),
+        })
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Converts the document on the calling thread & queues it, mismatching the schema is an error right away
+    pub fn send<T: Serialize>(&self, data: &T) -> Result<(), IndexError> {
+        let source = serde_json::to_value(data)?;
+        let shaped = self.shape.apply(&self.schema, source.clone())?;
+        let mut document = json_as_document_with_source(&self.schema, shaped, &source)?;
+        if let Some(ttl) = self.ttl {
+            stamp_expiry(&self.schema, &mut document, ttl, Utc::now())?;
+        };
+        let stopped = || {
+            let message = format!("Unable to ingest: {}", self.name);
+            IndexError::new(message, "Ingester stopped, close it for the cause".to_string())
+        };
+        match &self.sender {
+            Some(sender) => sender.send(document).map_err(|_| stopped()),
+            None => Err(stopped()),
+        }
+    }
+    /// Waits until every queued document is committed, returns how many were
+    pub fn close(mut self) -> Result<usize, IndexError> {
+        self.finish()
+    }
+    fn finish(&mut self) -> Result<usize, IndexError> {
+        self.sender.take();
+        match self.worker.take() {
+            Some(worker) => worker.join().unwrap_or_else(|_| {
+                let message = format!("Unable to ingest: {}", self.name);
+                Err(IndexError::new(message, "Ingester thread panicked".to_string()))
+            }),
+            None => Ok(0),
+        }
+    }
+}
+
+/// Commits what is left
+impl Drop for Ingester {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+/// Adds what is queued up to a batch, commits & waits for more until every sender is gone
+fn index_received(writer: &RwLock<IndexWriter>, reader: &IndexReader, receiver: Receiver<Document>) -> Result<usize, IndexError> {
+    let mut indexed = 0;
+    while let Ok(document) = receiver.recv() {
+        let mut batch = 1;
+        {
+            let writer = writer.read()?;
+            writer.add_document(document);
+            while batch < BATCH_SIZE {
+                match receiver.try_recv() {
+                    Ok(document) => writer.add_document(document),
+                    Err(_) => break,
+                };
+                batch += 1;
+            };
+        }
+        writer.write()?.commit()?;
+        reader.reload()?;
+        indexed += batch;
+    };
+    Ok(indexed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_ingest_report() {
+        let mut report = IngestReport::default();
+        assert!(report.is_ok());
+        report.add_inserted(2);
+        report.add_error(3, IndexError::new("Unable to parse line", "EOF"));
+        report.add_inserted(1);
+        assert_eq!(report.inserted(), 3);
+        assert!(!report.is_ok());
+        assert_eq!(report.errors()[0].line(), 3);
+    }
+
+    #[test]
+    fn validate_ingest_options() {
+        let mut reported = Vec::new();
+        {
+            let mut options = IngestOptions::new();
+            assert!(!options.is_due(usize::MAX, usize::MAX));
+            options.set_commit_docs(100);
+            options.set_commit_megabytes(1);
+            assert!(!options.is_due(99, 1024));
+            assert!(options.is_due(100, 0));
+            assert!(options.is_due(1, 1024 * 1024));
+
+            options.on_progress(|progress| reported.push(*progress));
+            let mut progress = Progress::default();
+            let mut report = IngestReport::default();
+            report.add_inserted(2);
+            report.add_error(3, IndexError::new("Unable to parse line", "EOF"));
+            progress.update(3, &report);
+            options.report(&progress);
+        }
+        assert_eq!(reported.len(), 1);
+        assert_eq!((reported[0].consumed(), reported[0].indexed(), reported[0].errors()), (3, 2, 1));
+    }
+}
@@ -0,0 +1,374 @@
+use std::collections::HashSet;
+
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+use tantivy::schema::{FieldType, IndexRecordOption, IntOptions, Schema, SchemaBuilder, TextFieldIndexing, TextOptions};
+
+use crate::prelude::*;
+
+/// String formats indexed untokenized like ids & keywords
+const KEYWORD_FORMATS: [&str; 9] = ["date", "time", "email", "hostname", "ipv4", "ipv6", "uri", "uri-reference", "uuid"];
+
+/// Fields mapped from the properties of a JSON Schema along with the ones read back as arrays, bools & maps
+pub(crate) struct DeclaredFields {
+    pub(crate) schema: Schema,
+    pub(crate) seqs: HashSet<String>,
+    pub(crate) object_seqs: HashSet<String>,
+    pub(crate) bools: HashSet<String>,
+    pub(crate) maps: HashSet<String>,
+}
+
+/// Maps a JSON Schema draft-07 document into fields, indexed & stored unless x-indexed or x-stored is false
+/// * string is text, untokenized when it has an enum or a format e.g. email, date-time is a date & base64 content is bytes
+/// * x-tokenizer names the tokenizer of a text field e.g. en_stem, raw is untokenized
+/// * integer is u64 with a minimum of 0 or more, i64 otherwise, number is f64 & boolean is u64 0 or 1
+/// * object is flattened into dotted fields, a map kept whole as JSON without properties
+/// * array is multi-valued, arrays of objects flattened the same way
+/// * Local $ref e.g. #/definitions/address are followed, type null alongside another type is ignored
+pub(crate) fn parse_json_schema(text: &str) -> Result<DeclaredFields, IndexError> {
+    let invalid = |reason: String| IndexError::new("Unable to read JSON schema", &reason);
+    let root: JsonValue = serde_json::from_str(text)?;
+    let mut mapping = Mapping {
+        root: &root,
+        builder: Schema::builder(),
+        names: HashSet::new(),
+        refs: Vec::new(),
+        seqs: HashSet::new(),
+        object_seqs: HashSet::new(),
+        bools: HashSet::new(),
+        maps: HashSet::new(),
+    };
+    if kind_of(&root) != Some("object") {
+        return Err(invalid("Root is not an object with properties".to_string()));
+    };
+    mapping.add_properties(None, &root, false).map_err(invalid)?;
+    if mapping.names.is_empty() {
+        return Err(invalid("Root does not have any property".to_string()));
+    };
+    Ok(DeclaredFields {
+        schema: mapping.builder.build(),
+        seqs: mapping.seqs,
+        object_seqs: mapping.object_seqs,
+        bools: mapping.bools,
+        maps: mapping.maps,
+    })
+}
+
+/// Fields of an index read back as arrays, bools, naive dates & maps, see Surfer::schema_as_json_schema
+pub(crate) struct FieldSets<'a> {
+    pub(crate) seqs: &'a HashSet<String>,
+    pub(crate) object_seqs: &'a HashSet<String>,
+    pub(crate) bools: &'a HashSet<String>,
+    pub(crate) naive_dates: &'a HashSet<String>,
+    pub(crate) maps: &'a HashSet<String>,
+}
+
+/// JSON Schema draft-07 of the fields nested back into objects, internal fields left out
+/// x-stored, x-indexed & x-tokenizer tell how every field is kept, parse_json_schema reads the document back into the same fields
+pub(crate) fn to_json_schema(name: &str, schema: &Schema, sets: &FieldSets) -> JsonValue {
+    let mut properties = JsonMap::new();
+    for (_, entry) in schema.fields() {
+        let field_name = entry.name();
+        if [SOURCE, ALL, EXPIRES_AT].contains(&field_name) {
+            continue;
+        };
+        let mut property = match entry.field_type() {
+            FieldType::Str(_) if sets.maps.contains(field_name) => serde_json::json!({"type": "object"}),
+            FieldType::Str(options) => match options.get_indexing_options() {
+                Some(indexing) => serde_json::json!({"type": "string", "x-tokenizer": indexing.tokenizer()}),
+                None => serde_json::json!({"type": "string"}),
+            },
+            FieldType::U64(_) if sets.bools.contains(field_name) => serde_json::json!({"type": "boolean"}),
+            FieldType::U64(_) => serde_json::json!({"type": "integer", "minimum": 0}),
+            FieldType::I64(_) => serde_json::json!({"type": "integer"}),
+            FieldType::F64(_) => serde_json::json!({"type": "number"}),
+            FieldType::Date(_) => serde_json::json!({"type": "string", "format": "date-time"}),
+            FieldType::HierarchicalFacet => serde_json::json!({"type": "string", "pattern": "^/"}),
+            FieldType::Bytes => serde_json::json!({"type": "string", "contentEncoding": "base64"}),
+        };
+        if let Some(property) = property.as_object_mut() {
+            property.insert("x-stored".to_string(), JsonValue::Bool(entry.is_stored()));
+            property.insert("x-indexed".to_string(), JsonValue::Bool(entry.is_indexed()));
+            if sets.naive_dates.contains(field_name) {
+                property.insert("x-naive".to_string(), JsonValue::Bool(true));
+            };
+        };
+        if sets.seqs.contains(field_name) {
+            property = serde_json::json!({"type": "array", "items": property});
+        };
+        insert_property(&mut properties, None, field_name, property, sets.object_seqs);
+    };
+    serde_json::json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": name,
+        "type": "object",
+        "properties": properties,
+    })
+}
+
+/// Nests the property of a dotted field under objects, or under the items of arrays of objects
+fn insert_property(properties: &mut JsonMap<String, JsonValue>, prefix: Option<&str>, field_name: &str, property: JsonValue, object_seqs: &HashSet<String>) {
+    let (head, rest) = match field_name.find('.') {
+        Some(i) => (&field_name[..i], &field_name[i + 1..]),
+        None => {
+            properties.insert(field_name.to_string(), property);
+            return;
+        }
+    };
+    let path = match prefix {
+        Some(prefix) => format!("{}.{}", prefix, head),
+        None => head.to_string(),
+    };
+    let object = || serde_json::json!({"type": "object", "properties": {}});
+    let parent = properties.entry(head.to_string()).or_insert_with(|| match object_seqs.contains(&path) {
+        true => serde_json::json!({"type": "array", "items": object()}),
+        false => object(),
+    });
+    let node = match parent.get_mut("items") {
+        Some(items) => items,
+        None => parent,
+    };
+    if let Some(nested) = node.get_mut("properties").and_then(JsonValue::as_object_mut) {
+        insert_property(nested, Some(&path), rest, property, object_seqs);
+    };
+}
+
+struct Mapping<'a> {
+    root: &'a JsonValue,
+    builder: SchemaBuilder,
+    names: HashSet<String>,
+    refs: Vec<String>,
+    seqs: HashSet<String>,
+    object_seqs: HashSet<String>,
+    bools: HashSet<String>,
+    maps: HashSet<String>,
+}
+
+impl<'a> Mapping<'a> {
+    fn add_properties(&mut self, prefix: Option<&str>, node: &'a JsonValue, repeated: bool) -> Result<(), String> {
+        if let Some(properties) = node.get("properties").and_then(JsonValue::as_object) {
+            for (key, property) in properties {
+                let name = match prefix {
+                    Some(prefix) => format!("{}.{}", prefix, key),
+                    None => key.to_string(),
+                };
+                self.add_property(&name, property, repeated)?;
+            };
+        };
+        Ok(())
+    }
+    fn add_property(&mut self, name: &str, property: &'a JsonValue, repeated: bool) -> Result<(), String> {
+        if let Some(reference) = property.get("$ref").and_then(JsonValue::as_str) {
+            let target = self.resolve(name, reference)?;
+            self.refs.push(reference.to_string());
+            let added = self.add_property(name, target, repeated);
+            self.refs.pop();
+            return added;
+        };
+        match kind_of(property) {
+            Some("object") if property.get("properties").is_some() => self.add_properties(Some(name), property, repeated),
+            Some("object") => {
+                self.maps.insert(name.to_string());
+                self.add_field(name, "object", property)
+            }
+            Some("array") => {
+                let items = match property.get("items") {
+                    Some(items) if items.is_object() => items,
+                    _ => return Err(format!("Property: {} is an array without a single items schema", name)),
+                };
+                let reference = items.get("$ref").and_then(JsonValue::as_str);
+                let items = match reference {
+                    Some(reference) => self.resolve(name, reference)?,
+                    None => items,
+                };
+                match kind_of(items) {
+                    Some("object") if items.get("properties").is_some() => {
+                        if !repeated {
+                            self.object_seqs.insert(name.to_string());
+                        };
+                        self.refs.extend(reference.map(str::to_string));
+                        let added = self.add_properties(Some(name), items, true);
+                        if reference.is_some() {
+                            self.refs.pop();
+                        };
+                        added
+                    }
+                    Some("array") => Err(format!("Property: {} is an array of arrays", name)),
+                    _ => {
+                        self.seqs.insert(name.to_string());
+                        self.add_property(name, items, true)
+                    }
+                }
+            }
+            Some(kind) => self.add_field(name, kind, property),
+            None => Err(format!("Property: {} does not have a supported type", name)),
+        }
+    }
+    fn add_field(&mut self, name: &str, kind: &str, property: &JsonValue) -> Result<(), String> {
+        if !self.names.insert(name.to_string()) {
+            return Err(format!("Field: {} is declared twice", name));
+        };
+        let format = property.get("format").and_then(JsonValue::as_str);
+        let flag = |key: &str| property.get(key).and_then(JsonValue::as_bool).unwrap_or(true);
+        let (stored, indexed) = (flag("x-stored"), flag("x-indexed"));
+        let mut number = IntOptions::default();
+        if stored {
+            number = number.set_stored();
+        };
+        if indexed {
+            number = number.set_indexed();
+        };
+        let keyword = property.get("enum").is_some() || format.is_some_and(|format| KEYWORD_FORMATS.contains(&format));
+        let tokenizer = match property.get("x-tokenizer").and_then(JsonValue::as_str) {
+            Some(tokenizer) => tokenizer,
+            None if keyword => "raw",
+            None => "default",
+        };
+        match kind {
+            "string" if format == Some("date-time") => {
+                self.builder.add_date_field(name, number);
+            }
+            "string" if property.get("contentEncoding").and_then(JsonValue::as_str) == Some("base64") => {
+                self.builder.add_bytes_field(name);
+            }
+            "string" | "object" => {
+                let record = match tokenizer {
+                    "raw" => IndexRecordOption::Basic,
+                    _ => IndexRecordOption::WithFreqsAndPositions,
+                };
+                let mut text = TextOptions::default();
+                if stored {
+                    text = text.set_stored();
+                };
+                if indexed {
+                    text = text.set_indexing_options(TextFieldIndexing::default().set_tokenizer(tokenizer).set_index_option(record));
+                };
+                self.builder.add_text_field(name, text);
+            }
+            "integer" if property.get("minimum").and_then(JsonValue::as_f64).is_some_and(|minimum| minimum >= 0.0) => {
+                self.builder.add_u64_field(name, number);
+            }
+            "integer" => {
+                self.builder.add_i64_field(name, number);
+            }
+            "number" => {
+                self.builder.add_f64_field(name, number);
+            }
+            "boolean" => {
+                self.bools.insert(name.to_string());
+                self.builder.add_u64_field(name, number);
+            }
+            kind => return Err(format!("Property: {} has the unsupported type: {}", name, kind)),
+        };
+        Ok(())
+    }
+    /// Definition of a local reference e.g. #/definitions/address, an error when already being mapped
+    fn resolve(&self, name: &str, reference: &str) -> Result<&'a JsonValue, String> {
+        if self.refs.iter().any(|seen| seen == reference) {
+            return Err(format!("Property: {} refers to {} recursively", name, reference));
+        };
+        if !reference.starts_with("#/") && reference != "#" {
+            return Err(format!("Property: {} refers to {} which is not local", name, reference));
+        };
+        self.root.pointer(&reference[1..])
+            .ok_or_else(|| format!("Property: {} refers to {} which does not exist", name, reference))
+    }
+}
+
+/// Type of the schema, the first besides null when several e.g. ["string", "null"]
+fn kind_of(node: &JsonValue) -> Option<&str> {
+    match node.get("type") {
+        Some(JsonValue::String(kind)) => Some(kind.as_str()),
+        Some(JsonValue::Array(kinds)) => kinds.iter().filter_map(JsonValue::as_str).find(|kind| *kind != "null"),
+        Some(_) => None,
+        None if node.get("properties").is_some() => Some("object"),
+        None if node.get("items").is_some() => Some("array"),
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::schema::FieldType;
+
+    #[test]
+    fn validate_parse_json_schema() {
+        let text = r##"{
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "definitions": {
+                "address": {"type": "object", "properties": {"city": {"type": "string"}}}
+            },
+            "properties": {
+                "title": {"type": "string"},
+                "email": {"type": "string", "format": "email"},
+                "published": {"type": "string", "format": "date-time"},
+                "pages": {"type": "integer", "minimum": 1},
+                "delta": {"type": ["integer", "null"]},
+                "price": {"type": "number"},
+                "active": {"type": "boolean"},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "address": {"$ref": "#/definitions/address"},
+                "items": {"type": "array", "items": {"type": "object", "properties": {"sku": {"type": "string", "enum": ["a", "b"]}}}},
+                "extra": {"type": "object", "additionalProperties": true}
+            }
+        }"##;
+        let declared = parse_json_schema(text).unwrap();
+        let schema = declared.schema;
+        let field_type = |name: &str| schema.get_field_entry(schema.get_field(name).unwrap()).field_type().clone();
+        assert!(matches!(field_type("title"), FieldType::Str(_)));
+        let email = schema.get_field_entry(schema.get_field("email").unwrap());
+        assert_eq!(email.field_type().get_index_record_option(), Some(tantivy::schema::IndexRecordOption::Basic));
+        assert!(matches!(field_type("published"), FieldType::Date(_)));
+        assert!(matches!(field_type("pages"), FieldType::U64(_)));
+        assert!(matches!(field_type("delta"), FieldType::I64(_)));
+        assert!(matches!(field_type("price"), FieldType::F64(_)));
+        assert!(matches!(field_type("address.city"), FieldType::Str(_)));
+        assert!(matches!(field_type("items.sku"), FieldType::Str(_)));
+        assert!(declared.bools.contains("active"));
+        assert!(declared.seqs.contains("tags"));
+        assert!(declared.object_seqs.contains("items"));
+        assert!(declared.maps.contains("extra"));
+
+        assert!(parse_json_schema(r#"{"type": "array", "items": {"type": "string"}}"#).is_err());
+        assert!(parse_json_schema(r##"{"properties": {"a": {"$ref": "#/definitions/b"}}}"##).is_err());
+        assert!(parse_json_schema(r##"{"definitions": {"b": {"properties": {"c": {"$ref": "#/definitions/b"}}}}, "properties": {"a": {"$ref": "#/definitions/b"}}}"##).is_err());
+        assert!(parse_json_schema(r##"{"definitions": {"b": {"properties": {"c": {"type": "array", "items": {"$ref": "#/definitions/b"}}}}}, "properties": {"a": {"$ref": "#/definitions/b"}}}"##).is_err());
+        assert!(parse_json_schema(r#"{"properties": {"a": {"type": "null"}}}"#).is_err());
+    }
+
+    #[test]
+    fn validate_to_json_schema() {
+        let text = r#"{"properties": {
+            "id": {"type": "string", "format": "uuid"},
+            "pages": {"type": "integer", "minimum": 0},
+            "active": {"type": "boolean"},
+            "tags": {"type": "array", "items": {"type": "string"}},
+            "address": {"properties": {"city": {"type": "string"}}},
+            "items": {"type": "array", "items": {"properties": {"quantity": {"type": "integer"}}}}
+        }}"#;
+        let declared = parse_json_schema(text).unwrap();
+        let naive_dates = HashSet::new();
+        let sets = FieldSets {
+            seqs: &declared.seqs,
+            object_seqs: &declared.object_seqs,
+            bools: &declared.bools,
+            naive_dates: &naive_dates,
+            maps: &declared.maps,
+        };
+        let computed = to_json_schema("books", &declared.schema, &sets);
+        assert_eq!(computed["title"], "books");
+        assert_eq!(computed["properties"]["pages"], serde_json::json!({"type": "integer", "minimum": 0, "x-stored": true, "x-indexed": true}));
+        assert_eq!(computed["properties"]["active"]["type"], "boolean");
+        assert_eq!(computed["properties"]["tags"]["items"]["type"], "string");
+        assert_eq!(computed["properties"]["address"]["properties"]["city"]["type"], "string");
+        assert_eq!(computed["properties"]["items"]["items"]["properties"]["quantity"]["type"], "integer");
+
+        let computed = parse_json_schema(&computed.to_string()).unwrap();
+        assert_eq!(serde_json::to_value(&computed.schema).unwrap(), serde_json::to_value(&declared.schema).unwrap());
+        assert_eq!(computed.seqs, declared.seqs);
+        assert_eq!(computed.object_seqs, declared.object_seqs);
+        assert_eq!(computed.bools, declared.bools);
+    }
+}
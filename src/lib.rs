@@ -2,8 +2,49 @@
 //! ## Features
 //! * Full text search
 //! * Serialize __**flat**__ JSON/Struct
+//! * Nested JSON/Struct flattened into dotted field names e.g. address.city
+//! * Arrays of objects flattened into multi-valued dotted fields e.g. items.sku of line items
+//! * Schema mapped from the properties of a JSON Schema draft-07 document, or exported as one
+//! * Fields filled from JSON pointers into arbitrarily shaped JSON e.g. /user/address/city
+//! * Null or missing fields skipped, given a declared default or rejecting the document per index
+//! * Opt-in coercion of strings & numbers into the type of their field e.g. "42" into a u64
 //! * Easy write and read API
+//! * Compose must, should & must_not clauses into boolean queries
+//! * Find similar documents to a seed document or query
+//! * Did you mean suggestions & autocomplete from the terms of the index
+//! * N-gram tokenized text fields for substring matches
+//! * Stopwords per index or per field, built-in for common languages
+//! * Custom tokenizers registered with every index opened
+//! * Synonyms per index expanding query strings, optionally indexed too
+//! * Stemming language per text field
+//! * Hierarchical facets from path-like fields, counted level by level
+//! * Distinct values of a field along with their counts
+//! * Scoring explanations of why a document did or did not rank
+//! * Timings of inserts, commits, parsing, searches & jsonify logged behind the tracing feature
+//! * Geo points searched within a radius, nearest first
+//! * Date range filters with date math e.g. now-7d
+//! * Documents expiring after a TTL per index, purged on demand
+//! * Original JSON kept as a _source field & read back as inserted
+//! * Catch-all _all field gathering the text fields for simple search boxes
+//! * Aliases pointing a logical name at an index, switched at once
+//! * Search across several indexes merged by score
+//! * Hits collapsed to the best one per distinct value of a field
+//! * Projection of the stored fields returned by a search
+//! * Stored documents mapped straight into user types implementing FromDocument, or deriving it behind the derive feature
+//! * Probing for an index on disk without creating directories
+//! * Lazy indexes created on disk with their first document
+//! * Indexes, fields & options declared in a JSON config file
+//! * Builders saved to disk & loaded by other processes with the very same schemas
+//! * Chinese, Japanese & Korean text fields tokenized into bigrams behind the cjk feature
 //! * Write multiple documents together
+//! * Commits per document, every N documents or on an interval from a background timer, durable or deferred
+//! * Share a single Surfer across threads behind an Arc
+//! * Indexes locked by a writer of another process reported as such, optionally waited for
+//! * Snapshot indexes while they stay in use
+//! * Derive schemas at compile time with #[derive(Surf)] behind the derive feature
+//! * Create, load & query indexes from the shell with the json-surf binary behind the cli feature
+//! * Serve indexes over HTTP behind the server feature
+//! * Bulk load NDJSON, or CSV mapped by header behind the csv feature
 //! * Depends mostly on stable crates
 //! * Requires no runtime
 //! * No unsafe block
@@ -82,7 +123,7 @@
 //!     builder.add_struct(name.clone(), &data);
 //!
 //!     // Make the Surfer
-//!     let mut surfer = Surfer::try_from(builder).unwrap();
+//!     let surfer = Surfer::try_from(builder).unwrap();
 //!
 //!     // Prepare your data or get it from somewhere
 //!     let title = "The Old Man and the Sea".to_string();
@@ -156,6 +197,43 @@ pub mod errors;
 pub mod utils;
 pub mod registry;
 pub mod fuzzy;
+pub mod boolean;
+pub mod similar;
+pub mod suggest;
+pub mod tokenizer;
+pub mod synonym;
+#[cfg(feature = "cjk")]
+pub mod cjk;
+pub mod search;
+pub mod aggregation;
+pub mod facet;
+pub mod distinct;
+pub mod explain;
+pub mod trace;
+pub mod geo;
+pub mod datemath;
+pub mod expiry;
+pub mod source;
+pub mod catch_all;
+pub mod migration;
+pub mod surf;
+pub mod snapshot;
+pub mod ingest;
+pub mod commit;
+pub mod document;
+pub mod shape;
+pub mod json_schema;
+pub mod config;
+#[cfg(feature = "csv")]
+pub(crate) mod csv;
+#[cfg(feature = "server")]
+pub mod server;
+
+#[cfg(feature = "derive")]
+pub use json_surf_derive::{Surf, FromDocument};
+
+// Lets the derive macro resolve ::json_surf paths within this crate
+extern crate self as json_surf;
 
 #[cfg(test)]
 mod tests {
@@ -324,11 +402,11 @@ mod tests {
 
         let mut expected = Schema::builder();
         expected.add_text_field("a", TEXT | STORED);
-        expected.add_text_field("b", TEXT | STORED);
 
         let options = IntOptions::default();
         let options = options.set_stored();
         let options = options.set_indexed();
+        expected.add_u64_field("b", options.clone());
         expected.add_u64_field("c", options.clone());
         expected.add_u64_field("d", options.clone());
         expected.add_u64_field("e", options.clone());
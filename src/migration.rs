@@ -0,0 +1,91 @@
+use tantivy::schema::Schema;
+
+use serde::Serialize;
+
+/// Fields which differ between the schema on disk and the expected one
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct SchemaDrift {
+    added: Vec<String>,
+    removed: Vec<String>,
+    retyped: Vec<String>,
+}
+
+impl SchemaDrift {
+    /// Fields only in the expected schema
+    pub fn added(&self) -> &Vec<String> {
+        &self.added
+    }
+    /// Fields only in the schema on disk
+    pub fn removed(&self) -> &Vec<String> {
+        &self.removed
+    }
+    /// Fields in both schemas with a different value type
+    pub fn retyped(&self) -> &Vec<String> {
+        &self.retyped
+    }
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.retyped.is_empty()
+    }
+    /// One line summary e.g. for error reasons
+    pub fn describe(&self) -> String {
+        format!("added: {:?}, removed: {:?}, retyped: {:?}", self.added, self.removed, self.retyped)
+    }
+}
+
+/// Compares the fields of the current & the expected schema by name and value type
+pub(crate) fn schema_drift(current: &Schema, expected: &Schema) -> SchemaDrift {
+    let mut drift = SchemaDrift::default();
+    for (field, entry) in expected.fields() {
+        let name = expected.get_field_name(field);
+        match current.get_field(name) {
+            Some(existing) => {
+                let existing = current.get_field_entry(existing).field_type().value_type();
+                if existing != entry.field_type().value_type() {
+                    drift.retyped.push(name.to_string());
+                };
+            }
+            None => drift.added.push(name.to_string()),
+        };
+    };
+    for (field, _) in current.fields() {
+        let name = current.get_field_name(field);
+        if expected.get_field(name).is_none() {
+            drift.removed.push(name.to_string());
+        };
+    };
+    drift.added.sort();
+    drift.removed.sort();
+    drift.retyped.sort();
+    drift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::schema::{SchemaBuilder, TEXT, STORED};
+
+    #[test]
+    fn validate_schema_drift() {
+        let mut builder = SchemaBuilder::new();
+        builder.add_text_field("title", TEXT);
+        builder.add_u64_field("count", STORED);
+        builder.add_text_field("body", TEXT);
+        let current = builder.build();
+
+        let mut builder = SchemaBuilder::new();
+        builder.add_text_field("title", TEXT);
+        builder.add_i64_field("count", STORED);
+        builder.add_text_field("author", TEXT);
+        let expected = builder.build();
+
+        let drift = schema_drift(&current, &expected);
+        assert!(!drift.is_empty());
+        assert_eq!(drift.added(), &vec!["author".to_string()]);
+        assert_eq!(drift.removed(), &vec!["body".to_string()]);
+        assert_eq!(drift.retyped(), &vec!["count".to_string()]);
+        assert_eq!(drift.describe(), r#"added: ["author"], removed: ["body"], retyped: ["count"]"#);
+
+        let drift = schema_drift(&current, &current);
+        assert!(drift.is_empty());
+    }
+}
@@ -1,5 +1,21 @@
 pub use crate::registry::{Surfer, SurferBuilder, Control};
+pub(crate) use crate::registry::as_struct;
 pub use crate::errors::IndexError;
+pub use crate::search::{SearchOptions, Order, Hit, MultiHit, Scan, Page, SearchResult};
+pub use crate::aggregation::Stats;
+pub use crate::facet::FacetCount;
+pub use crate::distinct::DistinctValue;
+pub use crate::explain::ScoreExplanation;
+pub use crate::trace::TRACE_TARGET;
+pub use crate::geo::{GeoPoint, Nearby};
+pub use crate::datemath::resolve_date;
+pub use crate::expiry::EXPIRES_AT;
+pub use crate::source::SOURCE;
+pub use crate::catch_all::ALL;
+pub use crate::migration::SchemaDrift;
+pub use crate::surf::{Surf, SurfField, SurfKind, SurfOptions};
+pub use crate::ingest::{IngestReport, LineError, IngestOptions, OnProgress, Progress, Ingester};
+pub use crate::config::{SurferConfig, IndexConfig, FieldConfig, FieldKind, StopwordsConfig};
 
 pub use crate::utils::field_names;
 pub use crate::utils::join;
@@ -9,11 +25,75 @@ pub use crate::utils::ls;
 
 pub(crate) use crate::utils::as_value;
 pub(crate) use crate::utils::to_schema;
+pub(crate) use crate::utils::to_surf_schema;
+pub(crate) use crate::surf::surf_fields_of;
+pub(crate) use crate::surf::surf_control;
+pub(crate) use crate::surf::surf_object_seqs;
+pub(crate) use crate::utils::as_term;
+pub(crate) use crate::utils::as_document;
+pub(crate) use crate::utils::{json_as_document, json_as_document_with_source};
+pub(crate) use crate::utils::flatten;
+pub(crate) use crate::utils::unflatten;
+pub(crate) use crate::utils::zip_items;
+pub(crate) use crate::utils::merge_patch;
+pub(crate) use crate::utils::text_seq_fields;
+pub(crate) use crate::utils::object_seq_fields;
+pub(crate) use crate::utils::bool_fields;
+pub(crate) use crate::utils::naive_date_fields;
+pub(crate) use crate::utils::map_fields;
+pub(crate) use crate::utils::date_range_query;
+pub(crate) use crate::utils::numeric_range_query;
+pub(crate) use crate::utils::text_field;
+pub(crate) use crate::utils::fuzzy_query;
+pub(crate) use crate::utils::regex_query;
+pub(crate) use crate::utils::phrase_query;
+pub(crate) use crate::search::top_docs;
+pub(crate) use crate::search::page_docs;
+pub(crate) use crate::aggregation::aggregate;
+pub(crate) use crate::facet::{facet_counts, facet_path, facet_text};
+pub(crate) use crate::distinct::distinct_values;
+pub(crate) use crate::explain::explain_doc;
+pub(crate) use crate::trace::Span;
+pub(crate) use crate::geo::{bounding_box_query, geo_point};
+pub(crate) use crate::expiry::{with_expiry, stamp_expiry, expired_terms, expired_query, expiries};
+pub(crate) use crate::source::{with_source, stamp_source, source_of};
+pub(crate) use crate::catch_all::{with_catch_all, stamp_catch_all};
+pub(crate) use crate::migration::schema_drift;
+pub(crate) use crate::ingest::BATCH_SIZE;
+pub use crate::commit::{CommitPolicy, Durability};
+pub(crate) use crate::commit::{spawn_committer, UnsyncedDirectory};
+pub use crate::document::{FromDocument, FromStored, StoredDocument};
+pub(crate) use crate::document::from_document;
+pub use crate::shape::NullPolicy;
+pub(crate) use crate::shape::Shape;
+pub(crate) use crate::json_schema::{parse_json_schema, to_json_schema, FieldSets};
+pub(crate) use crate::snapshot::snapshot_index;
+pub(crate) use crate::snapshot::validate_snapshot;
+pub(crate) use crate::snapshot::copy_snapshot;
 pub(crate) use crate::seed::open_index;
+pub(crate) use crate::seed::open_index_in_ram;
 pub(crate) use crate::seed::open_mmap_directory;
 pub(crate) use crate::seed::open_index_writer;
+pub(crate) use crate::seed::open_index_writer_waiting;
 pub(crate) use crate::seed::open_index_reader;
+pub(crate) use crate::seed::open_existing_schema;
+pub(crate) use crate::seed::probe_index;
 pub(crate) use crate::seed::resolve_home;
 pub(crate) use crate::seed::resolve_index_directory_path;
 
 pub use crate::fuzzy::{FuzzyConfig, FuzzyWord};
+pub use crate::boolean::{BoolQuery, Clause};
+pub(crate) use crate::boolean::bool_query;
+pub use crate::similar::{Like, MoreLikeThis};
+pub(crate) use crate::similar::similar_clauses;
+pub use crate::suggest::Suggestion;
+pub(crate) use crate::suggest::suggest_terms;
+pub(crate) use crate::suggest::complete_terms;
+pub(crate) use crate::suggest::prefix_pattern;
+pub use crate::tokenizer::{Stopwords, Language, ngram_tokenizer, ngram_options, stem_tokenizer, stem_options};
+pub(crate) use crate::tokenizer::{Analysis, Tokenizers};
+pub(crate) use crate::tokenizer::register_tokenizers;
+pub use crate::synonym::Synonyms;
+pub(crate) use crate::synonym::{expand_query, SynonymFilter};
+#[cfg(feature = "cjk")]
+pub use crate::cjk::{CjkTokenizer, CJK_TOKENIZER};
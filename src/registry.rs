@@ -1,38 +1,120 @@
-use std::collections::{HashMap, BTreeMap};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::time::Duration;
+use std::collections::{HashMap, HashSet, BTreeSet};
 use std::convert::TryFrom;
+use std::ops::Range;
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::path::Path;
+use std::io::{BufRead, Write};
 
-use tantivy::schema::{Schema, Field, TextOptions, IntOptions};
-use tantivy::{Index, IndexReader, IndexWriter, Document};
-use tantivy::query::QueryParser;
-use tantivy::collector::TopDocs;
+use chrono::{DateTime, Utc};
+
+use tantivy::schema::{Schema, Field, FieldType, TextOptions, IntOptions, IndexRecordOption, Cardinality};
+use tantivy::{Index, IndexReader, IndexWriter, Document, DocAddress, LeasedItem, Searcher, Term};
+use tantivy::UserOperation;
+use tantivy::query::{QueryParser, Query, TermQuery, BooleanQuery, Occur, AllQuery};
+use tantivy::SnippetGenerator;
+use tantivy::collector::{Count, TopDocs};
 use tantivy::schema::Value as SchemaValue;
+use tantivy::tokenizer::TextAnalyzer;
 
 
 use crate::prelude::*;
 use crate::prelude::join;
 use serde_value::Value;
-use serde::{Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use serde::{Serialize, Deserialize};
 use serde::de::DeserializeOwned;
 
 /// Builder struct for Surfer
-#[derive(Clone)]
+/// Serializable apart from the tokenizers registered, see save & load
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SurferBuilder {
     schemas: HashMap<String, Schema>,
     home: Option<String>,
+    primary_keys: HashMap<String, String>,
+    auto_commits: HashMap<String, bool>,
+    commit_policies: HashMap<String, CommitPolicy>,
+    durabilities: HashMap<String, Durability>,
+    conjunctions: HashMap<String, bool>,
+    search_fields: HashMap<String, Vec<String>>,
+    boosts: HashMap<String, HashMap<String, f32>>,
+    analyses: HashMap<String, Analysis>,
+    shapes: HashMap<String, Shape>,
+    #[serde(skip)]
+    tokenizers: Tokenizers,
+    ttls: HashMap<String, Duration>,
+    default_limits: HashMap<String, usize>,
+    max_limits: HashMap<String, usize>,
+    sources: HashMap<String, bool>,
+    catch_alls: HashMap<String, bool>,
+    text_seqs: HashMap<String, HashSet<String>>,
+    object_seqs: HashMap<String, HashSet<String>>,
+    bools: HashMap<String, HashSet<String>>,
+    naive_dates: HashMap<String, HashSet<String>>,
+    maps: HashMap<String, HashSet<String>>,
+    in_memory: HashMap<String, bool>,
+    lazy: bool,
+    lock_timeout: Option<Duration>,
 }
 
-
-#[derive(Serialize)]
-struct SingleValuedNamedFieldDocument<'a>(BTreeMap<&'a str, &'a SchemaValue>);
-
 /// Default impl to get things going
 impl Default for SurferBuilder {
     fn default() -> Self {
         let schemas = HashMap::new();
         let home = None;
+        let primary_keys = HashMap::new();
+        let auto_commits = HashMap::new();
+        let commit_policies = HashMap::new();
+        let durabilities = HashMap::new();
+        let conjunctions = HashMap::new();
+        let search_fields = HashMap::new();
+        let boosts = HashMap::new();
+        let analyses = HashMap::new();
+        let shapes = HashMap::new();
+        let tokenizers = Tokenizers::new();
+        let ttls = HashMap::new();
+        let default_limits = HashMap::new();
+        let max_limits = HashMap::new();
+        let sources = HashMap::new();
+        let catch_alls = HashMap::new();
+        let text_seqs = HashMap::new();
+        let object_seqs = HashMap::new();
+        let bools = HashMap::new();
+        let naive_dates = HashMap::new();
+        let maps = HashMap::new();
+        let in_memory = HashMap::new();
+        let lazy = false;
+        let lock_timeout = None;
         Self {
             schemas,
             home,
+            primary_keys,
+            auto_commits,
+            commit_policies,
+            durabilities,
+            conjunctions,
+            search_fields,
+            boosts,
+            analyses,
+            shapes,
+            tokenizers,
+            ttls,
+            default_limits,
+            max_limits,
+            sources,
+            catch_alls,
+            text_seqs,
+            object_seqs,
+            bools,
+            naive_dates,
+            maps,
+            in_memory,
+            lazy,
+            lock_timeout,
         }
     }
 }
@@ -48,9 +130,68 @@ impl SurferBuilder {
     pub fn add_schema(&mut self, name: String, schema: Schema) {
         self.schemas.insert(name, schema);
     }
+    /// Builder declaring the indexes, fields & options of the JSON config file, see SurferConfig
+    pub fn from_config<P: AsRef<Path>>(path: P) -> Result<SurferBuilder, IndexError> {
+        SurferConfig::from_path(path)?.builder()
+    }
+    /// Writes the schemas & options as JSON, other processes load the very same builder
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), IndexError> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+    /// Builder saved before, register the tokenizers again since analysis chains are not saved
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<SurferBuilder, IndexError> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)?;
+        serde_json::from_str(&json).map_err(|e| {
+            let message = format!("Unable to load builder: {:?}", path);
+            IndexError::new(message, e.to_string())
+        })
+    }
+    /// Schema declared field by field along with its multi-valued text & bool fields
+    pub(crate) fn add_declared_schema(&mut self, name: &str, schema: Schema, text_seqs: HashSet<String>, bools: HashSet<String>) {
+        self.text_seqs.insert(name.to_string(), text_seqs);
+        self.bools.insert(name.to_string(), bools);
+        self.schemas.insert(name.to_string(), schema);
+    }
+    /// Add an index already on disk under home using its own schema, set home first
+    /// Bools come back as u64 & naive dates as UTC since the sample struct is unknown
+    pub fn add_existing(&mut self, name: &str) -> Result<(), IndexError> {
+        let home = self.home.as_deref();
+        let path = resolve_index_directory_path(name, home)?;
+        let schema = open_existing_schema(path)?;
+        self.schemas.insert(name.to_string(), schema);
+        Ok(())
+    }
+    /// Creates indexes not on disk yet with the first document inserted instead of up front
+    pub fn set_lazy(&mut self, lazy: bool) {
+        self.lazy = lazy;
+    }
+    /// Waits up to the timeout for another process to release the writer of an index - default is to fail at once
+    pub fn set_lock_timeout(&mut self, timeout: Duration) {
+        self.lock_timeout = Some(timeout);
+    }
+    /// Does an index with a valid meta.json exist on disk under home, creates no directories
+    pub fn probe(home: &str, name: &str) -> bool {
+        probe_index(home, name)
+    }
     /// Add serde value panics otherwise
+    /// Sequences of strings need at least one element to be detected as multi-valued text
     pub fn add_serde(&mut self, name: String, data: &Value) {
-        let schema = to_schema(data, None).unwrap();
+        self.add_serde_with_control(name, data, HashMap::new());
+    }
+    /// Add serde value with options per field e.g. indexed only, stored only or tokenizer panics otherwise
+    pub fn add_serde_with_control(&mut self, name: String, data: &Value, control: HashMap<String, Control>) {
+        let schema = to_schema(data, Some(&control)).unwrap();
+        let text_seqs = text_seq_fields(data);
+        self.text_seqs.insert(name.clone(), text_seqs);
+        self.object_seqs.insert(name.clone(), object_seq_fields(data));
+        let bools = bool_fields(data);
+        self.bools.insert(name.clone(), bools);
+        let naive_dates = naive_date_fields(data);
+        self.naive_dates.insert(name.clone(), naive_dates);
+        self.maps.insert(name.clone(), map_fields(&control));
         self.schemas.insert(name, schema);
     }
     /// Add a serializable rust struct panics otherwise
@@ -58,15 +199,167 @@ impl SurferBuilder {
         let value = as_value(data).unwrap();
         self.add_serde(name, &value);
     }
+    /// Add a serializable rust struct with options per field panics otherwise
+    pub fn add_struct_with_control<T: Serialize>(&mut self, name: String, data: &T, control: HashMap<String, Control>) {
+        let value = as_value(data).unwrap();
+        self.add_serde_with_control(name, &value, control);
+    }
+    /// Add the fields of a JSON Schema draft-07 document, no sample or struct is needed, see json_schema
+    pub fn add_json_schema(&mut self, name: &str, json_schema: &str) -> Result<(), IndexError> {
+        let declared = parse_json_schema(json_schema)?;
+        self.text_seqs.insert(name.to_string(), declared.seqs);
+        self.object_seqs.insert(name.to_string(), declared.object_seqs);
+        self.bools.insert(name.to_string(), declared.bools);
+        self.maps.insert(name.to_string(), declared.maps);
+        self.schemas.insert(name.to_string(), declared.schema);
+        Ok(())
+    }
+    /// Add a struct deriving Surf, no sample instance is needed
+    pub fn add_surf<T: Surf>(&mut self) {
+        self.add_surf_with_control::<T>(HashMap::new());
+    }
+    /// Add a struct deriving Surf with options per field overriding #[surf(..)] panics otherwise
+    pub fn add_surf_with_control<T: Surf>(&mut self, control: HashMap<String, Control>) {
+        let name = T::index_name().to_string();
+        let fields = T::surf_fields();
+        let control = surf_control(&fields, control);
+        let schema = to_surf_schema(&fields, Some(&control)).unwrap();
+        self.text_seqs.insert(name.clone(), surf_fields_of(&fields, SurfKind::TextSeq));
+        self.object_seqs.insert(name.clone(), surf_object_seqs(&fields));
+        self.bools.insert(name.clone(), surf_fields_of(&fields, SurfKind::Bool));
+        self.naive_dates.insert(name.clone(), surf_fields_of(&fields, SurfKind::NaiveDate));
+        self.maps.insert(name.clone(), map_fields(&control));
+        self.schemas.insert(name, schema);
+    }
+    /// Declare the field uniquely identifying a document, required for upserts
+    pub fn set_primary_key(&mut self, name: &str, field: &str) {
+        self.primary_keys.insert(name.to_string(), field.to_string());
+    }
+    /// Commit after every write - default is true, otherwise call Surfer::commit
+    pub fn set_auto_commit(&mut self, name: &str, auto_commit: bool) {
+        self.auto_commits.insert(name.to_string(), auto_commit);
+    }
+    /// When auto commit commits - default is every document
+    pub fn set_commit_policy(&mut self, name: &str, policy: CommitPolicy) {
+        self.commit_policies.insert(name.to_string(), policy);
+    }
+    /// Fills the field with the value at the JSON pointer e.g. /user/address/city into city
+    /// Once an index declares a pointer its documents are built from the pointers only
+    pub fn add_json_pointer(&mut self, name: &str, pointer: &str, field: &str) {
+        self.shapes.entry(name.to_string()).or_default().add_pointer(pointer, field);
+    }
+    /// What inserts do with fields holding null or missing - default is skipping them, see NullPolicy
+    pub fn set_null_policy(&mut self, name: &str, policy: NullPolicy) {
+        self.shapes.entry(name.to_string()).or_default().set_null_policy(policy);
+    }
+    /// Value indexed for the field when null or missing under NullPolicy::Default, nested fields are dotted
+    pub fn set_null_default(&mut self, name: &str, field: &str, value: JsonValue) {
+        self.shapes.entry(name.to_string()).or_default().set_default(field, value);
+    }
+    /// Converts strings & numbers to the type of their field at insert e.g. "42" into a u64 - default is false
+    pub fn set_coercion(&mut self, name: &str, coerce: bool) {
+        self.shapes.entry(name.to_string()).or_default().set_coercion(coerce);
+    }
+    /// Coerces the field or keeps it strict whatever the index does, nested fields are dotted
+    pub fn set_field_coercion(&mut self, name: &str, field: &str, coerce: bool) {
+        self.shapes.entry(name.to_string()).or_default().set_field_coercion(field, coerce);
+    }
+    /// Whether commits wait until the writes are on disk - default is durable
+    pub fn set_durability(&mut self, name: &str, durability: Durability) {
+        self.durabilities.insert(name.to_string(), durability);
+    }
+    /// Query strings match documents holding every term - default is false, any term matches
+    pub fn set_conjunction_by_default(&mut self, name: &str, conjunction: bool) {
+        self.conjunctions.insert(name.to_string(), conjunction);
+    }
+    /// Fields searched by query strings without a field prefix - default is every indexed text field
+    pub fn set_default_search_fields(&mut self, name: &str, fields: &[&str]) {
+        let fields = fields.iter().map(|field| field.to_string()).collect();
+        self.search_fields.insert(name.to_string(), fields);
+    }
+    /// Multiplies the score of query string matches on the field e.g. title worth 3 times the body
+    pub fn set_field_boost(&mut self, name: &str, field: &str, boost: f32) {
+        let boosts = self.boosts.entry(name.to_string()).or_default();
+        boosts.insert(field.to_string(), boost);
+    }
+    /// Removes the stopwords from every text field of the index at analysis time
+    pub fn set_stopwords(&mut self, name: &str, stopwords: Stopwords) {
+        self.analyses.entry(name.to_string()).or_default().set_stopwords(stopwords);
+    }
+    /// Removes the stopwords from the text field instead of those of the index, applies to indexes created afterwards
+    pub fn set_field_stopwords(&mut self, name: &str, field: &str, stopwords: Stopwords) {
+        self.analyses.entry(name.to_string()).or_default().set_field_stopwords(field, stopwords);
+    }
+    /// Query strings match the synonyms of their words, at index time too adds single word synonyms to the text indexed
+    /// Index time synonyms apply to indexes opened afterwards, reindex the documents written before
+    pub fn set_synonyms(&mut self, name: &str, synonyms: Synonyms, at_index: bool) {
+        self.analyses.entry(name.to_string()).or_default().set_synonyms(synonyms, at_index);
+    }
+    /// Documents of the index expire once the TTL elapsed since they were written, applies to indexes created afterwards
+    pub fn set_ttl(&mut self, name: &str, ttl: Duration) {
+        self.ttls.insert(name.to_string(), ttl);
+    }
+    /// Documents returned by a search without a limit - default is 10
+    pub fn set_default_limit(&mut self, name: &str, limit: usize) {
+        self.default_limits.insert(name.to_string(), limit);
+    }
+    /// Caps the limit of every search, larger limits are lowered to it
+    pub fn set_max_limit(&mut self, name: &str, limit: usize) {
+        self.max_limits.insert(name.to_string(), limit);
+    }
+    /// Keeps the JSON inserted in a stored only _source field read back as is - default is false
+    /// Applies to indexes created afterwards, reads then skip the reconstruction from the stored fields
+    pub fn set_store_source(&mut self, name: &str, store_source: bool) {
+        self.sources.insert(name.to_string(), store_source);
+    }
+    /// Copies every text field into an indexed only _all field searched by default - default is false
+    /// Applies to indexes created afterwards, queries prefixed by a field still target that field
+    pub fn set_catch_all(&mut self, name: &str, catch_all: bool) {
+        self.catch_alls.insert(name.to_string(), catch_all);
+    }
+    /// Registers the analysis chain with every index opened, name it as the tokenizer of Control text options
+    pub fn register_tokenizer<T: Into<TextAnalyzer>>(&mut self, name: &str, analyzer: T) {
+        self.tokenizers.insert(name.to_string(), analyzer.into());
+    }
+    /// Back the index with a RAM directory instead of mmap - default is false, data is lost on drop
+    pub fn set_in_memory(&mut self, name: &str, in_memory: bool) {
+        self.in_memory.insert(name.to_string(), in_memory);
+    }
 }
 
-/// Surfer: Client API
+/// Surfer: Client API, share it across threads behind an Arc
 pub struct Surfer {
     home: String,
-    indexes: HashMap<String, Index>,
-    fields: HashMap<String, Vec<Field>>,
-    readers: HashMap<String, Option<IndexReader>>,
-    writers: HashMap<String, Option<IndexWriter>>,
+    indexes: RwLock<HashMap<String, Index>>,
+    fields: RwLock<HashMap<String, Vec<Field>>>,
+    readers: RwLock<HashMap<String, IndexReader>>,
+    writers: RwLock<HashMap<String, Arc<RwLock<IndexWriter>>>>,
+    primary_keys: RwLock<HashMap<String, String>>,
+    auto_commits: RwLock<HashMap<String, bool>>,
+    commit_policies: RwLock<HashMap<String, CommitPolicy>>,
+    uncommitted: RwLock<HashMap<String, Arc<AtomicUsize>>>,
+    durabilities: RwLock<HashMap<String, Durability>>,
+    conjunctions: RwLock<HashMap<String, bool>>,
+    search_fields: RwLock<HashMap<String, Vec<String>>>,
+    boosts: RwLock<HashMap<String, HashMap<String, f32>>>,
+    analyses: RwLock<HashMap<String, Analysis>>,
+    shapes: RwLock<HashMap<String, Shape>>,
+    tokenizers: Tokenizers,
+    ttls: RwLock<HashMap<String, Duration>>,
+    default_limits: RwLock<HashMap<String, usize>>,
+    max_limits: RwLock<HashMap<String, usize>>,
+    sources: RwLock<HashMap<String, bool>>,
+    catch_alls: RwLock<HashMap<String, bool>>,
+    text_seqs: RwLock<HashMap<String, HashSet<String>>>,
+    object_seqs: RwLock<HashMap<String, HashSet<String>>>,
+    bools: RwLock<HashMap<String, HashSet<String>>>,
+    naive_dates: RwLock<HashMap<String, HashSet<String>>>,
+    maps: RwLock<HashMap<String, HashSet<String>>>,
+    closed: RwLock<HashMap<String, Schema>>,
+    in_memory: RwLock<HashMap<String, bool>>,
+    aliases: RwLock<HashMap<String, String>>,
+    pending: RwLock<HashMap<String, Schema>>,
+    lock_timeout: Option<Duration>,
 }
 
 impl Surfer {
@@ -74,9 +367,78 @@ impl Surfer {
     pub fn home(&self) -> &String {
         &self.home
     }
-    /// Location of Index
+    /// Creates or opens an index at runtime from a serializable rust struct
+    pub fn add_index<T: Serialize>(&self, name: &str, data: &T) -> Result<(), IndexError> {
+        let name = &self.resolve(name);
+        let value = as_value(data)?;
+        let schema = to_schema(&value, None)?;
+        self.add_index_with_schema(name, schema)?;
+        self.text_seqs.write()?.insert(name.to_string(), text_seq_fields(&value));
+        self.object_seqs.write()?.insert(name.to_string(), object_seq_fields(&value));
+        self.bools.write()?.insert(name.to_string(), bool_fields(&value));
+        self.naive_dates.write()?.insert(name.to_string(), naive_date_fields(&value));
+        Ok(())
+    }
+    /// Creates or opens an index at runtime from a schema
+    pub fn add_index_with_schema(&self, name: &str, schema: Schema) -> Result<(), IndexError> {
+        let name = &self.resolve(name);
+        let closed = self.is_closed(name) || self.is_pending(name);
+        let mut indexes = self.indexes.write()?;
+        if closed || indexes.contains_key(name) {
+            let reason = format!("Index: {} is already registered", name);
+            return Err(IndexError::new("Unable to add index", &reason));
+        };
+        let schema = self.managed_schema(name, &schema)?;
+        let index = initialize_mmap(name, &self.home, &schema, self.analyses.read()?.get(name), &self.tokenizers, self.durability(name))?;
+        self.fields.write()?.insert(name.to_string(), default_fields(&schema, self.search_fields.read()?.get(name)));
+        indexes.insert(name.to_string(), index);
+        Ok(())
+    }
+    /// Schema of the index, needed to build tantivy queries
+    pub fn schema(&self, name: &str) -> Option<Schema> {
+        let name = &self.resolve(name);
+        let schema = self.indexes.read().ok()?.get(name).map(|index| index.schema());
+        schema.or_else(|| self.pending.read().ok()?.get(name).cloned())
+    }
+    /// JSON Schema draft-07 of the fields of the index with x-stored & x-indexed per field, see SurferBuilder::add_json_schema
+    pub fn schema_as_json_schema(&self, name: &str) -> Result<Option<JsonValue>, IndexError> {
+        let name = &self.resolve(name);
+        let schema = match self.schema(name) {
+            Some(schema) => schema,
+            None => return Ok(None),
+        };
+        let text_seqs = self.text_seqs.read()?;
+        let object_seqs = self.object_seqs.read()?;
+        let bools = self.bools.read()?;
+        let naive_dates = self.naive_dates.read()?;
+        let maps = self.maps.read()?;
+        let none = HashSet::new();
+        let sets = FieldSets {
+            seqs: text_seqs.get(name).unwrap_or(&none),
+            object_seqs: object_seqs.get(name).unwrap_or(&none),
+            bools: bools.get(name).unwrap_or(&none),
+            naive_dates: naive_dates.get(name).unwrap_or(&none),
+            maps: maps.get(name).unwrap_or(&none),
+        };
+        Ok(Some(to_json_schema(name, &schema, &sets)))
+    }
+    /// Is the index backed by a RAM directory
+    pub fn is_in_memory(&self, name: &str) -> bool {
+        let name = &self.resolve(name);
+        match self.in_memory.read() {
+            Ok(in_memory) => *in_memory.get(name).unwrap_or(&false),
+            Err(_) => false
+        }
+    }
+    /// Does the index exist on disk with a valid meta.json, false for in memory indexes & creates no directories
+    pub fn index_exists_on_disk(&self, name: &str) -> bool {
+        let name = &self.resolve(name);
+        !self.is_in_memory(name) && probe_index(&self.home, name)
+    }
+    /// Location of Index, None for in memory indexes
     pub fn which_index(&self, name: &str) -> Option<String> {
-        if !self.indexes.contains_key(name) {
+        let name = &self.resolve(name);
+        if !self.indexes.read().ok()?.contains_key(name) || self.is_in_memory(name) {
             return None;
         }
         if name.starts_with(&self.home) {
@@ -85,290 +447,6223 @@ impl Surfer {
             join(&self.home, name)
         }
     }
+    /// Underlying tantivy index e.g. for features not wrapped yet, a lazy index is created on disk
+    /// Cloning it is cheap, writing through it fails while the Surfer holds the writer
+    pub fn raw_index(&self, name: &str) -> Result<Option<Index>, IndexError> {
+        let name = &self.resolve(name);
+        self.materialize(name)?;
+        self.index(name)
+    }
+    /// Searcher over the last commit of the index, drop it soon as searchers are pooled
+    pub fn raw_searcher(&self, name: &str) -> Result<Option<LeasedItem<Searcher>>, IndexError> {
+        let name = &self.resolve(name);
+        Ok(self.reader(name)?.map(|reader| reader.searcher()))
+    }
     /// Inserts a struct
-    pub fn insert_struct<T: Serialize>(&mut self, name: &str, data: &T) -> Result<(), IndexError> {
-        let data = serde_json::to_string(data)?;
-        let writer = self.writers.get(name);
-        if writer.is_none() {
+    pub fn insert_struct<T: Serialize>(&self, name: &str, data: &T) -> Result<(), IndexError> {
+        let name = &self.resolve(name);
+        let mut span = Span::enter("insert", name);
+        let schema = self.schema(name);
+        if schema.is_none() {
             return Ok(());
         };
+        let schema = schema.unwrap();
+        let document = self.incoming(name, &schema, serde_json::to_value(data)?)?;
 
-        let index = self.indexes.get(name).unwrap();
-        let schema = &index.schema();
-
-        let writer = writer.unwrap();
+        let writer = self.writer(name)?;
         if writer.is_none() {
-            let writer = open_index_writer(index)?;
-            self.writers.insert(name.to_string(), Some(writer));
+            return Ok(());
         };
-
-        let writer = self.writers.get_mut(name).unwrap().as_mut().unwrap();
-        let document = schema.parse_document(&data)?;
-        writer.add_document(document);
-        writer.commit()?;
-        Ok(())
+        let writer = writer.unwrap();
+        writer.read()?.add_document(document);
+        span.record_docs(1);
+        self.flush(name, 1)
+    }
+    /// Inserts JSON e.g. off the wire without a struct, fields missing from the schema or of another type are errors
+    pub fn insert_value(&self, name: &str, data: &JsonValue) -> Result<(), IndexError> {
+        if !data.is_object() {
+            let message = format!("Unable to insert: {}", name);
+            let reason = "Value is not a JSON object".to_string();
+            return Err(IndexError::schema_mismatch(message, reason));
+        };
+        self.insert_struct(name, data)
+    }
+    /// Inserts a JSON string, see insert_value
+    pub fn insert_json(&self, name: &str, json: &str) -> Result<(), IndexError> {
+        let data = serde_json::from_str::<JsonValue>(json)?;
+        self.insert_value(name, &data)
     }
     /// Inserts a structs
-    pub fn insert_structs<T: Serialize>(&mut self, name: &str, payload: &Vec<T>) -> Result<(), IndexError> {
-        let writer = self.writers.get(name);
-        if writer.is_none() {
+    pub fn insert_structs<T: Serialize>(&self, name: &str, payload: &Vec<T>) -> Result<(), IndexError> {
+        let name = &self.resolve(name);
+        let mut span = Span::enter("insert", name);
+        span.record_docs(payload.len());
+        let schema = self.schema(name);
+        if schema.is_none() {
             return Ok(());
         };
+        let schema = schema.unwrap();
 
-        let index = self.indexes.get(name).unwrap();
-        let schema = &index.schema();
-
+        let writer = self.writer(name)?;
+        if writer.is_none() {
+            return Ok(());
+        };
         let writer = writer.unwrap();
+        {
+            let writer = writer.read()?;
+            for data in payload {
+                let document = self.incoming(name, &schema, serde_json::to_value(data)?)?;
+                writer.add_document(document);
+            }
+        }
+        self.flush(name, payload.len())
+    }
+    /// Streams newline delimited JSON in batches & commits once at the end
+    /// Lines failing to parse or to match the schema are reported & skipped
+    pub fn insert_ndjson<R: BufRead>(&self, name: &str, reader: R) -> Result<Option<IngestReport>, IndexError> {
+        let name = &self.resolve(name);
+        let schema = self.schema(name);
+        if schema.is_none() {
+            return Ok(None);
+        };
+        let schema = schema.unwrap();
+
+        let documents = reader.lines().enumerate().map(|(i, line)| {
+            let line = line?;
+            if line.trim().is_empty() {
+                return Ok(None);
+            };
+            let document = serde_json::from_str::<JsonValue>(&line)
+                .map_err(IndexError::from)
+                .and_then(|data| self.incoming(name, &schema, data));
+            Ok(Some((i + 1, document)))
+        });
+        self.ingest(name, documents)
+    }
+    /// Inserts the items in batches, committing every N documents or M megabytes of JSON as the options tell
+    /// Items failing to match the schema are reported by position from 1 & skipped, progress follows every commit
+    pub fn insert_iter<T, I>(&self, name: &str, items: I, mut options: IngestOptions) -> Result<Option<IngestReport>, IndexError>
+        where T: Serialize, I: Iterator<Item = T> {
+        let name = &self.resolve(name);
+        let mut span = Span::enter("ingest", name);
+        let schema = self.schema(name);
+        if schema.is_none() {
+            return Ok(None);
+        };
+        let schema = schema.unwrap();
+        let writer = self.writer(name)?;
         if writer.is_none() {
-            let writer = open_index_writer(index)?;
-            self.writers.insert(name.to_string(), Some(writer));
+            return Ok(None);
         };
+        let writer = writer.unwrap();
 
-        let writer = self.writers.get_mut(name).unwrap().as_mut().unwrap();
-        for data in payload {
-            let data = serde_json::to_string(data)?;
-            let document = schema.parse_document(&data)?;
-            writer.add_document(document);
+        let mut report = IngestReport::default();
+        let mut progress = Progress::default();
+        let mut batch = Vec::with_capacity(BATCH_SIZE);
+        let (mut consumed, mut docs, mut bytes) = (0, 0, 0);
+        for item in items {
+            consumed += 1;
+            let document = serde_json::to_value(&item)
+                .map_err(IndexError::from)
+                .and_then(|data| {
+                    bytes += data.to_string().len();
+                    self.incoming(name, &schema, data)
+                });
+            match document {
+                Ok(document) => {
+                    batch.push(document);
+                    docs += 1;
+                }
+                Err(e) => report.add_error(consumed, e),
+            };
+            let is_due = options.is_due(docs, bytes);
+            if batch.len() == BATCH_SIZE || is_due {
+                report.add_inserted(batch.len());
+                let writer = writer.read()?;
+                batch.drain(..).for_each(|document| {
+                    writer.add_document(document);
+                });
+            };
+            if is_due {
+                self.commit(name)?;
+                progress.update(consumed, &report);
+                options.report(&progress);
+                docs = 0;
+                bytes = 0;
+            };
+        };
+        report.add_inserted(batch.len());
+        {
+            let writer = writer.read()?;
+            batch.into_iter().for_each(|document| {
+                writer.add_document(document);
+            });
         }
-
-        writer.commit()?;
-        Ok(())
+        span.record_docs(report.inserted());
+        self.commit(name)?;
+        progress.update(consumed, &report);
+        options.report(&progress);
+        Ok(Some(report))
     }
-    /// Massive hack look away ;)
-    fn jsonify(&self, name: &str, document: &Document) -> Result<String, IndexError> {
-        let schema = self.indexes.get(name).unwrap().schema();
+    /// Ingester indexing & committing on a background thread, send blocks once capacity documents are queued
+    pub fn ingester(&self, name: &str, capacity: usize) -> Result<Option<Ingester>, IndexError> {
+        let name = &self.resolve(name);
+        let schema = self.schema(name);
+        if schema.is_none() {
+            return Ok(None);
+        };
+        let schema = schema.unwrap();
+        let writer = self.writer(name)?;
+        let reader = self.reader(name)?;
+        if writer.is_none() || reader.is_none() {
+            return Ok(None);
+        };
+        let ttl = self.ttls.read()?.get(name).copied();
+        let shape = self.shapes.read()?.get(name).cloned().unwrap_or_default();
+        Ingester::spawn(name, schema, ttl, shape, writer.unwrap(), reader.unwrap(), capacity).map(Some)
+    }
+    /// Adds the valid documents in batches, reports the others by line & commits once
+    fn ingest<I>(&self, name: &str, documents: I) -> Result<Option<IngestReport>, IndexError>
+        where I: Iterator<Item = Result<Option<(usize, Result<Document, IndexError>)>, IndexError>> {
+        let mut span = Span::enter("ingest", name);
+        let writer = self.writer(name)?;
+        if writer.is_none() {
+            return Ok(None);
+        };
+        let writer = writer.unwrap();
 
-        let mut field_map = BTreeMap::new();
-        for (field, field_values) in document.get_sorted_field_values() {
-            let field_name = schema.get_field_name(field);
-            let fv = field_values.get(0);
-            if fv.is_none() {
-                let message = format!("Unable to jsonify: {}", name);
-                let reason = format!("Field: {} does not have any value", field_name);
-                let error = IndexError::new(message, reason);
-                return Err(error);
+        let mut report = IngestReport::default();
+        let mut batch = Vec::with_capacity(BATCH_SIZE);
+        for document in documents {
+            let (line, document) = match document? {
+                Some(document) => document,
+                None => continue,
+            };
+            match document {
+                Ok(document) => batch.push(document),
+                Err(e) => report.add_error(line, e),
+            };
+            if batch.len() == BATCH_SIZE {
+                report.add_inserted(batch.len());
+                let writer = writer.read()?;
+                batch.drain(..).for_each(|document| {
+                    writer.add_document(document);
+                });
             };
-            let fv = fv.unwrap().value();
-            field_map.insert(field_name, fv);
         };
-        let payload = SingleValuedNamedFieldDocument(field_map);
-        let result = serde_json::to_string(&payload)
-            .map_err(|e| {
-                let message = "Unable to serialize struct".to_string();
-                let reason = e.to_string();
-                IndexError::new(
-                    message,
-                    reason,
-                )
+        report.add_inserted(batch.len());
+        {
+            let writer = writer.read()?;
+            batch.into_iter().for_each(|document| {
+                writer.add_document(document);
             });
-        result
+        }
+        span.record_docs(report.inserted());
+        self.commit(name)?;
+        Ok(Some(report))
     }
-    /// Reads as string
-    pub fn read_string(&mut self, name: &str, query: &str, limit: Option<usize>, score: Option<f32>) -> Result<Option<Vec<String>>, IndexError> {
-        let reader = self.readers.get(name);
-        if reader.is_none() {
+    /// Streams CSV mapping columns to fields by header name, cells are coerced to the field types
+    /// Rows failing to coerce are reported & skipped, commits once at the end
+    #[cfg(feature = "csv")]
+    pub fn insert_csv<R: BufRead>(&self, name: &str, reader: R) -> Result<Option<IngestReport>, IndexError> {
+        let name = &self.resolve(name);
+        let schema = self.schema(name);
+        if schema.is_none() {
             return Ok(None);
         };
+        let schema = schema.unwrap();
 
-        let reader = reader.unwrap();
-        let index = self.indexes.get(name);
-        if index.is_none() {
+        let mut records = crate::csv::Records::new(reader);
+        let headers = match records.next() {
+            Some(headers) => headers?.1,
+            None => return Ok(Some(IngestReport::default())),
+        };
+        crate::csv::validate_headers(&schema, &headers)?;
+
+        let documents = records.map(|record| {
+            let (line, cells) = record?;
+            let document = crate::csv::as_json(&schema, &headers, cells)
+                .and_then(|data| json_as_document(&schema, data))
+                .and_then(|document| self.expiring(name, &schema, document));
+            Ok(Some((line, document)))
+        });
+        self.ingest(name, documents)
+    }
+    /// Commits pending writes and makes them visible to readers
+    pub fn commit(&self, name: &str) -> Result<(), IndexError> {
+        let name = &self.resolve(name);
+        let _span = Span::enter("commit", name);
+        let writer = self.writers.read()?.get(name).cloned();
+        if let Some(writer) = writer {
+            self.uncommitted(name)?.store(0, AtomicOrdering::SeqCst);
+            writer.write()?.commit()?;
+        };
+        self.reload(name)
+    }
+    /// Discards the writes since the last commit e.g. a bulk load failing halfway, with auto commit turned off
+    pub fn rollback(&self, name: &str) -> Result<(), IndexError> {
+        let name = &self.resolve(name);
+        let _span = Span::enter("rollback", name);
+        let writer = self.writers.read()?.get(name).cloned();
+        if let Some(writer) = writer {
+            self.uncommitted(name)?.store(0, AtomicOrdering::SeqCst);
+            writer.write()?.rollback()?;
+        };
+        Ok(())
+    }
+    /// Is the index committed after every write
+    pub fn is_auto_commit(&self, name: &str) -> bool {
+        let name = &self.resolve(name);
+        let auto_commits = self.auto_commits.read();
+        match auto_commits {
+            Ok(auto_commits) => *auto_commits.get(name).unwrap_or(&true),
+            Err(_) => true
+        }
+    }
+    /// When auto commit commits
+    pub fn commit_policy(&self, name: &str) -> CommitPolicy {
+        let name = &self.resolve(name);
+        let policies = self.commit_policies.read();
+        match policies {
+            Ok(policies) => policies.get(name).copied().unwrap_or_default(),
+            Err(_) => CommitPolicy::default(),
+        }
+    }
+    /// Whether commits wait until the writes are on disk
+    pub fn durability(&self, name: &str) -> Durability {
+        let name = &self.resolve(name);
+        let durabilities = self.durabilities.read();
+        match durabilities {
+            Ok(durabilities) => durabilities.get(name).copied().unwrap_or_default(),
+            Err(_) => Durability::default(),
+        }
+    }
+    /// Count of the documents written since the last commit
+    fn uncommitted(&self, name: &str) -> Result<Arc<AtomicUsize>, IndexError> {
+        if let Some(pending) = self.uncommitted.read()?.get(name) {
+            return Ok(pending.clone());
+        };
+        let mut uncommitted = self.uncommitted.write()?;
+        Ok(uncommitted.entry(name.to_string()).or_default().clone())
+    }
+    /// Do query strings require every term to match
+    pub fn is_conjunction_by_default(&self, name: &str) -> bool {
+        let name = &self.resolve(name);
+        let conjunctions = self.conjunctions.read();
+        match conjunctions {
+            Ok(conjunctions) => *conjunctions.get(name).unwrap_or(&false),
+            Err(_) => false
+        }
+    }
+    /// Boost of query string matches on the field - default is 1
+    pub fn field_boost(&self, name: &str, field: &str) -> f32 {
+        let name = &self.resolve(name);
+        let boosts = self.boosts.read();
+        match boosts {
+            Ok(boosts) => *boosts.get(name).and_then(|boosts| boosts.get(field)).unwrap_or(&1.0),
+            Err(_) => 1.0
+        }
+    }
+    /// Stopwords removed from the text field at analysis time
+    pub fn stopwords(&self, name: &str, field: &str) -> Option<Stopwords> {
+        let name = &self.resolve(name);
+        let analyses = self.analyses.read().ok()?;
+        analyses.get(name)?.stopwords(field).cloned()
+    }
+    /// Synonyms expanding the query strings of the index
+    pub fn synonyms(&self, name: &str) -> Option<Synonyms> {
+        let name = &self.resolve(name);
+        let analyses = self.analyses.read().ok()?;
+        analyses.get(name)?.synonyms().cloned()
+    }
+    /// Is the index waiting for its first document to be created on disk
+    pub fn is_pending(&self, name: &str) -> bool {
+        let name = &self.resolve(name);
+        match self.pending.read() {
+            Ok(pending) => pending.contains_key(name),
+            Err(_) => false
+        }
+    }
+    /// Creates the pending index on disk, done on the first write
+    fn materialize(&self, name: &str) -> Result<(), IndexError> {
+        let mut pending = self.pending.write()?;
+        let schema = pending.get(name);
+        if schema.is_none() {
+            return Ok(());
+        };
+        let index = initialize_mmap(name, &self.home, schema.unwrap(), self.analyses.read()?.get(name), &self.tokenizers, self.durability(name))?;
+        pending.remove(name);
+        self.indexes.write()?.insert(name.to_string(), index);
+        Ok(())
+    }
+    /// Time documents of the index live for
+    pub fn ttl(&self, name: &str) -> Option<Duration> {
+        let name = &self.resolve(name);
+        self.ttls.read().ok()?.get(name).cloned()
+    }
+    /// Documents returned by a search without a limit
+    pub fn default_limit(&self, name: &str) -> usize {
+        let name = &self.resolve(name);
+        let default_limits = self.default_limits.read();
+        match default_limits {
+            Ok(default_limits) => *default_limits.get(name).unwrap_or(&10),
+            Err(_) => 10
+        }
+    }
+    /// Largest limit of a search if capped
+    pub fn max_limit(&self, name: &str) -> Option<usize> {
+        let name = &self.resolve(name);
+        self.max_limits.read().ok()?.get(name).cloned()
+    }
+    /// Limit of the search resolved against the default & the cap of the index
+    fn resolve_limit(&self, name: &str, limit: Option<usize>) -> usize {
+        let limit = limit.unwrap_or_else(|| self.default_limit(name));
+        match self.max_limit(name) {
+            Some(max_limit) => limit.min(max_limit),
+            None => limit
+        }
+    }
+    /// Options with the limit resolved, cloned only when it changes
+    fn bounded<'a>(&self, name: &str, options: &'a SearchOptions) -> Cow<'a, SearchOptions> {
+        let limit = self.resolve_limit(name, options.limit());
+        if options.limit() == Some(limit) {
+            return Cow::Borrowed(options);
+        };
+        let mut options = options.clone();
+        options.set_limit(limit);
+        Cow::Owned(options)
+    }
+    /// Is the JSON inserted kept as the _source of the documents
+    pub fn is_storing_source(&self, name: &str) -> bool {
+        let name = &self.resolve(name);
+        match self.sources.read() {
+            Ok(sources) => *sources.get(name).unwrap_or(&false),
+            Err(_) => false
+        }
+    }
+    /// Are the text fields copied into the _all field
+    pub fn is_catch_all(&self, name: &str) -> bool {
+        let name = &self.resolve(name);
+        match self.catch_alls.read() {
+            Ok(catch_alls) => *catch_alls.get(name).unwrap_or(&false),
+            Err(_) => false
+        }
+    }
+    /// Schema along with the managed fields of the index e.g. expiry, source & catch-all
+    fn managed_schema(&self, name: &str, schema: &Schema) -> Result<Schema, IndexError> {
+        let schema = match self.ttls.read()?.get(name) {
+            Some(_) => with_expiry(schema),
+            None => schema.clone(),
+        };
+        let schema = match self.is_storing_source(name) {
+            true => with_source(&schema),
+            false => schema,
+        };
+        match self.is_catch_all(name) {
+            true => Ok(with_catch_all(&schema)),
+            false => Ok(schema),
+        }
+    }
+    /// Incoming JSON as indexed, see SurferBuilder::add_json_pointer, set_null_policy & set_coercion
+    fn shaped(&self, name: &str, schema: &Schema, data: JsonValue) -> Result<JsonValue, IndexError> {
+        match self.shapes.read()?.get(name) {
+            Some(shape) => shape.apply(schema, data),
+            None => Ok(data),
+        }
+    }
+    /// Document of the incoming JSON shaped & stamped with its expiry
+    fn incoming(&self, name: &str, schema: &Schema, data: JsonValue) -> Result<Document, IndexError> {
+        let source = data.clone();
+        let document = json_as_document_with_source(schema, self.shaped(name, schema, data)?, &source)?;
+        self.expiring(name, schema, document)
+    }
+    /// Stamps the expiry of the document when the index has a TTL
+    fn expiring(&self, name: &str, schema: &Schema, mut document: Document) -> Result<Document, IndexError> {
+        if let Some(ttl) = self.ttls.read()?.get(name) {
+            stamp_expiry(schema, &mut document, *ttl, Utc::now())?;
+        };
+        Ok(document)
+    }
+    /// Deletes the documents whose TTL elapsed & commits, returns how many were deleted
+    pub fn purge_expired(&self, name: &str) -> Result<Option<u64>, IndexError> {
+        let name = &self.resolve(name);
+        let reader = self.reader(name)?;
+        let writer = self.writer(name)?;
+        if reader.is_none() || writer.is_none() {
             return Ok(None);
         };
-        let index = index.unwrap();
-        let reader = if reader.is_none() {
-            let reader = open_index_reader(index)?;
-            self.readers.insert(name.to_string(), Some(reader));
-            let reader = self.readers.get(name);
-            reader.unwrap().as_ref().unwrap()
-        } else {
-            let reader = self.readers.get(name);
-            reader.unwrap().as_ref().unwrap()
+        let now = Utc::now();
+        let (terms, expired) = {
+            let searcher = reader.unwrap().searcher();
+            let schema = searcher.schema().clone();
+            let expired = searcher.search(&expired_query(&schema, now)?, &Count)? as u64;
+            (expired_terms(&searcher, &schema, now)?, expired)
+        };
+        if terms.is_empty() {
+            return Ok(Some(0));
+        };
+        {
+            let writer = writer.unwrap();
+            let writer = writer.read()?;
+            for term in terms {
+                writer.delete_term(term);
+            };
+        }
+        self.commit(name)?;
+        Ok(Some(expired))
+    }
+    /// Commits the documents written unless the index opted out of auto commit or its policy defers it
+    fn flush(&self, name: &str, docs: usize) -> Result<(), IndexError> {
+        if !self.is_auto_commit(name) {
+            return Ok(());
+        };
+        let pending = self.uncommitted(name)?.fetch_add(docs, AtomicOrdering::SeqCst) + docs;
+        if !self.commit_policy(name).is_due(pending) {
+            return Ok(());
+        };
+        self.commit(name)
+    }
+    /// Replaces the document sharing the primary key or inserts it within one commit
+    pub fn upsert_struct<T: Serialize>(&self, name: &str, data: &T) -> Result<(), IndexError> {
+        let name = &self.resolve(name);
+        let _span = Span::enter("upsert", name);
+        let schema = self.schema(name);
+        if schema.is_none() {
+            return Ok(());
         };
+        let schema = schema.unwrap();
 
-        let default_fields = self.fields.get(name).unwrap().clone();
-        let searcher = reader.searcher();
+        let key = self.primary_keys.read()?.get(name).cloned();
+        if key.is_none() {
+            let message = format!("Unable to upsert: {}", name);
+            let reason = "Primary key is not declared".to_string();
+            return Err(IndexError::new(message, reason));
+        };
+        let key = key.unwrap();
 
-        let query_parser = QueryParser::for_index(&index, default_fields);
-        let query = query_parser.parse_query(query)?;
-        let limit = if limit.is_some() {
-            limit.unwrap()
-        } else {
-            10
+        let source = serde_json::to_value(data)?;
+        let data = self.shaped(name, &schema, source.clone())?;
+        let flat = flatten(&data);
+        let value = flat.get(&key);
+        if value.is_none() {
+            let message = format!("Unable to upsert: {}", name);
+            let reason = format!("Primary key: {} does not have any value", key);
+            return Err(IndexError::new(message, reason));
         };
-        let top_docs = searcher.search(&query, &TopDocs::with_limit(limit))?;
+        let term = as_term(&schema, &key, value.unwrap())?;
+        let document = self.expiring(name, &schema, json_as_document_with_source(&schema, data, &source)?)?;
 
-        let mut docs = Vec::with_capacity(top_docs.len());
-        for (doc_score, doc_address) in top_docs {
-            if score.is_some() && doc_score < score.unwrap() {
-                continue;
-            }
-            let doc = searcher.doc(doc_address)?;
-            let doc = self.jsonify(name, &doc)?;
-            docs.push(doc);
+        let writer = self.writer(name)?;
+        if writer.is_none() {
+            return Ok(());
         };
-        Ok(Some(docs))
+        let writer = writer.unwrap();
+        writer.read()?.run(vec![UserOperation::Delete(term), UserOperation::Add(document)]);
+        self.flush(name, 1)
     }
-    /// Reads as struct
-    pub fn read_structs<T: Serialize + DeserializeOwned>(&mut self, name: &str, query: &str, limit: Option<usize>, score: Option<f32>) -> Result<Option<Vec<T>>, IndexError> {
-        let reader = self.readers.get(name);
-        if reader.is_none() {
+    /// Merges the patch over every document matching the query & rewrites them within one commit, returns the number updated
+    /// Documents are replaced by primary key, nulls in the patch remove the field
+    pub fn update_by_query(&self, name: &str, query: &str, patch: &JsonValue) -> Result<Option<usize>, IndexError> {
+        let name = &self.resolve(name);
+        let _span = Span::enter("update_by_query", name);
+        let schema = self.schema(name);
+        if schema.is_none() {
             return Ok(None);
         };
+        let schema = schema.unwrap();
+        if !patch.is_object() {
+            let message = format!("Unable to update: {}", name);
+            let reason = "Patch is not a JSON object".to_string();
+            return Err(IndexError::new(message, reason));
+        };
 
-        let reader = reader.unwrap();
-        let index = self.indexes.get(name);
-        if index.is_none() {
+        let key = self.primary_keys.read()?.get(name).cloned();
+        if key.is_none() {
+            let message = format!("Unable to update: {}", name);
+            let reason = "Primary key is not declared".to_string();
+            return Err(IndexError::new(message, reason));
+        };
+        let key = key.unwrap();
+
+        let query = self.parse(name, query)?;
+        if query.is_none() {
             return Ok(None);
         };
-        let index = index.unwrap();
-        let reader = if reader.is_none() {
-            let reader = open_index_reader(index)?;
-            self.readers.insert(name.to_string(), Some(reader));
-            let reader = self.readers.get(name);
-            reader.unwrap().as_ref().unwrap()
-        } else {
-            let reader = self.readers.get(name);
-            reader.unwrap().as_ref().unwrap()
+        let query = query.unwrap();
+        // Collected before writing so the scan returns its searcher to the pool
+        let docs: Vec<JsonValue> = match self.scan_with_query(name, &query)? {
+            Some(scan) => scan.collect::<Result<_, _>>()?,
+            None => return Ok(None),
         };
 
-        let default_fields = self.fields.get(name).unwrap().clone();
-        let searcher = reader.searcher();
+        let mut operations = Vec::with_capacity(docs.len() * 2);
+        for mut doc in docs {
+            let flat = flatten(&doc);
+            let value = flat.get(&key);
+            if value.is_none() {
+                let message = format!("Unable to update: {}", name);
+                let reason = format!("Primary key: {} does not have any value", key);
+                return Err(IndexError::new(message, reason));
+            };
+            operations.push(UserOperation::Delete(as_term(&schema, &key, value.unwrap())?));
+            merge_patch(&mut doc, patch);
+            let document = self.expiring(name, &schema, json_as_document(&schema, doc)?)?;
+            operations.push(UserOperation::Add(document));
+        };
+        let updated = operations.len() / 2;
 
-        let query_parser = QueryParser::for_index(&index, default_fields);
-        let query = query_parser.parse_query(query)?;
-        let limit = if limit.is_some() {
-            limit.unwrap()
-        } else {
-            10
+        let writer = self.writer(name)?;
+        if writer.is_none() {
+            return Ok(None);
         };
-        let top_docs = searcher.search(&query, &TopDocs::with_limit(limit))?;
+        let writer = writer.unwrap();
+        writer.read()?.run(operations);
+        self.flush(name, updated)?;
+        Ok(Some(updated))
+    }
+    /// Deletes documents matching the query
+    /// Matches are deleted by primary key, without one only a term or a disjunction of terms is accepted
+    pub fn delete_by_query(&self, name: &str, query: &str) -> Result<(), IndexError> {
+        let name = &self.resolve(name);
+        let schema = self.schema(name);
+        if schema.is_none() {
+            return Ok(());
+        };
+        let schema = schema.unwrap();
+        let query = self.parse(name, query)?;
+        if query.is_none() {
+            return Ok(());
+        };
+        let query = query.unwrap();
 
-        let mut docs = Vec::with_capacity(top_docs.len());
-        for (doc_score, doc_address) in top_docs {
-            if score.is_some() && doc_score < score.unwrap() {
-                continue;
+        let key = self.primary_keys.read()?.get(name).cloned();
+        let terms = match key {
+            Some(key) => {
+                // Collected before writing so the scan returns its searcher to the pool
+                let docs: Vec<JsonValue> = match self.scan_with_query(name, &query)? {
+                    Some(scan) => scan.collect::<Result<_, _>>()?,
+                    None => return Ok(()),
+                };
+                let mut terms = Vec::with_capacity(docs.len());
+                for doc in docs {
+                    let flat = flatten(&doc);
+                    let value = flat.get(&key);
+                    if value.is_none() {
+                        let message = format!("Unable to delete: {}", name);
+                        let reason = format!("Primary key: {} does not have any value", key);
+                        return Err(IndexError::new(message, reason));
+                    };
+                    terms.push(as_term(&schema, &key, value.unwrap())?);
+                };
+                terms
+            }
+            None => {
+                if !is_disjunction(query.as_ref()) {
+                    let message = format!("Unable to delete: {}", name);
+                    let reason = "Primary key is not declared & query is not a term or a disjunction of terms".to_string();
+                    return Err(IndexError::new(message, reason));
+                };
+                let mut terms = BTreeSet::<Term>::new();
+                query.query_terms(&mut terms);
+                terms.into_iter().collect()
             }
-            let doc = searcher.doc(doc_address)?;
-            let doc = self.jsonify(name, &doc)?;
-            let doc = serde_json::from_str::<T>(&doc).unwrap();
-            docs.push(doc);
         };
-        Ok(Some(docs))
-    }
-}
 
-/// Panics if somethings goes wrong
-impl Surfer {
-    pub fn new(builder: SurferBuilder) -> Self {
-        Surfer::try_from(builder).unwrap()
+        let writer = self.writer(name)?;
+        if writer.is_none() {
+            return Ok(());
+        };
+        let writer = writer.unwrap();
+        let deleted = terms.len();
+        {
+            let writer = writer.read()?;
+            for term in terms {
+                writer.delete_term(term);
+            };
+        }
+        self.flush(name, deleted)
     }
-}
+    /// Deletes documents holding the exact value for the field e.g. a unique id
+    pub fn delete_by_term<T: Serialize>(&self, name: &str, field: &str, value: &T) -> Result<(), IndexError> {
+        let name = &self.resolve(name);
+        let schema = self.schema(name);
+        if schema.is_none() {
+            return Ok(());
+        };
+        let term = as_term(&schema.unwrap(), field, value)?;
 
-/// Opens mmap dir
-fn initialize_mmap(name: &str, home: &str, schema: &Schema) -> Result<Index, IndexError> {
-    let path = resolve_index_directory_path(name, Some(home))?;
-    if path.exists() {
-        let dir = open_mmap_directory(path)?;
-        open_index(dir, None)
-    } else {
-        let dir = open_mmap_directory(path)?;
-        open_index(dir, Some(&schema))
+        let writer = self.writer(name)?;
+        if writer.is_none() {
+            return Ok(());
+        };
+        let writer = writer.unwrap();
+        writer.read()?.delete_term(term);
+        self.flush(name, 1)
     }
-}
-
-/// Get home location
-fn extract_home(builder: &SurferBuilder) -> Result<String, IndexError> {
-    let home = builder.home.as_ref();
-    let home = resolve_home(home)?;
-    Ok(home.to_str().unwrap().to_string())
-}
-
-/// Setup indexes
+    /// Deletes all documents and commits, the index stays registered
+    pub fn clear(&self, name: &str) -> Result<(), IndexError> {
+        let name = &self.resolve(name);
+        let writer = self.writer(name)?;
+        if writer.is_none() {
+            return Ok(());
+        };
+        let writer = writer.unwrap();
+        writer.read()?.delete_all_documents()?;
+        self.commit(name)
+    }
+    /// Releases the reader, writer & mmap of the index keeping the data, reads return None until reopened
+    pub fn close_index(&self, name: &str) -> Result<(), IndexError> {
+        let name = &self.resolve(name);
+        if self.is_in_memory(name) {
+            let reason = format!("Index: {} is in memory and would lose its data", name);
+            return Err(IndexError::new("Unable to close index", &reason));
+        };
+        let index = self.indexes.write()?.remove(name);
+        if index.is_none() {
+            return Ok(());
+        };
+        self.release(name)?;
+        self.closed.write()?.insert(name.to_string(), index.unwrap().schema());
+        Ok(())
+    }
+    /// Opens a closed index again
+    pub fn reopen_index(&self, name: &str) -> Result<(), IndexError> {
+        let name = &self.resolve(name);
+        let mut closed = self.closed.write()?;
+        let schema = closed.get(name);
+        if schema.is_none() {
+            return Ok(());
+        };
+        let index = initialize_mmap(name, &self.home, schema.unwrap(), self.analyses.read()?.get(name), &self.tokenizers, self.durability(name))?;
+        closed.remove(name);
+        self.indexes.write()?.insert(name.to_string(), index);
+        Ok(())
+    }
+    /// Is the index registered but closed
+    pub fn is_closed(&self, name: &str) -> bool {
+        let name = &self.resolve(name);
+        match self.closed.read() {
+            Ok(closed) => closed.contains_key(name),
+            Err(_) => false
+        }
+    }
+    /// Closes the handles, removes the index directory under home & unregisters the index
+    pub fn drop_index(&self, name: &str) -> Result<(), IndexError> {
+        let name = &self.resolve(name);
+        self.reopen_index(name)?;
+        let pending = self.pending.write()?.remove(name).is_some();
+        if !pending && self.index(name)?.is_none() {
+            return Ok(());
+        };
+        let path = self.which_index(name);
+        self.release(name)?;
+        self.indexes.write()?.remove(name);
+        self.fields.write()?.remove(name);
+        self.primary_keys.write()?.remove(name);
+        self.auto_commits.write()?.remove(name);
+        self.commit_policies.write()?.remove(name);
+        self.uncommitted.write()?.remove(name);
+        self.durabilities.write()?.remove(name);
+        self.conjunctions.write()?.remove(name);
+        self.search_fields.write()?.remove(name);
+        self.boosts.write()?.remove(name);
+        self.analyses.write()?.remove(name);
+        self.shapes.write()?.remove(name);
+        self.ttls.write()?.remove(name);
+        self.default_limits.write()?.remove(name);
+        self.max_limits.write()?.remove(name);
+        self.sources.write()?.remove(name);
+        self.catch_alls.write()?.remove(name);
+        self.text_seqs.write()?.remove(name);
+        self.object_seqs.write()?.remove(name);
+        self.bools.write()?.remove(name);
+        self.naive_dates.write()?.remove(name);
+        self.maps.write()?.remove(name);
+        self.in_memory.write()?.remove(name);
+        self.aliases.write()?.retain(|_, index| index != name);
+        if let Some(path) = path {
+            std::fs::remove_dir_all(path)?;
+        };
+        Ok(())
+    }
+    /// Copies the committed state of the index into an empty directory while it stays in use
+    pub fn snapshot<P: AsRef<Path>>(&self, name: &str, dest: P) -> Result<(), IndexError> {
+        let name = &self.resolve(name);
+        let index = self.index(name)?;
+        if index.is_none() {
+            return Err(IndexError::unknown_index(name));
+        };
+        snapshot_index(&index.unwrap(), dest.as_ref())
+    }
+    /// Replaces the data of the index with a validated snapshot & opens it, registering the index when new
+    pub fn restore<P: AsRef<Path>>(&self, name: &str, snapshot: P) -> Result<(), IndexError> {
+        let name = &self.resolve(name);
+        if self.is_in_memory(name) {
+            let reason = format!("Index: {} is in memory", name);
+            return Err(IndexError::new("Unable to restore index", &reason));
+        };
+        let schema = validate_snapshot(snapshot.as_ref())?;
+        self.reopen_index(name)?;
+        let previous = self.indexes.write()?.remove(name);
+        self.release(name)?;
+
+        if let Some(previous) = previous {
+            if !schema_drift(&previous.schema(), &schema).is_empty() {
+                self.text_seqs.write()?.remove(name);
+                self.object_seqs.write()?.remove(name);
+                self.bools.write()?.remove(name);
+                self.naive_dates.write()?.remove(name);
+                self.maps.write()?.remove(name);
+            };
+        };
+        let path = resolve_index_directory_path(name, Some(&self.home))?;
+        if path.exists() {
+            std::fs::remove_dir_all(&path)?;
+        };
+        copy_snapshot(snapshot.as_ref(), &path)?;
+
+        let index = initialize_mmap(name, &self.home, &schema, self.analyses.read()?.get(name), &self.tokenizers, self.durability(name))?;
+        self.fields.write()?.insert(name.to_string(), default_fields(&schema, self.search_fields.read()?.get(name)));
+        self.indexes.write()?.insert(name.to_string(), index);
+        Ok(())
+    }
+    /// Fields of the struct differing from the schema of the index
+    pub fn schema_drift<T: Serialize>(&self, name: &str, data: &T) -> Result<Option<SchemaDrift>, IndexError> {
+        let name = &self.resolve(name);
+        let schema = self.schema(name);
+        if schema.is_none() {
+            return Ok(None);
+        };
+        let expected = to_schema(&as_value(data)?, None)?;
+        Ok(Some(schema_drift(&schema.unwrap(), &expected)))
+    }
+    /// Reindex every document through the transform into a new schema derived from New & swap it in
+    /// The index is briefly unavailable during the swap & an empty index can not be migrated
+    pub fn migrate<Old, New, F>(&self, name: &str, transform: F) -> Result<(), IndexError>
+        where Old: DeserializeOwned, New: Serialize, F: Fn(Old) -> New {
+        self.migrate_with_control(name, HashMap::new(), transform)
+    }
+    /// Reindex every document through the transform with options per field of the new schema
+    pub fn migrate_with_control<Old, New, F>(&self, name: &str, control: HashMap<String, Control>, transform: F) -> Result<(), IndexError>
+        where Old: DeserializeOwned, New: Serialize, F: Fn(Old) -> New {
+        let name = &self.resolve(name);
+        if self.index(name)?.is_none() {
+            return Ok(());
+        };
+        self.commit(name)?;
+        let searcher = self.reader(name)?.ok_or_else(|| IndexError::unknown_index(name))?.searcher();
+
+        let mut target: Option<(Index, IndexWriter, Value)> = None;
+        for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+            let expiries = expiries(segment_reader, searcher.schema());
+            for doc_id in 0..segment_reader.max_doc() {
+                if segment_reader.is_deleted(doc_id) {
+                    continue;
+                };
+                let doc = searcher.doc(DocAddress(segment_ord as u32, doc_id))?;
+                let old = serde_json::from_str::<Old>(&self.jsonify(name, &doc)?)?;
+                let new = transform(old);
+                if target.is_none() {
+                    let value = as_value(&new)?;
+                    let schema = to_schema(&value, Some(&control))?;
+                    let index = self.migration_index(name, &schema)?;
+                    let writer = open_index_writer(&index)?;
+                    target = Some((index, writer, value));
+                };
+                let (index, writer, _) = target.as_ref().unwrap();
+                let schema = index.schema();
+                let mut document = as_document(&schema, &new)?;
+                // Keeps the remaining TTL, only documents without an expiry are stamped
+                match (expiries.get(&doc_id), schema.get_field(EXPIRES_AT)) {
+                    (Some(expires_at), Some(field)) => document.add_date(field, expires_at),
+                    _ => document = self.expiring(name, &schema, document)?,
+                };
+                writer.add_document(document);
+            };
+        };
+        drop(searcher);
+        if target.is_none() {
+            let reason = format!("Index: {} does not have any document", name);
+            return Err(IndexError::new("Unable to migrate", &reason));
+        };
+        let (index, mut writer, value) = target.unwrap();
+        writer.commit()?;
+        writer.wait_merging_threads()?;
+        self.swap(name, index, &value)?;
+        self.maps.write()?.insert(name.to_string(), map_fields(&control));
+        Ok(())
+    }
+    /// Index receiving the migrated documents, next to the current one unless in memory
+    fn migration_index(&self, name: &str, schema: &Schema) -> Result<Index, IndexError> {
+        let schema = &self.managed_schema(name, schema)?;
+        if self.is_in_memory(name) {
+            return open_index_in_ram(schema, self.analyses.read()?.get(name), &self.tokenizers);
+        };
+        let migrating = format!("{}.migrating", name);
+        let path = resolve_index_directory_path(migrating.as_str(), Some(&self.home))?;
+        if path.exists() {
+            std::fs::remove_dir_all(&path)?;
+        };
+        initialize_mmap(&migrating, &self.home, schema, self.analyses.read()?.get(name), &self.tokenizers, self.durability(name))
+    }
+    /// Replaces the current index with the migrated one
+    fn swap(&self, name: &str, index: Index, value: &Value) -> Result<(), IndexError> {
+        self.release(name)?;
+        self.indexes.write()?.remove(name);
+        let schema = index.schema();
+        let index = if self.is_in_memory(name) {
+            index
+        } else {
+            drop(index);
+            let migrating = format!("{}.migrating", name);
+            let from = resolve_index_directory_path(migrating.as_str(), Some(&self.home))?;
+            let to = resolve_index_directory_path(name, Some(&self.home))?;
+            std::fs::remove_dir_all(&to)?;
+            std::fs::rename(from, to)?;
+            initialize_mmap(name, &self.home, &schema, self.analyses.read()?.get(name), &self.tokenizers, self.durability(name))?
+        };
+        self.fields.write()?.insert(name.to_string(), default_fields(&schema, self.search_fields.read()?.get(name)));
+        self.text_seqs.write()?.insert(name.to_string(), text_seq_fields(value));
+        self.object_seqs.write()?.insert(name.to_string(), object_seq_fields(value));
+        self.bools.write()?.insert(name.to_string(), bool_fields(value));
+        self.naive_dates.write()?.insert(name.to_string(), naive_date_fields(value));
+        self.indexes.write()?.insert(name.to_string(), index);
+        Ok(())
+    }
+    /// Drops the reader & writer, waits for merges unless another thread still holds the writer
+    fn release(&self, name: &str) -> Result<(), IndexError> {
+        self.readers.write()?.remove(name);
+        let writer = self.writers.write()?.remove(name);
+        if let Some(writer) = writer {
+            if let Ok(writer) = Arc::try_unwrap(writer) {
+                writer.into_inner()?.wait_merging_threads()?;
+            };
+        };
+        Ok(())
+    }
+    /// Opens the writer on first use
+    fn writer(&self, name: &str) -> Result<Option<Arc<RwLock<IndexWriter>>>, IndexError> {
+        if let Some(writer) = self.writers.read()?.get(name) {
+            return Ok(Some(writer.clone()));
+        };
+        self.materialize(name)?;
+        let location = self.which_index(name).unwrap_or_else(|| name.to_string());
+        let indexes = self.indexes.read()?;
+        let index = indexes.get(name);
+        if index.is_none() {
+            return Ok(None);
+        };
+        let mut writers = self.writers.write()?;
+        if let Some(writer) = writers.get(name) {
+            return Ok(Some(writer.clone()));
+        };
+        let writer = open_index_writer_waiting(index.unwrap(), &location, self.lock_timeout)?;
+        let writer = Arc::new(RwLock::new(writer));
+        writers.insert(name.to_string(), writer.clone());
+        if let CommitPolicy::Interval(interval) = self.commit_policy(name) {
+            if self.is_auto_commit(name) {
+                spawn_committer(name, interval, Arc::downgrade(&writer), self.uncommitted(name)?)?;
+            };
+        };
+        Ok(Some(writer))
+    }
+    /// Makes the last commit visible to an opened reader
+    fn reload(&self, name: &str) -> Result<(), IndexError> {
+        if let Some(reader) = self.readers.read()?.get(name) {
+            reader.reload()?;
+        };
+        Ok(())
+    }
+    /// Massive hack look away ;)
+    pub(crate) fn jsonify(&self, name: &str, document: &Document) -> Result<String, IndexError> {
+        self.jsonify_selected(name, document, None)
+    }
+    /// Jsonifies only the selected fields, every field when None
+    fn jsonify_selected(&self, name: &str, document: &Document, select: Option<&[String]>) -> Result<String, IndexError> {
+        if select.is_none() {
+            if let Some(source) = source_of(&self.schema(name).ok_or_else(|| IndexError::unknown_index(name))?, document) {
+                return Ok(source.to_string());
+            };
+        };
+        let payload = self.valueify_selected(name, document, select)?;
+        let result = serde_json::to_string(&payload)
+            .map_err(|e| {
+                let message = "Unable to serialize struct".to_string();
+                let reason = e.to_string();
+                IndexError::new(
+                    message,
+                    reason,
+                )
+            });
+        result
+    }
+    /// JSON value of the selected fields, nested back into objects
+    pub(crate) fn valueify_selected(&self, name: &str, document: &Document, select: Option<&[String]>) -> Result<JsonValue, IndexError> {
+        let _span = Span::enter_fine("jsonify", name);
+        let schema = self.schema(name).ok_or_else(|| IndexError::unknown_index(name))?;
+        if select.is_none() {
+            if let Some(source) = source_of(&schema, document) {
+                return Ok(serde_json::from_str(source)?);
+            };
+        };
+        let text_seqs = self.text_seqs.read()?;
+        let object_seqs = self.object_seqs.read()?;
+        let bools = self.bools.read()?;
+        let naive_dates = self.naive_dates.read()?;
+        let maps = self.maps.read()?;
+        let seqs = object_seqs.get(name);
+        let within_seq = |field_name: &str| seqs.is_some_and(|seqs| seqs.iter().any(|seq| field_name.starts_with(&format!("{}.", seq))));
+
+        let mut field_map = JsonMap::new();
+        for (field, field_values) in document.get_sorted_field_values() {
+            let field_name = schema.get_field_name(field);
+            if field_name == SOURCE || !is_selected(select, field_name) {
+                continue;
+            };
+            if field_values.is_empty() {
+                let message = format!("Unable to jsonify: {}", name);
+                let reason = format!("Field: {} does not have any value", field_name);
+                let error = IndexError::new(message, reason);
+                return Err(error);
+            };
+            let as_json = |value: &SchemaValue| -> Result<JsonValue, IndexError> {
+                let value = if contains(&bools, name, field_name) {
+                    JsonValue::Bool(value.u64_value() != 0)
+                } else if contains(&naive_dates, name, field_name) {
+                    serde_json::to_value(value.date_value().naive_utc())?
+                } else if contains(&maps, name, field_name) {
+                    serde_json::from_str(value.text().unwrap_or("{}"))?
+                } else if let SchemaValue::Facet(facet) = value {
+                    JsonValue::String(facet_path(facet))
+                } else {
+                    serde_json::to_value(value)?
+                };
+                Ok(value)
+            };
+            // Multi-valued text & fields of a sequence of objects are always arrays, any other field becomes one once it holds several values
+            let fv = if field_values.len() > 1 || contains(&text_seqs, name, field_name) || within_seq(field_name) {
+                let values = field_values.iter()
+                    .map(|v| as_json(v.value()))
+                    .collect::<Result<_, _>>()?;
+                JsonValue::Array(values)
+            } else {
+                as_json(field_values[0].value())?
+            };
+            field_map.insert(field_name.to_string(), fv);
+        };
+        if let Some(text_seqs) = text_seqs.get(name) {
+            for field_name in text_seqs {
+                if !field_map.contains_key(field_name) && is_selected(select, field_name) {
+                    field_map.insert(field_name.to_string(), JsonValue::Array(Vec::new()));
+                };
+            };
+        };
+        if let Some(maps) = maps.get(name) {
+            for field_name in maps {
+                if !field_map.contains_key(field_name) && is_selected(select, field_name) {
+                    field_map.insert(field_name.to_string(), JsonValue::Object(JsonMap::new()));
+                };
+            };
+        };
+        if let Some(seqs) = seqs {
+            for seq in seqs {
+                if is_selected(select, seq) || field_map.keys().any(|key| key.starts_with(&format!("{}.", seq))) {
+                    zip_items(&mut field_map, seq);
+                };
+            };
+        };
+        Ok(unflatten(field_map))
+    }
+    /// Reads as string
+    pub fn read_string(&self, name: &str, query: &str, limit: Option<usize>, score: Option<f32>) -> Result<Option<Vec<String>>, IndexError> {
+        let name = &self.resolve(name);
+        let options = SearchOptions::new(limit, 0, score);
+        self.read_string_with_options(name, query, &options)
+    }
+    /// Reads as string e.g. paginated with limit & offset
+    pub fn read_string_with_options(&self, name: &str, query: &str, options: &SearchOptions) -> Result<Option<Vec<String>>, IndexError> {
+        let name = &self.resolve(name);
+        let query = self.parse_with_options(name, query, options)?;
+        if query.is_none() {
+            return Ok(None);
+        };
+        let query = query.unwrap();
+        let result = self.search(name, &query, options)?;
+        Ok(result.map(SearchResult::into_hits).map(as_docs))
+    }
+    /// Reads as JSON values, no struct needed e.g. to explore or proxy the hits
+    pub fn read_values(&self, name: &str, query: &str, limit: Option<usize>, score: Option<f32>) -> Result<Option<Vec<JsonValue>>, IndexError> {
+        let name = &self.resolve(name);
+        let options = SearchOptions::new(limit, 0, score);
+        self.read_values_with_options(name, query, &options)
+    }
+    /// Reads as JSON values e.g. paginated with limit & offset
+    pub fn read_values_with_options(&self, name: &str, query: &str, options: &SearchOptions) -> Result<Option<Vec<JsonValue>>, IndexError> {
+        let name = &self.resolve(name);
+        let query = self.parse_with_options(name, query, options)?;
+        if query.is_none() {
+            return Ok(None);
+        };
+        let query = query.unwrap();
+        let result = self.search_as(name, &query, options, Self::valueify_selected)?;
+        Ok(result.map(SearchResult::into_hits).map(as_docs))
+    }
+    /// Reads as user types converted straight from the stored documents, see FromDocument
+    pub fn read_documents<T: FromDocument>(&self, name: &str, query: &str, limit: Option<usize>, score: Option<f32>) -> Result<Option<Vec<T>>, IndexError> {
+        let name = &self.resolve(name);
+        let options = SearchOptions::new(limit, 0, score);
+        self.read_documents_with_options(name, query, &options)
+    }
+    /// Reads as user types e.g. paginated with limit & offset
+    pub fn read_documents_with_options<T: FromDocument>(&self, name: &str, query: &str, options: &SearchOptions) -> Result<Option<Vec<T>>, IndexError> {
+        let name = &self.resolve(name);
+        let query = self.parse_with_options(name, query, options)?;
+        if query.is_none() {
+            return Ok(None);
+        };
+        let query = query.unwrap();
+        let result = self.search_as(name, &query, options, from_document::<T>)?;
+        Ok(result.map(SearchResult::into_hits).map(as_docs))
+    }
+    /// Reads as struct
+    pub fn read_structs<T: Serialize + DeserializeOwned>(&self, name: &str, query: &str, limit: Option<usize>, score: Option<f32>) -> Result<Option<Vec<T>>, IndexError> {
+        let name = &self.resolve(name);
+        let options = SearchOptions::new(limit, 0, score);
+        self.read_structs_with_options(name, query, &options)
+    }
+    /// Reads as struct e.g. paginated with limit & offset
+    pub fn read_structs_with_options<T: Serialize + DeserializeOwned>(&self, name: &str, query: &str, options: &SearchOptions) -> Result<Option<Vec<T>>, IndexError> {
+        let name = &self.resolve(name);
+        let hits = self.read_scored_structs(name, query, options)?;
+        Ok(hits.map(as_docs))
+    }
+    /// Reads as struct along with the relevance score
+    pub fn read_scored_structs<T: Serialize + DeserializeOwned>(&self, name: &str, query: &str, options: &SearchOptions) -> Result<Option<Vec<Hit<T>>>, IndexError> {
+        let name = &self.resolve(name);
+        let query = self.parse_with_options(name, query, options)?;
+        if query.is_none() {
+            return Ok(None);
+        };
+        let query = query.unwrap();
+        self.read_scored_structs_with_query(name, &query, options)
+    }
+    /// Reads as struct with the scores along with the total number of matching documents
+    pub fn read_result<T: Serialize + DeserializeOwned>(&self, name: &str, query: &str, options: &SearchOptions) -> Result<Option<SearchResult<T>>, IndexError> {
+        let name = &self.resolve(name);
+        let query = self.parse_with_options(name, query, options)?;
+        if query.is_none() {
+            return Ok(None);
+        };
+        let query = query.unwrap();
+        self.read_result_with_query(name, &query, options)
+    }
+    /// Reads as struct with the scores & the total using any tantivy query
+    pub fn read_result_with_query<T: Serialize + DeserializeOwned>(&self, name: &str, query: &dyn Query, options: &SearchOptions) -> Result<Option<SearchResult<T>>, IndexError> {
+        let name = &self.resolve(name);
+        let result = self.search(name, query, options)?;
+        if result.is_none() {
+            return Ok(None);
+        };
+        let result = result.unwrap();
+        let total = result.total();
+        let hits = as_structs(result.into_hits(), options.lenient())?;
+        Ok(Some(SearchResult::new(total, hits)))
+    }
+    /// Reads a page of structs by relevance continuing after the token of the previous page
+    pub fn read_page<T: Serialize + DeserializeOwned>(&self, name: &str, query: &str, limit: usize, after: Option<&str>) -> Result<Option<Page<T>>, IndexError> {
+        let name = &self.resolve(name);
+        let query = self.parse(name, query)?;
+        if query.is_none() {
+            return Ok(None);
+        };
+        let query = query.unwrap();
+        self.read_page_with_query(name, &query, limit, after)
+    }
+    /// Reads a page of structs using any tantivy query, cheap for deep pages unlike offsets
+    pub fn read_page_with_query<T: Serialize + DeserializeOwned>(&self, name: &str, query: &dyn Query, limit: usize, after: Option<&str>) -> Result<Option<Page<T>>, IndexError> {
+        let name = &self.resolve(name);
+        let reader = self.reader(name)?;
+        if reader.is_none() {
+            return Ok(None);
+        };
+        let searcher = reader.unwrap().searcher();
+        let page = page_docs(&searcher, query, self.resolve_limit(name, Some(limit)), after)?;
+        let after = page.after().map(|after| after.to_string());
+
+        let mut hits = Vec::with_capacity(page.hits().len());
+        for hit in page.into_hits() {
+            let doc = searcher.doc(*hit.doc())?;
+            let doc = self.jsonify(name, &doc)?;
+            hits.push(Hit::new(hit.score(), doc));
+        };
+        Ok(Some(Page::new(as_structs(hits, false)?, after)))
+    }
+    /// Reads as string along with the html snippet of the field, matched terms marked with <b>
+    pub fn read_highlighted(&self, name: &str, query: &str, field: &str, max_num_chars: Option<usize>, options: &SearchOptions) -> Result<Option<Vec<(String, String)>>, IndexError> {
+        let name = &self.resolve(name);
+        let query = self.parse_with_options(name, query, options)?;
+        if query.is_none() {
+            return Ok(None);
+        };
+        let query = query.unwrap();
+        let schema = self.schema(name).ok_or_else(|| IndexError::unknown_index(name))?;
+        let field = text_field(&schema, field)?;
+
+        let reader = self.reader(name)?.ok_or_else(|| IndexError::unknown_index(name))?;
+        let searcher = reader.searcher();
+        let top_docs = top_docs(&searcher, &query, &self.bounded(name, options))?;
+        let mut generator = SnippetGenerator::create(&searcher, &query, field)?;
+        if let Some(max_num_chars) = max_num_chars {
+            generator.set_max_num_chars(max_num_chars);
+        };
+
+        let mut highlights = Vec::with_capacity(top_docs.hits().len());
+        for hit in top_docs.into_hits() {
+            let doc = searcher.doc(hit.into_doc())?;
+            let snippet = generator.snippet_from_doc(&doc).to_html();
+            let doc = self.jsonify_selected(name, &doc, options.selected())?;
+            highlights.push((doc, snippet));
+        };
+        Ok(Some(highlights))
+    }
+    /// Reads as struct using any tantivy query built against the schema
+    pub fn read_structs_with_query<T: Serialize + DeserializeOwned>(&self, name: &str, query: &dyn Query, options: &SearchOptions) -> Result<Option<Vec<T>>, IndexError> {
+        let name = &self.resolve(name);
+        let hits = self.read_scored_structs_with_query(name, query, options)?;
+        Ok(hits.map(as_docs))
+    }
+    /// Reads as struct with the relevance score using any tantivy query
+    pub fn read_scored_structs_with_query<T: Serialize + DeserializeOwned>(&self, name: &str, query: &dyn Query, options: &SearchOptions) -> Result<Option<Vec<Hit<T>>>, IndexError> {
+        let name = &self.resolve(name);
+        let result = self.search(name, query, options)?;
+        if result.is_none() {
+            return Ok(None);
+        };
+        let hits = as_structs(result.unwrap().into_hits(), options.lenient())?;
+        Ok(Some(hits))
+    }
+    /// Runs the query string on each index & merges the hits by score, indexes that do not exist are skipped
+    /// Limit & offset apply to the merged hits, the limit resolved per index with the strictest one winning
+    pub fn read_multi<T: Serialize + DeserializeOwned>(&self, names: &[&str], query: &str, options: &SearchOptions) -> Result<Vec<MultiHit<T>>, IndexError> {
+        let names: Vec<String> = names.iter().map(|name| self.resolve(name)).collect();
+        let offset = options.offset();
+        // The strictest of the index limits applies to the merged hits
+        let limit = names.iter()
+            .map(|name| self.resolve_limit(name, options.limit()))
+            .min()
+            .unwrap_or(0);
+        // Every index returns the whole window up to the page, later pages would miss hits if it was clamped again
+        let mut window = options.clone();
+        window.set_offset(0);
+        window.set_limit(offset + limit);
+
+        let mut hits = Vec::new();
+        for name in &names {
+            let query = match self.parse_with_options(name, query, &window)? {
+                Some(query) => query,
+                None => continue,
+            };
+            if let Some(result) = self.search_window_as(name, &query, &window, Self::jsonify_selected)? {
+                let found = as_structs::<T>(result.into_hits(), options.lenient())?;
+                hits.extend(found.into_iter().map(|hit| MultiHit::new(name, hit)));
+            };
+        };
+        hits.sort_by(|a, b| b.score().partial_cmp(&a.score()).unwrap_or(Ordering::Equal));
+        Ok(hits.into_iter().skip(offset).take(limit).collect())
+    }
+    /// Runs the query string on every index whose name starts with the prefix e.g. logs-
+    pub fn read_multi_prefix<T: Serialize + DeserializeOwned>(&self, prefix: &str, query: &str, options: &SearchOptions) -> Result<Vec<MultiHit<T>>, IndexError> {
+        let mut names: Vec<String> = self.indexes.read()?
+            .keys()
+            .filter(|name| name.starts_with(prefix))
+            .cloned()
+            .collect();
+        names.sort();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        self.read_multi(&names, query, options)
+    }
+    /// Reads the struct holding the exact value for the key field, the value is not tokenized
+    pub fn get_by_id<T: Serialize + DeserializeOwned, V: Serialize>(&self, name: &str, field: &str, value: &V) -> Result<Option<T>, IndexError> {
+        let name = &self.resolve(name);
+        let schema = self.schema(name);
+        if schema.is_none() {
+            return Ok(None);
+        };
+        let term = as_term(&schema.unwrap(), field, value)?;
+        let query = TermQuery::new(term, IndexRecordOption::Basic);
+        let options = SearchOptions::new(Some(1), 0, None);
+        let docs = self.read_structs_with_query::<T>(name, &query, &options)?;
+        Ok(docs.and_then(|docs| docs.into_iter().next()))
+    }
+    /// Iterates lazily over every document matching the query with constant memory
+    pub fn scan<T: DeserializeOwned>(&self, name: &str, query: &str) -> Result<Option<Scan<'_, T>>, IndexError> {
+        let name = &self.resolve(name);
+        let query = self.parse(name, query)?;
+        if query.is_none() {
+            return Ok(None);
+        };
+        let query = query.unwrap();
+        self.scan_with_query(name, &query)
+    }
+    /// Iterates lazily over every document matching any tantivy query
+    pub fn scan_with_query<T: DeserializeOwned>(&self, name: &str, query: &dyn Query) -> Result<Option<Scan<'_, T>>, IndexError> {
+        let name = &self.resolve(name);
+        let reader = self.reader(name)?;
+        if reader.is_none() {
+            return Ok(None);
+        };
+        let searcher = reader.unwrap().searcher();
+        let scan = Scan::new(self, name, searcher, query)?;
+        Ok(Some(scan))
+    }
+    /// Iterates lazily over every live document of every segment, no query needed e.g. for exports & migrations
+    /// Documents are read a batch at a time, see Scan::set_batch_size
+    pub fn read_all<T: DeserializeOwned>(&self, name: &str) -> Result<Option<Scan<'_, T>>, IndexError> {
+        self.scan_with_query(name, &AllQuery)
+    }
+    /// Writes every live document of the doc store as a JSON line, returns the number written
+    pub fn export<W: Write>(&self, name: &str, mut writer: W) -> Result<Option<usize>, IndexError> {
+        let name = &self.resolve(name);
+        let reader = self.reader(name)?;
+        if reader.is_none() {
+            return Ok(None);
+        };
+        let searcher = reader.unwrap().searcher();
+        let mut exported = 0;
+        for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+            for doc_id in 0..segment_reader.max_doc() {
+                if segment_reader.is_deleted(doc_id) {
+                    continue;
+                };
+                let doc = searcher.doc(DocAddress(segment_ord as u32, doc_id))?;
+                writeln!(writer, "{}", self.jsonify(name, &doc)?)?;
+                exported += 1;
+            };
+        };
+        writer.flush()?;
+        Ok(Some(exported))
+    }
+    /// Number of documents matching the query, no document is fetched
+    pub fn count(&self, name: &str, query: &str) -> Result<Option<usize>, IndexError> {
+        let name = &self.resolve(name);
+        let query = self.parse(name, query)?;
+        if query.is_none() {
+            return Ok(None);
+        };
+        let query = query.unwrap();
+        let reader = self.reader(name)?.ok_or_else(|| IndexError::unknown_index(name))?;
+        let searcher = reader.searcher();
+        let count = searcher.search(&query, &Count)?;
+        Ok(Some(count))
+    }
+    /// Computes count, sum, avg, min & max of a numeric fast field over the matching documents
+    pub fn aggregate(&self, name: &str, query: &str, field: &str) -> Result<Option<Stats>, IndexError> {
+        let name = &self.resolve(name);
+        let query = self.parse(name, query)?;
+        if query.is_none() {
+            return Ok(None);
+        };
+        let query = query.unwrap();
+        self.aggregate_with_query(name, &query, field)
+    }
+    /// Computes the Stats of a numeric fast field using any tantivy query
+    pub fn aggregate_with_query(&self, name: &str, query: &dyn Query, field: &str) -> Result<Option<Stats>, IndexError> {
+        let name = &self.resolve(name);
+        let reader = self.reader(name)?;
+        if reader.is_none() {
+            return Ok(None);
+        };
+        let searcher = reader.unwrap().searcher();
+        let stats = aggregate(&searcher, query, field)?;
+        Ok(Some(stats))
+    }
+    /// Counts the sub-categories of the facet one level below the path among the matching documents
+    /// An empty path counts the top level, filter by a sub-category with a term clause on its path
+    pub fn facets(&self, name: &str, query: &str, field: &str, path: &str) -> Result<Option<Vec<FacetCount>>, IndexError> {
+        let name = &self.resolve(name);
+        let query = self.parse(name, query)?;
+        if query.is_none() {
+            return Ok(None);
+        };
+        let query = query.unwrap();
+        self.facets_with_query(name, &query, field, path)
+    }
+    /// Counts the sub-categories of the facet one level below the path using any tantivy query
+    pub fn facets_with_query(&self, name: &str, query: &dyn Query, field: &str, path: &str) -> Result<Option<Vec<FacetCount>>, IndexError> {
+        let name = &self.resolve(name);
+        let reader = self.reader(name)?;
+        if reader.is_none() {
+            return Ok(None);
+        };
+        let searcher = reader.unwrap().searcher();
+        let schema = searcher.schema().clone();
+        let counts = facet_counts(&searcher, query, &schema, field, path)?;
+        Ok(Some(counts))
+    }
+    /// Explains the score of the document holding the exact value for the key field against the query string
+    /// None when the index or the document does not exist
+    pub fn explain<V: Serialize>(&self, name: &str, query: &str, field: &str, value: &V) -> Result<Option<ScoreExplanation>, IndexError> {
+        let name = &self.resolve(name);
+        let query = self.parse(name, query)?;
+        if query.is_none() {
+            return Ok(None);
+        };
+        let query = query.unwrap();
+        self.explain_with_query(name, &query, field, value)
+    }
+    /// Explains the score of the document holding the exact value for the key field against any tantivy query
+    pub fn explain_with_query<V: Serialize>(&self, name: &str, query: &dyn Query, field: &str, value: &V) -> Result<Option<ScoreExplanation>, IndexError> {
+        let name = &self.resolve(name);
+        let reader = self.reader(name)?;
+        if reader.is_none() {
+            return Ok(None);
+        };
+        let searcher = reader.unwrap().searcher();
+        let term = as_term(searcher.schema(), field, value)?;
+        let key = TermQuery::new(term, IndexRecordOption::Basic);
+        let doc_address = searcher.search(&key, &TopDocs::with_limit(1))?.into_iter().next();
+        if doc_address.is_none() {
+            return Ok(None);
+        };
+        let (_, doc_address) = doc_address.unwrap();
+        let explanation = explain_doc(&searcher, query, doc_address)?;
+        Ok(Some(explanation))
+    }
+    /// Unique values of the field across the index along with their counts, most documents first - default limit is 10
+    /// The field is a single valued fast numeric field or a stored field
+    pub fn distinct(&self, name: &str, field: &str, limit: Option<usize>) -> Result<Option<Vec<DistinctValue>>, IndexError> {
+        self.distinct_with_query(name, &AllQuery, field, limit)
+    }
+    /// Unique values of the field among the documents matching any tantivy query
+    pub fn distinct_with_query(&self, name: &str, query: &dyn Query, field: &str, limit: Option<usize>) -> Result<Option<Vec<DistinctValue>>, IndexError> {
+        let name = &self.resolve(name);
+        let reader = self.reader(name)?;
+        if reader.is_none() {
+            return Ok(None);
+        };
+        let searcher = reader.unwrap().searcher();
+        let schema = searcher.schema().clone();
+        let values = distinct_values(&searcher, query, &schema, field, limit.unwrap_or(10))?;
+        let bools = self.bools.read()?;
+        if !contains(&bools, name, field) {
+            return Ok(Some(values));
+        };
+        let values = values.into_iter()
+            .map(|distinct| match distinct.value().as_u64() {
+                Some(value) => DistinctValue::new(JsonValue::Bool(value != 0), distinct.count()),
+                None => distinct,
+            })
+            .collect();
+        Ok(Some(values))
+    }
+    /// Reads as struct where the text field has a term within the edit distance
+    pub fn read_structs_fuzzy<T: Serialize + DeserializeOwned>(&self, name: &str, field: &str, term: &str, distance: u8, options: &SearchOptions) -> Result<Option<Vec<T>>, IndexError> {
+        let name = &self.resolve(name);
+        let index = self.index(name)?;
+        if index.is_none() {
+            return Ok(None);
+        };
+        let query = fuzzy_query(&index.unwrap(), field, term, distance)?;
+        self.read_structs_with_query(name, &query, options)
+    }
+    /// Did you mean: terms of the text field within the edit distance, closest & most frequent first - default limit is 10
+    pub fn did_you_mean(&self, name: &str, field: &str, term: &str, distance: u8, limit: Option<usize>) -> Result<Option<Vec<Suggestion>>, IndexError> {
+        let name = &self.resolve(name);
+        let index = self.index(name)?;
+        let reader = self.reader(name)?;
+        if index.is_none() || reader.is_none() {
+            return Ok(None);
+        };
+        let searcher = reader.unwrap().searcher();
+        let suggestions = suggest_terms(&index.unwrap(), &searcher, field, term, distance, limit.unwrap_or(10))?;
+        Ok(Some(suggestions))
+    }
+    /// Autocomplete: terms of the text field starting with the prefix, most frequent first - default limit is 10
+    pub fn suggest(&self, name: &str, field: &str, prefix: &str, limit: Option<usize>) -> Result<Option<Vec<Suggestion>>, IndexError> {
+        let name = &self.resolve(name);
+        let index = self.index(name)?;
+        let reader = self.reader(name)?;
+        if index.is_none() || reader.is_none() {
+            return Ok(None);
+        };
+        let searcher = reader.unwrap().searcher();
+        let completions = complete_terms(&index.unwrap(), &searcher, field, prefix, limit.unwrap_or(10))?;
+        Ok(Some(completions))
+    }
+    /// Reads as struct where the text field has a term starting with the prefix e.g. for type-ahead
+    pub fn read_structs_prefix<T: Serialize + DeserializeOwned>(&self, name: &str, field: &str, prefix: &str, options: &SearchOptions) -> Result<Option<Vec<T>>, IndexError> {
+        let name = &self.resolve(name);
+        let index = self.index(name)?;
+        if index.is_none() {
+            return Ok(None);
+        };
+        let index = index.unwrap();
+        let schema = index.schema();
+        let pattern = prefix_pattern(&index, text_field(&schema, field)?, prefix)?;
+        let query = regex_query(&schema, field, &pattern)?;
+        self.read_structs_with_query(name, &query, options)
+    }
+    /// Reads as struct where the text field has a term matching the regex, terms are lowercase tokens
+    pub fn read_regex<T: Serialize + DeserializeOwned>(&self, name: &str, field: &str, pattern: &str, options: &SearchOptions) -> Result<Option<Vec<T>>, IndexError> {
+        let name = &self.resolve(name);
+        let schema = self.schema(name);
+        if schema.is_none() {
+            return Ok(None);
+        };
+        let schema = schema.unwrap();
+        let query = regex_query(&schema, field, pattern)?;
+        self.read_structs_with_query(name, &query, options)
+    }
+    /// Reads as struct where the text field has the words next to each other in order
+    pub fn read_phrase<T: Serialize + DeserializeOwned>(&self, name: &str, field: &str, words: &[&str], options: &SearchOptions) -> Result<Option<Vec<T>>, IndexError> {
+        let name = &self.resolve(name);
+        let index = self.index(name)?;
+        if index.is_none() {
+            return Ok(None);
+        };
+        let query = phrase_query(&index.unwrap(), field, words)?;
+        self.read_structs_with_query(name, &query, options)
+    }
+    /// Reads as struct where the numeric field falls within the range, start inclusive & end exclusive
+    pub fn read_structs_in_range<T: Serialize + DeserializeOwned, V: Serialize>(&self, name: &str, field: &str, range: Range<V>, limit: Option<usize>, score: Option<f32>) -> Result<Option<Vec<T>>, IndexError> {
+        let name = &self.resolve(name);
+        let schema = self.schema(name);
+        if schema.is_none() {
+            return Ok(None);
+        };
+        let schema = schema.unwrap();
+        let query = numeric_range_query(&schema, field, range)?;
+        let options = SearchOptions::new(limit, 0, score);
+        self.read_structs_with_query(name, &query, &options)
+    }
+    /// Reads as struct where the date field falls within the range, start inclusive & end exclusive
+    pub fn read_structs_in_date_range<T: Serialize + DeserializeOwned>(&self, name: &str, field: &str, range: Range<DateTime<Utc>>, limit: Option<usize>, score: Option<f32>) -> Result<Option<Vec<T>>, IndexError> {
+        let name = &self.resolve(name);
+        let schema = self.schema(name);
+        if schema.is_none() {
+            return Ok(None);
+        };
+        let schema = schema.unwrap();
+        let query = date_range_query(&schema, field, range)?;
+        let options = SearchOptions::new(limit, 0, score);
+        self.read_structs_with_query(name, &query, &options)
+    }
+    /// Reads as struct where the date field falls between date math expressions e.g. now-7d & now, end exclusive
+    pub fn between<T: Serialize + DeserializeOwned>(&self, name: &str, field: &str, from: &str, to: &str) -> Result<Option<Vec<T>>, IndexError> {
+        self.between_with_options(name, field, from, to, &SearchOptions::default())
+    }
+    /// Reads as struct where the date field falls between date math expressions e.g. paginated & sorted by date
+    pub fn between_with_options<T: Serialize + DeserializeOwned>(&self, name: &str, field: &str, from: &str, to: &str, options: &SearchOptions) -> Result<Option<Vec<T>>, IndexError> {
+        let name = &self.resolve(name);
+        let schema = self.schema(name);
+        if schema.is_none() {
+            return Ok(None);
+        };
+        let now = Utc::now();
+        let range = resolve_date(from, now)?..resolve_date(to, now)?;
+        let query = date_range_query(&schema.unwrap(), field, range)?;
+        self.read_structs_with_query(name, &query, options)
+    }
+    /// Reads as struct the documents whose GeoPoint field lies within the radius, nearest first
+    pub fn within_radius<T: DeserializeOwned>(&self, name: &str, field: &str, lat: f64, lon: f64, km: f64) -> Result<Option<Vec<T>>, IndexError> {
+        let name = &self.resolve(name);
+        let nearby = self.within_radius_with_distance(name, field, lat, lon, km)?;
+        Ok(nearby.map(|nearby| nearby.into_iter().map(Nearby::into_doc).collect()))
+    }
+    /// Reads as struct with the distance to the center the documents within the radius, nearest first
+    /// The box bounding the circle is searched & the documents outside the circle are left out
+    pub fn within_radius_with_distance<T: DeserializeOwned>(&self, name: &str, field: &str, lat: f64, lon: f64, km: f64) -> Result<Option<Vec<Nearby<T>>>, IndexError> {
+        let name = &self.resolve(name);
+        let schema = self.schema(name);
+        if schema.is_none() {
+            return Ok(None);
+        };
+        let center = GeoPoint::new(lat, lon);
+        let query = bounding_box_query(&schema.unwrap(), field, &center, km)?;
+        let scan = self.scan_with_query::<JsonValue>(name, &query)?;
+        if scan.is_none() {
+            return Ok(None);
+        };
+        let mut nearby = Vec::new();
+        for doc in scan.unwrap() {
+            let doc = doc?;
+            let point = match geo_point(&doc, field) {
+                Some(point) => point,
+                None => continue,
+            };
+            let distance = center.distance_km(&point);
+            if distance > km {
+                continue;
+            };
+            nearby.push(Nearby::new(distance, serde_json::from_value::<T>(doc)?));
+        };
+        nearby.sort_by(|a, b| a.distance_km().partial_cmp(&b.distance_km()).unwrap_or(Ordering::Equal));
+        Ok(Some(nearby))
+    }
+    /// Reads as struct the documents sharing the most distinctive terms of the seed, the seed itself is left out
+    pub fn more_like_this<T: Serialize + DeserializeOwned>(&self, name: &str, like: &Like, limit: Option<usize>) -> Result<Option<Vec<T>>, IndexError> {
+        self.more_like_this_with_options(name, like, &MoreLikeThis::default(), limit)
+    }
+    /// Reads as struct the similar documents with the terms picked by the options
+    pub fn more_like_this_with_options<T: Serialize + DeserializeOwned>(&self, name: &str, like: &Like, options: &MoreLikeThis, limit: Option<usize>) -> Result<Option<Vec<T>>, IndexError> {
+        let name = &self.resolve(name);
+        let index = self.index(name)?;
+        let reader = self.reader(name)?;
+        if index.is_none() || reader.is_none() {
+            return Ok(None);
+        };
+        let index = index.unwrap();
+        let schema = index.schema();
+        let fields = self.fields.read()?.get(name).cloned().unwrap_or_default();
+
+        // Hands back the searcher to the pool before searching with the similar query
+        let query = {
+            let searcher = reader.unwrap().searcher();
+            let (seeds, exclude) = match like {
+                Like::Doc(doc) => (vec![json_as_document(&schema, doc.clone())?], None),
+                Like::Query(query) => {
+                    let query = self.parse(name, query)?.ok_or_else(|| IndexError::unknown_index(name))?;
+                    let hits = top_docs(&searcher, query.as_ref(), &SearchOptions::default())?.into_hits();
+                    let mut seeds = Vec::with_capacity(hits.len());
+                    for hit in hits {
+                        seeds.push(searcher.doc(*hit.doc())?);
+                    };
+                    (seeds, Some(query))
+                }
+            };
+            let mut clauses = similar_clauses(&index, &searcher, &seeds, &fields, options)?;
+            if let Some(exclude) = exclude {
+                clauses.push((Occur::MustNot, exclude));
+            };
+            BooleanQuery::from(clauses)
+        };
+
+        let limit = limit.unwrap_or(10);
+        let search = SearchOptions::new(Some(limit + 1), 0, None);
+        let docs = self.read_structs_with_query::<T>(name, &query, &search)?.unwrap_or_default();
+        let seed = match like {
+            Like::Doc(doc) => Some(doc),
+            Like::Query(_) => None,
+        };
+        let docs = docs.into_iter()
+            .filter(|doc| match seed {
+                Some(seed) => serde_json::to_value(doc).ok().as_ref() != Some(seed),
+                None => true,
+            })
+            .take(limit)
+            .collect();
+        Ok(Some(docs))
+    }
+    /// Compiles the boolean query against the index, usable with any of the *_with_query reads
+    pub fn compile_query(&self, name: &str, query: &BoolQuery) -> Result<Option<Box<dyn Query>>, IndexError> {
+        let name = &self.resolve(name);
+        let index = self.index(name)?;
+        if index.is_none() {
+            return Ok(None);
+        };
+        let index = index.unwrap();
+        let parse = |text: &str| match self.parse(name, text)? {
+            Some(query) => Ok(query),
+            None => Err(IndexError::unknown_index(name)),
+        };
+        let query = bool_query(&index, query, &parse)?;
+        Ok(Some(Box::new(query)))
+    }
+    /// Reads as struct matching the boolean query
+    pub fn read_structs_with_bool<T: Serialize + DeserializeOwned>(&self, name: &str, query: &BoolQuery, options: &SearchOptions) -> Result<Option<Vec<T>>, IndexError> {
+        let name = &self.resolve(name);
+        let query = self.compile_query(name, query)?;
+        if query.is_none() {
+            return Ok(None);
+        };
+        let query = query.unwrap();
+        self.read_structs_with_query(name, query.as_ref(), options)
+    }
+    /// Parses the query against the default fields
+    fn parse(&self, name: &str, query: &str) -> Result<Option<Box<dyn Query>>, IndexError> {
+        self.parse_with_options(name, query, &SearchOptions::default())
+    }
+    /// Parses the query with the field boosts of the index, overridden by those of the call
+    fn parse_with_options(&self, name: &str, query: &str, options: &SearchOptions) -> Result<Option<Box<dyn Query>>, IndexError> {
+        let span = Span::enter("parse", name);
+        span.event(&format!("query={}", query));
+        let index = self.index(name)?;
+        if index.is_none() {
+            return Ok(None);
+        };
+        let index = index.unwrap();
+        let schema = index.schema();
+        let default_fields = self.fields.read()?.get(name).cloned().ok_or_else(|| IndexError::unknown_index(name))?;
+        let mut query_parser = QueryParser::for_index(&index, default_fields);
+        if self.is_conjunction_by_default(name) {
+            query_parser.set_conjunction_by_default();
+        };
+        let mut boosts = self.boosts.read()?.get(name).cloned().unwrap_or_default();
+        boosts.extend(options.field_boosts().iter().map(|(field, boost)| (field.to_string(), *boost)));
+        for (field_name, boost) in boosts {
+            let field = schema.get_field(&field_name);
+            if field.is_none() {
+                let reason = format!("Field: {} not found", field_name);
+                return Err(IndexError::schema_mismatch("Unable to boost field", &reason));
+            };
+            query_parser.set_field_boost(field.unwrap(), boost);
+        };
+        let synonyms = self.analyses.read()?.get(name).and_then(Analysis::synonyms).cloned();
+        let query = match synonyms {
+            Some(synonyms) => query_parser.parse_query(&expand_query(query, &synonyms))?,
+            None => query_parser.parse_query(query)?,
+        };
+        Ok(Some(query))
+    }
+    /// Points the alias at the index, switching from the previous index at once
+    /// Every API resolves the alias e.g. products -> products_v2
+    pub fn set_alias(&self, alias: &str, index: &str) -> Result<(), IndexError> {
+        let index = self.resolve(index);
+        if self.index(&index)?.is_none() {
+            let reason = format!("Index: {} does not exist", index);
+            return Err(IndexError::new("Unable to set alias", &reason));
+        };
+        if self.index(alias)?.is_some() {
+            let reason = format!("Alias: {} is the name of an index", alias);
+            return Err(IndexError::new("Unable to set alias", &reason));
+        };
+        self.aliases.write()?.insert(alias.to_string(), index);
+        Ok(())
+    }
+    /// Removes the alias, the index stays as is
+    pub fn remove_alias(&self, alias: &str) -> Result<(), IndexError> {
+        self.aliases.write()?.remove(alias);
+        Ok(())
+    }
+    /// Index the alias points at
+    pub fn alias(&self, alias: &str) -> Option<String> {
+        self.aliases.read().ok()?.get(alias).cloned()
+    }
+    /// Index behind the alias or the name itself
+    fn resolve(&self, name: &str) -> String {
+        self.alias(name).unwrap_or_else(|| name.to_string())
+    }
+    /// Index handle, cheap to clone
+    fn index(&self, name: &str) -> Result<Option<Index>, IndexError> {
+        Ok(self.indexes.read()?.get(name).cloned())
+    }
+    /// Opens the reader on first use
+    fn reader(&self, name: &str) -> Result<Option<IndexReader>, IndexError> {
+        if let Some(reader) = self.readers.read()?.get(name) {
+            return Ok(Some(reader.clone()));
+        };
+        let index = self.index(name)?;
+        if index.is_none() {
+            return Ok(None);
+        };
+        let mut readers = self.readers.write()?;
+        if let Some(reader) = readers.get(name) {
+            return Ok(Some(reader.clone()));
+        };
+        let reader = open_index_reader(&index.unwrap())?;
+        readers.insert(name.to_string(), reader.clone());
+        Ok(Some(reader))
+    }
+    /// Runs the query & jsonify the top documents
+    fn search(&self, name: &str, query: &dyn Query, options: &SearchOptions) -> Result<Option<SearchResult<String>>, IndexError> {
+        self.search_as(name, query, options, Self::jsonify_selected)
+    }
+    /// Top docs converted one by one e.g. jsonified
+    fn search_as<T>(&self, name: &str, query: &dyn Query, options: &SearchOptions, as_doc: AsDoc<T>) -> Result<Option<SearchResult<T>>, IndexError> {
+        self.search_window_as(name, query, &self.bounded(name, options), as_doc)
+    }
+    /// Top docs within the limit as given, already resolved by the caller e.g. the window of read_multi
+    fn search_window_as<T>(&self, name: &str, query: &dyn Query, options: &SearchOptions, as_doc: AsDoc<T>) -> Result<Option<SearchResult<T>>, IndexError> {
+        let mut span = Span::enter("search", name);
+        let reader = self.reader(name)?;
+        if reader.is_none() {
+            return Ok(None);
+        };
+        let searcher = reader.unwrap().searcher();
+        let top_docs = top_docs(&searcher, query, options)?;
+        let total = top_docs.total();
+
+        let mut hits = Vec::with_capacity(top_docs.hits().len());
+        for hit in top_docs.into_hits() {
+            let doc = searcher.doc(*hit.doc())?;
+            let doc = as_doc(self, name, &doc, options.selected())?;
+            hits.push(Hit::new(hit.score(), doc));
+        };
+        span.record_docs(hits.len());
+        Ok(Some(SearchResult::new(total, hits)))
+    }
+}
+
+/// Converts a stored document keeping the selected fields
+type AsDoc<T> = fn(&Surfer, &str, &Document, Option<&[String]>) -> Result<T, IndexError>;
+
+/// Is the field within the per index set
+fn contains(sets: &HashMap<String, HashSet<String>>, name: &str, field_name: &str) -> bool {
+    match sets.get(name) {
+        Some(fields) => fields.contains(field_name),
+        None => false
+    }
+}
+
+/// Is the field or the struct holding it selected
+fn is_selected(select: Option<&[String]>, field_name: &str) -> bool {
+    match select {
+        Some(select) => select.iter().any(|selected| {
+            field_name == selected || (field_name.starts_with(selected.as_str()) && field_name[selected.len()..].starts_with('.'))
+        }),
+        None => true
+    }
+}
+
+/// Is the query a term or a disjunction of terms, i.e. deleting its terms deletes exactly its matches
+fn is_disjunction(query: &dyn Query) -> bool {
+    if query.downcast_ref::<TermQuery>().is_some() {
+        return true;
+    };
+    match query.downcast_ref::<BooleanQuery>() {
+        Some(query) => query.clauses().iter().all(|(occur, clause)| {
+            *occur == Occur::Should && is_disjunction(clause.as_ref())
+        }),
+        None => false
+    }
+}
+
+/// Deserialize jsonified documents
+fn as_structs<T: DeserializeOwned>(hits: Vec<Hit<String>>, lenient: bool) -> Result<Vec<Hit<T>>, IndexError> {
+    let mut structs = Vec::with_capacity(hits.len());
+    for hit in hits {
+        let score = hit.score();
+        match as_struct::<T>(hit.doc()) {
+            Ok(doc) => structs.push(Hit::new(score, doc)),
+            Err(_) if lenient => continue,
+            Err(e) => return Err(e),
+        };
+    };
+    Ok(structs)
+}
+
+/// Error names the stored document along with the missing or mistyped field
+pub(crate) fn as_struct<T: DeserializeOwned>(doc: &str) -> Result<T, IndexError> {
+    serde_json::from_str::<T>(doc).map_err(|e| {
+        let message = format!("Unable to deserialize document: {}", doc);
+        IndexError::Serde {
+            message,
+            source: e,
+        }
+    })
+}
+
+/// Drops the scores
+fn as_docs<T>(hits: Vec<Hit<T>>) -> Vec<T> {
+    hits.into_iter().map(Hit::into_doc).collect()
+}
+
+/// Panics if somethings goes wrong
+impl Surfer {
+    pub fn new(builder: SurferBuilder) -> Self {
+        Surfer::try_from(builder).unwrap()
+    }
+    /// Opens every index found on disk under home using their own schemas
+    pub fn open_existing(home: &str) -> Result<Self, IndexError> {
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        let home = resolve_home(Some(home))?;
+        let not_utf8 = |path: &Path| {
+            let reason = format!("Path: {} is not valid UTF-8", path.display());
+            IndexError::new("Unable to open existing indexes", &reason)
+        };
+        for path in ls(home.to_str().ok_or_else(|| not_utf8(&home))?)? {
+            if !path.join("meta.json").exists() {
+                continue;
+            };
+            let name = path.file_name().and_then(|name| name.to_str()).ok_or_else(|| not_utf8(&path))?;
+            // Staging directory left over by a migration that did not complete
+            if name.ends_with(".migrating") {
+                continue;
+            };
+            builder.add_existing(name)?;
+        };
+        Surfer::try_from(builder)
+    }
+}
+
+/// Opens mmap dir, an existing index must match the schema by field names & types
+/// A new index gives the fields with their own analysis a tokenizer of their own
+fn initialize_mmap(name: &str, home: &str, schema: &Schema, analysis: Option<&Analysis>, tokenizers: &Tokenizers, durability: Durability) -> Result<Index, IndexError> {
+    let path = resolve_index_directory_path(name, Some(home))?;
+    let exists = path.exists();
+    let created = match (exists, analysis) {
+        (true, _) => None,
+        (false, Some(analysis)) => Some(analysis.schema(schema)),
+        (false, None) => Some(schema.clone()),
+    };
+    let index = match durability {
+        Durability::Durable => open_index(open_mmap_directory(path)?, created.as_ref())?,
+        Durability::Deferred => open_index(UnsyncedDirectory::open(path)?, created.as_ref())?,
+    };
+    if exists {
+        let drift = schema_drift(&index.schema(), schema);
+        if !drift.is_empty() {
+            let message = format!("Schema of index: {} does not match", name);
+            return Err(IndexError::schema_mismatch(message, drift.describe()));
+        };
+    };
+    register_tokenizers(&index, analysis, tokenizers)?;
+    Ok(index)
+}
+
+/// Get home location
+fn extract_home(builder: &SurferBuilder) -> Result<String, IndexError> {
+    let home = builder.home.as_ref();
+    let home = resolve_home(home)?;
+    Ok(home.to_str().unwrap().to_string())
+}
+
+/// Setup indexes, lazy ones not on disk yet are left pending
 fn initialized_index(home: &str, builder: &SurferBuilder) -> Result<HashMap<String, Index>, IndexError> {
     let schemas = &builder.schemas;
     let mut indexes = HashMap::<String, Index>::with_capacity(schemas.len());
     for (name, schema) in schemas {
-        let index = initialize_mmap(name, &home, &schema)?;
+        if is_lazy(home, builder, name) {
+            continue;
+        };
+        let schema = &managed_schema(builder, name, schema);
+        let in_memory = *builder.in_memory.get(name).unwrap_or(&false);
+        let index = if in_memory {
+            open_index_in_ram(schema, builder.analyses.get(name), &builder.tokenizers)?
+        } else {
+            initialize_mmap(name, &home, schema, builder.analyses.get(name), &builder.tokenizers, builder.durabilities.get(name).copied().unwrap_or_default())?
+        };
         indexes.insert(name.to_string(), index);
     };
     Ok(indexes)
 }
 
-/// Extract field information
-fn extract_fields(builder: &SurferBuilder) -> HashMap<String, Vec<Field>> {
-    let data = &builder.schemas;
-    let mut fields = HashMap::<String, Vec<Field>>::with_capacity(data.len());
-    for (data, schema) in data {
-        let key = data.clone();
-        let value: Vec<Field> = schema.fields().map(|(f, _)| f).collect();
-        fields.insert(key, value);
-    };
-    fields
-}
+/// Schemas of the lazy indexes not on disk yet
+fn pending_index(home: &str, builder: &SurferBuilder) -> HashMap<String, Schema> {
+    builder.schemas.iter()
+        .filter(|(name, _)| is_lazy(home, builder, name))
+        .map(|(name, schema)| (name.to_string(), managed_schema(builder, name, schema)))
+        .collect()
+}
+
+fn is_lazy(home: &str, builder: &SurferBuilder, name: &str) -> bool {
+    builder.lazy && !*builder.in_memory.get(name).unwrap_or(&false) && !probe_index(home, name)
+}
+
+fn managed_schema(builder: &SurferBuilder, name: &str, schema: &Schema) -> Schema {
+    let schema = match builder.ttls.get(name) {
+        Some(_) => with_expiry(schema),
+        None => schema.clone(),
+    };
+    let schema = match builder.sources.get(name) {
+        Some(true) => with_source(&schema),
+        _ => schema,
+    };
+    match builder.catch_alls.get(name) {
+        Some(true) => with_catch_all(&schema),
+        _ => schema,
+    }
+}
+
+/// Extract default search field information, declared fields must be indexed
+fn extract_fields(builder: &SurferBuilder) -> Result<HashMap<String, Vec<Field>>, IndexError> {
+    let data = &builder.schemas;
+    let mut fields = HashMap::<String, Vec<Field>>::with_capacity(data.len());
+    for (data, schema) in data {
+        let declared = builder.search_fields.get(data);
+        for field_name in declared.into_iter().flatten() {
+            let field = schema.get_field(field_name);
+            if field.is_none() || !schema.get_field_entry(field.unwrap()).is_indexed() {
+                let message = format!("Unable to set default search fields: {}", data);
+                let reason = format!("Field: {} is not an indexed field", field_name);
+                return Err(IndexError::schema_mismatch(message, reason));
+            };
+        };
+        let key = data.clone();
+        let value = default_fields(&managed_schema(builder, data, schema), declared);
+        fields.insert(key, value);
+    };
+    Ok(fields)
+}
+
+/// Fields which are used as default query fields, the declared ones, the catch-all field or every indexed text field
+fn default_fields(schema: &Schema, declared: Option<&Vec<String>>) -> Vec<Field> {
+    match declared {
+        Some(names) => names.iter().filter_map(|name| schema.get_field(name)).collect(),
+        None if schema.get_field(ALL).is_some() => schema.get_field(ALL).into_iter().collect(),
+        None => schema.fields()
+            .filter(|(_, entry)| entry.is_indexed() && matches!(entry.field_type(), FieldType::Str(_)))
+            .map(|(f, _)| f)
+            .collect()
+    }
+}
+
+
+impl TryFrom<SurferBuilder> for Surfer {
+    type Error = IndexError;
+    fn try_from(builder: SurferBuilder) -> Result<Self, Self::Error> {
+        let home = extract_home(&builder)?;
+        let pending = pending_index(&home, &builder);
+        let indexes = initialized_index(&home, &builder)?;
+        let fields = extract_fields(&builder)?;
+
+        let readers = HashMap::new();
+        let writers = HashMap::new();
+        let primary_keys = builder.primary_keys.clone();
+        let auto_commits = builder.auto_commits.clone();
+        let commit_policies = builder.commit_policies.clone();
+        let durabilities = builder.durabilities.clone();
+        let conjunctions = builder.conjunctions.clone();
+        let search_fields = builder.search_fields.clone();
+        let boosts = builder.boosts.clone();
+        let analyses = builder.analyses.clone();
+        let shapes = builder.shapes.clone();
+        let tokenizers = builder.tokenizers.clone();
+        let ttls = builder.ttls.clone();
+        let default_limits = builder.default_limits.clone();
+        let max_limits = builder.max_limits.clone();
+        let sources = builder.sources.clone();
+        let catch_alls = builder.catch_alls.clone();
+        let text_seqs = builder.text_seqs.clone();
+        let object_seqs = builder.object_seqs.clone();
+        let bools = builder.bools.clone();
+        let naive_dates = builder.naive_dates.clone();
+        let maps = builder.maps.clone();
+        let closed = HashMap::new();
+        let in_memory = builder.in_memory.clone();
+
+        Ok(Surfer {
+            home,
+            indexes: RwLock::new(indexes),
+            fields: RwLock::new(fields),
+            readers: RwLock::new(readers),
+            writers: RwLock::new(writers),
+            primary_keys: RwLock::new(primary_keys),
+            auto_commits: RwLock::new(auto_commits),
+            commit_policies: RwLock::new(commit_policies),
+            uncommitted: RwLock::new(HashMap::new()),
+            durabilities: RwLock::new(durabilities),
+            conjunctions: RwLock::new(conjunctions),
+            search_fields: RwLock::new(search_fields),
+            boosts: RwLock::new(boosts),
+            analyses: RwLock::new(analyses),
+            shapes: RwLock::new(shapes),
+            tokenizers,
+            ttls: RwLock::new(ttls),
+            default_limits: RwLock::new(default_limits),
+            max_limits: RwLock::new(max_limits),
+            sources: RwLock::new(sources),
+            catch_alls: RwLock::new(catch_alls),
+            text_seqs: RwLock::new(text_seqs),
+            object_seqs: RwLock::new(object_seqs),
+            bools: RwLock::new(bools),
+            naive_dates: RwLock::new(naive_dates),
+            maps: RwLock::new(maps),
+            closed: RwLock::new(closed),
+            in_memory: RwLock::new(in_memory),
+            aliases: RwLock::new(HashMap::new()),
+            pending: RwLock::new(pending),
+            lock_timeout: builder.lock_timeout,
+        })
+    }
+}
+
+/// Container to pass through config to tantivy
+/// Also declares the type of fields sampled as None, ControlIntOptions declares a u64 field
+pub enum Control {
+    ControlTextOptions(TextOptions),
+    ControlIntOptions(IntOptions),
+    ControlDateOptions(IntOptions),
+    ControlI64Options(IntOptions),
+    ControlF64Options(IntOptions),
+    /// Keeps a map e.g. HashMap<String, String> as one text field holding its JSON, searchable by its keys & values
+    ControlMapOptions(TextOptions),
+    /// Keeps the default options of a number, bool or date field adding a fast field e.g. for sorting & aggregations
+    ControlFastOptions(Cardinality),
+    /// Indexes a path-like string e.g. electronics/phones/android as a hierarchical facet, counted level by level
+    ControlFacet,
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Serialize, Deserialize};
+    use std::fmt::Debug;
+    use std::path::Path;
+    use std::fs::remove_dir_all;
+    use tantivy::schema::{IndexRecordOption, TextFieldIndexing};
+    use tantivy::tokenizer::{LowerCaser, RawTokenizer};
+
+
+    #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+    struct OldMan {
+        title: String,
+        body: String,
+    }
+
+    impl FromDocument for OldMan {}
+
+    impl Default for OldMan {
+        fn default() -> Self {
+            let title = "".to_string();
+            let body = "".to_string();
+            Self {
+                title,
+                body,
+            }
+        }
+    }
+
+    #[test]
+    fn validate_read_existing_documents_as_structs() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+
+        {
+            let title = "The Old Man and the Sea".to_string();
+            let body = "He was an old man who fished alone in a skiff in the Gulf Stream and he had gone eighty-four days now without taking a fish.".to_string();
+            let old_man_doc = OldMan {
+                title,
+                body,
+            };
+
+            let surfer = Surfer::new(builder.clone());
+            let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
+        }
+
+        let surfer = Surfer::new(builder.clone());
+        let query = "sea whale";
+        let result = surfer.read_structs::<OldMan>(&name, query, None, None);
+        assert!(result.is_ok());
+        assert!(path.exists());
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_read_existing_documents_as_strings() {
+        let title = "The Old Man and the Sea".to_string();
+        let body = "He was an old man who fished alone in a skiff in the Gulf Stream and he had gone eighty-four days now without taking a fish.".to_string();
+        let expected = OldMan {
+            title,
+            body,
+        };
+
+
+        let name = random_string(None);
+        let mut builder = SurferBuilder::default();
+        let data = OldMan::default();
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+        builder.set_home(home);
+        builder.add_struct(name.to_string(), &data);
+
+        {
+            let title = "The Old Man and the Sea".to_string();
+            let body = "He was an old man who fished alone in a skiff in the Gulf Stream and he had gone eighty-four days now without taking a fish.".to_string();
+            let old_man_doc = OldMan {
+                title,
+                body,
+            };
+
+            let surfer = Surfer::new(builder.clone());
+            let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
+        }
+
+        let surfer = Surfer::new(builder.clone());
+        let query = "sea whale";
+        let result = surfer.read_string("Non-existent", query, None, None);
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        assert!(result.is_none());
+        let result = surfer.read_string(&name, query, None, None);
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        assert!(result.is_some());
+        let result = result.unwrap();
+        let mut computed = Vec::new();
+        for entry in result {
+            let data: serde_json::Result<OldMan> = serde_json::from_str(&entry);
+            let data = data.unwrap();
+            computed.push(data);
+        };
+        assert_eq!(computed, vec![expected.clone()]);
+
+        // Reading documents again
+        let result = surfer.read_string(&name, query, None, None);
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        assert!(result.is_some());
+        let result = result.unwrap();
+        let mut computed = Vec::new();
+        for entry in result {
+            let data: serde_json::Result<OldMan> = serde_json::from_str(&entry);
+            let data = data.unwrap();
+            computed.push(data);
+        };
+        assert_eq!(computed, vec![expected.clone()]);
+
+        let _ = remove_dir_all(&index_path);
+    }
+
+    #[test]
+    fn validate_as_rust_structs() {
+        let name = random_string(None);
+        let home = "tmp".to_string();
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let title = "The Old Man and the Sea".to_string();
+        let body = "He was an old man who fished alone in a skiff in the Gulf Stream and he had gone eighty-four days now without taking a fish.".to_string();
+        let old_man_doc = OldMan {
+            title,
+            body,
+        };
+
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home.as_str());
+        builder.add_struct(name.to_string(), &old_man_doc);
+        let surfer = Surfer::new(builder);
+
+        let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
+        let query = "sea whale";
+
+        let result = surfer.read_structs::<OldMan>("non-existent", query, None, None);
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        assert!(result.is_none());
+
+        let result = surfer.read_structs::<OldMan>(&name, query, None, None).unwrap().unwrap();
+        for computed in result {
+            assert_eq!(computed, old_man_doc);
+        };
+        assert!(path.exists());
+
+        // Reading documents again
+
+        let result = surfer.read_structs::<OldMan>(&name, query, None, None).unwrap().unwrap();
+        for computed in result {
+            assert_eq!(computed, old_man_doc);
+        };
+
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_delete_by_query() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+
+        let title = "The Old Man and the Sea".to_string();
+        let body = "He was an old man who fished alone in a skiff in the Gulf Stream and he had gone eighty-four days now without taking a fish.".to_string();
+        let old_man_doc = OldMan {
+            title,
+            body,
+        };
+        let title = "Moby Dick".to_string();
+        let body = "Call me Ishmael.".to_string();
+        let moby_dick_doc = OldMan {
+            title,
+            body,
+        };
+
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
+        let _ = surfer.insert_struct(&name, &moby_dick_doc).unwrap();
+
+        let query = "sea ishmael";
+        let result = surfer.read_structs::<OldMan>(&name, query, None, None).unwrap().unwrap();
+        assert_eq!(result.len(), 2);
+
+        let result = surfer.delete_by_query("non-existent", "sea");
+        assert!(result.is_ok());
+
+        let result = surfer.delete_by_query(&name, "sea");
+        assert!(result.is_ok());
+
+        let result = surfer.read_structs::<OldMan>(&name, query, None, None).unwrap().unwrap();
+        assert_eq!(result, vec![moby_dick_doc]);
+
+        let result = surfer.delete_by_query(&name, "+call +ishmael");
+        assert!(result.is_err());
+        let result = surfer.read_structs::<OldMan>(&name, query, None, None).unwrap().unwrap();
+        assert_eq!(result.len(), 1);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_delete_by_query_with_primary_key() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Product {
+            id: u64,
+            name: String,
+            price: u64,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let product = |id: u64, title: &str, price: u64| Product {
+            id,
+            name: title.to_string(),
+            price,
+        };
+        let products = vec![
+            product(1, "blue widget", 10),
+            product(2, "red widget", 20),
+            product(3, "blue gadget", 30),
+            product(4, "red gadget", 40),
+            product(5, "green gizmo", 50),
+        ];
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &products[0]);
+        builder.set_primary_key(&name, "id");
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_structs(&name, &products).unwrap();
+
+        let remaining = |surfer: &Surfer| {
+            let mut computed: Vec<u64> = surfer.read_structs::<Product>(&name, "widget gadget gizmo", Some(10), None)
+                .unwrap().unwrap()
+                .into_iter()
+                .map(|product| product.id)
+                .collect();
+            computed.sort();
+            computed
+        };
+
+        surfer.delete_by_query(&name, "+name:blue +name:widget").unwrap();
+        assert_eq!(remaining(&surfer), vec![2, 3, 4, 5]);
+
+        surfer.delete_by_query(&name, "name:gadget -name:red").unwrap();
+        assert_eq!(remaining(&surfer), vec![2, 4, 5]);
+
+        surfer.delete_by_query(&name, "price:[40 TO 50}").unwrap();
+        assert_eq!(remaining(&surfer), vec![2, 5]);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_delete_by_term() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Book {
+            id: u64,
+            title: String,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let first = Book {
+            id: 1,
+            title: "The Old Man and the Sea".to_string(),
+        };
+        let second = Book {
+            id: 2,
+            title: "The Sea Wolf".to_string(),
+        };
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &first);
+
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_structs(&name, &vec![first.clone(), second.clone()]).unwrap();
+
+        let query = "title:sea";
+        let result = surfer.read_structs::<Book>(&name, query, None, None).unwrap().unwrap();
+        assert_eq!(result.len(), 2);
+
+        let result = surfer.delete_by_term(&name, "missing", &1u64);
+        assert!(result.is_err());
+
+        let result = surfer.delete_by_term(&name, "id", &1u64);
+        assert!(result.is_ok());
+
+        let result = surfer.read_structs::<Book>(&name, query, None, None).unwrap().unwrap();
+        assert_eq!(result, vec![second]);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_read_structs_errors() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        let surfer = Surfer::new(builder);
+
+        let docs = "{\"title\": \"The Sea Wolf\"}\n{\"title\": \"The Old Man and the Sea\", \"body\": \"Santiago\"}\n";
+        let _ = surfer.insert_ndjson(&name, docs.as_bytes()).unwrap();
+
+        let computed = surfer.read_structs::<OldMan>(&name, "title:sea", None, None);
+        let computed = format!("{:?}", computed.unwrap_err());
+        assert!(computed.contains("The Sea Wolf"));
+        assert!(computed.contains("missing field `body`"));
+
+        let mut options = SearchOptions::default();
+        options.set_lenient(true);
+        let computed = surfer.read_structs_with_options::<OldMan>(&name, "title:sea", &options).unwrap().unwrap();
+        let expected = vec![OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: "Santiago".to_string(),
+        }];
+        assert_eq!(computed, expected);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_get_by_id() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Book {
+            id: u64,
+            title: String,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let first = Book {
+            id: 1,
+            title: "The Old Man and the Sea".to_string(),
+        };
+        let second = Book {
+            id: 2,
+            title: "The Sea Wolf".to_string(),
+        };
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &first);
+
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_structs(&name, &vec![first.clone(), second.clone()]).unwrap();
+
+        let computed = surfer.get_by_id::<Book, _>(&name, "id", &2u64).unwrap();
+        assert_eq!(computed, Some(second));
+
+        let computed = surfer.get_by_id::<Book, _>(&name, "id", &3u64).unwrap();
+        assert_eq!(computed, None);
+
+        let computed = surfer.get_by_id::<Book, _>(&name, "missing", &1u64);
+        assert!(computed.is_err());
+
+        let computed = surfer.get_by_id::<Book, _>("missing", "id", &1u64).unwrap();
+        assert_eq!(computed, None);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_read_result() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+
+        let surfer = Surfer::new(builder);
+        let payload: Vec<OldMan> = (0..5).map(|i| OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: format!("Chapter {}", i),
+        }).collect();
+        let _ = surfer.insert_structs(&name, &payload).unwrap();
+
+        let options = SearchOptions::new(Some(2), 1, None);
+        let computed = surfer.read_result::<OldMan>(&name, "title:sea", &options).unwrap().unwrap();
+        assert_eq!(computed.total(), 5);
+        assert_eq!(computed.hits().len(), 2);
+
+        let options = SearchOptions::new(Some(0), 0, None);
+        let computed = surfer.read_result::<OldMan>(&name, "title:sea", &options).unwrap().unwrap();
+        assert_eq!(computed.total(), 5);
+        assert!(computed.hits().is_empty());
+
+        let computed = surfer.read_result::<OldMan>(&name, "title:wolf", &SearchOptions::default()).unwrap().unwrap();
+        assert_eq!(computed.total(), 0);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_read_page() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+
+        let surfer = Surfer::new(builder);
+        let payload: Vec<OldMan> = (0..5).map(|i| OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: format!("Chapter {}", i),
+        }).collect();
+        let _ = surfer.insert_structs(&name, &payload).unwrap();
+
+        let mut computed = Vec::new();
+        let mut after: Option<String> = None;
+        let mut pages = 0;
+        loop {
+            let page = surfer.read_page::<OldMan>(&name, "title:sea", 2, after.as_deref()).unwrap().unwrap();
+            pages += 1;
+            after = page.after().map(|after| after.to_string());
+            computed.extend(page.into_hits().into_iter().map(|hit| hit.into_doc()));
+            if after.is_none() {
+                break;
+            };
+        };
+        assert_eq!(pages, 3);
+        assert_eq!(computed, payload);
+
+        let computed = surfer.read_page::<OldMan>(&name, "title:sea", 2, Some("nonsense"));
+        assert!(computed.is_err());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_scan() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+
+        let surfer = Surfer::new(builder);
+        let payload: Vec<OldMan> = (0..5).map(|i| OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: format!("Chapter {}", i),
+        }).collect();
+        let _ = surfer.insert_structs(&name, &payload).unwrap();
+
+        let mut scan = surfer.scan::<OldMan>(&name, "title:sea").unwrap().unwrap();
+        scan.set_batch_size(2);
+        let computed: Vec<OldMan> = scan.map(|doc| doc.unwrap()).collect();
+        assert_eq!(computed, payload);
+
+        let computed = surfer.scan::<OldMan>(&name, "title:wolf").unwrap().unwrap().count();
+        assert_eq!(computed, 0);
+
+        let computed = surfer.scan::<OldMan>("missing", "title:sea").unwrap();
+        assert!(computed.is_none());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_read_all() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+
+        let surfer = Surfer::new(builder);
+        for i in 0..3 {
+            let payload: Vec<OldMan> = (0..3).map(|j| OldMan {
+                title: format!("Part {}", i),
+                body: format!("Chapter {}", i * 3 + j),
+            }).collect();
+            let _ = surfer.insert_structs(&name, &payload).unwrap();
+        };
+        surfer.delete_by_query(&name, "title:2").unwrap();
+
+        let mut scan = surfer.read_all::<OldMan>(&name).unwrap().unwrap();
+        scan.set_batch_size(2);
+        let mut computed: Vec<String> = scan.map(|doc| doc.unwrap().body).collect();
+        computed.sort();
+        let expected: Vec<String> = (0..6).map(|i| format!("Chapter {}", i)).collect();
+        assert_eq!(computed, expected);
+
+        let computed = surfer.read_all::<OldMan>("missing").unwrap();
+        assert!(computed.is_none());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_rollback() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.set_auto_commit(&name, false);
+        builder.add_struct(name.clone(), &data);
+        let surfer = Surfer::new(builder);
+
+        let committed = OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: "Gulf Stream".to_string(),
+        };
+        let pending = OldMan {
+            title: "The Sea Wolf".to_string(),
+            body: "Pacific".to_string(),
+        };
+        surfer.insert_struct(&name, &committed).unwrap();
+        surfer.commit(&name).unwrap();
+        surfer.insert_struct(&name, &pending).unwrap();
+        surfer.rollback(&name).unwrap();
+        surfer.commit(&name).unwrap();
+
+        let computed = surfer.read_structs::<OldMan>(&name, "title:sea", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![committed]);
+        surfer.insert_struct(&name, &pending).unwrap();
+        surfer.commit(&name).unwrap();
+        assert_eq!(surfer.count(&name, "title:sea").unwrap(), Some(2));
+        assert!(surfer.rollback("missing").is_ok());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_limits() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        builder.set_default_limit(&name, 3);
+        builder.set_max_limit(&name, 5);
+        let surfer = Surfer::new(builder);
+        assert_eq!(surfer.default_limit(&name), 3);
+        assert_eq!(surfer.max_limit(&name), Some(5));
+        assert_eq!(surfer.default_limit("missing"), 10);
+        assert_eq!(surfer.max_limit("missing"), None);
+
+        let payload: Vec<OldMan> = (0..8).map(|i| OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: format!("Chapter {}", i),
+        }).collect();
+        let _ = surfer.insert_structs(&name, &payload).unwrap();
+
+        let computed = surfer.read_structs::<OldMan>(&name, "sea", None, None).unwrap().unwrap();
+        assert_eq!(computed.len(), 3);
+        let computed = surfer.read_structs::<OldMan>(&name, "sea", Some(4), None).unwrap().unwrap();
+        assert_eq!(computed.len(), 4);
+        let computed = surfer.read_structs::<OldMan>(&name, "sea", Some(100), None).unwrap().unwrap();
+        assert_eq!(computed.len(), 5);
+        let computed = surfer.read_page::<OldMan>(&name, "sea", 100, None).unwrap().unwrap();
+        assert_eq!(computed.hits().len(), 5);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_insert_json() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        let surfer = Surfer::new(builder);
+
+        let value = serde_json::json!({"title": "The Old Man and the Sea", "body": "Gulf Stream"});
+        surfer.insert_value(&name, &value).unwrap();
+        surfer.insert_json(&name, r#"{"title": "The Sea Wolf", "body": "Pacific"}"#).unwrap();
+        let computed = surfer.read_values(&name, "title:sea", None, None).unwrap().unwrap();
+        assert_eq!(computed.len(), 2);
+        assert!(computed.contains(&value));
+
+        let computed = surfer.insert_json(&name, r#"{"title": "Moby Dick", "pages": 635}"#).unwrap_err();
+        assert!(computed.reason().contains("pages"));
+        assert!(surfer.insert_json(&name, r#"{"title": 42}"#).is_err());
+        assert!(surfer.insert_json(&name, r#"{"title": "#).is_err());
+        assert!(surfer.insert_value(&name, &serde_json::json!(["title"])).is_err());
+        assert_eq!(surfer.count(&name, "title:sea").unwrap(), Some(2));
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_insert_iter() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+        struct Pages {
+            title: String,
+            pages: u64,
+        }
+
+        let data = Pages {
+            title: "".to_string(),
+            pages: 0,
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        builder.set_auto_commit(&name, false);
+        let surfer = Surfer::new(builder);
+
+        let items = (0..25).map(|i| match i {
+            7 => serde_json::json!({"title": "sea", "pages": "many"}),
+            _ => serde_json::json!({"title": "sea", "pages": i}),
+        });
+        let mut reported = Vec::new();
+        let report = {
+            let mut options = IngestOptions::new();
+            options.set_commit_docs(10);
+            options.on_progress(|progress| reported.push((progress.consumed(), progress.indexed(), progress.errors())));
+            surfer.insert_iter(&name, items, options).unwrap().unwrap()
+        };
+        assert_eq!(report.inserted(), 24);
+        assert_eq!(report.errors().len(), 1);
+        assert_eq!(report.errors()[0].line(), 8);
+        assert_eq!(reported, vec![(11, 10, 1), (21, 20, 1), (25, 24, 1)]);
+        assert_eq!(surfer.count(&name, "title:sea").unwrap(), Some(24));
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_ingester() {
+        use std::thread;
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        let surfer = Surfer::new(builder);
+        assert!(surfer.ingester("unknown", 8).unwrap().is_none());
+
+        let ingester = surfer.ingester(&name, 8).unwrap().unwrap();
+        let sent = thread::scope(|scope| {
+            let ingester = &ingester;
+            let workers: Vec<_> = (0..4).map(|i| scope.spawn(move || {
+                for j in 0..25 {
+                    let data = OldMan {
+                        title: format!("sea {} {}", i, j),
+                        body: "Gulf Stream".to_string(),
+                    };
+                    ingester.send(&data).unwrap();
+                };
+                25
+            })).collect();
+            workers.into_iter().map(|worker| worker.join().unwrap()).sum::<usize>()
+        });
+        assert!(ingester.send(&serde_json::json!({"pages": 1})).is_err());
+        assert_eq!(ingester.close().unwrap(), sent);
+        assert_eq!(surfer.count(&name, "title:sea").unwrap(), Some(100));
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_commit_policy() {
+        let name = random_string(None);
+        let interval = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let interval_path = format!("{}/{}", home, interval);
+        assert!(!Path::new(&index_path).exists());
+        assert!(!Path::new(&interval_path).exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        builder.add_struct(interval.clone(), &data);
+        builder.set_commit_policy(&name, CommitPolicy::EveryN(3));
+        builder.set_commit_policy(&interval, CommitPolicy::Interval(Duration::from_millis(50)));
+        let surfer = Surfer::new(builder);
+        assert_eq!(surfer.commit_policy(&name), CommitPolicy::EveryN(3));
+        assert_eq!(surfer.commit_policy("non-existent"), CommitPolicy::EveryDoc);
+
+        let data = OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: "Gulf Stream".to_string(),
+        };
+        surfer.insert_struct(&name, &data).unwrap();
+        surfer.insert_struct(&name, &data).unwrap();
+        assert_eq!(surfer.count(&name, "title:sea").unwrap(), Some(0));
+        surfer.insert_struct(&name, &data).unwrap();
+        assert_eq!(surfer.count(&name, "title:sea").unwrap(), Some(3));
+
+        surfer.insert_struct(&name, &data).unwrap();
+        surfer.insert_struct(&name, &data).unwrap();
+        surfer.rollback(&name).unwrap();
+        surfer.insert_struct(&name, &data).unwrap();
+        surfer.insert_struct(&name, &data).unwrap();
+        assert_eq!(surfer.count(&name, "title:sea").unwrap(), Some(3));
+        surfer.insert_struct(&name, &data).unwrap();
+        assert_eq!(surfer.count(&name, "title:sea").unwrap(), Some(6));
+
+        surfer.insert_struct(&interval, &data).unwrap();
+        assert_eq!(surfer.count(&interval, "title:sea").unwrap(), Some(0));
+        let mut count = Some(0);
+        for _ in 0..100 {
+            count = surfer.count(&interval, "title:sea").unwrap();
+            if count == Some(1) {
+                break;
+            };
+            std::thread::sleep(Duration::from_millis(20));
+        };
+        assert_eq!(count, Some(1));
+
+        drop(surfer);
+        let _ = remove_dir_all(index_path);
+        let _ = remove_dir_all(interval_path);
+    }
+
+    #[test]
+    fn validate_deferred_durability() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        builder.set_durability(&name, Durability::Deferred);
+        builder.set_auto_commit(&name, false);
+        let surfer = Surfer::new(builder);
+        assert_eq!(surfer.durability(&name), Durability::Deferred);
+        assert_eq!(surfer.durability("non-existent"), Durability::Durable);
+
+        let data = OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: "Gulf Stream".to_string(),
+        };
+        for i in 0..5 {
+            surfer.insert_struct(&name, &data).unwrap();
+            assert_eq!(surfer.count(&name, "title:sea").unwrap(), Some(i));
+            surfer.commit(&name).unwrap();
+            assert_eq!(surfer.count(&name, "title:sea").unwrap(), Some(i + 1));
+        };
+        let computed = surfer.read_structs::<OldMan>(&name, "title:sea", None, None).unwrap().unwrap();
+        assert_eq!(computed.len(), 5);
+        drop(surfer);
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        let surfer = Surfer::new(builder);
+        assert_eq!(surfer.count(&name, "title:sea").unwrap(), Some(5));
+        drop(surfer);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_locked_index() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: "Gulf Stream".to_string(),
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        let holder = Surfer::new(builder.clone());
+        holder.insert_struct(&name, &data).unwrap();
+
+        let surfer = Surfer::new(builder.clone());
+        let computed = surfer.insert_struct(&name, &data).unwrap_err();
+        assert!(matches!(computed, IndexError::Locked { ref holder_hint } if holder_hint.contains(&index_path)));
+
+        builder.set_lock_timeout(Duration::from_secs(10));
+        let surfer = Surfer::new(builder);
+        let released = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            drop(holder);
+        });
+        surfer.insert_struct(&name, &data).unwrap();
+        released.join().unwrap();
+        assert_eq!(surfer.count(&name, "title:sea").unwrap(), Some(2));
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_raw_handles() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: "Gulf Stream".to_string(),
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        let surfer = Surfer::new(builder);
+        surfer.insert_struct(&name, &data).unwrap();
+        assert!(surfer.raw_index("non-existent").unwrap().is_none());
+        assert!(surfer.raw_searcher("non-existent").unwrap().is_none());
+
+        let index = surfer.raw_index(&name).unwrap().unwrap();
+        let title = index.schema().get_field("title").unwrap();
+        let computed = {
+            let searcher = surfer.raw_searcher(&name).unwrap().unwrap();
+            let query = TermQuery::new(Term::from_field_text(title, "sea"), IndexRecordOption::Basic);
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(computed, 1);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_read_documents() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Title {
+            title: String,
+        }
+
+        impl FromDocument for Title {
+            fn from_document(document: &StoredDocument) -> Result<Self, IndexError> {
+                let title = document.get("title").and_then(|value| value.text()).unwrap_or_default();
+                Ok(Title {
+                    title: title.to_uppercase(),
+                })
+            }
+        }
+
+        let data = OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: "Gulf Stream".to_string(),
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        let surfer = Surfer::new(builder);
+        surfer.insert_struct(&name, &data).unwrap();
+
+        let computed = surfer.read_documents::<OldMan>(&name, "title:sea", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![data.clone()]);
+        let computed = surfer.read_documents::<Title>(&name, "title:sea", None, None).unwrap().unwrap();
+        assert_eq!(computed[0].title, "THE OLD MAN AND THE SEA");
+        assert!(surfer.read_documents::<Title>("non-existent", "title:sea", None, None).unwrap().is_none());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_json_pointers() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+        struct Flat {
+            id: String,
+            city: String,
+        }
+
+        let data = Flat {
+            id: "".to_string(),
+            city: "".to_string(),
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        builder.set_primary_key(&name, "id");
+        builder.add_json_pointer(&name, "/id", "id");
+        builder.add_json_pointer(&name, "/user/address/city", "city");
+        let surfer = Surfer::new(builder);
+
+        let nested = serde_json::json!({"id": "1", "user": {"name": "Jon", "address": {"city": "Winterfell"}}});
+        surfer.insert_value(&name, &nested).unwrap();
+        let computed = surfer.read_structs::<Flat>(&name, "city:winterfell", None, None).unwrap().unwrap();
+        let expected = Flat {
+            id: "1".to_string(),
+            city: "Winterfell".to_string(),
+        };
+        assert_eq!(computed, vec![expected]);
+
+        let moved = serde_json::json!({"id": "1", "user": {"address": {"city": "Castle Black"}}});
+        surfer.upsert_struct(&name, &moved).unwrap();
+        assert_eq!(surfer.count(&name, "city:winterfell").unwrap(), Some(0));
+        assert_eq!(surfer.count(&name, "city:castle").unwrap(), Some(1));
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_object_seqs() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+        struct LineItem {
+            sku: String,
+            quantity: u64,
+        }
+
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+        struct Order {
+            id: String,
+            items: Vec<LineItem>,
+        }
+
+        let item = |sku: &str, quantity: u64| LineItem {
+            sku: sku.to_string(),
+            quantity,
+        };
+        let sample = Order {
+            id: "".to_string(),
+            items: vec![item("", 0)],
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &sample);
+        let surfer = Surfer::new(builder);
+        assert!(surfer.schema(&name).unwrap().get_field("items.sku").is_some());
+
+        let first = Order {
+            id: "1".to_string(),
+            items: vec![item("apple", 3), item("pear", 1)],
+        };
+        let second = Order {
+            id: "2".to_string(),
+            items: vec![item("pear", 2)],
+        };
+        surfer.insert_structs(&name, &vec![first.clone(), second]).unwrap();
+        assert_eq!(surfer.count(&name, "pear").unwrap(), Some(2));
+        assert_eq!(surfer.count(&name, "apple").unwrap(), Some(1));
+
+        let computed = surfer.read_structs::<Order>(&name, "apple", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![first]);
+        let mut options = SearchOptions::default();
+        options.select(&["items.sku"]);
+        let computed = surfer.read_values_with_options(&name, "apple", &options).unwrap().unwrap();
+        assert_eq!(computed[0], serde_json::json!({"items": [{"sku": "apple"}, {"sku": "pear"}]}));
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_sparse_object_seqs() {
+        let name = random_string(None);
+        let stored = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let stored_path = format!("{}/{}", home, stored);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+        struct LineItem {
+            sku: String,
+            quantity: Option<u64>,
+        }
+
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+        struct Order {
+            id: String,
+            items: Vec<LineItem>,
+        }
+
+        let item = |sku: &str, quantity: Option<u64>| LineItem {
+            sku: sku.to_string(),
+            quantity,
+        };
+        let sample = Order {
+            id: "".to_string(),
+            items: vec![item("", Some(0))],
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &sample);
+        builder.add_struct(stored.clone(), &sample);
+        builder.set_store_source(&stored, true);
+        let surfer = Surfer::new(builder);
+
+        let order = Order {
+            id: "1".to_string(),
+            items: vec![item("apple", Some(3)), item("pear", None), item("plum", Some(5))],
+        };
+        let result = surfer.insert_struct(&name, &order);
+        assert!(matches!(result, Err(IndexError::SchemaMismatch { .. })));
+        assert_eq!(surfer.count(&name, "apple").unwrap(), Some(0));
+
+        surfer.insert_struct(&stored, &order).unwrap();
+        let computed = surfer.read_structs::<Order>(&stored, "pear", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![order]);
+
+        let _ = remove_dir_all(index_path);
+        let _ = remove_dir_all(stored_path);
+    }
+
+    #[test]
+    fn validate_null_policy() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        builder.set_null_policy(&name, NullPolicy::Default);
+        builder.set_null_default(&name, "body", serde_json::json!("Unknown"));
+        let surfer = Surfer::new(builder);
+
+        surfer.insert_value(&name, &serde_json::json!({"title": "The Old Man and the Sea", "body": null})).unwrap();
+        let computed = surfer.read_structs::<OldMan>(&name, "title:sea", None, None).unwrap().unwrap();
+        assert_eq!(computed[0].body, "Unknown");
+        drop(surfer);
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        builder.set_null_policy(&name, NullPolicy::Reject);
+        let surfer = Surfer::new(builder);
+        let computed = surfer.insert_value(&name, &serde_json::json!({"title": "The Old Man and the Sea"})).unwrap_err();
+        assert!(computed.reason().contains("Field: body is null or missing"));
+        assert_eq!(surfer.count(&name, "title:sea").unwrap(), Some(1));
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_coercion() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+        struct Book {
+            title: String,
+            pages: u64,
+            rating: u64,
+        }
+
+        let data = Book {
+            title: "".to_string(),
+            pages: 0,
+            rating: 0,
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        builder.set_coercion(&name, true);
+        builder.set_field_coercion(&name, "rating", false);
+        let surfer = Surfer::new(builder);
+
+        surfer.insert_value(&name, &serde_json::json!({"title": 1984, "pages": "328", "rating": 5})).unwrap();
+        let computed = surfer.read_structs::<Book>(&name, "title:1984", None, None).unwrap().unwrap();
+        let expected = Book {
+            title: "1984".to_string(),
+            pages: 328,
+            rating: 5,
+        };
+        assert_eq!(computed, vec![expected]);
+        assert!(surfer.insert_value(&name, &serde_json::json!({"title": "Animal Farm", "pages": 112, "rating": "4"})).is_err());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_add_json_schema() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let json_schema = r#"{
+            "type": "object",
+            "properties": {
+                "title": {"type": "string"},
+                "pages": {"type": "integer", "minimum": 0},
+                "available": {"type": "boolean"},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "author": {"type": "object", "properties": {"name": {"type": "string"}}}
+            }
+        }"#;
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_json_schema(&name, json_schema).unwrap();
+        assert!(builder.add_json_schema("invalid", r#"{"type": "string"}"#).is_err());
+        let surfer = Surfer::new(builder);
+
+        let data = serde_json::json!({"title": "The Old Man and the Sea", "pages": 127, "available": true, "tags": ["sea"], "author": {"name": "Hemingway"}});
+        surfer.insert_value(&name, &data).unwrap();
+        let computed = surfer.read_values(&name, "title:sea", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![data]);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_schema_as_json_schema() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        builder.set_store_source(&name, true);
+        let surfer = Surfer::new(builder);
+
+        let computed = surfer.schema_as_json_schema(&name).unwrap().unwrap();
+        let expected = serde_json::json!({"type": "string", "x-tokenizer": "default", "x-stored": true, "x-indexed": true});
+        assert_eq!(computed["properties"]["title"], expected);
+        assert!(computed["properties"].get(SOURCE).is_none());
+        assert!(surfer.schema_as_json_schema("non-existent").unwrap().is_none());
+
+        let mut builder = SurferBuilder::default();
+        builder.add_json_schema("copy", &computed.to_string()).unwrap();
+        assert!(builder.schemas.get("copy").unwrap().get_field("body").is_some());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_insert_ndjson() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.set_auto_commit(&name, false);
+        builder.add_struct(name.clone(), &data);
+        let surfer = Surfer::new(builder);
+
+        let ndjson = r#"{"title": "The Old Man and the Sea", "body": "Gulf Stream"}
+
+{"title": "The Sea Wolf", "body": "Pacific"
+{"title": "Moby Dick", "pages": 635}
+{"title": "Sea of Poppies", "body": "Ibis"}
+"#;
+        let report = surfer.insert_ndjson(&name, ndjson.as_bytes()).unwrap().unwrap();
+        assert_eq!(report.inserted(), 2);
+        let lines: Vec<usize> = report.errors().iter().map(|error| error.line()).collect();
+        assert_eq!(lines, vec![3, 4]);
+
+        let computed = surfer.count(&name, "title:sea").unwrap();
+        assert_eq!(computed, Some(2));
+
+        let computed = surfer.insert_ndjson("missing", ndjson.as_bytes()).unwrap();
+        assert!(computed.is_none());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn validate_insert_csv() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Book {
+            title: String,
+            pages: u64,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = Book {
+            title: "".to_string(),
+            pages: 0,
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        let surfer = Surfer::new(builder);
+
+        let csv = "pages,title\n120,The Old Man and the Sea\nmany,Moby Dick\n300,\"The Sea Wolf\"\n";
+        let report = surfer.insert_csv(&name, csv.as_bytes()).unwrap().unwrap();
+        assert_eq!(report.inserted(), 2);
+        assert_eq!(report.errors()[0].line(), 3);
+
+        let computed = surfer.read_structs::<Book>(&name, "title:wolf", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![Book { title: "The Sea Wolf".to_string(), pages: 300 }]);
+
+        let computed = surfer.insert_csv(&name, "author\nMelville\n".as_bytes());
+        assert!(computed.is_err());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_option_fields() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Listing {
+            title: String,
+            subtitle: Option<String>,
+            pages: Option<u64>,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let sample = Listing {
+            title: "".to_string(),
+            subtitle: Some("".to_string()),
+            pages: None,
+        };
+        let mut control = HashMap::new();
+        control.insert("pages".to_string(), Control::ControlIntOptions(IntOptions::default().set_indexed().set_stored()));
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct_with_control(name.clone(), &sample, control);
+        let surfer = Surfer::new(builder);
+
+        let payload = vec![
+            Listing {
+                title: "The Old Man and the Sea".to_string(),
+                subtitle: None,
+                pages: Some(127),
+            },
+            Listing {
+                title: "The Sea Wolf".to_string(),
+                subtitle: Some("A novel".to_string()),
+                pages: None,
+            },
+        ];
+        surfer.insert_structs(&name, &payload).unwrap();
+
+        let computed = surfer.read_structs::<Listing>(&name, "title:old", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![payload[0].clone()]);
+        let computed = surfer.read_structs::<Listing>(&name, "subtitle:novel", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![payload[1].clone()]);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_enum_fields() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        enum Genre {
+            Novel,
+            Poetry,
+        }
+
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        #[serde(tag = "type")]
+        enum Format {
+            Print { pages: u64 },
+        }
+
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Book {
+            title: String,
+            genre: Genre,
+            format: Format,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let sample = Book {
+            title: "".to_string(),
+            genre: Genre::Novel,
+            format: Format::Print { pages: 0 },
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &sample);
+        let surfer = Surfer::new(builder);
+
+        let payload = vec![
+            Book {
+                title: "The Old Man and the Sea".to_string(),
+                genre: Genre::Novel,
+                format: Format::Print { pages: 127 },
+            },
+            Book {
+                title: "Leaves of Grass".to_string(),
+                genre: Genre::Poetry,
+                format: Format::Print { pages: 95 },
+            },
+        ];
+        surfer.insert_structs(&name, &payload).unwrap();
+
+        let computed = surfer.read_structs::<Book>(&name, "genre:poetry", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![payload[1].clone()]);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_map_fields() {
+        use tantivy::schema::{TEXT, STORED};
+
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Product {
+            name: String,
+            attributes: HashMap<String, String>,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let sample = Product {
+            name: "".to_string(),
+            attributes: HashMap::new(),
+        };
+        let mut control = HashMap::new();
+        control.insert("attributes".to_string(), Control::ControlMapOptions(TEXT | STORED));
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct_with_control(name.clone(), &sample, control);
+        let surfer = Surfer::new(builder);
+
+        let product = |name: &str, attributes: &[(&str, &str)]| Product {
+            name: name.to_string(),
+            attributes: attributes.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        };
+        let payload = vec![
+            product("Shirt", &[("color", "red"), ("size", "L")]),
+            product("Mug", &[("material", "stoneware")]),
+        ];
+        surfer.insert_structs(&name, &payload).unwrap();
+
+        let computed = surfer.read_structs::<Product>(&name, "attributes:red", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![payload[0].clone()]);
+        let computed = surfer.read_structs::<Product>(&name, "attributes:material", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![payload[1].clone()]);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_stopwords() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let sample = OldMan {
+            title: "".to_string(),
+            body: "".to_string(),
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &sample);
+        builder.set_stopwords(&name, Stopwords::English);
+        builder.set_field_stopwords(&name, "body", Stopwords::Custom(vec!["Gulf".to_string()]));
+        let surfer = Surfer::new(builder);
+        assert_eq!(surfer.stopwords(&name, "title"), Some(Stopwords::English));
+        assert_eq!(surfer.stopwords(&name, "body"), Some(Stopwords::Custom(vec!["Gulf".to_string()])));
+
+        let payload = vec![
+            OldMan {
+                title: "The Old Man and the Sea".to_string(),
+                body: "He was an old man who fished alone in a skiff in the Gulf Stream.".to_string(),
+            },
+        ];
+        surfer.insert_structs(&name, &payload).unwrap();
+
+        let computed = surfer.read_structs::<OldMan>(&name, "title:the", None, None).unwrap().unwrap();
+        assert!(computed.is_empty());
+        let computed = surfer.read_structs::<OldMan>(&name, "body:gulf", None, None).unwrap().unwrap();
+        assert!(computed.is_empty());
+        let computed = surfer.read_structs::<OldMan>(&name, "body:the", None, None).unwrap().unwrap();
+        assert_eq!(computed, payload);
+        let computed = surfer.read_structs::<OldMan>(&name, "title:sea", None, None).unwrap().unwrap();
+        assert_eq!(computed, payload);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_register_tokenizer() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Tagged {
+            title: String,
+            tag: String,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let sample = Tagged {
+            title: "".to_string(),
+            tag: "".to_string(),
+        };
+        let indexing = TextFieldIndexing::default()
+            .set_tokenizer("keyword")
+            .set_index_option(IndexRecordOption::Basic);
+        let options = TextOptions::default().set_indexing_options(indexing).set_stored();
+        let mut control = HashMap::new();
+        control.insert("tag".to_string(), Control::ControlTextOptions(options));
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.register_tokenizer("keyword", TextAnalyzer::from(RawTokenizer).filter(LowerCaser));
+        builder.add_struct_with_control(name.clone(), &sample, control);
+        let surfer = Surfer::new(builder.clone());
+
+        let payload = vec![
+            Tagged {
+                title: "The Old Man and the Sea".to_string(),
+                tag: "Deep Sea".to_string(),
+            },
+            Tagged {
+                title: "The Sea Wolf".to_string(),
+                tag: "Sea".to_string(),
+            },
+        ];
+        surfer.insert_structs(&name, &payload).unwrap();
+
+        let computed = surfer.read_structs::<Tagged>(&name, "tag:\"DEEP SEA\"", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![payload[0].clone()]);
+        let computed = surfer.read_structs::<Tagged>(&name, "tag:sea", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![payload[1].clone()]);
+        drop(surfer);
+
+        let surfer = Surfer::new(builder);
+        let computed = surfer.read_structs::<Tagged>(&name, "tag:\"deep sea\"", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![payload[0].clone()]);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_facets() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Product {
+            name: String,
+            category: String,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let sample = Product {
+            name: "".to_string(),
+            category: "".to_string(),
+        };
+        let mut control = HashMap::new();
+        control.insert("category".to_string(), Control::ControlFacet);
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct_with_control(name.clone(), &sample, control);
+        let surfer = Surfer::new(builder);
+
+        let product = |name: &str, category: &str| Product {
+            name: name.to_string(),
+            category: category.to_string(),
+        };
+        let payload = vec![
+            product("Pixel phone", "electronics/phones/android"),
+            product("Galaxy phone", "electronics/phones/android"),
+            product("iPhone phone", "electronics/phones/ios"),
+            product("Kindle", "electronics/readers"),
+            product("Dune", "books/scifi"),
+        ];
+        surfer.insert_structs(&name, &payload).unwrap();
+
+        let computed = surfer.facets_with_query(&name, &AllQuery, "category", "").unwrap().unwrap();
+        assert_eq!(computed, vec![FacetCount::new("electronics", 4), FacetCount::new("books", 1)]);
+        let computed = surfer.facets(&name, "name:phone", "category", "electronics").unwrap().unwrap();
+        assert_eq!(computed, vec![FacetCount::new("electronics/phones", 3)]);
+        let computed = surfer.facets(&name, "name:phone", "category", "electronics/phones").unwrap().unwrap();
+        assert_eq!(computed, vec![FacetCount::new("electronics/phones/android", 2), FacetCount::new("electronics/phones/ios", 1)]);
+        assert!(surfer.facets(&name, "name:phone", "name", "").is_err());
+        assert!(surfer.facets("missing", "name:phone", "category", "").unwrap().is_none());
+
+        let mut query = BoolQuery::default();
+        query.must(Clause::term("category", &"electronics/phones/ios"));
+        let computed = surfer.read_structs_with_bool::<Product>(&name, &query, &SearchOptions::default()).unwrap().unwrap();
+        assert_eq!(computed, vec![payload[2].clone()]);
+        let mut query = BoolQuery::default();
+        query.must(Clause::term("category", &"books"));
+        let computed = surfer.read_structs_with_bool::<Product>(&name, &query, &SearchOptions::default()).unwrap().unwrap();
+        assert_eq!(computed, vec![payload[4].clone()]);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_within_radius() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Cafe {
+            name: String,
+            location: GeoPoint,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let cafe = |name: &str, lat: f64, lon: f64| Cafe {
+            name: name.to_string(),
+            location: GeoPoint::new(lat, lon),
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &cafe("", 0.0, 0.0));
+        let surfer = Surfer::new(builder);
+
+        let payload = vec![
+            cafe("Louvre", 48.8606, 2.3376),
+            cafe("Notre Dame", 48.8530, 2.3499),
+            cafe("Versailles", 48.8049, 2.1204),
+            cafe("London", 51.5074, -0.1278),
+        ];
+        surfer.insert_structs(&name, &payload).unwrap();
+
+        let computed = surfer.within_radius::<Cafe>(&name, "location", 48.8566, 2.3522, 5.0).unwrap().unwrap();
+        assert_eq!(computed, vec![payload[1].clone(), payload[0].clone()]);
+        let computed = surfer.within_radius_with_distance::<Cafe>(&name, "location", 48.8566, 2.3522, 20.0).unwrap().unwrap();
+        assert_eq!(computed.len(), 3);
+        assert_eq!(computed[2].doc(), &payload[2]);
+        assert!(computed[2].distance_km() > 15.0 && computed[2].distance_km() < 20.0);
+        let computed = surfer.within_radius::<Cafe>(&name, "location", 0.0, 0.0, 100.0).unwrap().unwrap();
+        assert!(computed.is_empty());
+        assert!(surfer.within_radius::<Cafe>(&name, "name", 0.0, 0.0, 100.0).is_err());
+        assert!(surfer.within_radius::<Cafe>("missing", "location", 0.0, 0.0, 100.0).unwrap().is_none());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_ngram_fields() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Product {
+            name: String,
+            sku: String,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let sample = Product {
+            name: "".to_string(),
+            sku: "".to_string(),
+        };
+        let mut control = HashMap::new();
+        control.insert("sku".to_string(), Control::ControlTextOptions(ngram_options(2, 4, false)));
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct_with_control(name.clone(), &sample, control);
+        let surfer = Surfer::new(builder);
+
+        let payload = vec![
+            Product {
+                name: "Shoe".to_string(),
+                sku: "SHOE-XL-2041".to_string(),
+            },
+            Product {
+                name: "Sock".to_string(),
+                sku: "SOCK-M-1999".to_string(),
+            },
+        ];
+        surfer.insert_structs(&name, &payload).unwrap();
+
+        let computed = surfer.read_structs::<Product>(&name, "sku:xl-20", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![payload[0].clone()]);
+        let computed = surfer.read_structs::<Product>(&name, "sku:99", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![payload[1].clone()]);
+        drop(surfer);
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_existing(&name).unwrap();
+        let surfer = Surfer::new(builder);
+        let computed = surfer.read_structs::<Product>(&name, "sku:xl", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![payload[0].clone()]);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_export() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        let surfer = Surfer::new(builder);
+
+        let payload: Vec<OldMan> = (0..3).map(|i| OldMan {
+            title: format!("Book {}", i),
+            body: "The Old Man and the Sea".to_string(),
+        }).collect();
+        let _ = surfer.insert_structs(&name, &payload).unwrap();
+        let _ = surfer.delete_by_query(&name, "title:1").unwrap();
+
+        let mut exported = Vec::new();
+        let computed = surfer.export(&name, &mut exported).unwrap();
+        assert_eq!(computed, Some(2));
+        let computed: Vec<OldMan> = String::from_utf8(exported).unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(computed, vec![payload[0].clone(), payload[2].clone()]);
+
+        let computed = surfer.export("missing", Vec::new()).unwrap();
+        assert!(computed.is_none());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_update_by_query() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Product {
+            id: u64,
+            name: String,
+            price: u64,
+            in_stock: bool,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let product = |id: u64, title: &str| Product {
+            id,
+            name: title.to_string(),
+            price: 10,
+            in_stock: true,
+        };
+        let products = vec![product(1, "blue widget"), product(2, "red widget"), product(3, "gadget")];
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &products[0]);
+
+        {
+            let surfer = Surfer::new(builder.clone());
+            let result = surfer.update_by_query(&name, "widget", &serde_json::json!({"price": 5}));
+            assert!(result.is_err());
+        }
+
+        builder.set_primary_key(&name, "id");
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_structs(&name, &products).unwrap();
+        let patch = serde_json::json!({"price": 5, "in_stock": false});
+        assert_eq!(surfer.update_by_query(&name, "name:widget", &patch).unwrap(), Some(2));
+        assert!(surfer.update_by_query(&name, "widget", &serde_json::json!([1])).is_err());
+
+        let mut computed = surfer.read_structs::<Product>(&name, "widget gadget", None, None).unwrap().unwrap();
+        computed.sort_by_key(|product| product.id);
+        let mut expected = products;
+        for product in expected.iter_mut().take(2) {
+            product.price = 5;
+            product.in_stock = false;
+        };
+        assert_eq!(computed, expected);
+        assert_eq!(surfer.update_by_query(&name, "name:gizmo", &patch).unwrap(), Some(0));
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_upsert_struct() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Book {
+            id: u64,
+            title: String,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let original = Book {
+            id: 1,
+            title: "The Old Man and the Sea".to_string(),
+        };
+        let revised = Book {
+            id: 1,
+            title: "The Old Man and the Deep Sea".to_string(),
+        };
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &original);
+
+        {
+            let surfer = Surfer::new(builder.clone());
+            let result = surfer.upsert_struct(&name, &original);
+            assert!(result.is_err());
+        }
+
+        builder.set_primary_key(&name, "id");
+        let surfer = Surfer::new(builder);
+        let _ = surfer.upsert_struct(&name, &original).unwrap();
+
+        let query = "title:sea";
+        let result = surfer.read_structs::<Book>(&name, query, None, None).unwrap().unwrap();
+        assert_eq!(result, vec![original]);
+
+        let _ = surfer.upsert_struct(&name, &revised).unwrap();
+        let result = surfer.read_structs::<Book>(&name, query, None, None).unwrap().unwrap();
+        assert_eq!(result, vec![revised]);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_explicit_commit() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        builder.set_auto_commit(&name, false);
+
+        let title = "The Old Man and the Sea".to_string();
+        let body = "He was an old man who fished alone in a skiff in the Gulf Stream and he had gone eighty-four days now without taking a fish.".to_string();
+        let old_man_doc = OldMan {
+            title,
+            body,
+        };
+
+        let surfer = Surfer::new(builder);
+        assert!(!surfer.is_auto_commit(&name));
+        assert!(surfer.is_auto_commit("non-existent"));
+
+        let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
+        let _ = surfer.insert_structs(&name, &vec![old_man_doc.clone()]).unwrap();
+
+        let query = "sea whale";
+        let result = surfer.read_structs::<OldMan>(&name, query, None, None).unwrap().unwrap();
+        assert!(result.is_empty());
+
+        let _ = surfer.commit(&name).unwrap();
+        let result = surfer.read_structs::<OldMan>(&name, query, None, None).unwrap().unwrap();
+        assert_eq!(result, vec![old_man_doc.clone(), old_man_doc]);
+
+        let result = surfer.commit("non-existent");
+        assert!(result.is_ok());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_nested_structs() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Address {
+            city: String,
+            zip: u64,
+        }
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Person {
+            name: String,
+            address: Address,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let person = Person {
+            name: "John Doe".to_string(),
+            address: Address {
+                city: "Paris".to_string(),
+                zip: 75001,
+            },
+        };
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &person);
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_struct(&name, &person).unwrap();
+
+        let query = "name:john";
+        let result = surfer.read_structs::<Person>(&name, query, None, None).unwrap().unwrap();
+        assert_eq!(result, vec![person]);
+
+        let _ = surfer.delete_by_term(&name, "address.zip", &75001u64).unwrap();
+        let result = surfer.read_structs::<Person>(&name, query, None, None).unwrap().unwrap();
+        assert!(result.is_empty());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_text_seq_structs() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Article {
+            title: String,
+            tags: Vec<String>,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let novel = Article {
+            title: "The Old Man and the Sea".to_string(),
+            tags: vec!["novel".to_string(), "fishing".to_string()],
+        };
+        let story = Article {
+            title: "The Sea Wolf".to_string(),
+            tags: vec!["story".to_string()],
+        };
+        let essay = Article {
+            title: "The Open Sea".to_string(),
+            tags: Vec::new(),
+        };
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &novel);
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_structs(&name, &vec![novel.clone(), story.clone(), essay.clone()]).unwrap();
+
+        let result = surfer.read_structs::<Article>(&name, "fishing", None, None).unwrap().unwrap();
+        assert_eq!(result, vec![novel]);
+        let result = surfer.read_structs::<Article>(&name, "story", None, None).unwrap().unwrap();
+        assert_eq!(result, vec![story]);
+        let result = surfer.read_structs::<Article>(&name, "open", None, None).unwrap().unwrap();
+        assert_eq!(result, vec![essay]);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_bool_structs() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Flag {
+            name: String,
+            active: bool,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let beta = Flag {
+            name: "beta".to_string(),
+            active: true,
+        };
+        let alpha = Flag {
+            name: "alpha".to_string(),
+            active: false,
+        };
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &beta);
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_structs(&name, &vec![beta.clone(), alpha.clone()]).unwrap();
+
+        let result = surfer.read_structs::<Flag>(&name, "active:1", None, None).unwrap().unwrap();
+        assert_eq!(result, vec![beta]);
+        let result = surfer.read_structs::<Flag>(&name, "active:0", None, None).unwrap().unwrap();
+        assert_eq!(result, vec![alpha.clone()]);
+
+        let _ = surfer.delete_by_term(&name, "active", &true).unwrap();
+        let result = surfer.read_structs::<Flag>(&name, "name:beta name:alpha", None, None).unwrap().unwrap();
+        assert_eq!(result, vec![alpha]);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_date_structs() {
+        use chrono::NaiveDateTime;
+
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Event {
+            title: String,
+            created: DateTime<Utc>,
+            seen: NaiveDateTime,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let launch = Event {
+            title: "launch".to_string(),
+            created: "2020-01-01T10:00:00Z".parse().unwrap(),
+            seen: "2020-01-01T10:00:00".parse().unwrap(),
+        };
+        let landing = Event {
+            title: "landing".to_string(),
+            created: "2020-06-01T10:00:00+02:00".parse().unwrap(),
+            seen: "2020-06-01T10:00:00".parse().unwrap(),
+        };
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &launch);
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_structs(&name, &vec![launch.clone(), landing.clone()]).unwrap();
+
+        let result = surfer.read_structs::<Event>(&name, "title:launch", None, None).unwrap().unwrap();
+        assert_eq!(result, vec![launch.clone()]);
+
+        let from = "2020-05-01T00:00:00Z".parse().unwrap();
+        let to: DateTime<Utc> = "2020-07-01T00:00:00Z".parse().unwrap();
+        let result = surfer.read_structs_in_date_range::<Event>(&name, "created", from..to, None, None).unwrap().unwrap();
+        assert_eq!(result, vec![landing.clone()]);
+
+        let from: DateTime<Utc> = "2019-01-01T00:00:00Z".parse().unwrap();
+        let result = surfer.read_structs_in_date_range::<Event>(&name, "seen", from..to, None, None).unwrap().unwrap();
+        assert_eq!(result.len(), 2);
+
+        let result = surfer.read_structs_in_date_range::<Event>(&name, "title", from..to, None, None);
+        assert!(result.is_err());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_between() {
+        use chrono::Timelike;
+
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Log {
+            message: String,
+            created: DateTime<Utc>,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let now = Utc::now().with_nanosecond(0).unwrap();
+        let log = |message: &str, days: i64| Log {
+            message: message.to_string(),
+            created: now - chrono::Duration::days(days),
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &log("", 0));
+        let surfer = Surfer::new(builder);
+        let payload = vec![log("today", 0), log("last week", 5), log("last month", 20)];
+        surfer.insert_structs(&name, &payload).unwrap();
+
+        let computed = surfer.between::<Log>(&name, "created", "now-7d", "now+1m").unwrap().unwrap();
+        assert_eq!(computed.len(), 2);
+        assert!(!computed.contains(&payload[2]));
+        let computed = surfer.between::<Log>(&name, "created", "now-30d", "now-7d").unwrap().unwrap();
+        assert_eq!(computed, vec![payload[2].clone()]);
+        assert!(surfer.between::<Log>(&name, "created", "yesterday", "now").is_err());
+        assert!(surfer.between::<Log>(&name, "message", "now-7d", "now").is_err());
+        assert!(surfer.between::<Log>("missing", "created", "now-7d", "now").unwrap().is_none());
+
+        let mut query = BoolQuery::default();
+        query.must(Clause::date_between("created", "now-1d", "now+1m").unwrap());
+        let computed = surfer.read_structs_with_bool::<Log>(&name, &query, &SearchOptions::default()).unwrap().unwrap();
+        assert_eq!(computed, vec![payload[0].clone()]);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_ttl() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Session {
+            token: String,
+        }
+
+        let name = random_string(None);
+        let other = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let other_path = format!("{}/{}", home, other);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let session = Session { token: "abc".to_string() };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &session);
+        builder.add_struct(other.clone(), &session);
+        builder.set_ttl(&name, Duration::from_secs(1));
+        let surfer = Surfer::new(builder);
+        assert_eq!(surfer.ttl(&name), Some(Duration::from_secs(1)));
+        assert_eq!(surfer.ttl(&other), None);
+        assert!(surfer.schema(&name).unwrap().get_field(EXPIRES_AT).is_some());
+
+        surfer.insert_struct(&name, &session).unwrap();
+        let computed = surfer.read_structs_with_query::<Session>(&name, &AllQuery, &SearchOptions::default()).unwrap().unwrap();
+        assert_eq!(computed, vec![session.clone()]);
+        assert_eq!(surfer.purge_expired(&name).unwrap(), Some(0));
+
+        block_thread(2);
+        assert_eq!(surfer.purge_expired(&name).unwrap(), Some(1));
+        assert!(surfer.read_structs_with_query::<Session>(&name, &AllQuery, &SearchOptions::default()).unwrap().unwrap().is_empty());
+        assert!(surfer.purge_expired(&other).is_err());
+        assert!(surfer.purge_expired("missing").unwrap().is_none());
+
+        let _ = remove_dir_all(index_path);
+        let _ = remove_dir_all(other_path);
+    }
+
+    #[test]
+    fn validate_alias() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Product {
+            title: String,
+        }
+
+        let v1 = random_string(None);
+        let v2 = random_string(None);
+        let alias = random_string(None);
+        let home = "tmp";
+        let v1_path = format!("{}/{}", home, v1);
+        let v2_path = format!("{}/{}", home, v2);
+        assert!(!Path::new(&v1_path).exists());
+        assert!(!Path::new(&v2_path).exists());
+
+        let product = |title: &str| Product { title: title.to_string() };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(v1.clone(), &product(""));
+        builder.add_struct(v2.clone(), &product(""));
+        let surfer = Surfer::new(builder);
+        surfer.insert_struct(&v1, &product("lamp")).unwrap();
+        surfer.insert_struct(&v2, &product("desk")).unwrap();
+
+        assert!(surfer.read_structs::<Product>(&alias, "*", None, None).unwrap().is_none());
+        surfer.set_alias(&alias, &v1).unwrap();
+        assert_eq!(surfer.alias(&alias), Some(v1.clone()));
+        assert_eq!(surfer.read_structs::<Product>(&alias, "lamp", None, None).unwrap().unwrap(), vec![product("lamp")]);
+        surfer.insert_struct(&alias, &product("lamp shade")).unwrap();
+        assert_eq!(surfer.count(&v1, "lamp").unwrap(), Some(2));
+
+        surfer.set_alias(&alias, &v2).unwrap();
+        assert!(surfer.read_structs::<Product>(&alias, "lamp", None, None).unwrap().unwrap().is_empty());
+        assert_eq!(surfer.read_structs::<Product>(&alias, "desk", None, None).unwrap().unwrap(), vec![product("desk")]);
+        assert_eq!(surfer.which_index(&alias), surfer.which_index(&v2));
+
+        assert!(surfer.set_alias(&alias, "missing").is_err());
+        assert!(surfer.set_alias(&v1, &v2).is_err());
+        surfer.drop_index(&v2).unwrap();
+        assert!(surfer.alias(&alias).is_none());
+        surfer.set_alias(&alias, &v1).unwrap();
+        surfer.remove_alias(&alias).unwrap();
+        assert!(surfer.count(&alias, "lamp").unwrap().is_none());
+
+        let _ = remove_dir_all(v1_path);
+        let _ = remove_dir_all(v2_path);
+    }
+
+    #[test]
+    fn validate_read_multi() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Article {
+            title: String,
+        }
+
+        let prefix = random_string(None);
+        let first = format!("{}-first", prefix);
+        let second = format!("{}-second", prefix);
+        let home = "tmp";
+        let first_path = format!("{}/{}", home, first);
+        let second_path = format!("{}/{}", home, second);
+        assert!(!Path::new(&first_path).exists());
+        assert!(!Path::new(&second_path).exists());
+
+        let article = |title: &str| Article { title: title.to_string() };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(first.clone(), &article(""));
+        builder.add_struct(second.clone(), &article(""));
+        let surfer = Surfer::new(builder);
+        surfer.insert_structs(&first, &vec![article("rust rust rust"), article("python")]).unwrap();
+        surfer.insert_structs(&second, &vec![article("rust in action guide"), article("go")]).unwrap();
+
+        let options = SearchOptions::default();
+        let computed = surfer.read_multi::<Article>(&[&first, &second, "missing"], "rust", &options).unwrap();
+        assert_eq!(computed.len(), 2);
+        assert_eq!(computed[0].index(), first);
+        assert_eq!(computed[0].doc(), &article("rust rust rust"));
+        assert_eq!(computed[1].index(), second);
+        assert!(computed[0].score() >= computed[1].score());
+
+        let computed = surfer.read_multi_prefix::<Article>(&prefix, "rust OR go OR python", &options).unwrap();
+        assert_eq!(computed.len(), 4);
+        let mut options = SearchOptions::default();
+        options.set_limit(1);
+        options.set_offset(1);
+        let computed = surfer.read_multi_prefix::<Article>(&prefix, "rust", &options).unwrap();
+        assert_eq!(computed.len(), 1);
+        assert_eq!(computed[0].index(), second);
+        assert!(surfer.read_multi_prefix::<Article>("missing", "rust", &options).unwrap().is_empty());
+
+        let _ = remove_dir_all(first_path);
+        let _ = remove_dir_all(second_path);
+    }
+
+    #[test]
+    fn validate_read_multi_pages() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Article {
+            title: String,
+        }
+
+        let prefix = random_string(None);
+        let first = format!("{}-first", prefix);
+        let second = format!("{}-second", prefix);
+        let home = "tmp";
+        let first_path = format!("{}/{}", home, first);
+        let second_path = format!("{}/{}", home, second);
+        assert!(!Path::new(&first_path).exists());
+        assert!(!Path::new(&second_path).exists());
+
+        let article = |repeat: usize| Article { title: format!("{}guide", "rust ".repeat(repeat)) };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(first.clone(), &article(0));
+        builder.add_struct(second.clone(), &article(0));
+        builder.set_max_limit(&first, 2);
+        builder.set_max_limit(&second, 2);
+        let surfer = Surfer::new(builder);
+        surfer.insert_structs(&first, &vec![article(1), article(3), article(5)]).unwrap();
+        surfer.insert_structs(&second, &vec![article(2), article(4), article(6)]).unwrap();
+
+        let mut computed = Vec::new();
+        let mut options = SearchOptions::default();
+        options.set_limit(2);
+        for page in 0..4 {
+            options.set_offset(page * 2);
+            let hits = surfer.read_multi::<Article>(&[&first, &second], "rust", &options).unwrap();
+            assert!(hits.len() <= 2);
+            computed.extend(hits.into_iter().map(|hit| hit.doc().clone()));
+        };
+        assert_eq!(computed.len(), 6);
+        let mut titles: Vec<String> = computed.into_iter().map(|article| article.title).collect();
+        titles.sort();
+        titles.dedup();
+        assert_eq!(titles.len(), 6);
+
+        let _ = remove_dir_all(first_path);
+        let _ = remove_dir_all(second_path);
+    }
+
+    #[test]
+    fn validate_read_multi_limits() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Article {
+            title: String,
+        }
+
+        let prefix = random_string(None);
+        let first = format!("{}-first", prefix);
+        let second = format!("{}-second", prefix);
+        let home = "tmp";
+        let first_path = format!("{}/{}", home, first);
+        let second_path = format!("{}/{}", home, second);
+        assert!(!Path::new(&first_path).exists());
+        assert!(!Path::new(&second_path).exists());
+
+        let articles: Vec<Article> = (0..5).map(|i| Article { title: format!("rust {}", i) }).collect();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(first.clone(), &articles[0]);
+        builder.add_struct(second.clone(), &articles[0]);
+        builder.set_default_limit(&first, 3);
+        builder.set_max_limit(&second, 4);
+        let surfer = Surfer::new(builder);
+        surfer.insert_structs(&first, &articles).unwrap();
+        surfer.insert_structs(&second, &articles).unwrap();
+
+        let mut options = SearchOptions::default();
+        let computed = surfer.read_multi::<Article>(&[&first, &second], "rust", &options).unwrap();
+        assert_eq!(computed.len(), 3);
+
+        options.set_limit(10);
+        let computed = surfer.read_multi::<Article>(&[&first, &second], "rust", &options).unwrap();
+        assert_eq!(computed.len(), 4);
+
+        let computed = surfer.read_multi::<Article>(&[&first], "rust", &options).unwrap();
+        assert_eq!(computed.len(), 5);
+
+        let _ = remove_dir_all(first_path);
+        let _ = remove_dir_all(second_path);
+    }
+
+    #[test]
+    fn validate_collapse() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Variant {
+            product_id: String,
+            color: String,
+            stock: u64,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let variant = |product_id: &str, color: &str, stock: u64| Variant {
+            product_id: product_id.to_string(),
+            color: color.to_string(),
+            stock,
+        };
+        let mut control = HashMap::new();
+        control.insert("stock".to_string(), Control::ControlFastOptions(Cardinality::SingleValue));
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct_with_control(name.clone(), &variant("", "", 0), control);
+        let surfer = Surfer::new(builder);
+        let payload = vec![
+            variant("shirt", "red blue", 1),
+            variant("shirt", "red", 1),
+            variant("shoe", "red", 2),
+            variant("hat", "blue", 3),
+        ];
+        surfer.insert_structs(&name, &payload).unwrap();
+
+        let mut options = SearchOptions::default();
+        options.set_collapse("product_id");
+        let computed = surfer.read_result::<Variant>(&name, "color:red", &options).unwrap().unwrap();
+        assert_eq!(computed.total(), 3);
+        let computed: Vec<Variant> = computed.into_hits().into_iter().map(Hit::into_doc).collect();
+        assert_eq!(computed.len(), 2);
+        assert!(computed.contains(&payload[1]));
+        assert!(computed.contains(&payload[2]));
+
+        let mut options = SearchOptions::default();
+        options.set_collapse("stock");
+        let computed = surfer.read_structs_with_options::<Variant>(&name, "color:red OR color:blue", &options).unwrap().unwrap();
+        assert_eq!(computed.len(), 3);
+
+        options.set_order_by("stock", Order::Asc);
+        assert!(surfer.read_structs_with_options::<Variant>(&name, "color:red", &options).is_err());
+        let mut options = SearchOptions::default();
+        options.set_collapse("missing");
+        assert!(surfer.read_structs_with_options::<Variant>(&name, "color:red", &options).is_err());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_distinct() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Car {
+            make: String,
+            electric: bool,
+            year: i64,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let car = |make: &str, electric: bool, year: i64| Car {
+            make: make.to_string(),
+            electric,
+            year,
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &car("", false, 0));
+        let surfer = Surfer::new(builder);
+        let payload = vec![car("Tesla", true, 2020), car("Ford", false, 2019), car("Ford", true, 2021)];
+        surfer.insert_structs(&name, &payload).unwrap();
+
+        let computed = surfer.distinct(&name, "electric", None).unwrap().unwrap();
+        assert_eq!(computed, vec![DistinctValue::new(JsonValue::Bool(true), 2), DistinctValue::new(JsonValue::Bool(false), 1)]);
+        let computed = surfer.distinct(&name, "make", Some(1)).unwrap().unwrap();
+        assert_eq!(computed, vec![DistinctValue::new(JsonValue::from("Ford"), 2)]);
+        let query = TermQuery::new(Term::from_field_i64(surfer.schema(&name).unwrap().get_field("year").unwrap(), 2020), IndexRecordOption::Basic);
+        let computed = surfer.distinct_with_query(&name, &query, "make", None).unwrap().unwrap();
+        assert_eq!(computed, vec![DistinctValue::new(JsonValue::from("Tesla"), 1)]);
+        assert!(surfer.distinct(&name, "missing", None).is_err());
+        assert!(surfer.distinct("missing", "make", None).unwrap().is_none());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_explain() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Book {
+            isbn: String,
+            title: String,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let book = |isbn: &str, title: &str| Book {
+            isbn: isbn.to_string(),
+            title: title.to_string(),
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &book("", ""));
+        let surfer = Surfer::new(builder);
+        surfer.insert_structs(&name, &vec![book("1", "Dune messiah"), book("2", "Foundation")]).unwrap();
+
+        let computed = surfer.explain(&name, "title:dune", "isbn", &"1").unwrap().unwrap();
+        assert!(computed.matched());
+        assert!(computed.score() > 0.0);
+        let computed = surfer.explain(&name, "title:dune", "isbn", &"2").unwrap().unwrap();
+        assert!(!computed.matched());
+        assert!(surfer.explain(&name, "title:dune", "isbn", &"3").unwrap().is_none());
+        assert!(surfer.explain("missing", "title:dune", "isbn", &"1").unwrap().is_none());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_select() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Address {
+            city: String,
+            zip: String,
+        }
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct User {
+            id: u64,
+            name: String,
+            bio: String,
+            address: Address,
+        }
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Summary {
+            id: u64,
+            name: String,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let user = User {
+            id: 1,
+            name: "Jane".to_string(),
+            bio: "Writes a lot of text about search".to_string(),
+            address: Address {
+                city: "Paris".to_string(),
+                zip: "75001".to_string(),
+            },
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &user);
+        let surfer = Surfer::new(builder);
+        surfer.insert_struct(&name, &user).unwrap();
+
+        let mut options = SearchOptions::default();
+        options.select(&["id", "name"]);
+        let computed = surfer.read_structs_with_options::<Summary>(&name, "jane", &options).unwrap().unwrap();
+        assert_eq!(computed, vec![Summary { id: 1, name: "Jane".to_string() }]);
+        let computed = surfer.read_string_with_options(&name, "jane", &options).unwrap().unwrap();
+        assert_eq!(computed, vec![r#"{"id":1,"name":"Jane"}"#.to_string()]);
+
+        options.select(&["address"]);
+        let computed = surfer.read_string_with_options(&name, "jane", &options).unwrap().unwrap();
+        assert_eq!(computed, vec![r#"{"address":{"city":"Paris","zip":"75001"}}"#.to_string()]);
+        options.select(&["addr"]);
+        let computed = surfer.read_string_with_options(&name, "jane", &options).unwrap().unwrap();
+        assert_eq!(computed, vec!["{}".to_string()]);
+
+        let computed = surfer.read_structs_with_options::<User>(&name, "jane", &SearchOptions::default()).unwrap().unwrap();
+        assert_eq!(computed, vec![user]);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_index_exists_on_disk() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Note {
+            text: String,
+        }
+
+        let name = random_string(None);
+        let memory = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+        assert!(!SurferBuilder::probe(home, &name));
+        assert!(!path.exists());
+
+        let note = Note { text: "".to_string() };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &note);
+        builder.add_struct(memory.clone(), &note);
+        builder.set_in_memory(&memory, true);
+        let surfer = Surfer::new(builder);
+        assert!(surfer.index_exists_on_disk(&name));
+        assert!(SurferBuilder::probe(home, &name));
+        assert!(!surfer.index_exists_on_disk(&memory));
+        assert!(!surfer.index_exists_on_disk("missing"));
+        assert!(!Path::new(home).join("missing").exists());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_lazy() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Event {
+            kind: String,
+        }
+
+        let used = random_string(None);
+        let unused = random_string(None);
+        let home = "tmp";
+        let used_path = format!("{}/{}", home, used);
+        let unused_path = format!("{}/{}", home, unused);
+        assert!(!Path::new(&used_path).exists());
+        assert!(!Path::new(&unused_path).exists());
+
+        let event = Event { kind: "click".to_string() };
+        let builder = || {
+            let mut builder = SurferBuilder::default();
+            builder.set_home(home);
+            builder.set_lazy(true);
+            builder.add_struct(used.clone(), &event);
+            builder.add_struct(unused.clone(), &event);
+            builder
+        };
+        let surfer = Surfer::new(builder());
+        assert!(!Path::new(&used_path).exists());
+        assert!(!Path::new(&unused_path).exists());
+        assert!(surfer.is_pending(&used));
+        assert!(surfer.schema(&used).is_some());
+        assert!(surfer.read_structs::<Event>(&used, "click", None, None).unwrap().is_none());
+
+        surfer.insert_struct(&used, &event).unwrap();
+        assert!(!surfer.is_pending(&used));
+        assert!(Path::new(&used_path).exists());
+        assert!(!Path::new(&unused_path).exists());
+        assert_eq!(surfer.read_structs::<Event>(&used, "click", None, None).unwrap().unwrap(), vec![event.clone()]);
+        drop(surfer);
+
+        let surfer = Surfer::new(builder());
+        assert!(!surfer.is_pending(&used));
+        assert!(surfer.is_pending(&unused));
+        assert_eq!(surfer.read_structs::<Event>(&used, "click", None, None).unwrap().unwrap(), vec![event]);
+        surfer.drop_index(&unused).unwrap();
+        assert!(!surfer.is_pending(&unused));
+        assert!(!Path::new(&unused_path).exists());
+
+        let _ = remove_dir_all(used_path);
+    }
+
+    #[test]
+    fn validate_from_config() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Book {
+            isbn: String,
+            title: String,
+            pages: u64,
+            available: bool,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let config_path = format!("{}/{}.json", home, name);
+        assert!(!Path::new(&index_path).exists());
+        let _ = std::fs::create_dir_all(home);
+        let config = serde_json::json!({
+            "home": home,
+            "indexes": [{
+                "name": name,
+                "fields": [
+                    {"name": "isbn", "type": "string"},
+                    {"name": "title", "type": "text", "boost": 2.0},
+                    {"name": "pages", "type": "u64", "fast": true},
+                    {"name": "available", "type": "bool"}
+                ],
+                "primary_key": "isbn",
+                "ttl_secs": 3600
+            }]
+        });
+        std::fs::write(&config_path, config.to_string()).unwrap();
+
+        let surfer = Surfer::new(SurferBuilder::from_config(&config_path).unwrap());
+        assert!(Path::new(&index_path).exists());
+        assert_eq!(surfer.field_boost(&name, "title"), 2.0);
+        assert_eq!(surfer.ttl(&name), Some(Duration::from_secs(3600)));
+        let book = Book {
+            isbn: "978-0441013593".to_string(),
+            title: "Dune".to_string(),
+            pages: 412,
+            available: true,
+        };
+        surfer.insert_struct(&name, &book).unwrap();
+        assert_eq!(surfer.read_structs::<Book>(&name, "dune", None, None).unwrap().unwrap(), vec![book]);
+        assert!(SurferBuilder::from_config(format!("{}/missing.json", home)).is_err());
+
+        let _ = remove_dir_all(index_path);
+        let _ = std::fs::remove_file(config_path);
+    }
+
+    #[test]
+    fn validate_save_load() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Article {
+            slug: String,
+            body: String,
+            tags: Vec<String>,
+            published: bool,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let builder_path = format!("{}/{}.json", home, name);
+        assert!(!Path::new(&index_path).exists());
+        let _ = std::fs::create_dir_all(home);
+        let article = Article {
+            slug: "gulf".to_string(),
+            body: "The Gulf Stream".to_string(),
+            tags: vec!["sea".to_string(), "fishing".to_string()],
+            published: true,
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &article);
+        builder.set_primary_key(&name, "slug");
+        builder.set_stopwords(&name, Stopwords::English);
+        builder.set_ttl(&name, Duration::from_secs(60));
+        builder.save(&builder_path).unwrap();
+
+        let loaded = SurferBuilder::load(&builder_path).unwrap();
+        assert_eq!(serde_json::to_value(&loaded).unwrap(), serde_json::to_value(&builder).unwrap());
+        let surfer = Surfer::new(loaded);
+        assert!(Path::new(&index_path).exists());
+        assert_eq!(surfer.stopwords(&name, "body"), Some(Stopwords::English));
+        assert_eq!(surfer.ttl(&name), Some(Duration::from_secs(60)));
+        surfer.insert_struct(&name, &article).unwrap();
+        assert_eq!(surfer.read_structs::<Article>(&name, "fishing", None, None).unwrap().unwrap(), vec![article]);
+        assert!(SurferBuilder::load(format!("{}/missing.json", home)).is_err());
+
+        let _ = remove_dir_all(index_path);
+        let _ = std::fs::remove_file(builder_path);
+    }
+
+    #[test]
+    fn validate_multi_valued_jsonify() {
+        use tantivy::schema::{SchemaBuilder, INDEXED, STORED, TEXT};
+
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Review {
+            title: String,
+            ratings: Vec<u64>,
+            scores: Vec<f64>,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let mut schema_builder = SchemaBuilder::new();
+        schema_builder.add_text_field("title", TEXT | STORED);
+        schema_builder.add_u64_field("ratings", INDEXED | STORED);
+        schema_builder.add_f64_field("scores", STORED);
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_schema(name.clone(), schema_builder.build());
+        let surfer = Surfer::new(builder);
+
+        let review = Review {
+            title: "Dune".to_string(),
+            ratings: vec![3, 5, 4],
+            scores: vec![0.5, 0.75],
+        };
+        surfer.insert_struct(&name, &review).unwrap();
+        let computed = surfer.read_string(&name, "dune", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![r#"{"ratings":[3,5,4],"scores":[0.5,0.75],"title":"Dune"}"#.to_string()]);
+        let computed = surfer.read_structs::<Review>(&name, "ratings:5", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![review]);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_read_values() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Address {
+            city: String,
+        }
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct User {
+            id: u64,
+            name: String,
+            active: bool,
+            address: Address,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let user = User {
+            id: 1,
+            name: "Jane".to_string(),
+            active: true,
+            address: Address { city: "Paris".to_string() },
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &user);
+        let surfer = Surfer::new(builder);
+        surfer.insert_struct(&name, &user).unwrap();
+
+        let computed = surfer.read_values(&name, "jane", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![serde_json::json!({"id": 1, "name": "Jane", "active": true, "address": {"city": "Paris"}})]);
+        let mut options = SearchOptions::new(None, 0, None);
+        options.select(&["name"]);
+        let computed = surfer.read_values_with_options(&name, "paris", &options).unwrap().unwrap();
+        assert_eq!(computed, vec![serde_json::json!({"name": "Jane"})]);
+        assert!(surfer.read_values(&name, "berlin", None, None).unwrap().unwrap().is_empty());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_store_source() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Note {
+            title: String,
+            done: bool,
+            due: Option<String>,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let note = Note {
+            title: "Buy milk".to_string(),
+            done: false,
+            due: Some("tomorrow".to_string()),
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &note);
+        builder.set_store_source(&name, true);
+        let surfer = Surfer::new(builder);
+        assert!(surfer.is_storing_source(&name));
+        assert!(surfer.schema(&name).unwrap().get_field(SOURCE).is_some());
+
+        let pending = Note {
+            title: "Call mom".to_string(),
+            done: false,
+            due: None,
+        };
+        surfer.insert_structs(&name, &vec![note.clone(), pending.clone()]).unwrap();
+        let computed = surfer.read_structs::<Note>(&name, "milk", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![note]);
+        let computed = surfer.read_string(&name, "mom", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![r#"{"done":false,"due":null,"title":"Call mom"}"#.to_string()]);
+
+        let mut options = SearchOptions::new(None, 0, None);
+        options.select(&["title"]);
+        let computed = surfer.read_values_with_options(&name, "mom", &options).unwrap().unwrap();
+        assert_eq!(computed, vec![serde_json::json!({"title": "Call mom"})]);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_store_source_keeps_unknown_fields() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Note {
+            title: String,
+            pages: u64,
+        }
+
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct TaggedNote {
+            title: String,
+            tags: Vec<String>,
+            meta: HashMap<String, u64>,
+            pages: String,
+        }
+
+        let name = random_string(None);
+        let plain = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let plain_path = format!("{}/{}", home, plain);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let sample = Note { title: "".to_string(), pages: 0 };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &sample);
+        builder.add_struct(plain.clone(), &sample);
+        builder.set_store_source(&name, true);
+        builder.set_coercion(&name, true);
+        builder.set_coercion(&plain, true);
+        let surfer = Surfer::new(builder);
+
+        let mut meta = HashMap::new();
+        meta.insert("rating".to_string(), 5);
+        let note = TaggedNote {
+            title: "Buy milk".to_string(),
+            tags: vec!["errand".to_string(), "food".to_string()],
+            meta,
+            pages: "12".to_string(),
+        };
+        surfer.insert_struct(&name, &note).unwrap();
+        let computed = surfer.read_structs::<TaggedNote>(&name, "milk", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![note.clone()]);
+        assert!(surfer.schema(&name).unwrap().get_field("tags").is_none());
+
+        assert!(surfer.insert_struct(&plain, &note).is_err());
+
+        let _ = remove_dir_all(index_path);
+        let _ = remove_dir_all(plain_path);
+    }
+
+    #[test]
+    fn validate_catch_all() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Book {
+            title: String,
+            author: String,
+            year: u64,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let hobbit = Book {
+            title: "The Hobbit".to_string(),
+            author: "Tolkien".to_string(),
+            year: 1937,
+        };
+        let dune = Book {
+            title: "Dune".to_string(),
+            author: "Herbert".to_string(),
+            year: 1965,
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &hobbit);
+        builder.set_catch_all(&name, true);
+        builder.set_conjunction_by_default(&name, true);
+        let surfer = Surfer::new(builder);
+        assert!(surfer.is_catch_all(&name));
+        surfer.insert_structs(&name, &vec![hobbit.clone(), dune.clone()]).unwrap();
+
+        let computed = surfer.read_structs::<Book>(&name, "hobbit tolkien", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![hobbit.clone()]);
+        let computed = surfer.read_structs::<Book>(&name, "herbert 1965", None, None).unwrap().unwrap();
+        assert!(computed.is_empty());
+        let computed = surfer.read_structs::<Book>(&name, "herbert", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![dune]);
+        let computed = surfer.read_structs::<Book>(&name, "title:tolkien", None, None).unwrap().unwrap();
+        assert!(computed.is_empty());
+        let computed = surfer.read_string(&name, "tolkien", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![r#"{"author":"Tolkien","title":"The Hobbit","year":1937}"#.to_string()]);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_synonyms() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Product {
+            name: String,
+        }
+
+        let product = |name: &str| Product { name: name.to_string() };
+        let products = vec![product("Television set"), product("TV stand"), product("Laptop bag")];
+        let synonyms = Synonyms::parse("tv, television\nlaptop => notebook computer").unwrap();
+        for at_index in &[false, true] {
+            let name = random_string(None);
+            let home = "tmp";
+            let index_path = format!("{}/{}", home, name);
+            let path = Path::new(&index_path);
+            assert!(!path.exists());
+
+            let mut builder = SurferBuilder::default();
+            builder.set_home(home);
+            builder.add_struct(name.clone(), &products[0]);
+            builder.set_synonyms(&name, synonyms.clone(), *at_index);
+            let surfer = Surfer::new(builder);
+            assert_eq!(surfer.synonyms(&name), Some(synonyms.clone()));
+            surfer.insert_structs(&name, &products).unwrap();
+
+            let computed = surfer.read_structs::<Product>(&name, "tv", None, None).unwrap().unwrap();
+            assert_eq!(computed.len(), 2);
+            let computed = surfer.read_structs::<Product>(&name, "name:television", None, None).unwrap().unwrap();
+            assert_eq!(computed.len(), 2);
+            let computed = surfer.read_structs::<Product>(&name, "laptop", None, None).unwrap().unwrap();
+            assert_eq!(computed, vec![product("Laptop bag")]);
+
+            let field = surfer.schema(&name).unwrap().get_field("name").unwrap();
+            let query = TermQuery::new(Term::from_field_text(field, "television"), IndexRecordOption::Basic);
+            let options = SearchOptions::new(None, 0, None);
+            let computed = surfer.read_structs_with_query::<Product>(&name, &query, &options).unwrap().unwrap();
+            assert_eq!(computed.len(), if *at_index { 2 } else { 1 });
+
+            let _ = remove_dir_all(index_path);
+        };
+    }
+
+    #[test]
+    fn validate_add_struct_with_control() {
+        use tantivy::schema::{TEXT, STORED};
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut control = HashMap::new();
+        control.insert("title".to_string(), Control::ControlTextOptions(STORED.into()));
+        control.insert("body".to_string(), Control::ControlTextOptions(TEXT));
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct_with_control(name.clone(), &data, control);
+
+        let title = "The Old Man and the Sea".to_string();
+        let body = "He was an old man who fished alone in a skiff in the Gulf Stream and he had gone eighty-four days now without taking a fish.".to_string();
+        let old_man_doc = OldMan {
+            title,
+            body,
+        };
+
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
+
+        let result = surfer.read_string(&name, "sea", None, None).unwrap().unwrap();
+        assert!(result.is_empty());
+
+        let result = surfer.read_string(&name, "skiff", None, None).unwrap().unwrap();
+        let expected = vec!["{\"title\":\"The Old Man and the Sea\"}".to_string()];
+        assert_eq!(result, expected);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_read_structs_with_offset() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Book {
+            id: u64,
+            title: String,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let books: Vec<Book> = (0..25).map(|id| Book {
+            id,
+            title: "The Old Man and the Sea".to_string(),
+        }).collect();
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &books[0]);
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_structs(&name, &books).unwrap();
+
+        let query = "title:sea";
+        let mut options = SearchOptions::default();
+        options.set_limit(10);
+
+        let mut computed = Vec::new();
+        for page in 0..3 {
+            options.set_offset(page * 10);
+            let result = surfer.read_structs_with_options::<Book>(&name, query, &options).unwrap().unwrap();
+            computed.extend(result.into_iter().map(|b| b.id));
+        };
+        computed.sort();
+        let expected: Vec<u64> = (0..25).collect();
+        assert_eq!(computed, expected);
+
+        options.set_offset(30);
+        let result = surfer.read_structs_with_options::<Book>(&name, query, &options).unwrap().unwrap();
+        assert!(result.is_empty());
+
+        let result = surfer.read_string_with_options("non-existent", query, &options).unwrap();
+        assert!(result.is_none());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_read_structs_order_by() {
+        use tantivy::schema::Cardinality;
+
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Book {
+            id: u64,
+            price: f64,
+            title: String,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let books = vec![
+            Book { id: 2, price: 9.5, title: "The Sea Wolf".to_string() },
+            Book { id: 1, price: 12.0, title: "The Old Man and the Sea".to_string() },
+            Book { id: 3, price: 4.25, title: "The Open Sea".to_string() },
+        ];
+
+        let fast = IntOptions::default()
+            .set_indexed()
+            .set_stored()
+            .set_fast(Cardinality::SingleValue);
+        let mut control = HashMap::new();
+        control.insert("id".to_string(), Control::ControlIntOptions(fast.clone()));
+        control.insert("price".to_string(), Control::ControlIntOptions(fast));
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct_with_control(name.clone(), &books[0], control);
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_structs(&name, &books).unwrap();
+
+        let query = "title:sea";
+        let mut options = SearchOptions::default();
+
+        options.set_order_by("id", Order::Desc);
+        let result = surfer.read_structs_with_options::<Book>(&name, query, &options).unwrap().unwrap();
+        let computed: Vec<u64> = result.iter().map(|b| b.id).collect();
+        assert_eq!(computed, vec![3, 2, 1]);
+
+        options.set_order_by("id", Order::Asc);
+        let result = surfer.read_structs_with_options::<Book>(&name, query, &options).unwrap().unwrap();
+        let computed: Vec<u64> = result.iter().map(|b| b.id).collect();
+        assert_eq!(computed, vec![1, 2, 3]);
+
+        options.set_order_by("price", Order::Asc);
+        options.set_limit(2);
+        let result = surfer.read_structs_with_options::<Book>(&name, query, &options).unwrap().unwrap();
+        let computed: Vec<u64> = result.iter().map(|b| b.id).collect();
+        assert_eq!(computed, vec![3, 2]);
+
+        options.set_order_by("title", Order::Asc);
+        let result = surfer.read_structs_with_options::<Book>(&name, query, &options);
+        assert!(result.is_err());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_read_structs_order_by_date() {
+        use tantivy::schema::Cardinality;
+
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Book {
+            id: u64,
+            published: DateTime<Utc>,
+            rating: u64,
+            title: String,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let book = |id: u64, published: &str, title: &str| Book {
+            id,
+            published: published.parse().unwrap(),
+            rating: id,
+            title: title.to_string(),
+        };
+        let books = vec![
+            book(1, "1904-01-01T00:00:00Z", "The Sea Wolf"),
+            book(2, "1952-09-01T00:00:00Z", "The Old Man and the Sea"),
+            book(3, "1931-06-15T00:00:00Z", "The Open Sea"),
+        ];
+
+        let date = IntOptions::default()
+            .set_indexed()
+            .set_stored()
+            .set_fast(Cardinality::SingleValue);
+        let multi = IntOptions::default()
+            .set_indexed()
+            .set_stored()
+            .set_fast(Cardinality::MultiValues);
+        let mut control = HashMap::new();
+        control.insert("published".to_string(), Control::ControlDateOptions(date));
+        control.insert("rating".to_string(), Control::ControlIntOptions(multi));
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct_with_control(name.clone(), &books[0], control);
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_structs(&name, &books).unwrap();
+
+        let query = "title:sea";
+        let mut options = SearchOptions::default();
+
+        options.set_order_by("published", Order::Desc);
+        let result = surfer.read_structs_with_options::<Book>(&name, query, &options).unwrap().unwrap();
+        let computed: Vec<u64> = result.iter().map(|b| b.id).collect();
+        assert_eq!(computed, vec![2, 3, 1]);
+
+        options.set_order_by("published", Order::Asc);
+        let result = surfer.read_structs_with_options::<Book>(&name, query, &options).unwrap().unwrap();
+        let computed: Vec<u64> = result.iter().map(|b| b.id).collect();
+        assert_eq!(computed, vec![1, 3, 2]);
+
+        options.set_order_by("rating", Order::Asc);
+        let result = surfer.read_structs_with_options::<Book>(&name, query, &options);
+        assert!(result.is_err());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_fast_control() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Book {
+            title: String,
+            pages: u64,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let books = vec![
+            Book { title: "The Sea Wolf".to_string(), pages: 300 },
+            Book { title: "The Old Man and the Sea".to_string(), pages: 127 },
+        ];
+        let mut control = HashMap::new();
+        control.insert("pages".to_string(), Control::ControlFastOptions(Cardinality::SingleValue));
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct_with_control(name.clone(), &books[0], control);
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_structs(&name, &books).unwrap();
+
+        let mut options = SearchOptions::default();
+        options.set_order_by("pages", Order::Asc);
+        let computed = surfer.read_structs_with_options::<Book>(&name, "title:sea", &options).unwrap().unwrap();
+        assert_eq!(computed, vec![books[1].clone(), books[0].clone()]);
+        let computed = surfer.read_structs::<Book>(&name, "pages:127", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![books[1].clone()]);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_field_boost() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        builder.set_field_boost(&name, "title", 3.0);
+        let surfer = Surfer::new(builder);
+        assert_eq!(surfer.field_boost(&name, "title"), 3.0);
+        assert_eq!(surfer.field_boost(&name, "body"), 1.0);
+
+        let in_title = OldMan {
+            title: "Sea".to_string(),
+            body: "Wolf".to_string(),
+        };
+        let in_body = OldMan {
+            title: "Wolf".to_string(),
+            body: "Sea".to_string(),
+        };
+        let _ = surfer.insert_structs(&name, &vec![in_body.clone(), in_title.clone()]).unwrap();
+        let computed = surfer.read_structs::<OldMan>(&name, "sea", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![in_title.clone(), in_body.clone()]);
+
+        let mut options = SearchOptions::default();
+        options.set_field_boost("body", 10.0);
+        let computed = surfer.read_structs_with_options::<OldMan>(&name, "sea", &options).unwrap().unwrap();
+        assert_eq!(computed, vec![in_body.clone(), in_title.clone()]);
+
+        let mut options = SearchOptions::default();
+        options.set_field_boost("author", 2.0);
+        assert!(surfer.read_structs_with_options::<OldMan>(&name, "sea", &options).is_err());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_conjunction_by_default() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        let disjunction = builder.clone();
+        builder.set_conjunction_by_default(&name, true);
+        let surfer = Surfer::new(builder);
+        assert!(surfer.is_conjunction_by_default(&name));
+
+        let payload = vec![
+            OldMan {
+                title: "The Old Man and the Sea".to_string(),
+                body: "Santiago".to_string(),
+            },
+            OldMan {
+                title: "The Sea Wolf".to_string(),
+                body: "Wolf Larsen".to_string(),
+            },
+        ];
+        let _ = surfer.insert_structs(&name, &payload).unwrap();
+        let computed = surfer.read_structs::<OldMan>(&name, "title:sea title:old", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![payload[0].clone()]);
+        drop(surfer);
+
+        let surfer = Surfer::new(disjunction);
+        assert!(!surfer.is_conjunction_by_default(&name));
+        let computed = surfer.read_structs::<OldMan>(&name, "title:sea title:old", None, None).unwrap().unwrap();
+        assert_eq!(computed.len(), 2);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_default_search_fields() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Book {
+            title: String,
+            body: String,
+            pages: u64,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let book = Book {
+            title: "The Old Man and the Sea".to_string(),
+            body: "Santiago is an old fisherman".to_string(),
+            pages: 127,
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &book);
+        let all = builder.clone();
+        builder.set_default_search_fields(&name, &["title"]);
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_struct(&name, &book).unwrap();
+
+        let computed = surfer.read_structs::<Book>(&name, "sea", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![book.clone()]);
+        let computed = surfer.read_structs::<Book>(&name, "santiago", None, None).unwrap().unwrap();
+        assert!(computed.is_empty());
+        drop(surfer);
+
+        let surfer = Surfer::new(all.clone());
+        let computed = surfer.read_structs::<Book>(&name, "santiago", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![book.clone()]);
+        drop(surfer);
+
+        let mut builder = all;
+        builder.set_default_search_fields(&name, &["author"]);
+        assert!(Surfer::try_from(builder).is_err());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_read_scored_structs() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+
+        let title = "The Old Man and the Sea".to_string();
+        let body = "He was an old man who fished alone in a skiff in the Gulf Stream and he had gone eighty-four days now without taking a fish.".to_string();
+        let old_man_doc = OldMan {
+            title,
+            body,
+        };
+        let sea_doc = OldMan {
+            title: "Sea".to_string(),
+            body: "The sea, the sea and nothing but the sea".to_string(),
+        };
+
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_structs(&name, &vec![old_man_doc.clone(), sea_doc.clone()]).unwrap();
+
+        let options = SearchOptions::default();
+        let result = surfer.read_scored_structs::<OldMan>(&name, "sea", &options).unwrap().unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].doc(), &sea_doc);
+        assert_eq!(result[1].doc(), &old_man_doc);
+        let first = result[0].score().unwrap();
+        let second = result[1].score().unwrap();
+        assert!(first > second);
+
+        let mut options = SearchOptions::default();
+        options.set_score((first + second) / 2.0);
+        let result = surfer.read_scored_structs::<OldMan>(&name, "sea", &options).unwrap().unwrap();
+        assert_eq!(result.len(), 1);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_read_highlighted() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+
+        let title = "The Old Man and the Sea".to_string();
+        let body = "He was an old man who fished alone in a skiff in the Gulf Stream and he had gone eighty-four days now without taking a fish.".to_string();
+        let old_man_doc = OldMan {
+            title,
+            body,
+        };
+
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
+
+        let options = SearchOptions::default();
+        let result = surfer.read_highlighted(&name, "skiff", "body", None, &options).unwrap().unwrap();
+        assert_eq!(result.len(), 1);
+        let (doc, snippet) = &result[0];
+        let doc = serde_json::from_str::<OldMan>(doc).unwrap();
+        assert_eq!(doc, old_man_doc);
+        assert!(snippet.contains("<b>skiff</b>"));
+
+        let result = surfer.read_highlighted(&name, "skiff", "body", Some(20), &options).unwrap().unwrap();
+        let (_, snippet) = &result[0];
+        assert!(snippet.contains("<b>skiff</b>"));
+        assert!(snippet.len() < old_man_doc.body.len());
+
+        let result = surfer.read_highlighted(&name, "skiff", "missing", None, &options);
+        assert!(result.is_err());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_suggest() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        let surfer = Surfer::new(builder);
+
+        let sea_wolf = OldMan {
+            title: "The Sea Wolf".to_string(),
+            body: "Wolf Larsen".to_string(),
+        };
+        let wolves = OldMan {
+            title: "Sea Wolves".to_string(),
+            body: "A pack".to_string(),
+        };
+        let _ = surfer.insert_structs(&name, &vec![sea_wolf.clone(), wolves.clone()]).unwrap();
+
+        let computed = surfer.suggest(&name, "title", "Wol", None).unwrap().unwrap();
+        let computed: Vec<&str> = computed.iter().map(|suggestion| suggestion.term()).collect();
+        assert_eq!(computed, vec!["wolf", "wolves"]);
+
+        let options = SearchOptions::default();
+        let computed = surfer.read_structs_prefix::<OldMan>(&name, "title", "Wolv", &options).unwrap().unwrap();
+        assert_eq!(computed, vec![wolves.clone()]);
+        let computed = surfer.read_structs_prefix::<OldMan>(&name, "title", "wol", &options).unwrap().unwrap();
+        assert_eq!(computed.len(), 2);
+
+        assert!(surfer.suggest(&name, "author", "wol", None).is_err());
+        assert!(surfer.suggest("missing", "title", "wol", None).unwrap().is_none());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_did_you_mean() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        let surfer = Surfer::new(builder);
+
+        let payload = vec![
+            OldMan {
+                title: "The Old Man and the Sea".to_string(),
+                body: "He was an old man who fished alone in a skiff".to_string(),
+            },
+            OldMan {
+                title: "The Sea Wolf".to_string(),
+                body: "Wolf Larsen".to_string(),
+            },
+        ];
+        let _ = surfer.insert_structs(&name, &payload).unwrap();
+        let computed = surfer.read_structs::<OldMan>(&name, "title:wolv", None, None).unwrap().unwrap();
+        assert!(computed.is_empty());
+
+        let computed = surfer.did_you_mean(&name, "title", "Wolv", 1, None).unwrap().unwrap();
+        assert_eq!(computed, vec![Suggestion::new("wolf".to_string(), 1, 1)]);
+        let computed = surfer.did_you_mean(&name, "body", "skif", 2, Some(1)).unwrap().unwrap();
+        assert_eq!(computed.len(), 1);
+        assert_eq!(computed[0].term(), "skiff");
+
+        assert!(surfer.did_you_mean(&name, "author", "wolv", 1, None).is_err());
+        assert!(surfer.did_you_mean("missing", "title", "wolv", 1, None).unwrap().is_none());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_read_structs_fuzzy() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+
+        let title = "The Old Man and the Sea".to_string();
+        let body = "He was an old man who fished alone in a skiff in the Gulf Stream and he had gone eighty-four days now without taking a fish.".to_string();
+        let old_man_doc = OldMan {
+            title,
+            body,
+        };
+
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
+
+        let options = SearchOptions::default();
+        let computed = surfer.read_structs_fuzzy::<OldMan>(&name, "body", "Skif", 1, &options).unwrap().unwrap();
+        assert_eq!(computed, vec![old_man_doc.clone()]);
+
+        let computed = surfer.read_structs_fuzzy::<OldMan>(&name, "body", "sky", 1, &options).unwrap().unwrap();
+        assert!(computed.is_empty());
+
+        let computed = surfer.read_structs_fuzzy::<OldMan>(&name, "body", "old man", 1, &options);
+        assert!(computed.is_err());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_read_regex() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+
+        let old_man_doc = OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: "He was an old man who fished alone in a skiff".to_string(),
+        };
+        let sku_doc = OldMan {
+            title: "sku-1042".to_string(),
+            body: "Spare parts".to_string(),
+        };
+
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_structs(&name, &vec![old_man_doc.clone(), sku_doc.clone()]).unwrap();
+
+        let options = SearchOptions::default();
+        let computed = surfer.read_regex::<OldMan>(&name, "body", "sk.f+", &options).unwrap().unwrap();
+        assert_eq!(computed, vec![old_man_doc]);
+
+        let computed = surfer.read_regex::<OldMan>(&name, "title", "10[0-9]+", &options).unwrap().unwrap();
+        assert_eq!(computed, vec![sku_doc]);
+
+        let computed = surfer.read_regex::<OldMan>(&name, "title", "[", &options);
+        assert!(computed.is_err());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_read_phrase() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+
+        let old_man_doc = OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: "He was an old man who fished alone in a skiff".to_string(),
+        };
+        let sea_doc = OldMan {
+            title: "The Sea and the Man".to_string(),
+            body: "The man was old".to_string(),
+        };
+
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_structs(&name, &vec![old_man_doc.clone(), sea_doc.clone()]).unwrap();
+
+        let options = SearchOptions::default();
+        let computed = surfer.read_phrase::<OldMan>(&name, "title", &["old", "man"], &options).unwrap().unwrap();
+        assert_eq!(computed, vec![old_man_doc]);
+
+        let computed = surfer.read_phrase::<OldMan>(&name, "title", &["the", "Sea"], &options).unwrap().unwrap();
+        assert_eq!(computed.len(), 2);
+
+        let computed = surfer.read_phrase::<OldMan>(&name, "title", &["man", "old"], &options).unwrap().unwrap();
+        assert!(computed.is_empty());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_more_like_this() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+
+        let old_man_doc = OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: "He was an old man who fished alone in a skiff in the Gulf Stream".to_string(),
+        };
+        let fisher_doc = OldMan {
+            title: "The Fisher".to_string(),
+            body: "An old man fished from his skiff".to_string(),
+        };
+        let whale_doc = OldMan {
+            title: "Moby Dick".to_string(),
+            body: "Call me Ishmael".to_string(),
+        };
+
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_structs(&name, &vec![old_man_doc.clone(), fisher_doc.clone(), whale_doc.clone()]).unwrap();
+
+        let like = Like::doc(&old_man_doc).unwrap();
+        let computed = surfer.more_like_this::<OldMan>(&name, &like, None).unwrap().unwrap();
+        assert_eq!(computed, vec![fisher_doc.clone()]);
+
+        let like = Like::query("title:fisher");
+        let computed = surfer.more_like_this::<OldMan>(&name, &like, None).unwrap().unwrap();
+        assert_eq!(computed, vec![old_man_doc.clone()]);
+
+        let mut options = MoreLikeThis::default();
+        options.set_min_term_frequency(5);
+        let computed = surfer.more_like_this_with_options::<OldMan>(&name, &like, &options, None).unwrap().unwrap();
+        assert!(computed.is_empty());
+
+        let computed = surfer.more_like_this::<OldMan>("missing", &like, None).unwrap();
+        assert!(computed.is_none());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_read_structs_with_bool() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+
+        let old_man_doc = OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: "He was an old man who fished alone in a skiff".to_string(),
+        };
+        let sea_doc = OldMan {
+            title: "The Sea".to_string(),
+            body: "Nothing but the sea".to_string(),
+        };
+
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_structs(&name, &vec![old_man_doc.clone(), sea_doc.clone()]).unwrap();
+
+        let mut query = BoolQuery::default();
+        query.must(Clause::text("sea"))
+            .should(Clause::phrase("title", &["old", "man"]))
+            .must_not(Clause::regex("body", "nothing"));
+        let options = SearchOptions::default();
+        let computed = surfer.read_structs_with_bool::<OldMan>(&name, &query, &options).unwrap().unwrap();
+        assert_eq!(computed, vec![old_man_doc.clone()]);
+
+        let mut query = BoolQuery::default();
+        query.must(Clause::All).must_not(Clause::term("title", &"old"));
+        let compiled = surfer.compile_query(&name, &query).unwrap().unwrap();
+        let computed = surfer.read_structs_with_query::<OldMan>(&name, compiled.as_ref(), &options).unwrap().unwrap();
+        assert_eq!(computed, vec![sea_doc.clone()]);
+
+        let computed = surfer.read_structs_with_bool::<OldMan>("missing", &query, &options).unwrap();
+        assert!(computed.is_none());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_read_structs_with_query() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+
+        let old_man_doc = OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: "He was an old man who fished alone in a skiff".to_string(),
+        };
+        let sea_doc = OldMan {
+            title: "The Sea".to_string(),
+            body: "Nothing but the sea".to_string(),
+        };
+
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_structs(&name, &vec![old_man_doc.clone(), sea_doc.clone()]).unwrap();
+
+        let schema = surfer.schema(&name).unwrap();
+        let title = schema.get_field("title").unwrap();
+        let body = schema.get_field("body").unwrap();
+        let sea: Box<dyn Query> = Box::new(TermQuery::new(Term::from_field_text(title, "sea"), IndexRecordOption::Basic));
+        let skiff: Box<dyn Query> = Box::new(TermQuery::new(Term::from_field_text(body, "skiff"), IndexRecordOption::Basic));
+        let query = BooleanQuery::from(vec![(Occur::Must, sea), (Occur::MustNot, skiff)]);
+
+        let options = SearchOptions::default();
+        let computed = surfer.read_structs_with_query::<OldMan>(&name, &query, &options).unwrap().unwrap();
+        assert_eq!(computed, vec![sea_doc.clone()]);
+
+        let computed = surfer.read_scored_structs_with_query::<OldMan>(&name, &query, &options).unwrap().unwrap();
+        assert_eq!(computed.len(), 1);
+        assert_eq!(computed[0].doc(), &sea_doc);
+        assert!(computed[0].score().is_some());
+
+        let computed = surfer.read_structs_with_query::<OldMan>("missing", &query, &options).unwrap();
+        assert!(computed.is_none());
+        assert!(surfer.schema("missing").is_none());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_aggregate() {
+        use tantivy::schema::Cardinality;
+
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Book {
+            id: u64,
+            price: f64,
+            title: String,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let books = vec![
+            Book { id: 2, price: 9.5, title: "The Sea Wolf".to_string() },
+            Book { id: 1, price: 12.0, title: "The Old Man and the Sea".to_string() },
+            Book { id: 3, price: 4.5, title: "Moby Dick".to_string() },
+        ];
+
+        let fast = IntOptions::default()
+            .set_indexed()
+            .set_stored()
+            .set_fast(Cardinality::SingleValue);
+        let mut control = HashMap::new();
+        control.insert("price".to_string(), Control::ControlIntOptions(fast));
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct_with_control(name.clone(), &books[0], control);
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_structs(&name, &books).unwrap();
+
+        let stats = surfer.aggregate(&name, "title:sea", "price").unwrap().unwrap();
+        assert_eq!(stats.count(), 2);
+        assert_eq!(stats.sum(), 21.5);
+        assert_eq!(stats.avg(), Some(10.75));
+        assert_eq!(stats.min(), Some(9.5));
+        assert_eq!(stats.max(), Some(12.0));
+
+        let stats = surfer.aggregate(&name, "title:whale", "price").unwrap().unwrap();
+        assert_eq!(stats.count(), 0);
+        assert_eq!(stats.avg(), None);
+
+        assert!(surfer.aggregate(&name, "title:sea", "id").is_err());
+        assert!(surfer.aggregate(&name, "title:sea", "title").is_err());
+        assert!(surfer.aggregate("missing", "title:sea", "price").unwrap().is_none());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_read_structs_in_range() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Book {
+            id: u64,
+            rank: i64,
+            price: f64,
+            title: String,
+        }
+
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let books = vec![
+            Book { id: 1, rank: -2, price: 9.5, title: "The Sea Wolf".to_string() },
+            Book { id: 2, rank: 0, price: 12.0, title: "The Old Man and the Sea".to_string() },
+            Book { id: 3, rank: 5, price: 4.5, title: "Moby Dick".to_string() },
+        ];
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &books[0]);
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_structs(&name, &books).unwrap();
+
+        let computed = surfer.read_structs_in_range::<Book, _>(&name, "id", 2..4, None, None).unwrap().unwrap();
+        let mut computed: Vec<u64> = computed.iter().map(|b| b.id).collect();
+        computed.sort();
+        assert_eq!(computed, vec![2, 3]);
+
+        let computed = surfer.read_structs_in_range::<Book, _>(&name, "rank", -5..0, None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![books[0].clone()]);
+
+        let computed = surfer.read_structs_in_range::<Book, _>(&name, "price", 5.0..20.0, None, None).unwrap().unwrap();
+        assert_eq!(computed.len(), 2);
+
+        let computed = surfer.read_structs_in_range::<Book, _>(&name, "title", 0..1, None, None);
+        assert!(computed.is_err());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_count() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+
+        let docs: Vec<OldMan> = (0..25).map(|i| OldMan {
+            title: format!("The Sea {}", i),
+            body: "Nothing but the sea".to_string(),
+        }).collect();
+
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_structs(&name, &docs).unwrap();
+
+        assert_eq!(surfer.count(&name, "title:sea").unwrap(), Some(25));
+        assert_eq!(surfer.count(&name, "title:7").unwrap(), Some(1));
+        assert_eq!(surfer.count(&name, "title:whale").unwrap(), Some(0));
+        assert_eq!(surfer.count("missing", "title:sea").unwrap(), None);
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_clear() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        builder.set_auto_commit(&name, false);
+
+        let old_man_doc = OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: "He was an old man who fished alone in a skiff".to_string(),
+        };
+
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
+        let _ = surfer.commit(&name).unwrap();
+        assert_eq!(surfer.count(&name, "title:sea").unwrap(), Some(1));
+
+        let _ = surfer.clear(&name).unwrap();
+        assert_eq!(surfer.count(&name, "title:sea").unwrap(), Some(0));
+
+        let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
+        let _ = surfer.commit(&name).unwrap();
+        assert_eq!(surfer.count(&name, "title:sea").unwrap(), Some(1));
+
+        assert!(surfer.clear("missing").is_ok());
+
+        let _ = remove_dir_all(index_path);
+    }
+
+    #[test]
+    fn validate_snapshot() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: "He was an old man who fished alone in a skiff in the Gulf Stream".to_string(),
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_struct(&name, &data).unwrap();
+
+        let snapshot = format!("{}/{}", home, random_string(None));
+        surfer.snapshot(&name, &snapshot).unwrap();
+        let _ = surfer.insert_struct(&name, &data).unwrap();
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        let copy = snapshot.trim_start_matches("tmp/").to_string();
+        builder.add_existing(&copy).unwrap();
+        let restored = Surfer::new(builder);
+        let computed = restored.read_structs::<OldMan>(&copy, "title:sea", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![data]);
+        drop(restored);
+
+        let computed = surfer.snapshot("missing", &snapshot);
+        assert!(computed.is_err());
+
+        let _ = remove_dir_all(index_path);
+        let _ = remove_dir_all(snapshot);
+    }
+
+    #[test]
+    fn validate_restore() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: "He was an old man who fished alone in a skiff in the Gulf Stream".to_string(),
+        };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_struct(&name, &data).unwrap();
+        let snapshot = format!("{}/{}", home, random_string(None));
+        surfer.snapshot(&name, &snapshot).unwrap();
+
+        let _ = surfer.insert_struct(&name, &data).unwrap();
+        let computed = surfer.count(&name, "title:sea").unwrap();
+        assert_eq!(computed, Some(2));
+
+        surfer.restore(&name, &snapshot).unwrap();
+        let computed = surfer.read_structs::<OldMan>(&name, "title:sea", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![data.clone()]);
+        let _ = surfer.insert_struct(&name, &data).unwrap();
+        let computed = surfer.count(&name, "title:sea").unwrap();
+        assert_eq!(computed, Some(2));
+
+        let other = random_string(None);
+        surfer.restore(&other, &snapshot).unwrap();
+        let computed = surfer.count(&other, "title:sea").unwrap();
+        assert_eq!(computed, Some(1));
+
+        let computed = surfer.restore(&name, format!("{}/{}", home, random_string(None)));
+        assert!(computed.is_err());
+
+        let _ = surfer.drop_index(&other);
+        let _ = remove_dir_all(index_path);
+        let _ = remove_dir_all(snapshot);
+    }
+
+    #[test]
+    fn validate_drop_index() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_struct(&name, &data).unwrap();
+        assert!(path.exists());
+
+        let _ = surfer.drop_index(&name).unwrap();
+        assert!(!path.exists());
+        assert!(surfer.which_index(&name).is_none());
+        assert!(surfer.read_string(&name, "title:sea", None, None).unwrap().is_none());
+        assert!(surfer.drop_index(&name).is_ok());
+    }
+
+    #[test]
+    fn validate_add_index_at_runtime() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        let surfer = Surfer::new(builder);
+        assert!(surfer.which_index(&name).is_none());
+
+        let old_man_doc = OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: "He was an old man who fished alone in a skiff".to_string(),
+        };
+        let _ = surfer.add_index(&name, &old_man_doc).unwrap();
+        assert!(path.exists());
+        assert!(surfer.add_index(&name, &old_man_doc).is_err());
+
+        let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
+        let computed = surfer.read_structs::<OldMan>(&name, "sea", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![old_man_doc]);
+
+        let _ = surfer.drop_index(&name).unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn validate_close_and_reopen_index() {
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+
+        let old_man_doc = OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: "He was an old man who fished alone in a skiff".to_string(),
+        };
 
+        let surfer = Surfer::new(builder);
+        let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
+        assert!(!surfer.is_closed(&name));
 
-impl TryFrom<SurferBuilder> for Surfer {
-    type Error = IndexError;
-    fn try_from(builder: SurferBuilder) -> Result<Self, Self::Error> {
-        let home = extract_home(&builder)?;
-        let indexes = initialized_index(&home, &builder)?;
-        let fields = extract_fields(&builder);
+        let _ = surfer.close_index(&name).unwrap();
+        assert!(surfer.is_closed(&name));
+        assert!(path.exists());
+        assert!(surfer.read_structs::<OldMan>(&name, "sea", None, None).unwrap().is_none());
+        assert!(surfer.add_index(&name, &data).is_err());
+        let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
+        assert!(surfer.count(&name, "title:sea").unwrap().is_none());
 
-        let mut readers = HashMap::new();
-        let mut writers = HashMap::new();
-        for (name, _) in &builder.schemas {
-            let reader: Option<IndexReader> = None;
-            let writer: Option<IndexWriter> = None;
-            writers.insert(name.to_string(), writer);
-            readers.insert(name.to_string(), reader);
-        }
+        let _ = surfer.reopen_index(&name).unwrap();
+        assert!(!surfer.is_closed(&name));
+        let computed = surfer.read_structs::<OldMan>(&name, "sea", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![old_man_doc.clone()]);
 
-        Ok(Surfer {
-            home,
-            indexes,
-            fields,
-            readers,
-            writers,
-        })
+        let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
+        assert_eq!(surfer.count(&name, "title:sea").unwrap(), Some(2));
+
+        let _ = surfer.close_index(&name).unwrap();
+        let _ = surfer.drop_index(&name).unwrap();
+        assert!(!path.exists());
+        assert!(!surfer.is_closed(&name));
     }
-}
 
-/// Container to pass through config to tantivy
-pub enum Control {
-    ControlTextOptions(TextOptions),
-    ControlIntOptions(IntOptions),
-}
+    #[test]
+    fn validate_shared_across_threads() {
+        use std::sync::Arc;
+        use std::thread;
 
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Surfer>();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serde::{Serialize, Deserialize};
-    use std::fmt::Debug;
-    use std::path::Path;
-    use std::fs::remove_dir_all;
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        let surfer = Arc::new(Surfer::new(builder));
 
+        let handles: Vec<thread::JoinHandle<()>> = (0..4).map(|i| {
+            let surfer = Arc::clone(&surfer);
+            let name = name.clone();
+            thread::spawn(move || {
+                let doc = OldMan {
+                    title: format!("The Sea {}", i),
+                    body: "Nothing but the sea".to_string(),
+                };
+                surfer.insert_struct(&name, &doc).unwrap();
+                let _ = surfer.read_structs::<OldMan>(&name, "sea", None, None).unwrap().unwrap();
+            })
+        }).collect();
+        for handle in handles {
+            handle.join().unwrap();
+        };
 
-    #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
-    struct OldMan {
-        title: String,
-        body: String,
-    }
+        assert_eq!(surfer.count(&name, "title:sea").unwrap(), Some(4));
 
-    impl Default for OldMan {
-        fn default() -> Self {
-            let title = "".to_string();
-            let body = "".to_string();
-            Self {
-                title,
-                body,
-            }
-        }
+        let _ = remove_dir_all(index_path);
     }
 
     #[test]
-    fn validate_read_existing_documents_as_structs() {
+    fn validate_in_memory_index() {
         let name = random_string(None);
         let home = "tmp";
         let index_path = format!("{}/{}", home, name);
@@ -376,143 +6671,391 @@ mod tests {
         assert!(!path.exists());
 
         let data = OldMan::default();
-
         let mut builder = SurferBuilder::default();
         builder.set_home(home);
         builder.add_struct(name.clone(), &data);
+        builder.set_in_memory(&name, true);
 
-        {
-            let title = "The Old Man and the Sea".to_string();
-            let body = "He was an old man who fished alone in a skiff in the Gulf Stream and he had gone eighty-four days now without taking a fish.".to_string();
-            let old_man_doc = OldMan {
-                title,
-                body,
-            };
+        let old_man_doc = OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: "He was an old man who fished alone in a skiff".to_string(),
+        };
 
-            let mut surfer = Surfer::new(builder.clone());
-            let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
-        }
+        let surfer = Surfer::new(builder);
+        assert!(surfer.is_in_memory(&name));
+        assert!(surfer.which_index(&name).is_none());
 
-        let mut surfer = Surfer::new(builder.clone());
-        let query = "sea whale";
-        let result = surfer.read_structs::<OldMan>(&name, query, None, None);
-        assert!(result.is_ok());
-        assert!(path.exists());
-        let _ = remove_dir_all(index_path);
+        let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
+        let computed = surfer.read_structs::<OldMan>(&name, "sea", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![old_man_doc]);
+        assert!(!path.exists());
+
+        assert!(surfer.close_index(&name).is_err());
+        let _ = surfer.drop_index(&name).unwrap();
+        assert!(surfer.read_structs::<OldMan>(&name, "sea", None, None).unwrap().is_none());
+        assert!(!surfer.is_in_memory(&name));
     }
 
     #[test]
-    fn validate_read_existing_documents_as_strings() {
-        let title = "The Old Man and the Sea".to_string();
-        let body = "He was an old man who fished alone in a skiff in the Gulf Stream and he had gone eighty-four days now without taking a fish.".to_string();
-        let expected = OldMan {
-            title,
-            body,
+    fn validate_open_existing() {
+        let home = format!("tmp/{}", random_string(None));
+        let name = random_string(None);
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(&home);
+        builder.add_struct(name.clone(), &data);
+
+        let old_man_doc = OldMan {
+            title: "The Old Man and the Sea".to_string(),
+            body: "He was an old man who fished alone in a skiff".to_string(),
         };
+        {
+            let surfer = Surfer::new(builder);
+            let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
+        }
+
+        let mut builder = SurferBuilder::default();
+        builder.set_home(&home);
+        let _ = builder.add_existing(&name).unwrap();
+        assert!(builder.add_existing("missing").is_err());
+        let surfer = Surfer::new(builder);
+        let computed = surfer.read_structs::<OldMan>(&name, "sea", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![old_man_doc.clone()]);
+        drop(surfer);
+
+        let staging = format!("{}/{}.migrating", home, name);
+        std::fs::create_dir_all(&staging).unwrap();
+        std::fs::copy(format!("{}/meta.json", index_path), format!("{}/meta.json", staging)).unwrap();
+        let surfer = Surfer::open_existing(&home).unwrap();
+        let computed = surfer.read_structs::<OldMan>(&name, "title:sea", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![old_man_doc]);
+        assert!(surfer.schema(&format!("{}.migrating", name)).is_none());
+        drop(surfer);
+
+        #[cfg(unix)]
+        {
+            use std::ffi::OsStr;
+            use std::os::unix::ffi::OsStrExt;
+            let invalid = Path::new(&home).join(OsStr::from_bytes(b"invalid-\xff"));
+            std::fs::create_dir_all(&invalid).unwrap();
+            std::fs::write(invalid.join("meta.json"), "{}").unwrap();
+            assert!(matches!(Surfer::open_existing(&home), Err(IndexError::Invalid { .. })));
+        }
+
+        let _ = remove_dir_all(home);
+    }
 
+    #[test]
+    fn validate_migrate() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Book {
+            title: String,
+            pages: u64,
+            published: bool,
+        }
 
         let name = random_string(None);
-        let mut builder = SurferBuilder::default();
-        let data = OldMan::default();
         let home = "tmp";
         let index_path = format!("{}/{}", home, name);
         let path = Path::new(&index_path);
         assert!(!path.exists());
+
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
         builder.set_home(home);
-        builder.add_struct(name.to_string(), &data);
+        builder.add_struct(name.clone(), &data);
+        let surfer = Surfer::new(builder);
 
-        {
-            let title = "The Old Man and the Sea".to_string();
-            let body = "He was an old man who fished alone in a skiff in the Gulf Stream and he had gone eighty-four days now without taking a fish.".to_string();
-            let old_man_doc = OldMan {
-                title,
-                body,
-            };
+        let docs = vec![
+            OldMan { title: "The Old Man and the Sea".to_string(), body: "He was an old man".to_string() },
+            OldMan { title: "The Sea Wolf".to_string(), body: "A wolf at sea".to_string() },
+        ];
+        let _ = surfer.insert_structs(&name, &docs).unwrap();
 
-            let mut surfer = Surfer::new(builder.clone());
-            let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
+        let sample = Book { title: "".to_string(), pages: 0, published: false };
+        let drift = surfer.schema_drift(&name, &sample).unwrap().unwrap();
+        assert_eq!(drift.added(), &vec!["pages".to_string(), "published".to_string()]);
+        assert_eq!(drift.removed(), &vec!["body".to_string()]);
+
+        let _ = surfer.migrate(&name, |old: OldMan| Book {
+            pages: old.body.len() as u64,
+            title: old.title,
+            published: true,
+        }).unwrap();
+
+        let drift = surfer.schema_drift(&name, &sample).unwrap().unwrap();
+        assert!(drift.is_empty());
+        let computed = surfer.read_structs::<Book>(&name, "title:wolf", None, None).unwrap().unwrap();
+        let expected = vec![Book { title: "The Sea Wolf".to_string(), pages: 13, published: true }];
+        assert_eq!(computed, expected);
+        assert_eq!(surfer.count(&name, "title:sea").unwrap(), Some(2));
+        assert!(!Path::new(&format!("{}.migrating", index_path)).exists());
+
+        let _ = surfer.clear(&name).unwrap();
+        assert!(surfer.migrate(&name, |old: Book| old).is_err());
+
+        let _ = surfer.drop_index(&name).unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn validate_migrate_keeps_expiry() {
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+        struct Session {
+            token: String,
         }
 
-        let mut surfer = Surfer::new(builder.clone());
-        let query = "sea whale";
-        let result = surfer.read_string("Non-existent", query, None, None);
-        assert!(result.is_ok());
-        let result = result.unwrap();
-        assert!(result.is_none());
-        let result = surfer.read_string(&name, query, None, None);
-        assert!(result.is_ok());
-        let result = result.unwrap();
-        assert!(result.is_some());
-        let result = result.unwrap();
-        let mut computed = Vec::new();
-        for entry in result {
-            let data: serde_json::Result<OldMan> = serde_json::from_str(&entry);
-            let data = data.unwrap();
-            computed.push(data);
-        };
-        assert_eq!(computed, vec![expected.clone()]);
+        let name = random_string(None);
+        let home = "tmp";
+        let index_path = format!("{}/{}", home, name);
+        let path = Path::new(&index_path);
+        assert!(!path.exists());
 
-        // Reading documents again
-        let result = surfer.read_string(&name, query, None, None);
-        assert!(result.is_ok());
-        let result = result.unwrap();
-        assert!(result.is_some());
-        let result = result.unwrap();
-        let mut computed = Vec::new();
-        for entry in result {
-            let data: serde_json::Result<OldMan> = serde_json::from_str(&entry);
-            let data = data.unwrap();
-            computed.push(data);
-        };
-        assert_eq!(computed, vec![expected.clone()]);
+        let session = Session { token: "abc".to_string() };
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &session);
+        builder.set_ttl(&name, Duration::from_secs(3));
+        let surfer = Surfer::new(builder);
+        surfer.insert_struct(&name, &session).unwrap();
 
-        let _ = remove_dir_all(&index_path);
+        block_thread(2);
+        surfer.migrate(&name, |old: Session| Session { token: old.token.to_uppercase() }).unwrap();
+        let computed = surfer.read_structs_with_query::<Session>(&name, &AllQuery, &SearchOptions::default()).unwrap().unwrap();
+        assert_eq!(computed, vec![Session { token: "ABC".to_string() }]);
+
+        block_thread(2);
+        assert_eq!(surfer.purge_expired(&name).unwrap(), Some(1));
+        assert!(surfer.read_structs_with_query::<Session>(&name, &AllQuery, &SearchOptions::default()).unwrap().unwrap().is_empty());
+
+        let _ = remove_dir_all(index_path);
     }
 
     #[test]
-    fn validate_as_rust_structs() {
+    fn validate_schema_mismatch_on_open() {
+        #[derive(Serialize)]
+        struct Book {
+            title: String,
+            pages: u64,
+        }
+
         let name = random_string(None);
-        let home = "tmp".to_string();
+        let home = "tmp";
         let index_path = format!("{}/{}", home, name);
         let path = Path::new(&index_path);
         assert!(!path.exists());
 
-        let title = "The Old Man and the Sea".to_string();
-        let body = "He was an old man who fished alone in a skiff in the Gulf Stream and he had gone eighty-four days now without taking a fish.".to_string();
-        let old_man_doc = OldMan {
-            title,
-            body,
-        };
+        let data = OldMan::default();
+        let mut builder = SurferBuilder::default();
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &data);
+        let surfer = Surfer::try_from(builder.clone());
+        assert!(surfer.is_ok());
+        drop(surfer);
 
+        let surfer = Surfer::try_from(builder);
+        assert!(surfer.is_ok());
+        drop(surfer);
 
         let mut builder = SurferBuilder::default();
-        builder.set_home(home.as_str());
-        builder.add_struct(name.to_string(), &old_man_doc);
-        let mut surfer = Surfer::new(builder);
+        builder.set_home(home);
+        builder.add_struct(name.clone(), &Book { title: "".to_string(), pages: 0 });
+        let computed = Surfer::try_from(builder);
+        assert!(computed.is_err());
+        let expected = IndexError::schema_mismatch(
+            format!("Schema of index: {} does not match", name),
+            r#"added: ["pages"], removed: ["body"], retyped: []"#.to_string(),
+        );
+        assert_eq!(format!("{:?}", computed.err().unwrap()), format!("{:?}", expected));
 
-        let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
-        let query = "sea whale";
+        let _ = remove_dir_all(index_path);
+    }
 
-        let result = surfer.read_structs::<OldMan>("non-existent", query, None, None);
-        assert!(result.is_ok());
-        let result = result.unwrap();
-        assert!(result.is_none());
+    #[test]
+    fn validate_stem_fields() {
+        use json_surf_derive::Surf;
 
-        let result = surfer.read_structs::<OldMan>(&name, query, None, None).unwrap().unwrap();
-        for computed in result {
-            assert_eq!(computed, old_man_doc);
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq, Surf)]
+        struct Livre {
+            #[surf(stored, stem = "french")]
+            titre: String,
+            #[surf(stored, stem = "de")]
+            titel: String,
+        }
+
+        let home = format!("tmp/{}", random_string(None));
+        let mut builder = SurferBuilder::default();
+        builder.set_home(&home);
+        builder.add_surf::<Livre>();
+        let surfer = Surfer::new(builder);
+
+        let livre = Livre {
+            titre: "Les chevaux mangeaient".to_string(),
+            titel: "Die Häuser".to_string(),
         };
-        assert!(path.exists());
+        surfer.insert_struct(Livre::index_name(), &livre).unwrap();
+        let computed = surfer.read_structs::<Livre>(Livre::index_name(), "titre:mangeait", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![livre.clone()]);
+        let computed = surfer.read_structs::<Livre>(Livre::index_name(), "titel:haus", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![livre]);
+        drop(surfer);
 
-        // Reading documents again
+        let _ = remove_dir_all(home);
+    }
 
-        let result = surfer.read_structs::<OldMan>(&name, query, None, None).unwrap().unwrap();
-        for computed in result {
-            assert_eq!(computed, old_man_doc);
+    #[test]
+    fn validate_derive_from_document() {
+        use json_surf_derive::{FromDocument, Surf};
+
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq, Surf, FromDocument)]
+        struct Address {
+            city: String,
+        }
+
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq, Surf, FromDocument)]
+        struct Reader {
+            name: String,
+            age: u8,
+            admin: bool,
+            score: Option<f64>,
+            tags: Vec<String>,
+            address: Address,
+            #[surf(skip)]
+            #[serde(skip)]
+            password: String,
+        }
+
+        let home = format!("tmp/{}", random_string(None));
+        let mut builder = SurferBuilder::default();
+        builder.set_home(&home);
+        builder.add_surf::<Reader>();
+        let surfer = Surfer::new(builder);
+
+        let reader = Reader {
+            name: "Jon Snow".to_string(),
+            age: 21,
+            admin: true,
+            score: None,
+            tags: vec!["north".to_string(), "watch".to_string()],
+            address: Address { city: "Winterfell".to_string() },
+            password: String::new(),
         };
+        surfer.insert_struct(Reader::index_name(), &reader).unwrap();
+        let computed = surfer.read_documents::<Reader>(Reader::index_name(), "name:jon", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![reader]);
+
+        #[derive(Debug, Deserialize, FromDocument)]
+        #[allow(dead_code)]
+        struct Narrow {
+            name: u64,
+        }
+        let computed = surfer.read_documents::<Narrow>(Reader::index_name(), "name:jon", None, None).unwrap_err();
+        assert!(computed.reason().contains("Field: name holds"));
+        drop(surfer);
 
+        let _ = remove_dir_all(home);
+    }
 
-        let _ = remove_dir_all(index_path);
+    #[test]
+    fn validate_derive_object_seqs() {
+        use json_surf_derive::{FromDocument, Surf};
+
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq, Surf, FromDocument)]
+        struct LineItem {
+            sku: String,
+            quantity: u64,
+        }
+
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq, Surf, FromDocument)]
+        struct Invoice {
+            id: String,
+            items: Vec<LineItem>,
+            returns: Option<Vec<LineItem>>,
+        }
+
+        let home = format!("tmp/{}", random_string(None));
+        let mut builder = SurferBuilder::default();
+        builder.set_home(&home);
+        builder.add_surf::<Invoice>();
+        let surfer = Surfer::new(builder);
+
+        let item = LineItem {
+            sku: "apple".to_string(),
+            quantity: 3,
+        };
+        let returned = LineItem {
+            sku: "pear".to_string(),
+            quantity: 1,
+        };
+        let invoice = Invoice {
+            id: "1".to_string(),
+            items: vec![item.clone(), item],
+            returns: Some(vec![returned]),
+        };
+        surfer.insert_struct(Invoice::index_name(), &invoice).unwrap();
+        let computed = surfer.read_documents::<Invoice>(Invoice::index_name(), "apple", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![invoice.clone()]);
+        let computed = surfer.read_structs::<Invoice>(Invoice::index_name(), "apple", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![invoice]);
+        drop(surfer);
+
+        let _ = remove_dir_all(home);
+    }
+
+    #[test]
+    fn validate_add_surf() {
+        use json_surf_derive::Surf;
+
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq, Surf)]
+        struct Address {
+            city: String,
+        }
+
+        #[derive(Clone, Serialize, Debug, Deserialize, PartialEq, Surf)]
+        struct SurfUser {
+            name: String,
+            #[surf(stored, indexed, fast)]
+            age: u8,
+            admin: bool,
+            tags: Vec<String>,
+            address: Address,
+            #[surf(skip)]
+            #[serde(skip)]
+            password: String,
+        }
+
+        assert_eq!(SurfUser::index_name(), "surf_user");
+        let expected: Vec<String> = ["name", "age", "admin", "tags", "address.city"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(SurfUser::field_names(), expected);
+
+        let home = format!("tmp/{}", random_string(None));
+        let mut builder = SurferBuilder::default();
+        builder.set_home(&home);
+        builder.add_surf::<SurfUser>();
+        let surfer = Surfer::new(builder);
+
+        let user = SurfUser {
+            name: "Jon Snow".to_string(),
+            age: 21,
+            admin: true,
+            tags: vec!["north".to_string(), "watch".to_string()],
+            address: Address { city: "Winterfell".to_string() },
+            password: String::new(),
+        };
+        let _ = surfer.insert_struct(SurfUser::index_name(), &user).unwrap();
+        let computed = surfer.read_structs::<SurfUser>(SurfUser::index_name(), "name:jon", None, None).unwrap().unwrap();
+        assert_eq!(computed, vec![user]);
+        let schema = surfer.schema(SurfUser::index_name()).unwrap();
+        assert!(schema.get_field("password").is_none());
+        let stats = surfer.aggregate(SurfUser::index_name(), "name:jon", "age").unwrap().unwrap();
+        assert_eq!(stats.sum(), 21.0);
+        drop(surfer);
+
+        let _ = remove_dir_all(home);
     }
 
     #[test]
@@ -525,7 +7068,7 @@ mod tests {
         let oldman = OldMan::default();
         let data = as_value(&oldman).unwrap();
         let schema = to_schema(&data, None).unwrap();
-        let _ = initialize_mmap(index_name, home, &schema);
+        let _ = initialize_mmap(index_name, home, &schema, None, &Tokenizers::new(), Durability::Durable);
         assert!(path.exists());
         let _ = std::fs::remove_dir_all(path_to_index);
     }
@@ -551,7 +7094,7 @@ mod tests {
             body,
         };
 
-        let mut surfer = Surfer::new(builder.clone());
+        let surfer = Surfer::new(builder.clone());
         let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
         let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
         let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
@@ -594,7 +7137,7 @@ mod tests {
             body,
         };
 
-        let mut surfer = Surfer::new(builder.clone());
+        let surfer = Surfer::new(builder.clone());
         for _ in 0..20 {
             let _ = surfer.insert_struct(&name, &old_man_doc).unwrap();
         }
@@ -0,0 +1,793 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::marker::PhantomData;
+
+use tantivy::collector::{Collector, Count, SegmentCollector, TopDocs};
+use tantivy::query::{Query, Scorer, Weight};
+use tantivy::schema::{Cardinality, Field, FieldType, Schema};
+use tantivy::{DocAddress, DocId, DocSet, LeasedItem, Score, Searcher, SegmentLocalId, SegmentReader};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::prelude::*;
+
+/// Direction of a sort
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+/// Per call search configuration
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchOptions {
+    limit: Option<usize>,
+    offset: usize,
+    score: Option<f32>,
+    order_by: Option<(String, Order)>,
+    lenient: bool,
+    boosts: HashMap<String, f32>,
+    collapse: Option<String>,
+    select: Option<Vec<String>>,
+}
+
+/// Default impl returns the first 10 documents
+impl Default for SearchOptions {
+    fn default() -> Self {
+        let limit = None;
+        let offset = 0;
+        let score = None;
+        let order_by = None;
+        let lenient = false;
+        let boosts = HashMap::new();
+        let collapse = None;
+        let select = None;
+        Self {
+            limit,
+            offset,
+            score,
+            order_by,
+            lenient,
+            boosts,
+            collapse,
+            select,
+        }
+    }
+}
+
+impl SearchOptions {
+    pub fn new(limit: Option<usize>, offset: usize, score: Option<f32>) -> Self {
+        let order_by = None;
+        let lenient = false;
+        let boosts = HashMap::new();
+        let collapse = None;
+        let select = None;
+        Self {
+            limit,
+            offset,
+            score,
+            order_by,
+            lenient,
+            boosts,
+            collapse,
+            select,
+        }
+    }
+    /// Maximum documents to return - default is 10 unless the index sets its own default
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = Some(limit);
+    }
+    /// Documents to skip before collecting
+    pub fn set_offset(&mut self, offset: usize) {
+        self.offset = offset;
+    }
+    /// Minimum score of a document
+    pub fn set_score(&mut self, score: f32) {
+        self.score = Some(score);
+    }
+    /// Sort by a FAST numeric or date field instead of relevance, minimum score is then ignored
+    pub fn set_order_by(&mut self, field: &str, order: Order) {
+        self.order_by = Some((field.to_string(), order));
+    }
+    /// Skip documents that do not deserialize into the struct instead of failing
+    pub fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+    /// Multiplies the score of query string matches on the field, overrides the boost of the index
+    pub fn set_field_boost(&mut self, field: &str, boost: f32) {
+        self.boosts.insert(field.to_string(), boost);
+    }
+    /// Keeps only the best scoring document per distinct value of the field e.g. one hit per product_id
+    /// The field is a single valued fast numeric field or a stored field, ranked by relevance only
+    pub fn set_collapse(&mut self, field: &str) {
+        self.collapse = Some(field.to_string());
+    }
+    /// Returns only the stored fields listed e.g. to read into a lighter struct, a nested field keeps all of its fields
+    pub fn select(&mut self, fields: &[&str]) {
+        self.select = Some(fields.iter().map(|field| field.to_string()).collect());
+    }
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+    pub fn score(&self) -> Option<f32> {
+        self.score
+    }
+    pub fn order_by(&self) -> Option<(&str, Order)> {
+        self.order_by.as_ref().map(|(field, order)| (field.as_str(), *order))
+    }
+    pub fn lenient(&self) -> bool {
+        self.lenient
+    }
+    pub fn field_boosts(&self) -> &HashMap<String, f32> {
+        &self.boosts
+    }
+    pub fn collapse(&self) -> Option<&str> {
+        self.collapse.as_deref()
+    }
+    pub fn selected(&self) -> Option<&[String]> {
+        self.select.as_deref()
+    }
+}
+
+/// Document along with its relevance score, score is None when sorted by field
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Hit<T> {
+    score: Option<Score>,
+    doc: T,
+}
+
+impl<T> Hit<T> {
+    pub fn new(score: Option<Score>, doc: T) -> Self {
+        Self {
+            score,
+            doc,
+        }
+    }
+    pub fn score(&self) -> Option<Score> {
+        self.score
+    }
+    pub fn doc(&self) -> &T {
+        &self.doc
+    }
+    pub fn into_doc(self) -> T {
+        self.doc
+    }
+    /// Converts the document keeping the score
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Hit<U> {
+        Hit::new(self.score, f(self.doc))
+    }
+}
+
+/// Hit of a search across several indexes along with the index it comes from
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MultiHit<T> {
+    index: String,
+    score: Option<Score>,
+    doc: T,
+}
+
+impl<T> MultiHit<T> {
+    pub fn new(index: &str, hit: Hit<T>) -> Self {
+        let index = index.to_string();
+        Self {
+            index,
+            score: hit.score,
+            doc: hit.doc,
+        }
+    }
+    pub fn index(&self) -> &str {
+        &self.index
+    }
+    pub fn score(&self) -> Option<Score> {
+        self.score
+    }
+    pub fn doc(&self) -> &T {
+        &self.doc
+    }
+    pub fn into_doc(self) -> T {
+        self.doc
+    }
+}
+
+/// Hits of the requested page along with the number of matching documents
+/// Total ignores the limit, the offset & the minimum score
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SearchResult<T> {
+    total: usize,
+    hits: Vec<Hit<T>>,
+}
+
+impl<T> SearchResult<T> {
+    pub fn new(total: usize, hits: Vec<Hit<T>>) -> Self {
+        Self {
+            total,
+            hits,
+        }
+    }
+    pub fn total(&self) -> usize {
+        self.total
+    }
+    pub fn hits(&self) -> &Vec<Hit<T>> {
+        &self.hits
+    }
+    pub fn into_hits(self) -> Vec<Hit<T>> {
+        self.hits
+    }
+    /// Converts the documents keeping the scores & the total
+    pub fn map<U, F: FnMut(T) -> U>(self, mut f: F) -> SearchResult<U> {
+        let hits = self.hits.into_iter().map(|hit| hit.map(&mut f)).collect();
+        SearchResult::new(self.total, hits)
+    }
+}
+
+/// Page of hits by relevance along with the token continuing after its last hit
+/// None when there is nothing left, tokens are only valid until the next commit
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Page<T> {
+    hits: Vec<Hit<T>>,
+    after: Option<String>,
+}
+
+impl<T> Page<T> {
+    pub fn new(hits: Vec<Hit<T>>, after: Option<String>) -> Self {
+        Self {
+            hits,
+            after,
+        }
+    }
+    pub fn hits(&self) -> &Vec<Hit<T>> {
+        &self.hits
+    }
+    pub fn after(&self) -> Option<&str> {
+        self.after.as_deref()
+    }
+    pub fn into_hits(self) -> Vec<Hit<T>> {
+        self.hits
+    }
+    /// Converts the documents keeping the scores & the token
+    pub fn map<U, F: FnMut(T) -> U>(self, mut f: F) -> Page<U> {
+        let hits = self.hits.into_iter().map(|hit| hit.map(&mut f)).collect();
+        Page::new(hits, self.after)
+    }
+}
+
+/// Position of a hit ordered by score then by address, better ranks are lower
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Cursor {
+    score: Score,
+    doc_address: DocAddress,
+}
+
+impl Cursor {
+    /// Opaque token of the score & the address
+    pub(crate) fn encode(&self) -> String {
+        let DocAddress(segment, doc) = self.doc_address;
+        format!("{:08x}{:08x}{:08x}", self.score.to_bits(), segment, doc)
+    }
+    pub(crate) fn decode(token: &str) -> Result<Self, IndexError> {
+        let part = |i: usize| token.get(i * 8..(i + 1) * 8).and_then(|part| u32::from_str_radix(part, 16).ok());
+        match (token.len(), part(0), part(1), part(2)) {
+            (24, Some(score), Some(segment), Some(doc)) => Ok(Self {
+                score: Score::from_bits(score),
+                doc_address: DocAddress(segment, doc),
+            }),
+            _ => Err(IndexError::new("Unable to read page", &format!("Invalid token: {}", token))),
+        }
+    }
+}
+
+impl Eq for Cursor {}
+
+impl Ord for Cursor {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.score.partial_cmp(&self.score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| self.doc_address.cmp(&other.doc_address))
+    }
+}
+
+impl PartialOrd for Cursor {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Collects the best hits ranked strictly after the cursor
+pub(crate) struct AfterCollector {
+    limit: usize,
+    after: Option<Cursor>,
+}
+
+impl AfterCollector {
+    pub(crate) fn new(limit: usize, after: Option<Cursor>) -> Self {
+        Self {
+            limit,
+            after,
+        }
+    }
+}
+
+pub(crate) struct AfterSegmentCollector {
+    segment: SegmentLocalId,
+    limit: usize,
+    after: Option<Cursor>,
+    heap: BinaryHeap<Cursor>,
+}
+
+impl Collector for AfterCollector {
+    type Fruit = Vec<Cursor>;
+    type Child = AfterSegmentCollector;
+
+    fn for_segment(&self, segment: SegmentLocalId, _: &SegmentReader) -> tantivy::Result<Self::Child> {
+        Ok(AfterSegmentCollector {
+            segment,
+            limit: self.limit,
+            after: self.after,
+            heap: BinaryHeap::with_capacity(self.limit + 1),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<Vec<Cursor>>) -> tantivy::Result<Vec<Cursor>> {
+        let mut cursors: Vec<Cursor> = segment_fruits.into_iter().flatten().collect();
+        cursors.sort();
+        cursors.truncate(self.limit);
+        Ok(cursors)
+    }
+}
+
+impl SegmentCollector for AfterSegmentCollector {
+    type Fruit = Vec<Cursor>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let cursor = Cursor {
+            score,
+            doc_address: DocAddress(self.segment, doc),
+        };
+        if let Some(after) = self.after {
+            if cursor <= after {
+                return;
+            };
+        };
+        self.heap.push(cursor);
+        if self.heap.len() > self.limit {
+            self.heap.pop();
+        };
+    }
+
+    fn harvest(self) -> Vec<Cursor> {
+        self.heap.into_sorted_vec()
+    }
+}
+
+/// Value documents are collapsed on, documents without it share the None key
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum CollapseKey {
+    Fast(u64),
+    Stored(String),
+}
+
+type KeyReader = Box<dyn Fn(DocId) -> Option<CollapseKey> + Send>;
+
+type Collapsed = HashMap<Option<CollapseKey>, Cursor>;
+
+/// Collects the best hit per distinct value of the field
+pub(crate) struct CollapseCollector {
+    field: Field,
+}
+
+impl CollapseCollector {
+    pub(crate) fn new(schema: &Schema, field_name: &str) -> Result<Self, IndexError> {
+        let field = schema.get_field(field_name);
+        if field.is_none() {
+            let reason = format!("Field: {} does not exist", field_name);
+            return Err(IndexError::schema_mismatch("Unable to collapse", &reason));
+        };
+        let field = field.unwrap();
+        let entry = schema.get_field_entry(field);
+        if !entry.is_int_fast() && !entry.is_stored() {
+            let reason = format!("Field: {} is neither a fast nor a stored field", field_name);
+            return Err(IndexError::schema_mismatch("Unable to collapse", &reason));
+        };
+        Ok(Self {
+            field,
+        })
+    }
+}
+
+pub(crate) struct CollapseSegmentCollector {
+    segment: SegmentLocalId,
+    key: KeyReader,
+    best: Collapsed,
+}
+
+impl Collector for CollapseCollector {
+    type Fruit = Collapsed;
+    type Child = CollapseSegmentCollector;
+
+    fn for_segment(&self, segment: SegmentLocalId, segment_reader: &SegmentReader) -> tantivy::Result<Self::Child> {
+        Ok(CollapseSegmentCollector {
+            segment,
+            key: key_reader(segment_reader, self.field),
+            best: HashMap::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<Collapsed>) -> tantivy::Result<Collapsed> {
+        let mut best = Collapsed::new();
+        for (key, cursor) in segment_fruits.into_iter().flatten() {
+            keep_best(&mut best, key, cursor);
+        };
+        Ok(best)
+    }
+}
+
+impl SegmentCollector for CollapseSegmentCollector {
+    type Fruit = Collapsed;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let cursor = Cursor {
+            score,
+            doc_address: DocAddress(self.segment, doc),
+        };
+        keep_best(&mut self.best, (self.key)(doc), cursor);
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        self.best
+    }
+}
+
+fn keep_best(best: &mut Collapsed, key: Option<CollapseKey>, cursor: Cursor) {
+    let current = best.entry(key).or_insert(cursor);
+    if cursor < *current {
+        *current = cursor;
+    };
+}
+
+/// Reads the value of the single valued fast field, falls back to the first stored value
+fn key_reader(segment_reader: &SegmentReader, field: Field) -> KeyReader {
+    let fast_fields = segment_reader.fast_fields();
+    if let Some(reader) = fast_fields.u64(field) {
+        return Box::new(move |doc| Some(CollapseKey::Fast(reader.get(doc))));
+    };
+    if let Some(reader) = fast_fields.i64(field) {
+        return Box::new(move |doc| Some(CollapseKey::Fast(reader.get(doc) as u64)));
+    };
+    if let Some(reader) = fast_fields.f64(field) {
+        return Box::new(move |doc| Some(CollapseKey::Fast(reader.get(doc).to_bits())));
+    };
+    if let Some(reader) = fast_fields.date(field) {
+        return Box::new(move |doc| Some(CollapseKey::Fast(reader.get(doc).timestamp() as u64)));
+    };
+    let store = segment_reader.get_store_reader();
+    Box::new(move |doc| {
+        let doc = store.get(doc).ok()?;
+        let value = doc.get_first(field)?;
+        serde_json::to_string(value).ok().map(CollapseKey::Stored)
+    })
+}
+
+/// Collects the page after the token, the token of the last hit continues it
+pub(crate) fn page_docs(searcher: &Searcher, query: &dyn Query, limit: usize, after: Option<&str>) -> Result<Page<DocAddress>, IndexError> {
+    if limit == 0 {
+        return Ok(Page::new(Vec::new(), None));
+    };
+    let after = match after {
+        Some(token) => Some(Cursor::decode(token)?),
+        None => None,
+    };
+    let cursors = searcher.search(query, &AfterCollector::new(limit, after))?;
+    let token = if cursors.len() == limit {
+        cursors.last().map(Cursor::encode)
+    } else {
+        None
+    };
+    let hits = cursors.into_iter().map(|cursor| Hit::new(Some(cursor.score), cursor.doc_address)).collect();
+    Ok(Page::new(hits, token))
+}
+
+/// Lazily iterates over every document matching a query, segment by segment
+/// Addresses are collected a batch at a time and documents are fetched as they are consumed
+/// A searcher is leased from the reader pool until the scan is dropped
+pub struct Scan<'a, T> {
+    surfer: &'a Surfer,
+    name: String,
+    searcher: LeasedItem<Searcher>,
+    weight: Box<dyn Weight>,
+    segment: usize,
+    scorer: Option<Box<dyn Scorer>>,
+    batch: VecDeque<DocAddress>,
+    batch_size: usize,
+    marker: PhantomData<T>,
+}
+
+impl<'a, T> Scan<'a, T> {
+    pub(crate) fn new(surfer: &'a Surfer, name: &str, searcher: LeasedItem<Searcher>, query: &dyn Query) -> Result<Self, IndexError> {
+        let weight = query.weight(&searcher, false)?;
+        Ok(Self {
+            surfer,
+            name: name.to_string(),
+            searcher,
+            weight,
+            segment: 0,
+            scorer: None,
+            batch: VecDeque::new(),
+            batch_size: 100,
+            marker: PhantomData,
+        })
+    }
+    /// Addresses collected at a time - default is 100
+    pub fn set_batch_size(&mut self, batch_size: usize) {
+        self.batch_size = batch_size.max(1);
+    }
+    /// Collects the next batch of live document addresses
+    fn fill(&mut self) -> Result<(), IndexError> {
+        let segment_readers = self.searcher.segment_readers();
+        while self.batch.len() < self.batch_size && self.segment < segment_readers.len() {
+            let segment_reader = &segment_readers[self.segment];
+            if self.scorer.is_none() {
+                self.scorer = Some(self.weight.scorer(segment_reader, 1.0)?);
+            };
+            let scorer = self.scorer.as_mut().unwrap();
+            let mut exhausted = true;
+            while scorer.advance() {
+                let doc = scorer.doc();
+                if segment_reader.is_deleted(doc) {
+                    continue;
+                };
+                self.batch.push_back(DocAddress(self.segment as u32, doc));
+                if self.batch.len() >= self.batch_size {
+                    exhausted = false;
+                    break;
+                };
+            };
+            if exhausted {
+                self.scorer = None;
+                self.segment += 1;
+            };
+        };
+        Ok(())
+    }
+    fn fetch(&self, doc_address: DocAddress) -> Result<T, IndexError> where T: DeserializeOwned {
+        let doc = self.searcher.doc(doc_address)?;
+        let doc = self.surfer.jsonify(&self.name, &doc)?;
+        as_struct(&doc)
+    }
+}
+
+impl<'a, T: DeserializeOwned> Iterator for Scan<'a, T> {
+    type Item = Result<T, IndexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.batch.is_empty() {
+            if let Err(e) = self.fill() {
+                self.segment = self.searcher.segment_readers().len();
+                return Some(Err(e));
+            };
+        };
+        let doc_address = self.batch.pop_front()?;
+        Some(self.fetch(doc_address))
+    }
+}
+
+/// Collects the page of documents & counts the matches in the same search, score is None when sorted by field
+pub(crate) fn top_docs(searcher: &Searcher, query: &dyn Query, options: &SearchOptions) -> Result<SearchResult<DocAddress>, IndexError> {
+    let limit = options.limit().unwrap_or(10);
+    if limit == 0 {
+        let total = searcher.search(query, &Count)?;
+        return Ok(SearchResult::new(total, Vec::new()));
+    };
+    let offset = options.offset();
+    let collector = TopDocs::with_limit(limit + offset);
+
+    let (total, docs): (usize, Vec<(Option<Score>, DocAddress)>) = match (options.collapse(), options.order_by()) {
+        (Some(_), Some(_)) => {
+            return Err(IndexError::new("Unable to collapse", "Collapsed hits are ranked by relevance, remove the sort"));
+        }
+        (Some(field), None) => {
+            let collector = CollapseCollector::new(searcher.schema(), field)?;
+            let (total, best) = searcher.search(query, &(Count, collector))?;
+            let mut cursors: Vec<Cursor> = best.into_values().collect();
+            cursors.sort();
+            cursors.truncate(limit + offset);
+            (total, cursors.into_iter().map(|cursor| (Some(cursor.score), cursor.doc_address)).collect())
+        }
+        (None, Some((field, order))) => {
+            let (total, docs) = order_by_field(searcher, query, collector, field, order)?;
+            (total, docs.into_iter().map(|doc_address| (None, doc_address)).collect())
+        }
+        (None, None) => {
+            let (total, docs) = searcher.search(query, &(Count, collector))?;
+            (total, docs.into_iter().map(|(doc_score, doc_address)| (Some(doc_score), doc_address)).collect())
+        }
+    };
+
+    let hits = docs.into_iter()
+        .skip(offset)
+        .filter(|(doc_score, _)| match (doc_score, options.score()) {
+            (Some(doc_score), Some(score)) => *doc_score >= score,
+            _ => true
+        })
+        .map(|(doc_score, doc_address)| Hit::new(doc_score, doc_address))
+        .collect();
+    Ok(SearchResult::new(total, hits))
+}
+
+/// Sorts using the fast field reader matching the field type
+fn order_by_field(searcher: &Searcher, query: &dyn Query, collector: TopDocs, field_name: &str, order: Order) -> Result<(usize, Vec<DocAddress>), IndexError> {
+    let schema = searcher.schema();
+    let field = schema.get_field(field_name);
+    if field.is_none() {
+        let reason = format!("Field: {} does not exist", field_name);
+        return Err(IndexError::schema_mismatch("Unable to sort", &reason));
+    };
+    let field = field.unwrap();
+    let entry = schema.get_field_entry(field);
+    let cardinality = match entry.field_type() {
+        FieldType::U64(options)
+        | FieldType::I64(options)
+        | FieldType::F64(options)
+        | FieldType::Date(options) => options.get_fastfield_cardinality(),
+        _ => None,
+    };
+    match cardinality {
+        Some(Cardinality::SingleValue) => {}
+        Some(Cardinality::MultiValues) => {
+            let reason = format!("Field: {} is a multi-valued fast field", field_name);
+            return Err(IndexError::schema_mismatch("Unable to sort", &reason));
+        }
+        None => {
+            let reason = format!("Field: {} is not a fast field", field_name);
+            return Err(IndexError::schema_mismatch("Unable to sort", &reason));
+        }
+    };
+
+    // Single valued fast fields have a reader in every segment
+
+    let docs = match (entry.field_type(), order) {
+        (FieldType::U64(_), Order::Desc) => {
+            addresses(searcher.search(query, &(Count, collector.order_by_u64_field(field)))?)
+        }
+        (FieldType::U64(_), Order::Asc) => {
+            let collector = collector.custom_score(move |segment_reader: &SegmentReader| {
+                let reader = segment_reader.fast_fields().u64(field).unwrap();
+                move |doc: DocId| Reverse(reader.get(doc))
+            });
+            addresses(searcher.search(query, &(Count, collector))?)
+        }
+        (FieldType::I64(_), Order::Desc) => {
+            let collector = collector.custom_score(move |segment_reader: &SegmentReader| {
+                let reader = segment_reader.fast_fields().i64(field).unwrap();
+                move |doc: DocId| reader.get(doc)
+            });
+            addresses(searcher.search(query, &(Count, collector))?)
+        }
+        (FieldType::I64(_), Order::Asc) => {
+            let collector = collector.custom_score(move |segment_reader: &SegmentReader| {
+                let reader = segment_reader.fast_fields().i64(field).unwrap();
+                move |doc: DocId| Reverse(reader.get(doc))
+            });
+            addresses(searcher.search(query, &(Count, collector))?)
+        }
+        (FieldType::F64(_), Order::Desc) => {
+            let collector = collector.custom_score(move |segment_reader: &SegmentReader| {
+                let reader = segment_reader.fast_fields().f64(field).unwrap();
+                move |doc: DocId| reader.get(doc)
+            });
+            addresses(searcher.search(query, &(Count, collector))?)
+        }
+        (FieldType::F64(_), Order::Asc) => {
+            let collector = collector.custom_score(move |segment_reader: &SegmentReader| {
+                let reader = segment_reader.fast_fields().f64(field).unwrap();
+                move |doc: DocId| Reverse(reader.get(doc))
+            });
+            addresses(searcher.search(query, &(Count, collector))?)
+        }
+        (FieldType::Date(_), Order::Desc) => {
+            let collector = collector.custom_score(move |segment_reader: &SegmentReader| {
+                let reader = segment_reader.fast_fields().date(field).unwrap();
+                move |doc: DocId| reader.get(doc)
+            });
+            addresses(searcher.search(query, &(Count, collector))?)
+        }
+        (FieldType::Date(_), Order::Asc) => {
+            let collector = collector.custom_score(move |segment_reader: &SegmentReader| {
+                let reader = segment_reader.fast_fields().date(field).unwrap();
+                move |doc: DocId| Reverse(reader.get(doc))
+            });
+            addresses(searcher.search(query, &(Count, collector))?)
+        }
+        _ => {
+            let reason = format!("Field: {} is not numeric", field_name);
+            return Err(IndexError::schema_mismatch("Unable to sort", &reason));
+        }
+    };
+    Ok(docs)
+}
+
+fn addresses<T>((total, docs): (usize, Vec<(T, DocAddress)>)) -> (usize, Vec<DocAddress>) {
+    (total, docs.into_iter().map(|(_, doc_address)| doc_address).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_default_search_options() {
+        let computed = SearchOptions::default();
+        let expected = SearchOptions::new(None, 0, None);
+        assert_eq!(computed, expected);
+    }
+
+    #[test]
+    fn validate_hit() {
+        let hit = Hit::new(Some(1.5), "42".to_string());
+        assert_eq!(hit.score(), Some(1.5));
+        assert_eq!(hit.doc(), "42");
+        let hit = hit.map(|doc| doc.parse::<u64>().unwrap());
+        assert_eq!(hit.score(), Some(1.5));
+        assert_eq!(hit.into_doc(), 42);
+    }
+
+    #[test]
+    fn validate_cursor() {
+        let cursor = Cursor {
+            score: 1.25,
+            doc_address: DocAddress(2, 42),
+        };
+        let token = cursor.encode();
+        assert_eq!(Cursor::decode(&token).unwrap(), cursor);
+        assert!(Cursor::decode("nonsense").is_err());
+        assert!(Cursor::decode(&format!("{}zz", &token[..22])).is_err());
+
+        let better = Cursor {
+            score: 2.0,
+            doc_address: DocAddress(3, 0),
+        };
+        let tie = Cursor {
+            score: 1.25,
+            doc_address: DocAddress(2, 43),
+        };
+        assert!(better < cursor);
+        assert!(cursor < tie);
+
+        let mut collector = AfterSegmentCollector {
+            segment: 2,
+            limit: 2,
+            after: Some(cursor),
+            heap: BinaryHeap::new(),
+        };
+        collector.collect(41, 1.25);
+        collector.collect(43, 1.25);
+        collector.collect(44, 0.5);
+        collector.collect(45, 1.0);
+        let computed: Vec<DocAddress> = collector.harvest().into_iter().map(|cursor| cursor.doc_address).collect();
+        assert_eq!(computed, vec![DocAddress(2, 43), DocAddress(2, 45)]);
+    }
+
+    #[test]
+    fn validate_search_options() {
+        let mut computed = SearchOptions::default();
+        computed.set_limit(5);
+        computed.set_offset(10);
+        computed.set_score(0.5);
+        computed.set_order_by("price", Order::Asc);
+        assert_eq!(computed.limit(), Some(5));
+        assert_eq!(computed.offset(), 10);
+        assert_eq!(computed.score(), Some(0.5));
+        assert_eq!(computed.order_by(), Some(("price", Order::Asc)));
+    }
+}
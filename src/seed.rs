@@ -1,12 +1,21 @@
 use std::path::{Path, PathBuf};
 use std::fs::create_dir_all;
 
-use tantivy::directory::MmapDirectory;
-use tantivy::{Index, ReloadPolicy, IndexWriter, IndexReader};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tantivy::directory::{Directory, MmapDirectory};
+use tantivy::directory::INDEX_WRITER_LOCK;
+use tantivy::directory::error::LockError;
+use tantivy::{Index, ReloadPolicy, IndexWriter, IndexReader, TantivyError};
 
 use crate::prelude::*;
 use tantivy::schema::Schema;
+use serde::Deserialize;
+
 
+/// Pause between attempts to lock a busy index writer
+const LOCK_RETRY: Duration = Duration::from_millis(50);
 
 /// Resolve home
 pub(crate) fn resolve_home<T: AsRef<str>>(home: Option<T>) -> Result<PathBuf, IndexError> {
@@ -38,7 +47,7 @@ pub(crate) fn open_mmap_directory(path: PathBuf) -> Result<MmapDirectory, IndexE
 
 
 /// Open a store or create & open using a schema
-pub(crate) fn open_index(dir: MmapDirectory, schema: Option<&Schema>) -> Result<Index, IndexError> {
+pub(crate) fn open_index<D: Directory>(dir: D, schema: Option<&Schema>) -> Result<Index, IndexError> {
     let index = if Index::exists(&dir) {
         Index::open(dir)
     } else {
@@ -52,38 +61,89 @@ pub(crate) fn open_index(dir: MmapDirectory, schema: Option<&Schema>) -> Result<
         let schema = schema.unwrap();
         Index::create(dir, schema.clone())
     }?;
+    register_tokenizers(&index, None, &Tokenizers::new())?;
+    Ok(index)
+}
 
+/// Create an index held in RAM only, gone on drop
+pub(crate) fn open_index_in_ram(schema: &Schema, analysis: Option<&Analysis>, tokenizers: &Tokenizers) -> Result<Index, IndexError> {
+    let schema = match analysis {
+        Some(analysis) => analysis.schema(schema),
+        None => schema.clone(),
+    };
+    let index = Index::create_in_ram(schema);
+    register_tokenizers(&index, analysis, tokenizers)?;
     Ok(index)
 }
 
+/// Read the schema of an index already on disk
+pub(crate) fn open_existing_schema(path: PathBuf) -> Result<Schema, IndexError> {
+    if !path.exists() {
+        let reason = format!("Directory: {:?} does not exist", path);
+        return Err(IndexError::new("Unable to open existing index", &reason));
+    };
+    let dir = MmapDirectory::open(&path)?;
+    if !Index::exists(&dir) {
+        let reason = format!("Directory: {:?} does not hold an index", path);
+        return Err(IndexError::new("Unable to open existing index", &reason));
+    };
+    let index = Index::open(dir)?;
+    Ok(index.schema())
+}
+
+/// Parts of meta.json an index cannot open without
+#[derive(Deserialize)]
+struct Meta {
+    #[allow(dead_code)]
+    segments: Vec<serde_json::Value>,
+    #[allow(dead_code)]
+    schema: Schema,
+}
+
+/// Does the directory hold an index with a readable meta.json, creates nothing
+pub(crate) fn probe_index(home: &str, name: &str) -> bool {
+    let meta = Path::new(home).join(name).join("meta.json");
+    match std::fs::read_to_string(meta) {
+        Ok(meta) => serde_json::from_str::<Meta>(&meta).is_ok(),
+        Err(_) => false,
+    }
+}
+
 /// Convenience method to open writer
 pub(crate) fn open_index_writer(index: &Index) -> Result<IndexWriter, IndexError> {
-    let index_writer = index.writer(50_000_000)
-        .map_err(|e| {
-            let reason = e.to_string();
-            let error = IndexError::new(
-                "Unable to create index writer",
-                reason.as_str(),
-            );
-            error
-        })?;
+    let index_writer = index.writer(50_000_000).map_err(|e| match e {
+        TantivyError::LockFailure(LockError::LockBusy, _) => IndexError::from(e),
+        e => IndexError::tantivy("Unable to create index writer", e),
+    })?;
     Ok(index_writer)
 }
 
+/// Opens the writer, retrying until the timeout while another writer holds the lock of the index at the location
+pub(crate) fn open_index_writer_waiting(index: &Index, location: &str, timeout: Option<Duration>) -> Result<IndexWriter, IndexError> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    loop {
+        match open_index_writer(index) {
+            Err(IndexError::Locked { .. }) if deadline.is_some_and(|deadline| Instant::now() < deadline) => {
+                thread::sleep(LOCK_RETRY);
+            }
+            Err(IndexError::Locked { .. }) => {
+                let lock = Path::new(location).join(&INDEX_WRITER_LOCK.filepath);
+                let hint = format!("Another writer holds {} through {}, in this process or another", location, lock.display());
+                return Err(IndexError::locked(hint));
+            }
+            writer => return writer,
+        };
+    }
+}
+
 
 /// Convenience method to open reader
 pub(crate) fn open_index_reader(index: &Index) -> Result<IndexReader, IndexError> {
     let index_reader = index
         .reader_builder()
         .reload_policy(ReloadPolicy::OnCommit)
-        .try_into().map_err(|e| {
-        let reason = e.to_string();
-        let error = IndexError::new(
-            "Unable to create index reader",
-            reason.as_str(),
-        );
-        error
-    })?;
+        .try_into()
+        .map_err(|e| IndexError::tantivy("Unable to create index reader", e))?;
     Ok(index_reader)
 }
 
@@ -214,4 +274,22 @@ mod tests {
         let reader = open_index_reader(&index);
         assert!(reader.is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn validate_probe_index() {
+        let home = random_string(Some(10));
+        assert!(!probe_index(&home, "missing"));
+        assert!(!Path::new(&home).exists());
+
+        let data = utils::as_value(&Dummy::default()).unwrap();
+        let schema = utils::to_schema(&data, None).unwrap();
+        let path = Path::new(&home).join("dummy");
+        let dir = open_mmap_directory(path.clone()).unwrap();
+        let _ = open_index(dir, Some(&schema)).unwrap();
+        assert!(probe_index(&home, "dummy"));
+
+        std::fs::write(path.join("meta.json"), "{}").unwrap();
+        assert!(!probe_index(&home, "dummy"));
+        let _ = remove_dir_all(&home);
+    }
+}
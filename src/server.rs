@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::thread;
+
+use serde_json::Value as JsonValue;
+
+use crate::prelude::*;
+
+/// Incoming HTTP request, one per connection
+#[derive(Clone, Debug, PartialEq)]
+pub struct Request {
+    method: String,
+    path: String,
+    params: HashMap<String, String>,
+    body: String,
+}
+
+impl Request {
+    pub fn new(method: &str, target: &str, body: &str) -> Self {
+        let (path, params) = match target.find('?') {
+            Some(i) => (&target[..i], parse_params(&target[i + 1..])),
+            None => (target, HashMap::new()),
+        };
+        Self {
+            method: method.to_string(),
+            path: path.to_string(),
+            params,
+            body: body.to_string(),
+        }
+    }
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.get(name).map(|value| value.as_str())
+    }
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+}
+
+/// JSON response
+#[derive(Clone, Debug, PartialEq)]
+pub struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    pub fn new(status: u16, body: String) -> Self {
+        Self {
+            status,
+            body,
+        }
+    }
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+    fn error(status: u16, error: &IndexError) -> Self {
+        let body = serde_json::to_string(error).unwrap_or_default();
+        Self::new(status, body)
+    }
+    fn not_found(message: &str, reason: &str) -> Self {
+        Self::error(404, &IndexError::new(message, reason))
+    }
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            201 => "Created",
+            400 => "Bad Request",
+            404 => "Not Found",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// Routes the request to the Surfer
+/// * `PUT /indexes/{name}` creates the index from a sample JSON object
+/// * `POST /indexes/{name}/docs` inserts a JSON object or an array of objects
+/// * `GET /indexes/{name}/search?q=..&limit=..` returns the matching documents as a JSON array, q defaults to *
+pub fn handle(surfer: &Surfer, request: &Request) -> Response {
+    let segments: Vec<&str> = request.path().trim_matches('/').split('/').collect();
+    let result = match (request.method(), segments.as_slice()) {
+        ("PUT", ["indexes", name]) => create(surfer, name, request),
+        ("POST", ["indexes", name, "docs"]) => insert(surfer, name, request),
+        ("GET", ["indexes", name, "search"]) => search(surfer, name, request),
+        _ => Ok(Response::not_found("Unable to route request", &format!("Route: {} {} does not exist", request.method(), request.path()))),
+    };
+    match result {
+        Ok(response) => response,
+        Err(e) => Response::error(400, &e),
+    }
+}
+
+fn create(surfer: &Surfer, name: &str, request: &Request) -> Result<Response, IndexError> {
+    let sample: JsonValue = serde_json::from_str(request.body())?;
+    if !sample.is_object() {
+        return Err(IndexError::new("Unable to create index", "Sample must be a JSON object"));
+    };
+    surfer.add_index(name, &sample)?;
+    Ok(Response::new(201, serde_json::json!({ "created": name }).to_string()))
+}
+
+fn insert(surfer: &Surfer, name: &str, request: &Request) -> Result<Response, IndexError> {
+    if surfer.schema(name).is_none() {
+        return Ok(Response::error(404, &IndexError::unknown_index(name)));
+    };
+    let docs = match serde_json::from_str(request.body())? {
+        JsonValue::Array(docs) => docs,
+        doc => vec![doc],
+    };
+    surfer.insert_structs(name, &docs)?;
+    Ok(Response::new(200, serde_json::json!({ "inserted": docs.len() }).to_string()))
+}
+
+fn search(surfer: &Surfer, name: &str, request: &Request) -> Result<Response, IndexError> {
+    let query = request.param("q").unwrap_or("*");
+    let limit = match request.param("limit") {
+        Some(limit) => Some(limit.parse::<usize>().map_err(|e| IndexError::new("Invalid limit", &e.to_string()))?),
+        None => None,
+    };
+    let docs = surfer.read_string(name, query, limit, None)?;
+    match docs {
+        Some(docs) => Ok(Response::new(200, format!("[{}]", docs.join(",")))),
+        None => Ok(Response::error(404, &IndexError::unknown_index(name))),
+    }
+}
+
+/// Serves the Surfer over HTTP, blocks & handles each connection on its own thread
+pub fn serve<A: ToSocketAddrs>(surfer: Arc<Surfer>, addr: A) -> Result<(), IndexError> {
+    let listener = TcpListener::bind(addr)?;
+    serve_on(surfer, listener)
+}
+
+/// Serves on a bound listener e.g. on port 0
+pub fn serve_on(surfer: Arc<Surfer>, listener: TcpListener) -> Result<(), IndexError> {
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let surfer = surfer.clone();
+        thread::spawn(move || {
+            let _ = respond(&surfer, stream);
+        });
+    };
+    Ok(())
+}
+
+fn respond(surfer: &Surfer, mut stream: TcpStream) -> Result<(), IndexError> {
+    let response = match read_request(&stream) {
+        Ok(request) => handle(surfer, &request),
+        Err(e) => Response::error(400, &e),
+    };
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status(),
+        response.reason(),
+        response.body().len(),
+    );
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(response.body().as_bytes())?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn read_request(stream: &TcpStream) -> Result<Request, IndexError> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method.to_string(), target.to_string()),
+        _ => return Err(IndexError::new("Invalid request", "Malformed request line")),
+    };
+
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        };
+        if let Some(i) = header.find(':') {
+            if header[..i].eq_ignore_ascii_case("content-length") {
+                length = header[i + 1..].trim().parse().unwrap_or(0);
+            };
+        };
+    };
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    Ok(Request::new(&method, &target, &String::from_utf8_lossy(&body)))
+}
+
+fn parse_params(query: &str) -> HashMap<String, String> {
+    query.split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.find('=') {
+            Some(i) => (decode(&pair[..i]), decode(&pair[i + 1..])),
+            None => (decode(pair), String::new()),
+        })
+        .collect()
+}
+
+/// Percent decoding with + as space
+fn decode(component: &str) -> String {
+    let bytes = component.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    None => decoded.push(b'%'),
+                };
+            }
+            byte => decoded.push(byte),
+        };
+        i += 1;
+    };
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+    use std::fs::remove_dir_all;
+
+    #[test]
+    fn validate_decode() {
+        assert_eq!(decode("title%3Asea+wolf"), "title:sea wolf");
+        assert_eq!(decode("100%"), "100%");
+        let request = Request::new("GET", "/indexes/books/search?q=title%3Asea&limit=5", "");
+        assert_eq!(request.path(), "/indexes/books/search");
+        assert_eq!(request.param("q"), Some("title:sea"));
+        assert_eq!(request.param("limit"), Some("5"));
+    }
+
+    #[test]
+    fn validate_handle() {
+        let home = format!("tmp/{}", random_string(None));
+        let mut builder = SurferBuilder::default();
+        builder.set_home(&home);
+        let surfer = Surfer::try_from(builder).unwrap();
+
+        let computed = handle(&surfer, &Request::new("PUT", "/indexes/books", r#"{"title": "", "pages": 1}"#));
+        assert_eq!(computed.status(), 201);
+
+        let docs = r#"[{"title": "The Old Man and the Sea", "pages": 120}, {"title": "The Sea Wolf", "pages": 300}]"#;
+        let computed = handle(&surfer, &Request::new("POST", "/indexes/books/docs", docs));
+        assert_eq!(computed, Response::new(200, r#"{"inserted":2}"#.to_string()));
+
+        let computed = handle(&surfer, &Request::new("GET", "/indexes/books/search?q=title%3Aold", ""));
+        assert_eq!(computed, Response::new(200, r#"[{"pages":120,"title":"The Old Man and the Sea"}]"#.to_string()));
+
+        let computed = handle(&surfer, &Request::new("GET", "/indexes/missing/search?q=sea", ""));
+        assert_eq!(computed.status(), 404);
+        assert!(computed.body().contains(r#""kind":"UnknownIndex""#));
+        let computed = handle(&surfer, &Request::new("POST", "/indexes/books/docs", "nonsense"));
+        assert_eq!(computed.status(), 400);
+        let computed = handle(&surfer, &Request::new("DELETE", "/indexes/books", ""));
+        assert_eq!(computed.status(), 404);
+
+        drop(surfer);
+        let _ = remove_dir_all(home);
+    }
+
+    #[test]
+    fn validate_serve_on() {
+        let home = format!("tmp/{}", random_string(None));
+        let mut builder = SurferBuilder::default();
+        builder.set_home(&home);
+        let surfer = Arc::new(Surfer::try_from(builder).unwrap());
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let served = surfer.clone();
+        thread::spawn(move || serve_on(served, listener));
+
+        let send = |request: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream.write_all(request.as_bytes()).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        let sample = r#"{"title": ""}"#;
+        let computed = send(&format!("PUT /indexes/books HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}", sample.len(), sample));
+        assert!(computed.starts_with("HTTP/1.1 201 Created"));
+
+        let doc = r#"{"title": "The Old Man and the Sea"}"#;
+        let computed = send(&format!("POST /indexes/books/docs HTTP/1.1\r\ncontent-length: {}\r\n\r\n{}", doc.len(), doc));
+        assert!(computed.starts_with("HTTP/1.1 200 OK"));
+
+        let computed = send("GET /indexes/books/search?q=title:sea HTTP/1.1\r\n\r\n");
+        assert!(computed.starts_with("HTTP/1.1 200 OK"));
+        assert!(computed.ends_with(r#"[{"title":"The Old Man and the Sea"}]"#));
+
+        let _ = remove_dir_all(home);
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+use tantivy::schema::{FieldType, Schema};
+
+use crate::prelude::*;
+
+/// What an insert does with a field of the schema holding null or missing from the document
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NullPolicy {
+    /// The document is indexed without the field
+    Skip,
+    /// The declared default of the field is indexed instead, fields without one are skipped
+    Default,
+    /// The document is rejected with an error naming the field
+    Reject,
+}
+
+/// Default impl skips the field
+impl Default for NullPolicy {
+    fn default() -> Self {
+        NullPolicy::Skip
+    }
+}
+
+/// Per index rewrites of the incoming JSON before it turns into a document
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Shape {
+    /// JSON pointer along with the field it fills e.g. /user/address/city into city
+    pointers: Vec<(String, String)>,
+    #[serde(default)]
+    nulls: NullPolicy,
+    /// Value of the field when null or missing under the default policy, nested ones are dotted
+    #[serde(default)]
+    defaults: HashMap<String, JsonValue>,
+    /// Values converted to the type of their field e.g. "42" into a u64
+    #[serde(default)]
+    coerce: bool,
+    /// Fields coerced or strict against the index wide setting, nested ones are dotted
+    #[serde(default)]
+    coerced_fields: HashMap<String, bool>,
+}
+
+impl Shape {
+    pub(crate) fn set_null_policy(&mut self, policy: NullPolicy) {
+        self.nulls = policy;
+    }
+    pub(crate) fn set_default(&mut self, field: &str, value: JsonValue) {
+        self.defaults.insert(field.to_string(), value);
+    }
+    pub(crate) fn set_coercion(&mut self, coerce: bool) {
+        self.coerce = coerce;
+    }
+    pub(crate) fn set_field_coercion(&mut self, field: &str, coerce: bool) {
+        self.coerced_fields.insert(field.to_string(), coerce);
+    }
+    /// JSON as indexed, pointers applied first then the null policy & the coercion
+    pub(crate) fn apply(&self, schema: &Schema, data: JsonValue) -> Result<JsonValue, IndexError> {
+        let data = self.extract(data)?;
+        let mut data = match self.nulls {
+            NullPolicy::Skip => data,
+            NullPolicy::Default => self.fill(schema, data),
+            NullPolicy::Reject => self.reject(schema, data)?,
+        };
+        if self.coerce || self.coerced_fields.values().any(|coerce| *coerce) {
+            self.coerce_into(schema, None, &mut data);
+        };
+        Ok(data)
+    }
+    pub(crate) fn add_pointer(&mut self, pointer: &str, field: &str) {
+        self.pointers.retain(|(_, mapped)| mapped != field);
+        self.pointers.push((pointer.to_string(), field.to_string()));
+    }
+    /// Fields read through the pointers, pointers not found leave their field out
+    fn extract(&self, data: JsonValue) -> Result<JsonValue, IndexError> {
+        if self.pointers.is_empty() {
+            return Ok(data);
+        };
+        let mut shaped = JsonMap::new();
+        for (pointer, field) in &self.pointers {
+            if !pointer.starts_with('/') {
+                let reason = format!("Pointer: {} of field: {} does not start with /", pointer, field);
+                return Err(IndexError::new("Unable to extract field", &reason));
+            };
+            match data.pointer(pointer) {
+                None | Some(JsonValue::Null) => continue,
+                Some(value) => shaped.insert(field.to_string(), value.clone()),
+            };
+        };
+        Ok(JsonValue::Object(shaped))
+    }
+    fn fill(&self, schema: &Schema, mut data: JsonValue) -> JsonValue {
+        for field in absent_fields(schema, &data) {
+            if let Some(value) = self.defaults.get(&field) {
+                insert_dotted(&mut data, &field, value.clone());
+            };
+        };
+        data
+    }
+    fn reject(&self, schema: &Schema, data: JsonValue) -> Result<JsonValue, IndexError> {
+        match absent_fields(schema, &data).first() {
+            Some(field) => {
+                let reason = format!("Field: {} is null or missing", field);
+                Err(IndexError::schema_mismatch("Unable to insert document", &reason))
+            }
+            None => Ok(data),
+        }
+    }
+    fn is_coerced(&self, field: &str) -> bool {
+        self.coerced_fields.get(field).copied().unwrap_or(self.coerce)
+    }
+    /// Walks nested objects & arrays converting every value of a coerced field, objects landing on a text field are left whole
+    fn coerce_into(&self, schema: &Schema, prefix: Option<&str>, data: &mut JsonValue) {
+        let kv = match data.as_object_mut() {
+            Some(kv) => kv,
+            None => return,
+        };
+        for (key, value) in kv.iter_mut() {
+            let key = match prefix {
+                Some(prefix) => format!("{}.{}", prefix, key),
+                None => key.to_string(),
+            };
+            let field_type = schema.get_field(&key).map(|field| schema.get_field_entry(field).field_type());
+            let values: Vec<&mut JsonValue> = match value {
+                JsonValue::Array(items) => items.iter_mut().collect(),
+                value => vec![value],
+            };
+            for value in values {
+                match (value.is_object(), field_type) {
+                    (true, Some(FieldType::Str(_))) => continue,
+                    (true, _) => self.coerce_into(schema, Some(&key), value),
+                    (false, Some(field_type)) if self.is_coerced(&key) => {
+                        if let Some(coerced) = coerced(field_type, value) {
+                            *value = coerced;
+                        };
+                    }
+                    (false, _) => continue,
+                };
+            };
+        };
+    }
+}
+
+/// Value converted to the type of the field, None when it already matches or cannot be converted
+/// Floats convert into integers only when whole & strings are trimmed before parsing
+fn coerced(field_type: &FieldType, value: &JsonValue) -> Option<JsonValue> {
+    match (field_type, value) {
+        (FieldType::U64(_), JsonValue::String(text)) => text.trim().parse::<u64>().ok().map(JsonValue::from),
+        (FieldType::U64(_), JsonValue::Number(number)) if !number.is_u64() => {
+            match number.as_i64() {
+                Some(int) => u64::try_from(int).ok(),
+                None => whole(number.as_f64()?).filter(|float| *float >= 0.0 && *float <= u64::MAX as f64).map(|float| float as u64),
+            }.map(JsonValue::from)
+        }
+        (FieldType::I64(_), JsonValue::String(text)) => text.trim().parse::<i64>().ok().map(JsonValue::from),
+        (FieldType::I64(_), JsonValue::Number(number)) if !number.is_i64() => {
+            match number.as_u64() {
+                Some(int) => i64::try_from(int).ok(),
+                None => whole(number.as_f64()?).filter(|float| *float >= i64::MIN as f64 && *float <= i64::MAX as f64).map(|float| float as i64),
+            }.map(JsonValue::from)
+        }
+        (FieldType::F64(_), JsonValue::String(text)) => text.trim().parse::<f64>().ok().filter(|float| float.is_finite()).map(JsonValue::from),
+        (FieldType::Str(_), JsonValue::Number(number)) => Some(JsonValue::String(number.to_string())),
+        (FieldType::Str(_), JsonValue::Bool(b)) => Some(JsonValue::String(b.to_string())),
+        _ => None,
+    }
+}
+
+fn whole(float: f64) -> Option<f64> {
+    match float.fract() == 0.0 {
+        true => Some(float),
+        false => None,
+    }
+}
+
+/// Fields of the schema without any value in the document in schema order, internal fields excluded
+/// A field under an array or a whole JSON object e.g. items.sku of items or a declared map is present along with it
+fn absent_fields(schema: &Schema, data: &JsonValue) -> Vec<String> {
+    let flat = flatten(data);
+    let present = |field: &str| -> bool {
+        let holds = |key: &str| flat.get(key).is_some_and(|value| !value.is_null());
+        holds(field) || field.match_indices('.').any(|(i, _)| holds(&field[..i]))
+    };
+    schema.fields()
+        .map(|(_, entry)| entry.name())
+        .filter(|name| ![SOURCE, ALL, EXPIRES_AT].contains(name) && !present(name))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Sets the value of a dotted field within nested objects e.g. address.city
+fn insert_dotted(data: &mut JsonValue, field: &str, value: JsonValue) {
+    let mut parts: Vec<&str> = field.split('.').collect();
+    let last = parts.pop().unwrap();
+    let mut current = data;
+    for part in parts {
+        let object = match current.as_object_mut() {
+            Some(object) => object,
+            None => return,
+        };
+        let entry = object.entry(part.to_string()).or_insert_with(|| JsonValue::Object(JsonMap::new()));
+        if entry.is_null() {
+            *entry = JsonValue::Object(JsonMap::new());
+        };
+        current = entry;
+    };
+    if let Some(object) = current.as_object_mut() {
+        object.insert(last.to_string(), value);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_pointers() {
+        let mut shape = Shape::default();
+        let data = serde_json::json!({"user": {"name": "Jon", "address": {"city": "Winterfell"}}, "a/b": [1, 2]});
+        assert_eq!(shape.extract(data.clone()).unwrap(), data);
+
+        shape.add_pointer("/user/name", "name");
+        shape.add_pointer("/user/address/city", "city");
+        shape.add_pointer("/a~1b", "numbers");
+        shape.add_pointer("/user/age", "age");
+        let expected = serde_json::json!({"name": "Jon", "city": "Winterfell", "numbers": [1, 2]});
+        assert_eq!(shape.extract(data.clone()).unwrap(), expected);
+
+        shape.add_pointer("user", "name");
+        assert!(shape.extract(data).is_err());
+    }
+
+    #[test]
+    fn validate_null_policy() {
+        let mut builder = Schema::builder();
+        builder.add_text_field("title", tantivy::schema::TEXT);
+        builder.add_u64_field("address.zip", tantivy::schema::INDEXED);
+        builder.add_text_field("items.sku", tantivy::schema::TEXT);
+        builder.add_text_field(SOURCE, tantivy::schema::STORED);
+        let schema = builder.build();
+        let data = serde_json::json!({"title": null, "items": [{"sku": "apple"}]});
+        assert_eq!(absent_fields(&schema, &data), vec!["title".to_string(), "address.zip".to_string()]);
+
+        let mut shape = Shape::default();
+        assert_eq!(shape.apply(&schema, data.clone()).unwrap(), data);
+
+        shape.set_null_policy(NullPolicy::Default);
+        shape.set_default("address.zip", serde_json::json!(75001));
+        let expected = serde_json::json!({"title": null, "address": {"zip": 75001}, "items": [{"sku": "apple"}]});
+        assert_eq!(shape.apply(&schema, data.clone()).unwrap(), expected);
+
+        shape.set_null_policy(NullPolicy::Reject);
+        let computed = shape.apply(&schema, data).unwrap_err();
+        assert!(computed.reason().contains("Field: title is null or missing"));
+        assert_eq!(serde_json::from_str::<NullPolicy>(r#""reject""#).unwrap(), NullPolicy::Reject);
+    }
+
+    #[test]
+    fn validate_coercion() {
+        let mut builder = Schema::builder();
+        builder.add_text_field("title", tantivy::schema::TEXT);
+        builder.add_u64_field("pages", tantivy::schema::INDEXED);
+        builder.add_i64_field("items.delta", tantivy::schema::INDEXED);
+        builder.add_f64_field("price", tantivy::schema::INDEXED);
+        builder.add_u64_field("isbn", tantivy::schema::INDEXED);
+        let schema = builder.build();
+        let data = serde_json::json!({"title": 1984, "pages": " 328 ", "items": [{"delta": "-2"}, {"delta": 3.0}], "price": "9.5", "isbn": "978-0"});
+
+        let mut shape = Shape::default();
+        assert_eq!(shape.apply(&schema, data.clone()).unwrap(), data);
+
+        shape.set_coercion(true);
+        let expected = serde_json::json!({"title": "1984", "pages": 328, "items": [{"delta": -2}, {"delta": 3}], "price": 9.5, "isbn": "978-0"});
+        assert_eq!(shape.apply(&schema, data.clone()).unwrap(), expected);
+
+        shape.set_field_coercion("pages", false);
+        assert_eq!(shape.apply(&schema, data.clone()).unwrap()["pages"], serde_json::json!(" 328 "));
+
+        let mut shape = Shape::default();
+        shape.set_field_coercion("price", true);
+        let computed = shape.apply(&schema, data).unwrap();
+        assert_eq!(computed["price"], serde_json::json!(9.5));
+        assert_eq!(computed["pages"], serde_json::json!(" 328 "));
+        assert!(coerced(schema.get_field_entry(schema.get_field("pages").unwrap()).field_type(), &serde_json::json!(1.5)).is_none());
+    }
+}
@@ -0,0 +1,188 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use tantivy::query::{BoostQuery, Occur, Query, TermQuery};
+use tantivy::schema::{Field, FieldType, IndexRecordOption, Value};
+use tantivy::{Document, Index, Searcher, Term};
+
+use crate::prelude::*;
+
+/// Seed of a similar documents search
+#[derive(Clone, Debug, PartialEq)]
+pub enum Like {
+    /// Document which need not be in the index
+    Doc(JsonValue),
+    /// Top documents matching the query string
+    Query(String),
+}
+
+impl Like {
+    pub fn doc<T: Serialize>(doc: &T) -> Result<Self, IndexError> {
+        Ok(Like::Doc(serde_json::to_value(doc)?))
+    }
+    pub fn query(query: &str) -> Self {
+        Like::Query(query.to_string())
+    }
+}
+
+/// Which terms of the seed make up the similar documents query
+#[derive(Clone, Debug, PartialEq)]
+pub struct MoreLikeThis {
+    min_term_frequency: usize,
+    min_doc_frequency: u64,
+    max_doc_frequency: Option<u64>,
+    min_word_length: usize,
+    max_query_terms: usize,
+}
+
+/// Default impl keeps the 25 best terms seen at least once in the seed & in the index
+impl Default for MoreLikeThis {
+    fn default() -> Self {
+        Self {
+            min_term_frequency: 1,
+            min_doc_frequency: 1,
+            max_doc_frequency: None,
+            min_word_length: 0,
+            max_query_terms: 25,
+        }
+    }
+}
+
+impl MoreLikeThis {
+    /// Times a term must occur in the seed
+    pub fn set_min_term_frequency(&mut self, frequency: usize) {
+        self.min_term_frequency = frequency;
+    }
+    /// Documents of the index a term must occur in
+    pub fn set_min_doc_frequency(&mut self, frequency: u64) {
+        self.min_doc_frequency = frequency;
+    }
+    /// Documents of the index a term may occur in at most e.g. to leave out common words
+    pub fn set_max_doc_frequency(&mut self, frequency: u64) {
+        self.max_doc_frequency = Some(frequency);
+    }
+    /// Characters a term must have
+    pub fn set_min_word_length(&mut self, length: usize) {
+        self.min_word_length = length;
+    }
+    /// Best scoring terms to query for
+    pub fn set_max_query_terms(&mut self, terms: usize) {
+        self.max_query_terms = terms;
+    }
+    pub fn min_term_frequency(&self) -> usize {
+        self.min_term_frequency
+    }
+    pub fn min_doc_frequency(&self) -> u64 {
+        self.min_doc_frequency
+    }
+    pub fn max_doc_frequency(&self) -> Option<u64> {
+        self.max_doc_frequency
+    }
+    pub fn min_word_length(&self) -> usize {
+        self.min_word_length
+    }
+    pub fn max_query_terms(&self) -> usize {
+        self.max_query_terms
+    }
+}
+
+/// Occur & query pairs of a BooleanQuery
+pub(crate) type Clauses = Vec<(Occur, Box<dyn Query>)>;
+
+/// Should clauses for the terms of the seeds scored by tf-idf, boosted relative to the best term
+pub(crate) fn similar_clauses(index: &Index, searcher: &Searcher, seeds: &[Document], fields: &[Field], options: &MoreLikeThis) -> Result<Clauses, IndexError> {
+    let schema = index.schema();
+    let mut frequencies: HashMap<Term, usize> = HashMap::new();
+    for field in fields {
+        let entry = schema.get_field_entry(*field);
+        if !entry.is_indexed() || !matches!(entry.field_type(), FieldType::Str(_)) {
+            continue;
+        };
+        let tokenizer = index.tokenizer_for_field(*field)?;
+        for seed in seeds {
+            for value in seed.get_all(*field) {
+                let text = match value {
+                    Value::Str(text) => text,
+                    _ => continue,
+                };
+                tokenizer.token_stream(text).process(&mut |token| {
+                    if token.text.chars().count() >= options.min_word_length {
+                        *frequencies.entry(Term::from_field_text(*field, &token.text)).or_insert(0) += 1;
+                    };
+                });
+            };
+        };
+    };
+
+    let total = searcher.num_docs() as f32;
+    let mut scored: Vec<(f32, Term)> = frequencies.into_iter()
+        .filter(|(_, frequency)| *frequency >= options.min_term_frequency)
+        .filter_map(|(term, frequency)| {
+            let doc_frequency = searcher.doc_freq(&term);
+            if doc_frequency < options.min_doc_frequency || matches!(options.max_doc_frequency, Some(max) if doc_frequency > max) {
+                return None;
+            };
+            let doc_frequency = doc_frequency as f32;
+            let idf = (1.0 + (total - doc_frequency + 0.5) / (doc_frequency + 0.5)).ln();
+            Some((frequency as f32 * idf, term))
+        })
+        .collect();
+    scored.sort_by(|(a, a_term), (b, b_term)| b.partial_cmp(a).unwrap_or(Ordering::Equal).then_with(|| a_term.cmp(b_term)));
+    scored.truncate(options.max_query_terms);
+
+    let best = scored.first().map_or(1.0, |(score, _)| *score);
+    let clauses = scored.into_iter()
+        .map(|(score, term)| {
+            let query: Box<dyn Query> = Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs));
+            let boost = if best > 0.0 { score / best } else { 1.0 };
+            let query: Box<dyn Query> = Box::new(BoostQuery::new(query, boost));
+            (Occur::Should, query)
+        })
+        .collect();
+    Ok(clauses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::collector::Count;
+    use tantivy::query::BooleanQuery;
+    use tantivy::schema::{SchemaBuilder, TEXT, STORED};
+    use tantivy::doc;
+
+    #[test]
+    fn validate_similar_clauses() {
+        let mut builder = SchemaBuilder::new();
+        let title = builder.add_text_field("title", TEXT | STORED);
+        let index = Index::create_in_ram(builder.build());
+        let mut writer = open_index_writer(&index).unwrap();
+        writer.add_document(doc!(title => "The Old Man and the Sea"));
+        writer.add_document(doc!(title => "The Sea Wolf"));
+        writer.add_document(doc!(title => "Moby Dick"));
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let seeds = vec![doc!(title => "Sea of the wolf")];
+        let options = MoreLikeThis::default();
+        let clauses = similar_clauses(&index, &searcher, &seeds, &[title], &options).unwrap();
+        assert_eq!(clauses.len(), 3);
+        let query = BooleanQuery::from(clauses);
+        assert_eq!(searcher.search(&query, &Count).unwrap(), 2);
+
+        let mut options = MoreLikeThis::default();
+        options.set_max_doc_frequency(1);
+        options.set_min_word_length(4);
+        let clauses = similar_clauses(&index, &searcher, &seeds, &[title], &options).unwrap();
+        assert_eq!(clauses.len(), 1);
+        let query = BooleanQuery::from(clauses);
+        assert_eq!(searcher.search(&query, &Count).unwrap(), 1);
+
+        let mut options = MoreLikeThis::default();
+        options.set_min_term_frequency(2);
+        let clauses = similar_clauses(&index, &searcher, &seeds, &[title], &options).unwrap();
+        assert!(clauses.is_empty());
+    }
+}
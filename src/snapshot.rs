@@ -0,0 +1,139 @@
+use std::fs::{copy, create_dir_all, read_dir, write};
+use std::path::Path;
+
+use tantivy::{Index, TantivyError};
+use tantivy::directory::Directory;
+use tantivy::schema::Schema;
+
+use crate::prelude::*;
+
+/// Copies the committed segments & a matching meta.json of the index into an empty directory
+/// Holding the loaded metas keeps their files from being garbage collected by merges while copying
+pub(crate) fn snapshot_index(index: &Index, dest: &Path) -> Result<(), IndexError> {
+    if dest.exists() && dest.read_dir()?.next().is_some() {
+        let reason = format!("Directory: {:?} is not empty", dest);
+        return Err(IndexError::new("Unable to snapshot index", &reason));
+    };
+    create_dir_all(dest)?;
+
+    let metas = index.load_metas()?;
+    let directory = index.directory();
+    for segment in &metas.segments {
+        for path in segment.list_files() {
+            if !directory.exists(&path) {
+                continue;
+            };
+            // Raw bytes including the footer, open_read would strip it
+            let bytes = directory.atomic_read(&path).map_err(TantivyError::from)?;
+            write(dest.join(&path), bytes)?;
+        };
+    };
+    let mut meta = serde_json::to_vec_pretty(&metas)?;
+    meta.push(b'\n');
+    write(dest.join("meta.json"), meta)?;
+    Ok(())
+}
+
+/// Checks every segment file of the snapshot is present & passes its checksum
+pub(crate) fn validate_snapshot(path: &Path) -> Result<Schema, IndexError> {
+    let schema = open_existing_schema(path.to_path_buf())?;
+    let index = Index::open_in_dir(path)?;
+    let metas = index.load_metas()?;
+    let directory = index.directory();
+    for segment in &metas.segments {
+        for file in segment.list_files() {
+            if file.to_string_lossy().ends_with(".del") && !segment.has_deletes() {
+                continue;
+            };
+            if !directory.exists(&file) {
+                let reason = format!("File: {:?} is missing", file);
+                return Err(IndexError::new("Invalid snapshot", &reason));
+            };
+            if !directory.validate_checksum(&file).map_err(TantivyError::from)? {
+                let reason = format!("File: {:?} is damaged", file);
+                return Err(IndexError::new("Invalid snapshot", &reason));
+            };
+        };
+    };
+    Ok(schema)
+}
+
+/// Copies the files of a snapshot into a fresh directory, locks & the managed list are left out
+pub(crate) fn copy_snapshot(from: &Path, to: &Path) -> Result<(), IndexError> {
+    create_dir_all(to)?;
+    for entry in read_dir(from)? {
+        let path = entry?.path();
+        let file_name = path.file_name().and_then(|file_name| file_name.to_str()).unwrap_or_default();
+        if !path.is_file() || file_name.starts_with('.') {
+            continue;
+        };
+        copy(&path, to.join(file_name))?;
+    };
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::remove_dir_all;
+    use tantivy::schema::{SchemaBuilder, TEXT, STORED};
+    use tantivy::doc;
+
+    #[test]
+    fn validate_snapshot_index() {
+        let mut builder = SchemaBuilder::new();
+        let title = builder.add_text_field("title", TEXT | STORED);
+        let index = Index::create_in_ram(builder.build());
+        let mut writer = open_index_writer(&index).unwrap();
+        writer.add_document(doc!(title => "The Old Man and the Sea"));
+        writer.commit().unwrap();
+
+        let dest = format!("tmp/{}", random_string(None));
+        let path = Path::new(&dest);
+        assert!(!path.exists());
+        snapshot_index(&index, path).unwrap();
+        assert!(path.join("meta.json").exists());
+
+        let computed = Index::open_in_dir(path).unwrap();
+        assert!(computed.schema() == index.schema());
+        let searcher = computed.reader().unwrap().searcher();
+        assert_eq!(searcher.num_docs(), 1);
+        drop(searcher);
+
+        let computed = snapshot_index(&index, path);
+        assert!(computed.is_err());
+
+        let _ = remove_dir_all(path);
+    }
+
+    #[test]
+    fn validate_validate_snapshot() {
+        let mut builder = SchemaBuilder::new();
+        let title = builder.add_text_field("title", TEXT | STORED);
+        let index = Index::create_in_ram(builder.build());
+        let mut writer = open_index_writer(&index).unwrap();
+        writer.add_document(doc!(title => "The Old Man and the Sea"));
+        writer.commit().unwrap();
+
+        let dest = format!("tmp/{}", random_string(None));
+        let path = Path::new(&dest);
+        snapshot_index(&index, path).unwrap();
+        assert!(validate_snapshot(path).unwrap() == index.schema());
+
+        let copied = format!("tmp/{}", random_string(None));
+        copy_snapshot(path, Path::new(&copied)).unwrap();
+        assert!(validate_snapshot(Path::new(&copied)).is_ok());
+
+        let store = ls(&dest).unwrap().into_iter().find(|file| file.to_string_lossy().ends_with(".store")).unwrap();
+        let mut bytes = std::fs::read(&store).unwrap();
+        bytes[0] ^= 0xff;
+        write(&store, bytes).unwrap();
+        assert!(validate_snapshot(path).is_err());
+
+        let missing = format!("tmp/{}", random_string(None));
+        assert!(validate_snapshot(Path::new(&missing)).is_err());
+
+        let _ = remove_dir_all(path);
+        let _ = remove_dir_all(copied);
+    }
+}
@@ -0,0 +1,60 @@
+use serde_json::Value as JsonValue;
+
+use tantivy::schema::{Schema, SchemaBuilder, STORED};
+use tantivy::Document;
+
+/// Stored only text field holding the JSON inserted for indexes storing their source
+pub const SOURCE: &str = "_source";
+
+/// Schema along with the source field
+pub(crate) fn with_source(schema: &Schema) -> Schema {
+    if schema.get_field(SOURCE).is_some() {
+        return schema.clone();
+    };
+    let mut builder = SchemaBuilder::new();
+    for (_, entry) in schema.fields() {
+        builder.add_field(entry.clone());
+    };
+    builder.add_text_field(SOURCE, STORED);
+    builder.build()
+}
+
+/// Keeps the JSON as inserted when the schema has the source field
+pub(crate) fn stamp_source(schema: &Schema, document: &mut Document, data: &JsonValue) {
+    if let Some(field) = schema.get_field(SOURCE) {
+        document.add_text(field, &data.to_string());
+    };
+}
+
+/// JSON the document was inserted from
+pub(crate) fn source_of<'a>(schema: &Schema, document: &'a Document) -> Option<&'a str> {
+    let field = schema.get_field(SOURCE)?;
+    document.get_first(field)?.text()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::schema::TEXT;
+
+    #[test]
+    fn validate_source() {
+        let mut builder = SchemaBuilder::new();
+        builder.add_text_field("title", TEXT);
+        let plain = builder.build();
+        let schema = with_source(&plain);
+        let entry = schema.get_field_entry(schema.get_field(SOURCE).unwrap());
+        assert!(entry.is_stored() && !entry.is_indexed());
+        assert_eq!(serde_json::to_string(&with_source(&schema)).unwrap(), serde_json::to_string(&schema).unwrap());
+
+        let data = serde_json::json!({"title": "Dune", "extra": [1, 2]});
+        let mut document = Document::default();
+        stamp_source(&schema, &mut document, &data);
+        assert_eq!(source_of(&schema, &document), Some(r#"{"extra":[1,2],"title":"Dune"}"#));
+
+        let mut document = Document::default();
+        stamp_source(&plain, &mut document, &data);
+        assert_eq!(document.len(), 0);
+        assert!(source_of(&plain, &document).is_none());
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use tantivy::schema::Field;
+use tantivy::{Index, Searcher};
+
+use crate::prelude::*;
+
+/// Term of the index close to or completing the requested one
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Suggestion {
+    term: String,
+    distance: usize,
+    doc_freq: u64,
+}
+
+impl Suggestion {
+    pub fn new(term: String, distance: usize, doc_freq: u64) -> Self {
+        Self {
+            term,
+            distance,
+            doc_freq,
+        }
+    }
+    pub fn term(&self) -> &str {
+        &self.term
+    }
+    /// Edits away from the requested term, for completions the chars appended to the prefix
+    pub fn distance(&self) -> usize {
+        self.distance
+    }
+    /// Documents holding the term, deleted ones included until merged away
+    pub fn doc_freq(&self) -> u64 {
+        self.doc_freq
+    }
+}
+
+/// Terms of the text field within the edit distance, closest first then the most frequent
+/// The requested term goes through the tokenizer of the field & is itself left out
+pub(crate) fn suggest_terms(index: &Index, searcher: &Searcher, field_name: &str, term: &str, distance: u8, limit: usize) -> Result<Vec<Suggestion>, IndexError> {
+    let field = text_field(&index.schema(), field_name)?;
+    let term = analyze(index, field, term)?;
+    let term: Vec<char> = term.chars().collect();
+
+    let mut candidates: HashMap<String, (usize, u64)> = HashMap::new();
+    for segment_reader in searcher.segment_readers() {
+        let inverted_index = segment_reader.inverted_index(field);
+        let mut stream = inverted_index.terms().stream();
+        while stream.advance() {
+            let key = match std::str::from_utf8(stream.key()) {
+                Ok(key) => key,
+                Err(_) => continue,
+            };
+            let doc_freq = u64::from(stream.value().doc_freq);
+            if let Some((_, freq)) = candidates.get_mut(key) {
+                *freq += doc_freq;
+                continue;
+            };
+            let edits = edit_distance(&term, &key.chars().collect::<Vec<char>>());
+            if edits == 0 || edits > distance as usize {
+                continue;
+            };
+            candidates.insert(key.to_string(), (edits, doc_freq));
+        };
+    };
+
+    let mut suggestions: Vec<Suggestion> = candidates.into_iter()
+        .map(|(term, (distance, doc_freq))| Suggestion::new(term, distance, doc_freq))
+        .collect();
+    suggestions.sort_by(|a, b| a.distance.cmp(&b.distance)
+        .then_with(|| b.doc_freq.cmp(&a.doc_freq))
+        .then_with(|| a.term.cmp(&b.term)));
+    suggestions.truncate(limit);
+    Ok(suggestions)
+}
+
+/// Terms of the text field starting with the prefix, most frequent first then the shortest
+/// The prefix goes through the tokenizer of the field, completions of the whole prefix only
+pub(crate) fn complete_terms(index: &Index, searcher: &Searcher, field_name: &str, prefix: &str, limit: usize) -> Result<Vec<Suggestion>, IndexError> {
+    let field = text_field(&index.schema(), field_name)?;
+    let prefix = analyze(index, field, prefix)?;
+    let length = prefix.chars().count();
+
+    let mut completions: HashMap<String, u64> = HashMap::new();
+    for segment_reader in searcher.segment_readers() {
+        let inverted_index = segment_reader.inverted_index(field);
+        let mut stream = inverted_index.terms().range().ge(prefix.as_bytes()).into_stream();
+        while stream.advance() {
+            if !stream.key().starts_with(prefix.as_bytes()) {
+                break;
+            };
+            let key = match std::str::from_utf8(stream.key()) {
+                Ok(key) => key,
+                Err(_) => continue,
+            };
+            *completions.entry(key.to_string()).or_insert(0) += u64::from(stream.value().doc_freq);
+        };
+    };
+
+    let mut suggestions: Vec<Suggestion> = completions.into_iter()
+        .map(|(term, doc_freq)| {
+            let distance = term.chars().count() - length;
+            Suggestion::new(term, distance, doc_freq)
+        })
+        .collect();
+    suggestions.sort_by(|a, b| b.doc_freq.cmp(&a.doc_freq)
+        .then_with(|| a.distance.cmp(&b.distance))
+        .then_with(|| a.term.cmp(&b.term)));
+    suggestions.truncate(limit);
+    Ok(suggestions)
+}
+
+/// Regex pattern matching the terms starting with the prefix, for a RegexQuery
+pub(crate) fn prefix_pattern(index: &Index, field: Field, prefix: &str) -> Result<String, IndexError> {
+    let prefix = analyze(index, field, prefix)?;
+    let mut pattern = String::with_capacity(prefix.len() + 2);
+    for c in prefix.chars() {
+        if "\\.+*?()|[]{}^$#&-~".contains(c) {
+            pattern.push('\\');
+        };
+        pattern.push(c);
+    };
+    pattern.push_str(".*");
+    Ok(pattern)
+}
+
+/// First token of the text as indexed by the field, lowercase text when it yields none
+fn analyze(index: &Index, field: Field, text: &str) -> Result<String, IndexError> {
+    let tokenizer = index.tokenizer_for_field(field)?;
+    let mut token = None;
+    tokenizer.token_stream(text).process(&mut |t| {
+        if token.is_none() {
+            token = Some(t.text.clone());
+        };
+    });
+    Ok(token.unwrap_or_else(|| text.to_lowercase()))
+}
+
+/// Levenshtein distance over chars
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = Vec::with_capacity(b.len() + 1);
+        current.push(i + 1);
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == cb { 0 } else { 1 };
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        };
+        previous = current;
+    };
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::schema::{SchemaBuilder, TEXT, STRING, STORED, INDEXED};
+    use tantivy::doc;
+
+    #[test]
+    fn validate_edit_distance() {
+        let chars = |s: &str| s.chars().collect::<Vec<char>>();
+        assert_eq!(edit_distance(&chars("skiff"), &chars("skiff")), 0);
+        assert_eq!(edit_distance(&chars("skif"), &chars("skiff")), 1);
+        assert_eq!(edit_distance(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(edit_distance(&chars(""), &chars("sea")), 3);
+    }
+
+    #[test]
+    fn validate_suggest_terms() {
+        let mut builder = SchemaBuilder::new();
+        let title = builder.add_text_field("title", TEXT | STORED);
+        let pages = builder.add_u64_field("pages", INDEXED | STORED);
+        let index = Index::create_in_ram(builder.build());
+        let mut writer = open_index_writer(&index).unwrap();
+        writer.add_document(doc!(title => "The Old Man and the Sea", pages => 127u64));
+        writer.add_document(doc!(title => "The Sea Wolf", pages => 300u64));
+        writer.commit().unwrap();
+        writer.add_document(doc!(title => "Sea Wolves", pages => 300u64));
+        writer.add_document(doc!(title => "The Set", pages => 10u64));
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let computed = suggest_terms(&index, &searcher, "title", "Sae", 2, 10).unwrap();
+        let expected = vec![
+            Suggestion::new("sea".to_string(), 2, 3),
+            Suggestion::new("the".to_string(), 2, 3),
+            Suggestion::new("man".to_string(), 2, 1),
+            Suggestion::new("set".to_string(), 2, 1),
+        ];
+        assert_eq!(computed, expected);
+
+        let computed = suggest_terms(&index, &searcher, "title", "wolv", 1, 10).unwrap();
+        assert_eq!(computed, vec![Suggestion::new("wolf".to_string(), 1, 1)]);
+
+        let computed = suggest_terms(&index, &searcher, "title", "sea", 1, 1).unwrap();
+        assert_eq!(computed, vec![Suggestion::new("set".to_string(), 1, 1)]);
+
+        assert!(suggest_terms(&index, &searcher, "pages", "300", 1, 10).is_err());
+        assert!(suggest_terms(&index, &searcher, "author", "sea", 1, 10).is_err());
+    }
+
+    #[test]
+    fn validate_complete_terms() {
+        let mut builder = SchemaBuilder::new();
+        let title = builder.add_text_field("title", TEXT | STORED);
+        let sku = builder.add_text_field("sku", STRING);
+        let index = Index::create_in_ram(builder.build());
+        let mut writer = open_index_writer(&index).unwrap();
+        writer.add_document(doc!(title => "The Sea Wolf"));
+        writer.commit().unwrap();
+        writer.add_document(doc!(title => "Sea Wolves"));
+        writer.add_document(doc!(title => "The Wolf of Wall Street"));
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let computed = complete_terms(&index, &searcher, "title", "Wo", 10).unwrap();
+        let expected = vec![
+            Suggestion::new("wolf".to_string(), 2, 2),
+            Suggestion::new("wolves".to_string(), 4, 1),
+        ];
+        assert_eq!(computed, expected);
+        let computed = complete_terms(&index, &searcher, "title", "w", 1).unwrap();
+        assert_eq!(computed, vec![Suggestion::new("wolf".to_string(), 3, 2)]);
+        assert!(complete_terms(&index, &searcher, "title", "x", 10).unwrap().is_empty());
+
+        assert_eq!(prefix_pattern(&index, title, "Wo").unwrap(), "wo.*");
+        assert_eq!(prefix_pattern(&index, title, "c++").unwrap(), "c.*");
+        assert_eq!(prefix_pattern(&index, sku, "c++").unwrap(), "c\\+\\+.*");
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::{HashMap, HashSet};
+
+use tantivy::schema::{Cardinality, IndexRecordOption, IntOptions, TextFieldIndexing, TextOptions};
+
+use crate::prelude::*;
+
+/// Kind of a field as resolved at compile time
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SurfKind {
+    Text,
+    TextSeq,
+    U64,
+    I64,
+    F64,
+    Bool,
+    Date,
+    NaiveDate,
+    Bytes,
+}
+
+/// Options declared with #[surf(stored, indexed, fast, tokenizer = "en_stem")], replacing the defaults like Control
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SurfOptions {
+    stored: bool,
+    indexed: bool,
+    fast: bool,
+    tokenizer: Option<String>,
+}
+
+impl SurfOptions {
+    pub fn new(stored: bool, indexed: bool, fast: bool, tokenizer: Option<&str>) -> Self {
+        let tokenizer = tokenizer.map(|tokenizer| tokenizer.to_string());
+        Self {
+            stored,
+            indexed,
+            fast,
+            tokenizer,
+        }
+    }
+    pub fn is_stored(&self) -> bool {
+        self.stored
+    }
+    /// Text fields are indexed when a tokenizer is declared too
+    pub fn is_indexed(&self) -> bool {
+        self.indexed || self.tokenizer.is_some()
+    }
+    pub fn is_fast(&self) -> bool {
+        self.fast
+    }
+    pub fn tokenizer(&self) -> Option<&str> {
+        self.tokenizer.as_deref()
+    }
+}
+
+/// Flattened field of a struct deriving Surf
+#[derive(Clone, Debug, PartialEq)]
+pub struct SurfField {
+    name: String,
+    kind: SurfKind,
+    options: Option<SurfOptions>,
+    seq: Option<String>,
+}
+
+impl SurfField {
+    pub fn new(name: &str, kind: SurfKind) -> Self {
+        let name = name.to_string();
+        let options = None;
+        let seq = None;
+        Self {
+            name,
+            kind,
+            options,
+            seq,
+        }
+    }
+    /// Declared options instead of the defaults
+    pub fn with_options(self, options: SurfOptions) -> Self {
+        let options = Some(options);
+        Self {
+            options,
+            ..self
+        }
+    }
+    pub fn options(&self) -> Option<&SurfOptions> {
+        self.options.as_ref()
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn kind(&self) -> SurfKind {
+        self.kind
+    }
+    /// Sequence of structs holding the field, multi-valued then e.g. items of items.sku
+    pub fn seq(&self) -> Option<&str> {
+        self.seq.as_deref()
+    }
+    /// Nests the field under the parent e.g. city becomes address.city
+    pub fn prefixed(self, prefix: &str) -> Self {
+        let name = format!("{}.{}", prefix, self.name);
+        let seq = self.seq.map(|seq| format!("{}.{}", prefix, seq));
+        Self {
+            name,
+            seq,
+            ..self
+        }
+    }
+    /// Nests the field under a sequence of structs e.g. sku of Vec<LineItem> becomes items.sku, the outermost sequence wins
+    pub fn repeated(self, prefix: &str) -> Self {
+        let seq = Some(prefix.to_string());
+        Self {
+            seq,
+            ..self.prefixed(prefix)
+        }
+    }
+}
+
+/// Schema known at compile time, implement with #[derive(Surf)]
+pub trait Surf {
+    /// Name of the index e.g. user_data for UserData
+    fn index_name() -> &'static str;
+    /// Flattened fields in declaration order
+    fn surf_fields() -> Vec<SurfField>;
+    /// Flattened field names in declaration order
+    fn field_names() -> Vec<String> {
+        Self::surf_fields().iter().map(|field| field.name().to_string()).collect()
+    }
+}
+
+/// Control matching the declared options, None for defaults
+fn as_control(field: &SurfField) -> Option<Control> {
+    let options = field.options()?;
+    let control = match field.kind() {
+        SurfKind::Text | SurfKind::TextSeq => {
+            let mut text = TextOptions::default();
+            if options.is_stored() {
+                text = text.set_stored();
+            };
+            if options.is_indexed() {
+                let indexing = TextFieldIndexing::default()
+                    .set_tokenizer(options.tokenizer().unwrap_or("default"))
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+                text = text.set_indexing_options(indexing);
+            };
+            Control::ControlTextOptions(text)
+        }
+        SurfKind::Bytes => return None,
+        kind => {
+            let mut number = IntOptions::default();
+            if options.is_stored() {
+                number = number.set_stored();
+            };
+            if options.is_indexed() {
+                number = number.set_indexed();
+            };
+            if options.is_fast() {
+                number = number.set_fast(Cardinality::SingleValue);
+            };
+            match kind {
+                SurfKind::Date | SurfKind::NaiveDate => Control::ControlDateOptions(number),
+                _ => Control::ControlIntOptions(number),
+            }
+        }
+    };
+    Some(control)
+}
+
+/// Control per field from the declared options, explicit entries win
+pub(crate) fn surf_control(fields: &[SurfField], mut control: HashMap<String, Control>) -> HashMap<String, Control> {
+    for field in fields {
+        if control.contains_key(field.name()) {
+            continue;
+        };
+        if let Some(c) = as_control(field) {
+            control.insert(field.name().to_string(), c);
+        };
+    };
+    control
+}
+
+/// Names of the fields of the kind
+pub(crate) fn surf_fields_of(fields: &[SurfField], kind: SurfKind) -> HashSet<String> {
+    fields.iter()
+        .filter(|field| field.kind() == kind)
+        .map(|field| field.name().to_string())
+        .collect()
+}
+
+/// Names of the sequences of structs holding fields, outermost only
+pub(crate) fn surf_object_seqs(fields: &[SurfField]) -> HashSet<String> {
+    fields.iter()
+        .filter_map(|field| field.seq())
+        .map(|seq| seq.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Address;
+
+    impl Surf for Address {
+        fn index_name() -> &'static str {
+            "address"
+        }
+        fn surf_fields() -> Vec<SurfField> {
+            vec![SurfField::new("city", SurfKind::Text), SurfField::new("zip", SurfKind::U64)]
+        }
+    }
+
+    #[test]
+    fn validate_surf_control() {
+        let options = SurfOptions::new(true, false, true, None);
+        let fields = vec![
+            SurfField::new("id", SurfKind::U64).with_options(options),
+            SurfField::new("title", SurfKind::Text).with_options(SurfOptions::new(false, false, false, Some("raw"))),
+            SurfField::new("body", SurfKind::Text),
+        ];
+        let control = surf_control(&fields, HashMap::new());
+        assert_eq!(control.len(), 2);
+        match control.get("id") {
+            Some(Control::ControlIntOptions(options)) => {
+                assert!(options.is_stored());
+                assert!(!options.is_indexed());
+                assert!(options.is_fast());
+            }
+            _ => panic!("id should be int options"),
+        };
+        match control.get("title") {
+            Some(Control::ControlTextOptions(options)) => {
+                assert!(!options.is_stored());
+                assert_eq!(options.get_indexing_options().unwrap().tokenizer(), "raw");
+            }
+            _ => panic!("title should be text options"),
+        };
+
+        let mut explicit = HashMap::new();
+        explicit.insert("id".to_string(), Control::ControlIntOptions(IntOptions::default()));
+        let control = surf_control(&fields, explicit);
+        match control.get("id") {
+            Some(Control::ControlIntOptions(options)) => assert!(!options.is_stored()),
+            _ => panic!("id should be int options"),
+        };
+    }
+
+    #[test]
+    fn validate_surf_fields() {
+        let fields: Vec<SurfField> = Address::surf_fields().into_iter().map(|field| field.prefixed("address")).collect();
+        assert_eq!(fields[0], SurfField::new("address.city", SurfKind::Text));
+        assert_eq!(Address::field_names(), vec!["city".to_string(), "zip".to_string()]);
+
+        let computed = surf_fields_of(&fields, SurfKind::U64);
+        let mut expected = HashSet::new();
+        expected.insert("address.zip".to_string());
+        assert_eq!(computed, expected);
+
+        let fields: Vec<SurfField> = Address::surf_fields().into_iter().map(|field| field.repeated("addresses").prefixed("user")).collect();
+        assert_eq!(fields[1].name(), "user.addresses.zip");
+        assert_eq!(fields[1].seq(), Some("user.addresses"));
+        let mut expected = HashSet::new();
+        expected.insert("user.addresses".to_string());
+        assert_eq!(surf_object_seqs(&fields), expected);
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use tantivy::tokenizer::{BoxTokenStream, Token, TokenFilter, TokenStream};
+
+use crate::prelude::*;
+
+/// Words matching other words or phrases e.g. tv matches television, compared lowercase
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Synonyms {
+    words: HashMap<String, Vec<String>>,
+}
+
+impl Synonyms {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Word matching the synonyms too, one way only
+    pub fn add(&mut self, word: &str, synonyms: &[&str]) {
+        let expanded = self.words.entry(word.trim().to_lowercase()).or_default();
+        for synonym in synonyms {
+            let synonym = synonym.trim().to_lowercase();
+            if !synonym.is_empty() && !expanded.contains(&synonym) {
+                expanded.push(synonym);
+            };
+        };
+    }
+    /// Words all matching one another
+    pub fn add_group(&mut self, words: &[&str]) {
+        for word in words {
+            let others: Vec<&str> = words.iter().filter(|other| other != &word).copied().collect();
+            self.add(word, &others);
+        };
+    }
+    /// Reads rules line by line, a, b, c for a group & a => b, c one way, # starts a comment
+    pub fn parse(rules: &str) -> Result<Self, IndexError> {
+        let mut synonyms = Self::new();
+        for (i, line) in rules.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            };
+            let words = |text: &str| -> Vec<String> {
+                text.split(',').map(str::trim).filter(|word| !word.is_empty()).map(str::to_string).collect()
+            };
+            let invalid = || {
+                let reason = format!("Line: {} is neither a, b nor a => b", i + 1);
+                IndexError::new("Unable to parse synonyms", &reason)
+            };
+            match line.find("=>") {
+                Some(arrow) => {
+                    let (from, to) = (words(&line[..arrow]), words(&line[arrow + 2..]));
+                    if from.is_empty() || to.is_empty() {
+                        return Err(invalid());
+                    };
+                    let to: Vec<&str> = to.iter().map(String::as_str).collect();
+                    for word in &from {
+                        synonyms.add(word, &to);
+                    };
+                }
+                None => {
+                    let group = words(line);
+                    if group.len() < 2 {
+                        return Err(invalid());
+                    };
+                    let group: Vec<&str> = group.iter().map(String::as_str).collect();
+                    synonyms.add_group(&group);
+                }
+            };
+        };
+        Ok(synonyms)
+    }
+    /// Reads the rules of a synonyms file, see parse
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, IndexError> {
+        let rules = std::fs::read_to_string(path)?;
+        Self::parse(&rules)
+    }
+    /// Synonyms of the word
+    pub fn expand(&self, word: &str) -> Option<&Vec<String>> {
+        self.words.get(&word.to_lowercase())
+    }
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+/// Rewrites the bare words of the query string having synonyms into OR groups e.g. tv -> (tv OR television)
+/// Quoted phrases, ranges & words holding query syntax are left as is
+pub(crate) fn expand_query(query: &str, synonyms: &Synonyms) -> String {
+    let mut expanded = Vec::new();
+    let mut chunks = query.split(' ');
+    while let Some(chunk) = chunks.next() {
+        let opening = chunk.matches('"').count() % 2 == 1 || chunk.contains('[') || chunk.contains('{');
+        if opening {
+            // Keeps the phrase or the range whole
+            let mut group = vec![chunk];
+            for next in chunks.by_ref() {
+                group.push(next);
+                if next.contains('"') || next.contains(']') || next.contains('}') {
+                    break;
+                };
+            };
+            expanded.push(group.join(" "));
+            continue;
+        };
+        expanded.push(expand_word(chunk, synonyms).unwrap_or_else(|| chunk.to_string()));
+    };
+    expanded.join(" ")
+}
+
+/// OR group of a word e.g. +title:tv -> +(title:tv OR title:television)
+fn expand_word(chunk: &str, synonyms: &Synonyms) -> Option<String> {
+    let (occur, rest) = match chunk.chars().next()? {
+        '+' | '-' => chunk.split_at(1),
+        _ => ("", chunk),
+    };
+    let (field, word) = match rest.find(':') {
+        Some(i) => (&rest[..=i], &rest[i + 1..]),
+        None => ("", rest),
+    };
+    let is_field = (field.is_empty() || field.starts_with(char::is_alphabetic))
+        && field.trim_end_matches(':').chars().all(|c| c.is_alphanumeric() || c == '_' || c == '.');
+    if !is_field || word.is_empty() || !word.chars().all(char::is_alphanumeric) || ["AND", "OR", "NOT"].contains(&word) {
+        return None;
+    };
+    let alternatives = synonyms.expand(word)?;
+    let mut clauses = vec![format!("{}{}", field, word)];
+    for alternative in alternatives {
+        match alternative.contains(' ') {
+            true => clauses.push(format!("{}\"{}\"", field, alternative)),
+            false => clauses.push(format!("{}{}", field, alternative)),
+        };
+    };
+    Some(format!("{}({})", occur, clauses.join(" OR ")))
+}
+
+/// Adds the single word synonyms of every token at its position, phrases are left to query time
+#[derive(Clone)]
+pub(crate) struct SynonymFilter {
+    synonyms: Synonyms,
+}
+
+impl SynonymFilter {
+    pub(crate) fn new(synonyms: Synonyms) -> Self {
+        Self {
+            synonyms,
+        }
+    }
+}
+
+impl TokenFilter for SynonymFilter {
+    fn transform<'a>(&self, token_stream: BoxTokenStream<'a>) -> BoxTokenStream<'a> {
+        BoxTokenStream::from(SynonymTokenStream {
+            tail: token_stream,
+            synonyms: self.synonyms.clone(),
+            pending: Vec::new(),
+            token: Token::default(),
+        })
+    }
+}
+
+struct SynonymTokenStream<'a> {
+    tail: BoxTokenStream<'a>,
+    synonyms: Synonyms,
+    pending: Vec<Token>,
+    token: Token,
+}
+
+impl<'a> TokenStream for SynonymTokenStream<'a> {
+    fn advance(&mut self) -> bool {
+        if let Some(token) = self.pending.pop() {
+            self.token = token;
+            return true;
+        };
+        if !self.tail.advance() {
+            return false;
+        };
+        self.token = self.tail.token().clone();
+        if let Some(synonyms) = self.synonyms.expand(&self.token.text) {
+            for synonym in synonyms.iter().rev().filter(|synonym| !synonym.contains(' ')) {
+                let mut token = self.token.clone();
+                token.text = synonym.to_string();
+                self.pending.push(token);
+            };
+        };
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::tokenizer::{LowerCaser, SimpleTokenizer, TextAnalyzer};
+
+    fn synonyms() -> Synonyms {
+        Synonyms::parse("# devices\ntv, television\nlaptop => notebook, portable computer\n").unwrap()
+    }
+
+    #[test]
+    fn validate_parse() {
+        let synonyms = synonyms();
+        assert_eq!(synonyms.expand("TV"), Some(&vec!["television".to_string()]));
+        assert_eq!(synonyms.expand("television"), Some(&vec!["tv".to_string()]));
+        assert_eq!(synonyms.expand("laptop"), Some(&vec!["notebook".to_string(), "portable computer".to_string()]));
+        assert!(synonyms.expand("notebook").is_none());
+        assert!(Synonyms::parse("tv").is_err());
+        assert!(Synonyms::parse("=> tv").is_err());
+        assert!(Synonyms::new().is_empty());
+    }
+
+    #[test]
+    fn validate_expand_query() {
+        let synonyms = synonyms();
+        assert_eq!(expand_query("cheap tv", &synonyms), "cheap (tv OR television)");
+        assert_eq!(expand_query("+title:laptop -tv", &synonyms), r#"+(title:laptop OR title:notebook OR title:"portable computer") -(tv OR television)"#);
+        assert_eq!(expand_query(r#""old tv set" price:[1 TO 10]"#, &synonyms), r#""old tv set" price:[1 TO 10]"#);
+        assert_eq!(expand_query("tv* (tv OR radio)", &synonyms), "tv* (tv OR radio)");
+    }
+
+    #[test]
+    fn validate_synonym_filter() {
+        let analyzer = TextAnalyzer::from(SimpleTokenizer)
+            .filter(LowerCaser)
+            .filter(SynonymFilter::new(synonyms()));
+        let mut stream = analyzer.token_stream("New Laptop TV");
+        let mut tokens = Vec::new();
+        while stream.advance() {
+            tokens.push((stream.token().text.clone(), stream.token().position));
+        };
+        let expected = vec![("new", 0), ("laptop", 1), ("notebook", 1), ("tv", 2), ("television", 2)];
+        let expected: Vec<(String, usize)> = expected.into_iter().map(|(text, position)| (text.to_string(), position)).collect();
+        assert_eq!(tokens, expected);
+    }
+}
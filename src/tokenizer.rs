@@ -0,0 +1,418 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use tantivy::Index;
+use tantivy::schema::{FieldEntry, FieldType, IndexRecordOption, Schema, SchemaBuilder, TextFieldIndexing, TextOptions};
+use tantivy::tokenizer::{LowerCaser, NgramTokenizer, RawTokenizer, RemoveLongFilter, SimpleTokenizer, Stemmer, StopWordFilter, TextAnalyzer};
+
+use crate::prelude::*;
+
+pub use tantivy::tokenizer::Language;
+
+/// Stemming languages by the ISO 639-1 code of their tokenizer e.g. fr_stem
+const STEMMERS: [(&str, Language); 18] = [
+    ("ar", Language::Arabic),
+    ("da", Language::Danish),
+    ("nl", Language::Dutch),
+    ("en", Language::English),
+    ("fi", Language::Finnish),
+    ("fr", Language::French),
+    ("de", Language::German),
+    ("el", Language::Greek),
+    ("hu", Language::Hungarian),
+    ("it", Language::Italian),
+    ("no", Language::Norwegian),
+    ("pt", Language::Portuguese),
+    ("ro", Language::Romanian),
+    ("ru", Language::Russian),
+    ("es", Language::Spanish),
+    ("sv", Language::Swedish),
+    ("ta", Language::Tamil),
+    ("tr", Language::Turkish),
+];
+
+/// Words removed at analysis time, before stemming
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Stopwords {
+    English,
+    French,
+    German,
+    Spanish,
+    Italian,
+    Portuguese,
+    Dutch,
+    /// Own list, compared lowercase
+    Custom(Vec<String>),
+}
+
+impl Stopwords {
+    pub fn words(&self) -> Vec<String> {
+        let words: &[&str] = match self {
+            Stopwords::English => &[
+                "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "if", "in", "into", "is", "it", "no", "not",
+                "of", "on", "or", "such", "that", "the", "their", "then", "there", "these", "they", "this", "to", "was",
+                "will", "with",
+            ],
+            Stopwords::French => &[
+                "au", "aux", "avec", "ce", "ces", "dans", "de", "des", "du", "elle", "en", "et", "eux", "il", "je", "la", "le",
+                "les", "leur", "lui", "ma", "mais", "me", "mes", "moi", "mon", "ne", "nos", "notre", "nous", "on", "ou", "par",
+                "pas", "pour", "qu", "que", "qui", "sa", "se", "ses", "son", "sur", "ta", "te", "tes", "toi", "ton", "tu", "un",
+                "une", "vos", "votre", "vous",
+            ],
+            Stopwords::German => &[
+                "aber", "alle", "als", "am", "an", "auch", "auf", "aus", "bei", "bin", "bis", "das", "dass", "dem", "den", "der",
+                "des", "die", "doch", "du", "ein", "eine", "einem", "einen", "einer", "es", "für", "hat", "ich", "im", "in",
+                "ist", "mit", "nach", "nicht", "noch", "oder", "sie", "sind", "so", "und", "uns", "von", "war", "was", "wie",
+                "wir", "zu", "zum", "zur",
+            ],
+            Stopwords::Spanish => &[
+                "a", "al", "algo", "como", "con", "de", "del", "el", "ella", "en", "es", "esta", "este", "la", "las", "le", "lo",
+                "los", "mas", "me", "mi", "no", "nos", "o", "para", "pero", "por", "que", "se", "si", "sin", "su", "sus", "te",
+                "tu", "un", "una", "uno", "y", "ya",
+            ],
+            Stopwords::Italian => &[
+                "a", "al", "alla", "che", "chi", "con", "da", "dal", "dei", "del", "della", "di", "e", "gli", "ha", "i", "il",
+                "in", "la", "le", "lo", "ma", "mi", "ne", "non", "per", "più", "se", "si", "su", "sua", "suo", "tra", "un",
+                "una", "uno",
+            ],
+            Stopwords::Portuguese => &[
+                "a", "ao", "as", "com", "como", "da", "das", "de", "do", "dos", "e", "ela", "ele", "em", "entre", "era", "está",
+                "eu", "isso", "mais", "mas", "me", "na", "nas", "no", "nos", "não", "o", "os", "ou", "para", "pela", "pelo",
+                "por", "que", "se", "sem", "seu", "sua", "um", "uma",
+            ],
+            Stopwords::Dutch => &[
+                "aan", "al", "als", "bij", "dat", "de", "den", "der", "die", "dit", "een", "en", "er", "het", "hij", "ik", "in",
+                "is", "je", "kan", "maar", "met", "naar", "niet", "nog", "of", "om", "op", "te", "tot", "uit", "van", "voor",
+                "wat", "was", "we", "wel", "zij", "zo",
+            ],
+            Stopwords::Custom(words) => return words.iter().map(|word| word.to_lowercase()).collect(),
+        };
+        words.iter().map(|word| word.to_string()).collect()
+    }
+}
+
+/// Analysis of the text fields of an index, applied whenever the index is opened
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Analysis {
+    stopwords: Option<Stopwords>,
+    field_stopwords: HashMap<String, Stopwords>,
+    #[serde(default)]
+    synonyms: Option<Synonyms>,
+    #[serde(default)]
+    index_synonyms: bool,
+}
+
+impl Analysis {
+    pub(crate) fn set_stopwords(&mut self, stopwords: Stopwords) {
+        self.stopwords = Some(stopwords);
+    }
+    pub(crate) fn set_field_stopwords(&mut self, field: &str, stopwords: Stopwords) {
+        self.field_stopwords.insert(field.to_string(), stopwords);
+    }
+    pub(crate) fn set_synonyms(&mut self, synonyms: Synonyms, at_index: bool) {
+        self.synonyms = Some(synonyms);
+        self.index_synonyms = at_index;
+    }
+    /// Synonyms expanding the query strings
+    pub(crate) fn synonyms(&self) -> Option<&Synonyms> {
+        self.synonyms.as_ref()
+    }
+    /// Synonyms added to the text fields at analysis time
+    fn index_synonyms(&self) -> Option<&Synonyms> {
+        self.synonyms.as_ref().filter(|_| self.index_synonyms)
+    }
+    /// Stopwords of the field, otherwise those of the index
+    pub(crate) fn stopwords(&self, field: &str) -> Option<&Stopwords> {
+        self.field_stopwords.get(field).or(self.stopwords.as_ref())
+    }
+    /// Gives the fields with their own stopwords a tokenizer of their own e.g. default:title
+    pub(crate) fn schema(&self, schema: &Schema) -> Schema {
+        let mut builder = SchemaBuilder::new();
+        for (_, entry) in schema.fields() {
+            let options = match entry.field_type() {
+                FieldType::Str(options) if self.field_stopwords.contains_key(entry.name()) => options,
+                _ => {
+                    builder.add_field(entry.clone());
+                    continue;
+                }
+            };
+            let options = match options.get_indexing_options() {
+                Some(indexing) if !indexing.tokenizer().contains(':') => {
+                    let tokenizer = format!("{}:{}", indexing.tokenizer(), entry.name());
+                    options.clone().set_indexing_options(indexing.clone().set_tokenizer(&tokenizer))
+                }
+                _ => options.clone(),
+            };
+            builder.add_field(FieldEntry::new_text(entry.name().to_string(), options));
+        };
+        builder.build()
+    }
+    /// Stopwords of a tokenizer, a field tokenizer ends with :field
+    fn tokenizer_stopwords(&self, tokenizer: &str) -> Option<&Stopwords> {
+        match tokenizer.find(':') {
+            Some(i) => self.field_stopwords.get(&tokenizer[i + 1..]),
+            None => self.stopwords.as_ref(),
+        }
+    }
+}
+
+/// Name of the lowercase n-gram tokenizer e.g. ngram_2_3, edge_ngram_1_10 for prefixes only
+/// Use it as the tokenizer of Control text options or #[surf(tokenizer = "ngram_2_3")]
+pub fn ngram_tokenizer(min_gram: usize, max_gram: usize, prefix_only: bool) -> String {
+    let kind = if prefix_only { "edge_ngram" } else { "ngram" };
+    format!("{}_{}_{}", kind, min_gram, max_gram)
+}
+
+/// Indexed & stored text options tokenized into n-grams e.g. for substring matches on SKUs
+pub fn ngram_options(min_gram: usize, max_gram: usize, prefix_only: bool) -> TextOptions {
+    let indexing = TextFieldIndexing::default()
+        .set_tokenizer(&ngram_tokenizer(min_gram, max_gram, prefix_only))
+        .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+    TextOptions::default()
+        .set_indexing_options(indexing)
+        .set_stored()
+}
+
+/// Name of the lowercase tokenizer stemming the language e.g. fr_stem
+/// Use it as the tokenizer of Control text options or #[surf(stem = "french")]
+pub fn stem_tokenizer(language: Language) -> String {
+    let code = STEMMERS.iter()
+        .find(|(_, stemmer)| *stemmer == language)
+        .map_or("en", |(code, _)| *code);
+    format!("{}_stem", code)
+}
+
+/// Indexed & stored text options stemmed for the language
+pub fn stem_options(language: Language) -> TextOptions {
+    let indexing = TextFieldIndexing::default()
+        .set_tokenizer(&stem_tokenizer(language))
+        .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+    TextOptions::default()
+        .set_indexing_options(indexing)
+        .set_stored()
+}
+
+/// Custom analysis chains by tokenizer name
+pub(crate) type Tokenizers = HashMap<String, TextAnalyzer>;
+
+/// Registers the custom tokenizers & those named by the text fields of the schema along with the stopwords of the analysis
+pub(crate) fn register_tokenizers(index: &Index, analysis: Option<&Analysis>, tokenizers: &Tokenizers) -> Result<(), IndexError> {
+    for (name, analyzer) in tokenizers {
+        index.tokenizers().register(name, analyzer.clone());
+    };
+    let schema = index.schema();
+    for (_, entry) in schema.fields() {
+        let indexing = match entry.field_type() {
+            FieldType::Str(options) => options.get_indexing_options(),
+            _ => None,
+        };
+        let name = match indexing {
+            Some(indexing) => indexing.tokenizer(),
+            None => continue,
+        };
+        let stopwords = analysis.and_then(|analysis| analysis.tokenizer_stopwords(name));
+        let synonyms = analysis.and_then(Analysis::index_synonyms);
+        if stopwords.is_none() && synonyms.is_none() && index.tokenizers().get(name).is_some() {
+            continue;
+        };
+        let base = name.split(':').next().unwrap_or(name);
+        if let Some((min_gram, max_gram, _)) = parse_ngram(base) {
+            if min_gram == 0 || min_gram > max_gram {
+                let reason = format!("Tokenizer: {} requires 0 < min gram <= max gram", name);
+                return Err(IndexError::schema_mismatch("Unable to register tokenizer", &reason));
+            };
+        };
+        if let Some(analyzer) = analyzer(base, stopwords, synonyms, tokenizers) {
+            index.tokenizers().register(name, analyzer);
+        };
+    };
+    Ok(())
+}
+
+/// Analysis chain of a tokenizer json-surf knows how to build, stopwords & synonyms go before stemming
+/// Custom chains come first & get the stopwords & synonyms last, raw tokens get no synonyms
+fn analyzer(name: &str, stopwords: Option<&Stopwords>, synonyms: Option<&Synonyms>, tokenizers: &Tokenizers) -> Option<TextAnalyzer> {
+    let stop = |analyzer: TextAnalyzer| {
+        let analyzer = match stopwords {
+            Some(stopwords) => analyzer.filter(StopWordFilter::remove(stopwords.words())),
+            None => analyzer,
+        };
+        match synonyms {
+            Some(synonyms) if name != "raw" => analyzer.filter(SynonymFilter::new(synonyms.clone())),
+            _ => analyzer,
+        }
+    };
+    let simple = || TextAnalyzer::from(SimpleTokenizer)
+        .filter(RemoveLongFilter::limit(40))
+        .filter(LowerCaser);
+    if let Some(analyzer) = tokenizers.get(name) {
+        return Some(stop(analyzer.clone()));
+    };
+    let analyzer = match name {
+        "raw" => stop(TextAnalyzer::from(RawTokenizer)),
+        "default" => stop(simple()),
+        #[cfg(feature = "cjk")]
+        CJK_TOKENIZER => stop(TextAnalyzer::from(CjkTokenizer).filter(RemoveLongFilter::limit(40)).filter(LowerCaser)),
+        _ => match parse_stem(name) {
+            Some(language) => stop(simple()).filter(Stemmer::new(language)),
+            None => {
+                let (min_gram, max_gram, prefix_only) = parse_ngram(name)?;
+                stop(TextAnalyzer::from(NgramTokenizer::new(min_gram, max_gram, prefix_only)).filter(LowerCaser))
+            }
+        },
+    };
+    Some(analyzer)
+}
+
+/// Language of {code}_stem
+fn parse_stem(name: &str) -> Option<Language> {
+    let code = name.strip_suffix("_stem")?;
+    STEMMERS.iter().find(|(c, _)| *c == code).map(|(_, language)| *language)
+}
+
+/// Grams & prefix only of ngram_{min}_{max} or edge_ngram_{min}_{max}
+fn parse_ngram(name: &str) -> Option<(usize, usize, bool)> {
+    let (grams, prefix_only) = if let Some(grams) = name.strip_prefix("edge_ngram_") {
+        (grams, true)
+    } else {
+        (name.strip_prefix("ngram_")?, false)
+    };
+    let mut grams = grams.splitn(2, '_');
+    let min_gram = grams.next()?.parse().ok()?;
+    let max_gram = grams.next()?.parse().ok()?;
+    Some((min_gram, max_gram, prefix_only))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::collector::Count;
+    use tantivy::query::QueryParser;
+    use tantivy::schema::{SchemaBuilder, TEXT};
+    use tantivy::doc;
+
+    #[test]
+    fn validate_parse_ngram() {
+        assert_eq!(parse_ngram(&ngram_tokenizer(2, 3, false)), Some((2, 3, false)));
+        assert_eq!(parse_ngram(&ngram_tokenizer(1, 10, true)), Some((1, 10, true)));
+        assert_eq!(parse_ngram("ngram_2"), None);
+        assert_eq!(parse_ngram("en_stem"), None);
+    }
+
+    #[test]
+    fn validate_stem_tokenizer() {
+        assert_eq!(stem_tokenizer(Language::French), "fr_stem");
+        assert_eq!(parse_stem(&stem_tokenizer(Language::German)), Some(Language::German));
+        assert_eq!(parse_stem("xx_stem"), None);
+        assert_eq!(parse_stem("default"), None);
+
+        let mut builder = SchemaBuilder::new();
+        let titre = builder.add_text_field("titre", stem_options(Language::French));
+        let title = builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(builder.build());
+        register_tokenizers(&index, None, &Tokenizers::new()).unwrap();
+        let mut writer = open_index_writer(&index).unwrap();
+        writer.add_document(doc!(titre => "Les chevaux mangeaient", title => "The horses were eating"));
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let parser = QueryParser::for_index(&index, vec![titre, title]);
+        let count = |query: &str| searcher.search(&parser.parse_query(query).unwrap(), &Count).unwrap();
+        assert_eq!(count("titre:mangeait"), 1);
+        assert_eq!(count("title:horse"), 0);
+    }
+
+    #[cfg(feature = "cjk")]
+    #[test]
+    fn validate_cjk_fields() {
+        let mut builder = SchemaBuilder::new();
+        let indexing = TextFieldIndexing::default()
+            .set_tokenizer(CJK_TOKENIZER)
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+        let title = builder.add_text_field("title", TextOptions::default().set_indexing_options(indexing));
+        let index = Index::create_in_ram(builder.build());
+        register_tokenizers(&index, None, &Tokenizers::new()).unwrap();
+        let mut writer = open_index_writer(&index).unwrap();
+        writer.add_document(doc!(title => "東京都に住む"));
+        writer.add_document(doc!(title => "京都の寺 Kyoto"));
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let parser = QueryParser::for_index(&index, vec![title]);
+        let count = |query: &str| searcher.search(&parser.parse_query(query).unwrap(), &Count).unwrap();
+        assert_eq!(count("\"京都\""), 2);
+        assert_eq!(count("\"東京都\""), 1);
+        assert_eq!(count("kyoto"), 1);
+    }
+
+    #[test]
+    fn validate_register_tokenizers() {
+        let mut builder = SchemaBuilder::new();
+        let title = builder.add_text_field("title", TEXT);
+        let sku = builder.add_text_field("sku", ngram_options(2, 3, false));
+        let index = Index::create_in_ram(builder.build());
+        assert!(index.tokenizers().get("ngram_2_3").is_none());
+        register_tokenizers(&index, None, &Tokenizers::new()).unwrap();
+        assert!(index.tokenizers().get("ngram_2_3").is_some());
+
+        let mut writer = open_index_writer(&index).unwrap();
+        writer.add_document(doc!(title => "Shoe", sku => "SHOE-XL-2041"));
+        writer.add_document(doc!(title => "Sock", sku => "SOCK-M-1999"));
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let parser = QueryParser::for_index(&index, vec![title, sku]);
+        let count = |query: &str| searcher.search(&parser.parse_query(query).unwrap(), &Count).unwrap();
+        assert_eq!(count("sku:xl"), 1);
+        assert_eq!(count("sku:204"), 1);
+        assert_eq!(count("sku:o"), 0);
+
+        let mut builder = SchemaBuilder::new();
+        builder.add_text_field("sku", ngram_options(3, 2, false));
+        let index = Index::create_in_ram(builder.build());
+        assert!(register_tokenizers(&index, None, &Tokenizers::new()).is_err());
+    }
+
+    #[test]
+    fn validate_stopwords() {
+        let mut builder = SchemaBuilder::new();
+        let title = builder.add_text_field("title", TEXT);
+        let body = builder.add_text_field("body", TEXT);
+        let mut analysis = Analysis::default();
+        analysis.set_stopwords(Stopwords::English);
+        analysis.set_field_stopwords("body", Stopwords::Custom(vec!["Wolf".to_string()]));
+        let schema = analysis.schema(&builder.build());
+        match schema.get_field_entry(body).field_type() {
+            FieldType::Str(options) => assert_eq!(options.get_indexing_options().unwrap().tokenizer(), "default:body"),
+            _ => unreachable!(),
+        };
+        assert_eq!(analysis.stopwords("title"), Some(&Stopwords::English));
+        assert_eq!(serde_json::to_string(&analysis.schema(&schema)).unwrap(), serde_json::to_string(&schema).unwrap());
+
+        let index = Index::create_in_ram(schema);
+        register_tokenizers(&index, Some(&analysis), &Tokenizers::new()).unwrap();
+        let mut writer = open_index_writer(&index).unwrap();
+        writer.add_document(doc!(title => "The Sea Wolf", body => "The wolf"));
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let parser = QueryParser::for_index(&index, vec![title, body]);
+        let count = |query: &str| searcher.search(&parser.parse_query(query).unwrap(), &Count).unwrap();
+        assert_eq!(count("title:wolf"), 1);
+        assert_eq!(count("title:the"), 0);
+        assert_eq!(count("body:the"), 1);
+        assert_eq!(count("body:wolf"), 0);
+
+        let mut builder = SchemaBuilder::new();
+        let title = builder.add_text_field("title", TextOptions::default().set_indexing_options(TextFieldIndexing::default().set_tokenizer("en_stem")));
+        let index = Index::create_in_ram(builder.build());
+        let mut analysis = Analysis::default();
+        analysis.set_stopwords(Stopwords::English);
+        register_tokenizers(&index, Some(&analysis), &Tokenizers::new()).unwrap();
+        let mut writer = open_index_writer(&index).unwrap();
+        writer.add_document(doc!(title => "This is fishing"));
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let parser = QueryParser::for_index(&index, vec![title]);
+        let count = |query: &str| searcher.search(&parser.parse_query(query).unwrap(), &Count).unwrap();
+        assert_eq!(count("fish"), 1);
+        assert_eq!(count("thi"), 0);
+    }
+}
@@ -0,0 +1,116 @@
+#[cfg(feature = "tracing")]
+use std::time::Instant;
+
+/// Target of every record, filter on it in the logger of the application
+pub const TRACE_TARGET: &str = "json_surf";
+
+/// Unit of work timed from enter until dropped, logged along with the index & the documents
+/// Compiles to nothing without the tracing feature
+#[cfg(feature = "tracing")]
+pub(crate) struct Span {
+    name: &'static str,
+    index: String,
+    docs: Option<usize>,
+    level: log::Level,
+    start: Instant,
+}
+
+#[cfg(feature = "tracing")]
+impl Span {
+    /// Debug level span
+    pub(crate) fn enter(name: &'static str, index: &str) -> Self {
+        Self::at(log::Level::Debug, name, index)
+    }
+    /// Trace level span for work done once per document
+    pub(crate) fn enter_fine(name: &'static str, index: &str) -> Self {
+        Self::at(log::Level::Trace, name, index)
+    }
+    fn at(level: log::Level, name: &'static str, index: &str) -> Self {
+        Self {
+            name,
+            index: index.to_string(),
+            docs: None,
+            level,
+            start: Instant::now(),
+        }
+    }
+    /// Number of documents written or read by the span
+    pub(crate) fn record_docs(&mut self, docs: usize) {
+        self.docs = Some(docs);
+    }
+    /// Event within the span e.g. the query parsed
+    pub(crate) fn event(&self, message: &str) {
+        log::log!(target: TRACE_TARGET, self.level, "{} index={} {}", self.name, self.index, message);
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Drop for Span {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed().as_micros();
+        match self.docs {
+            Some(docs) => log::log!(target: TRACE_TARGET, self.level, "{} index={} docs={} elapsed_us={}", self.name, self.index, docs, elapsed),
+            None => log::log!(target: TRACE_TARGET, self.level, "{} index={} elapsed_us={}", self.name, self.index, elapsed),
+        };
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Span;
+
+#[cfg(not(feature = "tracing"))]
+impl Span {
+    #[inline]
+    pub(crate) fn enter(_: &'static str, _: &str) -> Self {
+        Span
+    }
+    #[inline]
+    pub(crate) fn enter_fine(_: &'static str, _: &str) -> Self {
+        Span
+    }
+    #[inline]
+    pub(crate) fn record_docs(&mut self, _: usize) {}
+    #[inline]
+    pub(crate) fn event(&self, _: &str) {}
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    static RECORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    struct Recorder;
+
+    impl log::Log for Recorder {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.target() == TRACE_TARGET
+        }
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                RECORDS.lock().unwrap().push(format!("{} {}", record.level(), record.args()));
+            };
+        }
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn validate_span() {
+        log::set_logger(&Recorder).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+        {
+            let mut span = Span::enter("insert", "books");
+            span.event("query=title:dune");
+            span.record_docs(3);
+        }
+        {
+            let _span = Span::enter_fine("jsonify", "books");
+        }
+        let records = RECORDS.lock().unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0], "DEBUG insert index=books query=title:dune");
+        assert!(records[1].starts_with("DEBUG insert index=books docs=3 elapsed_us="));
+        assert!(records[2].starts_with("TRACE jsonify index=books elapsed_us="));
+    }
+}
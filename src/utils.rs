@@ -1,6 +1,9 @@
 use std::{thread::sleep, time::Duration, time::Instant};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, BTreeMap};
 use std::path::{Path, PathBuf};
+use std::ops::{Bound, Range};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
 
 use rand::{Rng};
 use rand::distributions::Alphanumeric;
@@ -9,12 +12,19 @@ use serde::{Serialize};
 use serde;
 use serde_value;
 use serde_value::Value;
+use serde_json::{Map as JsonMap, Value as JsonValue};
 
 use tantivy::schema::{Schema, TextOptions, TEXT, IntOptions, STORED, SchemaBuilder};
+use tantivy::schema::{Field, FieldType, FieldValue, Type, DocParsingError};
+use tantivy::schema::Value as SchemaValue;
+use tantivy::query::{RangeQuery, FuzzyTermQuery, RegexQuery, PhraseQuery};
+use tantivy::{Document, Index, Term};
 
 use crate::prelude::*;
 
-/// Convert a JSON serializable struct as JSON
+const NAIVE_DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
+
+/// Convert a JSON serializable struct as JSON, enum variants are shaped the way serde_json writes them
 pub(crate) fn as_value<T>(data: &T) -> Result<Value, IndexError>
     where
         T: Serialize,
@@ -26,7 +36,71 @@ pub(crate) fn as_value<T>(data: &T) -> Result<Value, IndexError>
             &e.to_string(),
         )
     })?;
-    Ok(result)
+    let json = serde_json::to_value(data)?;
+    Ok(name_variants(result, &json))
+}
+
+/// serde_value drops the names of externally tagged variants, take them back from the JSON
+/// Unit variants become strings, newtype, tuple & struct variants maps keyed by the variant
+fn name_variants(value: Value, json: &JsonValue) -> Value {
+    let variant = match json {
+        JsonValue::Object(kv) if kv.len() == 1 => kv.iter().next(),
+        _ => None
+    };
+    match (value, json) {
+        (Value::Unit, JsonValue::String(text)) => Value::String(text.to_string()),
+        (Value::Option(Some(inner)), _) => Value::Option(Some(Box::new(name_variants(*inner, json)))),
+        (Value::Newtype(inner), _) => match variant {
+            Some((name, inner_json)) if !same_keys(&inner, json) => tagged(name, name_variants(*inner, inner_json)),
+            _ => name_variants(*inner, json)
+        },
+        (Value::Seq(items), JsonValue::Object(_)) => match variant {
+            Some((name, inner_json)) => tagged(name, name_variants(Value::Seq(items), inner_json)),
+            None => Value::Seq(items)
+        },
+        (Value::Seq(items), JsonValue::Array(array)) => {
+            let items = items.into_iter().zip(array)
+                .map(|(item, item_json)| name_variants(item, item_json))
+                .collect();
+            Value::Seq(items)
+        }
+        (Value::Map(kv), JsonValue::Object(object)) => match variant {
+            Some((name, inner_json)) if !keys_match(&kv, object) => tagged(name, name_variants(Value::Map(kv), inner_json)),
+            _ => {
+                let kv = kv.into_iter()
+                    .map(|(k, v)| {
+                        let v = match &k {
+                            Value::String(name) if object.contains_key(name) => name_variants(v, &object[name]),
+                            _ => v
+                        };
+                        (k, v)
+                    })
+                    .collect();
+                Value::Map(kv)
+            }
+        },
+        (value, _) => value
+    }
+}
+
+/// Map holding the same keys as the JSON object, i.e. not wrapped in a variant
+fn same_keys(value: &Value, json: &JsonValue) -> bool {
+    match (value, json) {
+        (Value::Newtype(inner), _) => same_keys(inner, json),
+        (Value::Option(Some(inner)), _) => same_keys(inner, json),
+        (Value::Map(kv), JsonValue::Object(object)) => keys_match(kv, object),
+        _ => false
+    }
+}
+
+fn keys_match(kv: &BTreeMap<Value, Value>, object: &JsonMap<String, JsonValue>) -> bool {
+    kv.len() == object.len() && kv.keys().all(|k| matches!(k, Value::String(name) if object.contains_key(name)))
+}
+
+fn tagged(name: &str, value: Value) -> Value {
+    let mut kv = BTreeMap::new();
+    kv.insert(Value::String(name.to_string()), value);
+    Value::Map(kv)
 }
 
 /// Get all field names
@@ -81,10 +155,11 @@ fn resolve_number_option(key: &str, control: Option<&HashMap<String, Control>>)
             }
             let x = x.unwrap();
             match x {
-                Control::ControlIntOptions(opt) => {
+                Control::ControlIntOptions(opt) | Control::ControlI64Options(opt) | Control::ControlF64Options(opt) => {
                     let option = opt.clone();
                     option
                 }
+                Control::ControlFastOptions(cardinality) => default.set_fast(*cardinality),
                 _ => default
             }
         }
@@ -92,6 +167,54 @@ fn resolve_number_option(key: &str, control: Option<&HashMap<String, Control>>)
     }
 }
 
+/// Store and index dates like numbers by default
+fn resolve_date_option(key: &str, control: Option<&HashMap<String, Control>>) -> IntOptions {
+    let default = resolve_number_option(key, None);
+    match control {
+        Some(c) => {
+            match c.get(key) {
+                Some(Control::ControlDateOptions(opt)) => opt.clone(),
+                Some(Control::ControlFastOptions(cardinality)) => default.set_fast(*cardinality),
+                _ => default
+            }
+        }
+        None => default
+    }
+}
+
+/// Field declared as date irrespective of the sample value
+fn is_date_control(key: &str, control: Option<&HashMap<String, Control>>) -> bool {
+    match control {
+        Some(c) => matches!(c.get(key), Some(Control::ControlDateOptions(_))),
+        None => false
+    }
+}
+
+/// Field declared as a hierarchical facet, its path-like strings are indexed level by level
+fn is_facet_control(key: &str, control: Option<&HashMap<String, Control>>) -> bool {
+    match control {
+        Some(c) => matches!(c.get(key), Some(Control::ControlFacet)),
+        None => false
+    }
+}
+
+fn is_fast_control(key: &str, control: Option<&HashMap<String, Control>>) -> bool {
+    match control {
+        Some(c) => matches!(c.get(key), Some(Control::ControlFastOptions(_))),
+        None => false
+    }
+}
+
+/// RFC3339 strings e.g. chrono DateTime are indexed as dates, tantivy keeps second precision
+fn is_date(text: &str) -> bool {
+    DateTime::parse_from_rfc3339(text).is_ok()
+}
+
+/// Strings without timezone e.g. chrono NaiveDateTime are indexed as UTC dates
+fn is_naive_date(text: &str) -> bool {
+    NaiveDateTime::parse_from_str(text, NAIVE_DATE_FORMAT).is_ok()
+}
+
 /// Join to path
 pub fn join(head: &str, tail: &str) -> Option<String> {
     let head = Path::new(head);
@@ -104,84 +227,11 @@ pub fn join(head: &str, tail: &str) -> Option<String> {
     }
 }
 
-/// Maps flat JSON structures
+/// Maps JSON structures, nested objects are flattened into dotted field names e.g. address.city
 pub(crate) fn as_schema_builder(data: &Value, control: Option<&HashMap<String, Control>>) -> Result<SchemaBuilder, IndexError> {
     if let Value::Map(kv) = data {
         let mut builder = Schema::builder();
-        let keys = kv.keys();
-        for key in keys {
-            let value = kv.get(key);
-            if value.is_none() {
-                continue;
-            };
-            let value = value.unwrap();
-            if let Value::String(k) = key {
-                match value {
-                    Value::String(_) => {
-                        let options = resolve_text_option(k, control);
-                        builder.add_text_field(k, options);
-                    }
-                    Value::Bool(_) => {
-                        let options = resolve_text_option(k, control);
-                        builder.add_text_field(k, options);
-                    }
-                    Value::U64(_) => {
-                        let options = resolve_number_option(k, control);
-                        builder.add_u64_field(k, options);
-                    }
-                    Value::U32(_) => {
-                        let options = resolve_number_option(k, control);
-                        builder.add_u64_field(k, options);
-                    }
-                    Value::U16(_) => {
-                        let options = resolve_number_option(k, control);
-                        builder.add_u64_field(k, options);
-                    }
-                    Value::U8(_) => {
-                        let options = resolve_number_option(k, control);
-                        builder.add_u64_field(k, options);
-                    }
-                    Value::I64(_) => {
-                        let options = resolve_number_option(k, control);
-                        builder.add_i64_field(k, options);
-                    }
-                    Value::I32(_) => {
-                        let options = resolve_number_option(k, control);
-                        builder.add_i64_field(k, options);
-                    }
-                    Value::I16(_) => {
-                        let options = resolve_number_option(k, control);
-                        builder.add_i64_field(k, options);
-                    }
-                    Value::I8(_) => {
-                        let options = resolve_number_option(k, control);
-                        builder.add_i64_field(k, options);
-                    }
-                    Value::F64(_) => {
-                        let options = resolve_number_option(k, control);
-                        builder.add_f64_field(k, options);
-                    }
-                    Value::F32(_) => {
-                        let options = resolve_number_option(k, control);
-                        builder.add_f64_field(k, options);
-                    }
-                    Value::Seq(_) => {
-                        builder.add_bytes_field(k);
-                    }
-                    _ => {
-                        return Err(IndexError::new(
-                            "Unable to create schema",
-                            "Unhandled value types", )
-                        );
-                    }
-                }
-            } else {
-                return Err(IndexError::new(
-                    "Unable to create schema",
-                    "keys were not string", )
-                );
-            }
-        }
+        add_fields(&mut builder, None, kv, control)?;
         // TODO: Throw up for empty json
         // return Err(IndexError::new(
         //     "Unable to create schema",
@@ -197,6 +247,631 @@ pub(crate) fn as_schema_builder(data: &Value, control: Option<&HashMap<String, C
     Err(error)
 }
 
+/// Adds the fields of a map, prefixing names of nested maps
+fn add_fields(builder: &mut SchemaBuilder, prefix: Option<&str>, kv: &BTreeMap<Value, Value>, control: Option<&HashMap<String, Control>>) -> Result<(), IndexError> {
+    let keys = kv.keys();
+    for key in keys {
+        let value = kv.get(key);
+        if value.is_none() {
+            continue;
+        };
+        let value = unwrap_option(value.unwrap());
+        if let Value::String(k) = key {
+            let k = &flat_name(prefix, k);
+            if let Some(Control::ControlMapOptions(options)) = control.and_then(|c| c.get(k.as_str())) {
+                builder.add_text_field(k, options.clone());
+                continue;
+            };
+            if is_facet_control(k, control) {
+                builder.add_facet_field(k);
+                continue;
+            };
+            match value {
+                Value::String(text) if is_date(text) || is_naive_date(text) || is_date_control(k, control) => {
+                    let options = resolve_date_option(k, control);
+                    builder.add_date_field(k, options);
+                }
+                Value::Seq(items) if is_object_seq(items) => {
+                    add_fields(builder, Some(k), &merged_items(items), control)?;
+                }
+                Value::String(_) | Value::Seq(_) if is_fast_control(k, control) => {
+                    let reason = format!("Field: {} is not a number, bool or date and cannot be fast", k);
+                    return Err(IndexError::schema_mismatch("Unable to create schema", &reason));
+                }
+                Value::String(_) => {
+                    let options = resolve_text_option(k, control);
+                    builder.add_text_field(k, options);
+                }
+                Value::Bool(_) => {
+                    let options = resolve_number_option(k, control);
+                    builder.add_u64_field(k, options);
+                }
+                Value::U64(_) => {
+                    let options = resolve_number_option(k, control);
+                    builder.add_u64_field(k, options);
+                }
+                Value::U32(_) => {
+                    let options = resolve_number_option(k, control);
+                    builder.add_u64_field(k, options);
+                }
+                Value::U16(_) => {
+                    let options = resolve_number_option(k, control);
+                    builder.add_u64_field(k, options);
+                }
+                Value::U8(_) => {
+                    let options = resolve_number_option(k, control);
+                    builder.add_u64_field(k, options);
+                }
+                Value::I64(_) => {
+                    let options = resolve_number_option(k, control);
+                    builder.add_i64_field(k, options);
+                }
+                Value::I32(_) => {
+                    let options = resolve_number_option(k, control);
+                    builder.add_i64_field(k, options);
+                }
+                Value::I16(_) => {
+                    let options = resolve_number_option(k, control);
+                    builder.add_i64_field(k, options);
+                }
+                Value::I8(_) => {
+                    let options = resolve_number_option(k, control);
+                    builder.add_i64_field(k, options);
+                }
+                Value::F64(_) => {
+                    let options = resolve_number_option(k, control);
+                    builder.add_f64_field(k, options);
+                }
+                Value::F32(_) => {
+                    let options = resolve_number_option(k, control);
+                    builder.add_f64_field(k, options);
+                }
+                Value::Seq(items) if is_text_seq(items) => {
+                    let options = resolve_text_option(k, control);
+                    builder.add_text_field(k, options);
+                }
+                Value::Seq(_) => {
+                    builder.add_bytes_field(k);
+                }
+                Value::Map(inner) => {
+                    add_fields(builder, Some(k), inner, control)?;
+                }
+                Value::Option(None) | Value::Unit => {
+                    add_declared_field(builder, k, control)?;
+                }
+                _ => {
+                    return Err(IndexError::new(
+                        "Unable to create schema",
+                        "Unhandled value types", )
+                    );
+                }
+            }
+        } else {
+            return Err(IndexError::new(
+                "Unable to create schema",
+                "keys were not string", )
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Names of the fields declared as maps
+pub(crate) fn map_fields(control: &HashMap<String, Control>) -> HashSet<String> {
+    control.iter()
+        .filter(|(_, c)| matches!(c, Control::ControlMapOptions(_)))
+        .map(|(k, _)| k.to_string())
+        .collect()
+}
+
+/// Some value is inferred as the value it holds
+fn unwrap_option(value: &Value) -> &Value {
+    match value {
+        Value::Option(Some(inner)) => unwrap_option(inner),
+        _ => value
+    }
+}
+
+/// Field sampled as None takes the type declared through Control
+fn add_declared_field(builder: &mut SchemaBuilder, key: &str, control: Option<&HashMap<String, Control>>) -> Result<(), IndexError> {
+    match control.and_then(|c| c.get(key)) {
+        Some(Control::ControlTextOptions(options)) => {
+            builder.add_text_field(key, options.clone());
+        }
+        Some(Control::ControlIntOptions(options)) => {
+            builder.add_u64_field(key, options.clone());
+        }
+        Some(Control::ControlI64Options(options)) => {
+            builder.add_i64_field(key, options.clone());
+        }
+        Some(Control::ControlF64Options(options)) => {
+            builder.add_f64_field(key, options.clone());
+        }
+        Some(Control::ControlDateOptions(options)) => {
+            builder.add_date_field(key, options.clone());
+        }
+        Some(Control::ControlMapOptions(options)) => {
+            builder.add_text_field(key, options.clone());
+        }
+        Some(Control::ControlFacet) => {
+            builder.add_facet_field(key);
+        }
+        Some(Control::ControlFastOptions(_)) | None => {
+            let reason = format!("Field: {} is None, declare its type through Control", key);
+            return Err(IndexError::schema_mismatch("Unable to create schema", &reason));
+        }
+    };
+    Ok(())
+}
+
+/// Sequence of strings e.g. Vec<String> is indexed as a multi-valued text field
+fn is_text_seq(items: &[Value]) -> bool {
+    matches!(items.first(), Some(Value::String(_)))
+}
+
+/// Sequence of objects e.g. Vec<LineItem> is flattened into multi-valued dotted fields
+fn is_object_seq(items: &[Value]) -> bool {
+    matches!(items.first(), Some(Value::Map(_)))
+}
+
+/// Keys of every object of the sequence, the first value seen of a key wins unless it is None
+fn merged_items(items: &[Value]) -> BTreeMap<Value, Value> {
+    let mut merged = BTreeMap::new();
+    for item in items {
+        if let Value::Map(kv) = item {
+            for (key, value) in kv {
+                let known = merged.get(key).map(|known| !matches!(unwrap_option(known), Value::Option(None) | Value::Unit));
+                if known != Some(true) {
+                    merged.insert(key.clone(), value.clone());
+                };
+            };
+        };
+    };
+    merged
+}
+
+/// Names of the sequences of objects, nested ones are dotted
+pub(crate) fn object_seq_fields(data: &Value) -> HashSet<String> {
+    matching_fields(data, &|value| match value {
+        Value::Seq(items) => is_object_seq(items),
+        _ => false
+    })
+}
+
+/// Names of the multi-valued text fields, nested ones are dotted
+pub(crate) fn text_seq_fields(data: &Value) -> HashSet<String> {
+    matching_fields(data, &|value| match value {
+        Value::Seq(items) => is_text_seq(items),
+        _ => false
+    })
+}
+
+/// Names of the bool fields stored as u64 0 or 1, nested ones are dotted
+pub(crate) fn bool_fields(data: &Value) -> HashSet<String> {
+    matching_fields(data, &|value| matches!(value, Value::Bool(_)))
+}
+
+fn matching_fields(data: &Value, predicate: &dyn Fn(&Value) -> bool) -> HashSet<String> {
+    let mut fields = HashSet::new();
+    if let Value::Map(kv) = data {
+        collect_fields(&mut fields, None, kv, predicate);
+    };
+    fields
+}
+
+fn collect_fields(fields: &mut HashSet<String>, prefix: Option<&str>, kv: &BTreeMap<Value, Value>, predicate: &dyn Fn(&Value) -> bool) {
+    for (key, value) in kv {
+        if let Value::String(k) = key {
+            let k = flat_name(prefix, k);
+            let value = unwrap_option(value);
+            if predicate(value) {
+                fields.insert(k);
+            } else if let Value::Map(inner) = value {
+                collect_fields(fields, Some(&k), inner, predicate);
+            } else if let Value::Seq(items) = value {
+                if is_object_seq(items) {
+                    collect_fields(fields, Some(&k), &merged_items(items), predicate);
+                };
+            };
+        };
+    };
+}
+
+/// Names of the date fields without timezone, nested ones are dotted
+pub(crate) fn naive_date_fields(data: &Value) -> HashSet<String> {
+    matching_fields(data, &|value| match value {
+        Value::String(text) => is_naive_date(text),
+        _ => false
+    })
+}
+
+/// Dotted name of a nested field
+fn flat_name(prefix: Option<&str>, name: &str) -> String {
+    match prefix {
+        Some(p) => format!("{}.{}", p, name),
+        None => name.to_string()
+    }
+}
+
+/// Flattens nested JSON objects into dotted keys
+pub(crate) fn flatten(data: &JsonValue) -> JsonValue {
+    let mut flat = JsonMap::new();
+    if let JsonValue::Object(kv) = data {
+        flatten_into(&mut flat, None, kv);
+        return JsonValue::Object(flat);
+    };
+    data.clone()
+}
+
+fn flatten_into(flat: &mut JsonMap<String, JsonValue>, prefix: Option<&str>, kv: &JsonMap<String, JsonValue>) {
+    for (key, value) in kv {
+        let key = flat_name(prefix, key);
+        match value {
+            JsonValue::Object(inner) => flatten_into(flat, Some(&key), inner),
+            _ => {
+                flat.insert(key, value.clone());
+            }
+        }
+    };
+}
+
+/// Rebuilds the objects of a flattened sequence by position from its multi-valued dotted fields
+/// Objects missing a member are rejected at insert, see ensure_dense_items
+pub(crate) fn zip_items(flat: &mut JsonMap<String, JsonValue>, prefix: &str) {
+    let dotted = format!("{}.", prefix);
+    let keys: Vec<String> = flat.keys().filter(|key| key.starts_with(&dotted)).cloned().collect();
+    let mut fields = Vec::with_capacity(keys.len());
+    for key in keys {
+        let values = match flat.remove(&key) {
+            Some(JsonValue::Array(values)) => values,
+            Some(value) => vec![value],
+            None => continue,
+        };
+        fields.push((key[dotted.len()..].to_string(), values));
+    };
+    let count = fields.iter().map(|(_, values)| values.len()).max().unwrap_or_default();
+    let mut items: Vec<JsonMap<String, JsonValue>> = (0..count).map(|_| JsonMap::new()).collect();
+    for (field, values) in fields {
+        for (item, value) in items.iter_mut().zip(values) {
+            item.insert(field.clone(), value);
+        };
+    };
+    flat.insert(prefix.to_string(), JsonValue::Array(items.into_iter().map(unflatten).collect()));
+}
+
+/// Rebuilds nested JSON objects from dotted keys
+pub(crate) fn unflatten(flat: JsonMap<String, JsonValue>) -> JsonValue {
+    let mut nested = JsonMap::new();
+    for (key, value) in flat {
+        let mut parts: Vec<&str> = key.split('.').collect();
+        let last = parts.pop().unwrap();
+        let mut current = &mut nested;
+        for part in parts {
+            let entry = current.entry(part.to_string())
+                .or_insert_with(|| JsonValue::Object(JsonMap::new()));
+            if !entry.is_object() {
+                *entry = JsonValue::Object(JsonMap::new());
+            };
+            current = entry.as_object_mut().unwrap();
+        };
+        current.insert(last.to_string(), value);
+    };
+    JsonValue::Object(nested)
+}
+
+/// Merges the patch over the target as a JSON merge patch, nulls remove the field & nested objects merge key by key
+pub(crate) fn merge_patch(target: &mut JsonValue, patch: &JsonValue) {
+    let patch = match patch {
+        JsonValue::Object(patch) => patch,
+        _ => {
+            *target = patch.clone();
+            return;
+        }
+    };
+    if !target.is_object() {
+        *target = JsonValue::Object(JsonMap::new());
+    };
+    let target = target.as_object_mut().unwrap();
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+            continue;
+        };
+        merge_patch(target.entry(key.clone()).or_insert(JsonValue::Null), value);
+    };
+}
+
+/// Bools are indexed as u64 0 or 1 and dates without timezone as UTC
+fn as_indexable(schema: &Schema, field_name: &str, value: JsonValue) -> JsonValue {
+    match value {
+        JsonValue::Bool(b) => JsonValue::from(b as u64),
+        JsonValue::String(text) if is_date_field(schema, field_name) && is_naive_date(&text) => {
+            JsonValue::String(format!("{}Z", text))
+        }
+        JsonValue::String(text) if is_facet_field(schema, field_name) => JsonValue::String(facet_text(&text)),
+        _ => value
+    }
+}
+
+fn is_text_field(schema: &Schema, field_name: &str) -> bool {
+    match schema.get_field(field_name) {
+        Some(field) => matches!(schema.get_field_entry(field).field_type(), FieldType::Str(_)),
+        None => false
+    }
+}
+
+fn is_facet_field(schema: &Schema, field_name: &str) -> bool {
+    match schema.get_field(field_name) {
+        Some(field) => matches!(schema.get_field_entry(field).field_type(), FieldType::HierarchicalFacet),
+        None => false
+    }
+}
+
+fn is_date_field(schema: &Schema, field_name: &str) -> bool {
+    match schema.get_field(field_name) {
+        Some(field) => matches!(schema.get_field_entry(field).field_type(), FieldType::Date(_)),
+        None => false
+    }
+}
+
+/// Range query over a date field, start inclusive & end exclusive
+pub(crate) fn date_range_query(schema: &Schema, field_name: &str, range: Range<DateTime<Utc>>) -> Result<RangeQuery, IndexError> {
+    if !is_date_field(schema, field_name) {
+        let reason = format!("Field: {} is not a date", field_name);
+        return Err(IndexError::schema_mismatch("Unable to create range query", &reason));
+    };
+    let field = schema.get_field(field_name).unwrap();
+    let start = Term::from_field_date(field, &range.start);
+    let end = Term::from_field_date(field, &range.end);
+    let query = RangeQuery::new_term_bounds(
+        field,
+        Type::Date,
+        &Bound::Included(start),
+        &Bound::Excluded(end),
+    );
+    Ok(query)
+}
+
+/// Resolves the field only if it is a text field
+pub(crate) fn text_field(schema: &Schema, field_name: &str) -> Result<Field, IndexError> {
+    let field = schema.get_field(field_name);
+    if field.is_none() {
+        let reason = format!("Field: {} not found", field_name);
+        return Err(IndexError::schema_mismatch("Unable to resolve text field", &reason));
+    };
+    let field = field.unwrap();
+    match schema.get_field_entry(field).field_type() {
+        FieldType::Str(_) => Ok(field),
+        _ => {
+            let reason = format!("Field: {} is not a text field", field_name);
+            Err(IndexError::schema_mismatch("Unable to resolve text field", &reason))
+        }
+    }
+}
+
+/// Tokenize the text the same way the text field was indexed
+pub(crate) fn text_terms(index: &Index, field_name: &str, text: &str) -> Result<Vec<Term>, IndexError> {
+    let field = text_field(&index.schema(), field_name)?;
+    let tokenizer = index.tokenizer_for_field(field)?;
+    let mut terms = Vec::new();
+    tokenizer.token_stream(text).process(&mut |token| {
+        terms.push(Term::from_field_text(field, &token.text));
+    });
+    Ok(terms)
+}
+
+/// Fuzzy query over a text field, the term must be a single token
+pub(crate) fn fuzzy_query(index: &Index, field_name: &str, term: &str, distance: u8) -> Result<FuzzyTermQuery, IndexError> {
+    let mut terms = text_terms(index, field_name, term)?;
+    if terms.len() != 1 {
+        let reason = format!("Term: {} is not a single token", term);
+        return Err(IndexError::new("Unable to create fuzzy query", &reason));
+    };
+    let query = FuzzyTermQuery::new(terms.remove(0), distance, true);
+    Ok(query)
+}
+
+/// Phrase query over a text field, the words must yield at least two tokens
+pub(crate) fn phrase_query(index: &Index, field_name: &str, words: &[&str]) -> Result<PhraseQuery, IndexError> {
+    let text = words.join(" ");
+    let terms = text_terms(index, field_name, &text)?;
+    if terms.len() < 2 {
+        let reason = format!("Phrase: {} has less than two tokens", text);
+        return Err(IndexError::new("Unable to create phrase query", &reason));
+    };
+    let query = PhraseQuery::new(terms);
+    Ok(query)
+}
+
+/// Regex query over the indexed terms of a text field
+pub(crate) fn regex_query(schema: &Schema, field_name: &str, pattern: &str) -> Result<RegexQuery, IndexError> {
+    let field = text_field(schema, field_name)?;
+    RegexQuery::from_pattern(pattern, field)
+        .map_err(|_| {
+            let reason = format!("Pattern: {} is not a valid regex", pattern);
+            IndexError::new("Unable to create regex query", &reason)
+        })
+}
+
+/// Range query over a u64, i64 or f64 field, start inclusive & end exclusive
+pub(crate) fn numeric_range_query<T: Serialize>(schema: &Schema, field_name: &str, range: Range<T>) -> Result<RangeQuery, IndexError> {
+    let field = schema.get_field(field_name);
+    if field.is_none() {
+        let reason = format!("Field: {} does not exist", field_name);
+        return Err(IndexError::schema_mismatch("Unable to create range query", &reason));
+    };
+    let field = field.unwrap();
+    let value_type = match schema.get_field_entry(field).field_type() {
+        FieldType::U64(_) => Type::U64,
+        FieldType::I64(_) => Type::I64,
+        FieldType::F64(_) => Type::F64,
+        _ => {
+            let reason = format!("Field: {} is not numeric", field_name);
+            return Err(IndexError::schema_mismatch("Unable to create range query", &reason));
+        }
+    };
+    let start = as_term(schema, field_name, &range.start)?;
+    let end = as_term(schema, field_name, &range.end)?;
+    let query = RangeQuery::new_term_bounds(
+        field,
+        value_type,
+        &Bound::Included(start),
+        &Bound::Excluded(end),
+    );
+    Ok(query)
+}
+
+/// Convert a JSON serializable struct as a document of the schema
+pub(crate) fn as_document<T: Serialize>(schema: &Schema, data: &T) -> Result<Document, IndexError> {
+    json_as_document(schema, serde_json::to_value(data)?)
+}
+
+/// Builds the document straight from JSON without printing & parsing it again
+pub(crate) fn json_as_document(schema: &Schema, data: JsonValue) -> Result<Document, IndexError> {
+    let mut document = Document::default();
+    stamp_source(schema, &mut document, &data);
+    add_json(schema, document, data)
+}
+
+/// Document of the JSON as indexed, the source kept is the JSON as inserted e.g. before it was shaped
+pub(crate) fn json_as_document_with_source(schema: &Schema, data: JsonValue, source: &JsonValue) -> Result<Document, IndexError> {
+    let mut document = Document::default();
+    stamp_source(schema, &mut document, source);
+    add_json(schema, document, data)
+}
+
+fn add_json(schema: &Schema, mut document: Document, data: JsonValue) -> Result<Document, IndexError> {
+    match data {
+        JsonValue::Object(kv) => add_field_values(schema, &mut document, None, kv)?,
+        _ => return Err(DocParsingError::NotJSON(data.to_string()).into()),
+    };
+    stamp_catch_all(schema, &mut document);
+    Ok(document)
+}
+
+/// Nested objects map to dotted fields and every item of an array is a value of the field, nulls are skipped
+/// Objects landing on a text field e.g. declared maps are kept whole as their JSON, objects of an array add a value to each of their dotted fields
+fn add_field_values(schema: &Schema, document: &mut Document, prefix: Option<&str>, kv: JsonMap<String, JsonValue>) -> Result<(), IndexError> {
+    for (key, value) in kv {
+        let key = flat_name(prefix, &key);
+        match value {
+            JsonValue::Null => continue,
+            JsonValue::Object(inner) if is_text_field(schema, &key) => {
+                add_field_value(schema, document, &key, JsonValue::String(JsonValue::Object(inner).to_string()))?;
+            }
+            JsonValue::Object(inner) => add_field_values(schema, document, Some(&key), inner)?,
+            JsonValue::Array(items) => {
+                if !is_text_field(schema, &key) {
+                    ensure_dense_items(schema, &key, &items)?;
+                };
+                for item in items.into_iter().filter(|item| !item.is_null()) {
+                    match item {
+                        JsonValue::Object(inner) if !is_text_field(schema, &key) => add_field_values(schema, document, Some(&key), inner)?,
+                        JsonValue::Object(inner) => add_field_value(schema, document, &key, JsonValue::String(JsonValue::Object(inner).to_string()))?,
+                        _ => add_field_value(schema, document, &key, item)?,
+                    };
+                };
+            }
+            _ => add_field_value(schema, document, &key, value)?,
+        };
+    };
+    Ok(())
+}
+
+/// Objects of an array are rebuilt by position on read, hence each has to hold one value of the same members
+/// Indexes keeping the _source read the documents back from it, any array is fine
+fn ensure_dense_items(schema: &Schema, key: &str, items: &[JsonValue]) -> Result<(), IndexError> {
+    if schema.get_field(SOURCE).is_some() {
+        return Ok(());
+    };
+    let mut expected: Option<BTreeMap<String, usize>> = None;
+    for item in items {
+        let inner = match item {
+            JsonValue::Object(inner) => inner,
+            _ => continue,
+        };
+        let mut members = BTreeMap::new();
+        count_members(schema, key, inner, &mut members);
+        let dense = match &expected {
+            Some(expected) => *expected == members,
+            None => members.values().all(|count| *count == 1),
+        };
+        if !dense {
+            let reason = format!("Field: {} holds objects with missing or repeated members, keep the _source to index them", key);
+            return Err(IndexError::schema_mismatch("Unable to index array of objects", &reason));
+        };
+        expected = Some(members);
+    };
+    Ok(())
+}
+
+/// Number of values each dotted member of the object adds, as add_field_values indexes them
+fn count_members(schema: &Schema, prefix: &str, kv: &JsonMap<String, JsonValue>, members: &mut BTreeMap<String, usize>) {
+    for (key, value) in kv {
+        let key = flat_name(Some(prefix), key);
+        match value {
+            JsonValue::Null => continue,
+            JsonValue::Object(inner) if !is_text_field(schema, &key) => count_members(schema, &key, inner, members),
+            JsonValue::Array(values) => {
+                for value in values.iter().filter(|value| !value.is_null()) {
+                    match value {
+                        JsonValue::Object(inner) if !is_text_field(schema, &key) => count_members(schema, &key, inner, members),
+                        _ => *members.entry(key.clone()).or_default() += 1,
+                    };
+                };
+            }
+            _ => *members.entry(key).or_default() += 1,
+        };
+    };
+}
+
+/// Fields missing from the schema are an error, unless the _source keeps them
+fn add_field_value(schema: &Schema, document: &mut Document, field_name: &str, value: JsonValue) -> Result<(), IndexError> {
+    let field = match schema.get_field(field_name) {
+        Some(field) => field,
+        None if schema.get_field(SOURCE).is_some() => return Ok(()),
+        None => return Err(DocParsingError::NoSuchFieldInSchema(field_name.to_string()).into()),
+    };
+    let value = as_indexable(schema, field_name, value);
+    let value = schema.get_field_entry(field)
+        .field_type()
+        .value_from_json(&value)
+        .map_err(|e| DocParsingError::ValueError(field_name.to_string(), e))?;
+    document.add(FieldValue::new(field, value));
+    Ok(())
+}
+
+/// Maps a serializable value to a term of the named field
+pub(crate) fn as_term<T: Serialize>(schema: &Schema, field_name: &str, value: &T) -> Result<Term, IndexError> {
+    let field = schema.get_field(field_name);
+    if field.is_none() {
+        let reason = format!("Field: {} does not exist", field_name);
+        return Err(IndexError::schema_mismatch("Unable to create term", &reason));
+    };
+    let field = field.unwrap();
+    let json = as_indexable(schema, field_name, serde_json::to_value(value)?);
+    let value = schema.get_field_entry(field)
+        .field_type()
+        .value_from_json(&json)
+        .map_err(|e| {
+            let reason = format!("Field: {} {:?}", field_name, e);
+            IndexError::schema_mismatch("Unable to create term", &reason)
+        })?;
+    let term = match value {
+        SchemaValue::Str(text) => Term::from_field_text(field, &text),
+        SchemaValue::U64(number) => Term::from_field_u64(field, number),
+        SchemaValue::I64(number) => Term::from_field_i64(field, number),
+        SchemaValue::F64(number) => Term::from_field_f64(field, number),
+        SchemaValue::Date(date) => Term::from_field_date(field, &date),
+        SchemaValue::Facet(facet) => Term::from_facet(field, &facet),
+        _ => {
+            let reason = format!("Field: {} is not indexed as terms", field_name);
+            return Err(IndexError::schema_mismatch("Unable to create term", &reason));
+        }
+    };
+    Ok(term)
+}
+
 /// List files within a dir
 pub fn ls<T: AsRef<str>>(home: T) -> Result<Vec<PathBuf>, IndexError> {
     let paths = std::fs::read_dir(home.as_ref())?;
@@ -216,6 +891,47 @@ pub(crate) fn to_schema(data: &Value, control: Option<&HashMap<String, Control>>
     Ok(builder.build())
 }
 
+/// Schema of the fields generated by #[derive(Surf)]
+pub(crate) fn to_surf_schema(fields: &[SurfField], control: Option<&HashMap<String, Control>>) -> Result<Schema, IndexError> {
+    let mut builder = SchemaBuilder::default();
+    for field in fields {
+        let k = field.name();
+        match field.kind() {
+            SurfKind::Text | SurfKind::TextSeq if is_facet_control(k, control) => {
+                builder.add_facet_field(k);
+            }
+            SurfKind::Text | SurfKind::TextSeq => {
+                let options = resolve_text_option(k, control);
+                builder.add_text_field(k, options);
+            }
+            SurfKind::U64 | SurfKind::Bool => {
+                let options = resolve_number_option(k, control);
+                builder.add_u64_field(k, options);
+            }
+            SurfKind::I64 => {
+                let options = resolve_number_option(k, control);
+                builder.add_i64_field(k, options);
+            }
+            SurfKind::F64 => {
+                let options = resolve_number_option(k, control);
+                builder.add_f64_field(k, options);
+            }
+            SurfKind::Date | SurfKind::NaiveDate => {
+                let options = resolve_date_option(k, control);
+                builder.add_date_field(k, options);
+            }
+            SurfKind::Bytes => {
+                builder.add_bytes_field(k);
+            }
+        };
+    };
+    let schema = builder.build();
+    if schema.fields().next().is_none() {
+        return Err(IndexError::new("Unable to create schema", "Struct does not have any field"));
+    };
+    Ok(schema)
+}
+
 /// block thread
 pub fn block_thread(sleep_in_seconds: u64) -> u64 {
     let duration = Duration::from_secs(sleep_in_seconds);
@@ -241,6 +957,7 @@ pub fn random_string(size: Option<usize>) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tantivy::schema::Cardinality;
 
     #[derive(Serialize)]
     struct Empty;
@@ -284,6 +1001,27 @@ mod tests {
         assert_eq!(options.is_stored(), false);
     }
 
+    #[test]
+    fn validate_resolve_fast_option() {
+        let key = "dummy";
+        let mut control = HashMap::new();
+        control.insert(key.to_string(), Control::ControlFastOptions(Cardinality::SingleValue));
+        let options = resolve_number_option(key, Some(&control));
+        assert!(options.is_stored());
+        assert!(options.is_indexed());
+        assert_eq!(options.get_fastfield_cardinality(), Some(Cardinality::SingleValue));
+        let options = resolve_date_option(key, Some(&control));
+        assert_eq!(options.get_fastfield_cardinality(), Some(Cardinality::SingleValue));
+
+        #[derive(Serialize)]
+        struct Book {
+            dummy: String,
+        }
+
+        let value = as_value(&Book { dummy: "The Sea Wolf".to_string() }).unwrap();
+        assert!(to_schema(&value, Some(&control)).is_err());
+    }
+
     #[test]
     fn validate_resolve_default_number_option() {
         let key = "dummy";
@@ -330,6 +1068,346 @@ mod tests {
         assert_eq!(options.is_stored(), true);
     }
 
+    #[test]
+    fn validate_as_term() {
+        let data = DataVec {
+            identity: "Hello".to_string(),
+            buffer: "World".as_bytes().to_vec(),
+        };
+        let data = as_value(&data).unwrap();
+        let schema = to_schema(&data, None).unwrap();
+        let field = schema.get_field("identity").unwrap();
+
+        let computed = as_term(&schema, "identity", &"hello").unwrap();
+        let expected = Term::from_field_text(field, "hello");
+        assert_eq!(computed, expected);
+
+        let computed = as_term(&schema, "identity", &100u64);
+        assert!(computed.is_err());
+
+        let computed = as_term(&schema, "missing", &"hello");
+        assert!(computed.is_err());
+    }
+
+    #[test]
+    fn validate_schema_builder_for_nested() {
+        #[derive(Serialize)]
+        struct Address {
+            city: String,
+            zip: u64,
+        }
+        #[derive(Serialize)]
+        struct Person {
+            name: String,
+            address: Address,
+        }
+
+        let data = Person {
+            name: "John Doe".to_string(),
+            address: Address {
+                city: "Paris".to_string(),
+                zip: 75001,
+            },
+        };
+        let value = as_value(&data).unwrap();
+        let schema = to_schema(&value, None).unwrap();
+        assert!(schema.get_field("name").is_some());
+        assert!(schema.get_field("address.city").is_some());
+        assert!(schema.get_field("address.zip").is_some());
+        assert!(schema.get_field("address").is_none());
+
+        let document = as_document(&schema, &data);
+        assert!(document.is_ok());
+    }
+
+    #[test]
+    fn validate_json_as_document() {
+        let mut builder = SchemaBuilder::new();
+        builder.add_text_field("title", TEXT | STORED);
+        builder.add_text_field("tags", TEXT | STORED);
+        builder.add_u64_field("meta.pages", IntOptions::default().set_stored());
+        builder.add_u64_field("meta.active", IntOptions::default().set_stored());
+        let schema = builder.build();
+
+        let data = serde_json::json!({
+            "title": "The Old Man and the Sea",
+            "tags": ["novel", "sea"],
+            "meta": {"pages": 127, "active": true},
+        });
+        let computed = json_as_document(&schema, data).unwrap();
+        let expected = schema.parse_document(r#"{"title": "The Old Man and the Sea", "tags": ["novel", "sea"], "meta.pages": 127, "meta.active": 1}"#).unwrap();
+        assert_eq!(computed.get_sorted_field_values(), expected.get_sorted_field_values());
+
+        assert!(json_as_document(&schema, serde_json::json!({"author": "Hemingway"})).is_err());
+        assert!(json_as_document(&schema, serde_json::json!({"meta": {"pages": "many"}})).is_err());
+        assert!(json_as_document(&schema, serde_json::json!(["The Old Man and the Sea"])).is_err());
+    }
+
+    #[test]
+    fn validate_schema_builder_for_text_seq() {
+        #[derive(Serialize)]
+        struct Article {
+            title: String,
+            tags: Vec<String>,
+        }
+
+        let data = Article {
+            title: "The Old Man and the Sea".to_string(),
+            tags: vec!["novel".to_string(), "sea".to_string()],
+        };
+        let value = as_value(&data).unwrap();
+        let schema = to_schema(&value, None).unwrap();
+        let field = schema.get_field("tags").unwrap();
+        let entry = schema.get_field_entry(field);
+        assert!(entry.is_indexed());
+
+        let document = as_document(&schema, &data).unwrap();
+        assert_eq!(document.get_all(field).len(), 2);
+
+        let computed = text_seq_fields(&value);
+        let mut expected = HashSet::new();
+        expected.insert("tags".to_string());
+        assert_eq!(computed, expected);
+    }
+
+    #[test]
+    fn validate_schema_builder_for_bool() {
+        #[derive(Serialize)]
+        struct Flag {
+            name: String,
+            active: bool,
+        }
+
+        let data = Flag {
+            name: "beta".to_string(),
+            active: true,
+        };
+        let value = as_value(&data).unwrap();
+        let schema = to_schema(&value, None).unwrap();
+        let field = schema.get_field("active").unwrap();
+
+        let document = as_document(&schema, &data).unwrap();
+        assert_eq!(document.get_first(field).unwrap().u64_value(), 1);
+
+        let computed = as_term(&schema, "active", &false).unwrap();
+        let expected = Term::from_field_u64(field, 0);
+        assert_eq!(computed, expected);
+
+        let computed = bool_fields(&value);
+        let mut expected = HashSet::new();
+        expected.insert("active".to_string());
+        assert_eq!(computed, expected);
+    }
+
+    #[test]
+    fn validate_schema_builder_for_date() {
+        #[derive(Serialize)]
+        struct Event {
+            title: String,
+            created: String,
+            seen: String,
+            due: String,
+        }
+
+        let data = Event {
+            title: "launch".to_string(),
+            created: "2020-01-01T10:00:00+02:00".to_string(),
+            seen: "2020-01-01T10:00:00".to_string(),
+            due: "".to_string(),
+        };
+        let mut control = HashMap::new();
+        control.insert("due".to_string(), Control::ControlDateOptions(IntOptions::default()));
+        let value = as_value(&data).unwrap();
+        let schema = to_schema(&value, Some(&control)).unwrap();
+        assert!(!is_date_field(&schema, "title"));
+        assert!(is_date_field(&schema, "created"));
+        assert!(is_date_field(&schema, "seen"));
+        assert!(is_date_field(&schema, "due"));
+
+        let computed = naive_date_fields(&value);
+        let mut expected = HashSet::new();
+        expected.insert("seen".to_string());
+        assert_eq!(computed, expected);
+
+        let schema = to_schema(&value, None).unwrap();
+        let document = as_document(&schema, &data);
+        assert!(document.is_ok());
+    }
+
+    #[test]
+    fn validate_text_terms() {
+        let mut builder = SchemaBuilder::new();
+        builder.add_text_field("title", TEXT);
+        builder.add_u64_field("count", STORED);
+        let index = Index::create_in_ram(builder.build());
+
+        let terms = text_terms(&index, "title", "Old MAN").unwrap();
+        let field = index.schema().get_field("title").unwrap();
+        let expected = vec![Term::from_field_text(field, "old"), Term::from_field_text(field, "man")];
+        assert_eq!(terms, expected);
+
+        assert!(text_terms(&index, "count", "1").is_err());
+        assert!(text_terms(&index, "missing", "old").is_err());
+        assert!(fuzzy_query(&index, "title", "old man", 1).is_err());
+        assert!(fuzzy_query(&index, "title", "old", 1).is_ok());
+    }
+
+    #[test]
+    fn validate_numeric_range_query() {
+        let mut builder = SchemaBuilder::new();
+        builder.add_text_field("title", TEXT);
+        builder.add_u64_field("count", STORED);
+        builder.add_f64_field("price", STORED);
+        let schema = builder.build();
+
+        assert!(numeric_range_query(&schema, "count", 1..10).is_ok());
+        assert!(numeric_range_query(&schema, "price", 1..10).is_ok());
+        assert!(numeric_range_query(&schema, "price", 1.5..10.0).is_ok());
+        assert!(numeric_range_query(&schema, "count", -1..10).is_err());
+        assert!(numeric_range_query(&schema, "title", 1..10).is_err());
+        assert!(numeric_range_query(&schema, "missing", 1..10).is_err());
+    }
+
+    #[test]
+    fn validate_phrase_query() {
+        let mut builder = SchemaBuilder::new();
+        builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(builder.build());
+
+        assert!(phrase_query(&index, "title", &["old", "man"]).is_ok());
+        assert!(phrase_query(&index, "title", &["old man"]).is_ok());
+        assert!(phrase_query(&index, "title", &["old"]).is_err());
+        assert!(phrase_query(&index, "title", &[]).is_err());
+    }
+
+    #[test]
+    fn validate_regex_query() {
+        let mut builder = SchemaBuilder::new();
+        builder.add_text_field("title", TEXT);
+        builder.add_u64_field("count", STORED);
+        let schema = builder.build();
+
+        assert!(regex_query(&schema, "title", "ol.*").is_ok());
+        assert!(regex_query(&schema, "title", "ol(").is_err());
+        assert!(regex_query(&schema, "count", "1.*").is_err());
+    }
+
+    #[test]
+    fn validate_surf_schema_matches_sample_schema() {
+        #[derive(Serialize)]
+        struct Address {
+            city: String,
+        }
+        #[derive(Serialize)]
+        struct User {
+            name: String,
+            age: u8,
+            score: f32,
+            admin: bool,
+            tags: Vec<String>,
+            address: Address,
+        }
+        let user = User {
+            name: "Jon".to_string(),
+            age: 1,
+            score: 1.0,
+            admin: true,
+            tags: vec!["a".to_string()],
+            address: Address { city: "Paris".to_string() },
+        };
+        let expected = to_schema(&as_value(&user).unwrap(), None).unwrap();
+        let fields = vec![
+            SurfField::new("name", SurfKind::Text),
+            SurfField::new("age", SurfKind::U64),
+            SurfField::new("score", SurfKind::F64),
+            SurfField::new("admin", SurfKind::Bool),
+            SurfField::new("tags", SurfKind::TextSeq),
+            SurfField::new("address.city", SurfKind::Text),
+        ];
+        let computed = to_surf_schema(&fields, None).unwrap();
+        assert!(schema_drift(&expected, &computed).is_empty());
+        assert!(to_surf_schema(&[], None).is_err());
+    }
+
+    #[test]
+    fn validate_object_seqs() {
+        let data = serde_json::json!({"id": 1, "items": [{"sku": "apple", "quantity": 3}, {"sku": "pear", "active": true}]});
+        let value = as_value(&data).unwrap();
+        let schema = to_schema(&value, None).unwrap();
+        assert!(schema.get_field("items.sku").is_some());
+        assert!(schema.get_field("items.quantity").is_some());
+        assert!(schema.get_field("items").is_none());
+        assert!(bool_fields(&value).contains("items.active"));
+        assert!(object_seq_fields(&value).contains("items"));
+        assert!(json_as_document(&schema, data).is_err());
+
+        let data = serde_json::json!({"id": 1, "items": [{"sku": "apple", "quantity": 3}, {"sku": "pear", "quantity": 1}]});
+        let document = json_as_document(&schema, data).unwrap();
+        assert_eq!(document.get_all(schema.get_field("items.sku").unwrap()).len(), 2);
+        let data = serde_json::json!({"id": 1, "items": [{"sku": "apple", "quantity": 3}, {"sku": "pear", "quantity": null}]});
+        assert!(json_as_document(&schema, data).is_err());
+        let data = serde_json::json!({"id": 1, "items": [{"sku": ["apple", "pear"], "quantity": 3}]});
+        assert!(json_as_document(&schema, data).is_err());
+
+        let mut flat = serde_json::json!({"id": 1, "items.sku": ["apple", "pear"], "items.quantity": [3]}).as_object().unwrap().clone();
+        zip_items(&mut flat, "items");
+        let expected = serde_json::json!({"id": 1, "items": [{"sku": "apple", "quantity": 3}, {"sku": "pear"}]});
+        assert_eq!(JsonValue::Object(flat), expected);
+    }
+
+    #[test]
+    fn validate_flatten_and_unflatten() {
+        let data = serde_json::json!({
+            "name": "John Doe",
+            "address": {
+                "city": "Paris",
+                "geo": {
+                    "zip": 75001
+                }
+            }
+        });
+        let computed = flatten(&data);
+        let expected = serde_json::json!({
+            "name": "John Doe",
+            "address.city": "Paris",
+            "address.geo.zip": 75001
+        });
+        assert_eq!(computed, expected);
+
+        let computed = unflatten(computed.as_object().unwrap().clone());
+        assert_eq!(computed, data);
+    }
+
+    #[test]
+    fn validate_merge_patch() {
+        let mut computed = serde_json::json!({
+            "name": "John Doe",
+            "tags": ["a", "b"],
+            "address": {
+                "city": "Paris",
+                "zip": 75001
+            }
+        });
+        let patch = serde_json::json!({
+            "name": null,
+            "tags": ["c"],
+            "address": {
+                "city": "Lyon"
+            },
+            "age": 42
+        });
+        merge_patch(&mut computed, &patch);
+        let expected = serde_json::json!({
+            "tags": ["c"],
+            "address": {
+                "city": "Lyon",
+                "zip": 75001
+            },
+            "age": 42
+        });
+        assert_eq!(computed, expected);
+    }
+
     #[test]
     fn invalid_field_names() {
         let empty = Empty;
@@ -360,6 +1438,114 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn validate_schema_builder_for_option() {
+        #[derive(Serialize)]
+        struct Listing {
+            title: Option<String>,
+            price: Option<f64>,
+            stock: Option<i64>,
+            active: Option<bool>,
+        }
+
+        let data = Listing {
+            title: Some("The Old Man and the Sea".to_string()),
+            price: None,
+            stock: None,
+            active: Some(true),
+        };
+        let value = as_value(&data).unwrap();
+        assert!(to_schema(&value, None).is_err());
+
+        let mut control = HashMap::new();
+        control.insert("price".to_string(), Control::ControlF64Options(IntOptions::default().set_indexed().set_stored()));
+        control.insert("stock".to_string(), Control::ControlI64Options(IntOptions::default().set_indexed().set_stored()));
+        let schema = to_schema(&value, Some(&control)).unwrap();
+        let field_type = |name: &str| schema.get_field_entry(schema.get_field(name).unwrap()).field_type().value_type();
+        assert_eq!(field_type("title"), Type::Str);
+        assert_eq!(field_type("price"), Type::F64);
+        assert_eq!(field_type("stock"), Type::I64);
+        assert_eq!(field_type("active"), Type::U64);
+        assert!(bool_fields(&value).contains("active"));
+
+        let document = as_document(&schema, &data).unwrap();
+        assert_eq!(document.len(), 2);
+    }
+
+    #[test]
+    fn validate_schema_builder_for_enum() {
+        #[derive(Serialize)]
+        enum Status {
+            Active,
+        }
+
+        #[derive(Serialize)]
+        enum Size {
+            Pages(u64),
+        }
+
+        #[derive(Serialize)]
+        #[serde(tag = "type")]
+        enum Cover {
+            Hard { weight: f64 },
+        }
+
+        #[derive(Serialize)]
+        struct Meters(f64);
+
+        #[derive(Serialize)]
+        struct Book {
+            status: Status,
+            size: Size,
+            cover: Cover,
+            width: Meters,
+        }
+
+        let data = Book {
+            status: Status::Active,
+            size: Size::Pages(127),
+            cover: Cover::Hard { weight: 0.5 },
+            width: Meters(0.2),
+        };
+        let value = as_value(&data).unwrap();
+        let schema = to_schema(&value, None).unwrap();
+        let field_type = |name: &str| schema.get_field_entry(schema.get_field(name).unwrap()).field_type().value_type();
+        assert_eq!(field_type("status"), Type::Str);
+        assert_eq!(field_type("size.Pages"), Type::U64);
+        assert_eq!(field_type("cover.type"), Type::Str);
+        assert_eq!(field_type("cover.weight"), Type::F64);
+        assert_eq!(field_type("width"), Type::F64);
+
+        let document = as_document(&schema, &data).unwrap();
+        assert_eq!(document.len(), 5);
+    }
+
+    #[test]
+    fn validate_schema_builder_for_map() {
+        #[derive(Serialize)]
+        struct Product {
+            name: String,
+            attributes: HashMap<String, String>,
+        }
+
+        let mut attributes = HashMap::new();
+        attributes.insert("color".to_string(), "red".to_string());
+        let data = Product {
+            name: "Shirt".to_string(),
+            attributes,
+        };
+        let value = as_value(&data).unwrap();
+        let mut control = HashMap::new();
+        control.insert("attributes".to_string(), Control::ControlMapOptions(TEXT | STORED));
+        let schema = to_schema(&value, Some(&control)).unwrap();
+        assert!(schema.get_field("attributes.color").is_none());
+        let field = schema.get_field("attributes").unwrap();
+
+        let document = as_document(&schema, &data).unwrap();
+        assert_eq!(document.get_first(field).unwrap().text(), Some(r#"{"color":"red"}"#));
+        assert_eq!(map_fields(&control), vec!["attributes".to_string()].into_iter().collect());
+    }
+
     #[test]
     fn validate_schema_builder_for_vec_does_not_work() {
         let identity = "Hello".to_string();